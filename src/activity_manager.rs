@@ -23,15 +23,17 @@
 *
 */
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // Deps
-use crate::filetransfer::FileTransferProtocol;
+use crate::filetransfer::proxy_jump::JumpHost;
+use crate::filetransfer::{FileTransferProtocol, SshIdentity};
 use crate::host::Localhost;
+use crate::system::config_client::ConfigClient;
+use crate::system::environment;
 use crate::ui::activities::{
-    auth_activity::AuthActivity,
-    filetransfer_activity::FileTransferActivity, filetransfer_activity::FileTransferParams,
-    Activity,
+    auth_activity::AuthActivity, filetransfer_activity::FileTransferActivity,
+    filetransfer_activity::FileTransferParams, filetransfer_activity::LogLevel, Activity,
 };
 use crate::ui::context::Context;
 
@@ -53,6 +55,13 @@ pub enum NextActivity {
 pub struct ActivityManager {
     context: Option<Context>,
     ftparams: Option<FileTransferParams>,
+    jump_hosts: Vec<JumpHost>,
+    identity: Option<SshIdentity>,
+    readonly: bool,
+    trash_dir: Option<PathBuf>,
+    buffer_size: Option<usize>,
+    log_file: Option<PathBuf>,
+    log_level: LogLevel,
     interval: Duration,
 }
 
@@ -69,14 +78,78 @@ impl ActivityManager {
             Ok(h) => h,
             Err(_) => return Err(()),
         };
-        let ctx: Context = Context::new(host);
+        // Initialize configuration client; if it fails, default configuration will be used
+        let config_client: Option<ConfigClient> = match environment::init_config_dir() {
+            Ok(Some(mut config_path)) => {
+                config_path.push("config.toml");
+                ConfigClient::new(config_path.as_path()).ok()
+            }
+            _ => None,
+        };
+        let ctx: Context = Context::new(host, config_client);
         Ok(ActivityManager {
             context: Some(ctx),
             ftparams: None,
+            jump_hosts: Vec::new(),
+            identity: None,
+            readonly: false,
+            trash_dir: None,
+            buffer_size: None,
+            log_file: None,
+            log_level: LogLevel::Info,
             interval,
         })
     }
 
+    /// ### set_jump_hosts
+    ///
+    /// Set the SSH jump hosts to go through for every connection
+    pub fn set_jump_hosts(&mut self, jump_hosts: Vec<JumpHost>) {
+        self.jump_hosts = jump_hosts;
+    }
+
+    /// ### set_identity
+    ///
+    /// Set the private key SSH connections authenticate with; it's also the default identity file of the authentication form
+    pub fn set_identity(&mut self, identity: Option<SshIdentity>) {
+        self.identity = identity;
+    }
+
+    /// ### set_readonly
+    ///
+    /// Set whether file transfer sessions are readonly (only browsing and downloading allowed)
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// ### set_buffer_size
+    ///
+    /// Set the size (bytes) of the transfer buffer passed in `FileTransferParams`; if None, the configured one is used
+    pub fn set_buffer_size(&mut self, buffer_size: Option<usize>) {
+        self.buffer_size = buffer_size;
+    }
+
+    /// ### set_log_file
+    ///
+    /// Set the file log records are appended to; if None, the configured one is used
+    pub fn set_log_file(&mut self, log_file: Option<PathBuf>) {
+        self.log_file = log_file;
+    }
+
+    /// ### set_log_level
+    ///
+    /// Set the least severe level of the records written to the log file
+    pub fn set_log_level(&mut self, log_level: LogLevel) {
+        self.log_level = log_level;
+    }
+
+    /// ### set_trash_dir
+    ///
+    /// Set the directory where local deletes are moved to, so they can be undone (trash mode)
+    pub fn set_trash_dir(&mut self, trash_dir: Option<PathBuf>) {
+        self.trash_dir = trash_dir;
+    }
+
     /// ### set_filetransfer_params
     ///
     /// Set file transfer params
@@ -94,6 +167,9 @@ impl ActivityManager {
             protocol,
             username,
             password,
+            jump_hosts: self.jump_hosts.clone(),
+            identity: self.identity.clone(),
+            buffer_size: self.buffer_size,
         });
     }
 
@@ -127,6 +203,9 @@ impl ActivityManager {
     fn run_authentication(&mut self) -> Option<NextActivity> {
         // Prepare activity
         let mut activity: AuthActivity = AuthActivity::new();
+        if let Some(identity) = self.identity.as_ref() {
+            activity.identity_file = identity.path.to_string_lossy().to_string();
+        }
         // Prepare result
         let result: Option<NextActivity>;
         // Get context
@@ -161,6 +240,23 @@ impl ActivityManager {
                         _ => Some(activity.password.clone()),
                     },
                     protocol: activity.protocol,
+                    jump_hosts: self.jump_hosts.clone(),
+                    identity: match activity.identity_file.as_str() {
+                        "" => None,
+                        path => Some(match self.identity.as_ref() {
+                            // Identity from CLI: its passphrase has been asked before starting the UI
+                            Some(identity) if identity.path == Path::new(path) => identity.clone(),
+                            // Otherwise the password is the passphrase of the key
+                            _ => SshIdentity::new(
+                                PathBuf::from(path),
+                                match activity.password.len() {
+                                    0 => None,
+                                    _ => Some(activity.password.clone()),
+                                },
+                            ),
+                        }),
+                    },
+                    buffer_size: self.buffer_size,
                 });
                 break;
             }
@@ -181,16 +277,22 @@ impl ActivityManager {
         if self.ftparams.is_none() {
             return Some(NextActivity::Authentication);
         }
-        // Prepare activity
-        let mut activity: FileTransferActivity =
-            FileTransferActivity::new(self.ftparams.take().unwrap());
-        // Prepare result
-        let result: Option<NextActivity>;
         // Get context
         let ctx: Context = match self.context.take() {
             Some(ctx) => ctx,
             None => return None
         };
+        // Prepare activity
+        let mut activity: FileTransferActivity = FileTransferActivity::new(
+            self.ftparams.take().unwrap(),
+            ctx.config_client.as_ref(),
+        );
+        activity.set_readonly(self.readonly);
+        activity.set_trash_dir(self.trash_dir.clone());
+        activity.set_log_file(self.log_file.clone());
+        activity.set_log_level(self.log_level);
+        // Prepare result
+        let result: Option<NextActivity>;
         // Create activity
         activity.on_create(ctx);
         loop {
@@ -0,0 +1,694 @@
+//! ## Config
+//!
+//! `config` is the module which provides data types and de/serializer for the user configuration
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+pub mod serializer;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::PathBuf;
+
+#[derive(Deserialize, Serialize, std::fmt::Debug, Default)]
+/// ## UserConfig
+///
+/// UserConfig contains all the configurations for the user,
+/// supported by Gateway
+pub struct UserConfig {
+    #[serde(default)]
+    pub ftp: FtpConfig,
+    #[serde(default)]
+    pub ssh: SshConfig,
+    #[serde(default)]
+    pub connection: ConnectionConfig,
+    #[serde(default)]
+    pub transfer: TransferConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub selections: HashMap<String, Vec<PathBuf>>, // Saved selection profiles: name => marked paths
+}
+
+#[derive(Deserialize, Serialize, std::fmt::Debug, Default)]
+/// ## FtpConfig
+///
+/// FtpConfig contains the configuration for the FTP file transfer
+pub struct FtpConfig {
+    pub list_args: Option<String>, // Arguments for LIST command (e.g. `-a`); server support varies
+    #[serde(default)]
+    pub flush_interval: u64, // Flush the upload stream each time this amount of bytes has been written; 0 flushes only at the end
+    pub default_username: Option<String>, // Username used when the connection string omits it (e.g. `anonymous`)
+}
+
+#[derive(Deserialize, Serialize, std::fmt::Debug)]
+/// ## SshConfig
+///
+/// SshConfig contains the configuration for the SSH based file transfers (SFTP/SCP)
+pub struct SshConfig {
+    #[serde(default = "SshConfig::default_read_timeout")]
+    pub read_timeout: u64, // Seconds a blocking operation may wait for the server; 0 disables the timeout
+    pub default_username: Option<String>, // Username used when the connection string omits it; current user if unset
+    #[serde(default = "SshConfig::default_sftp_concurrency")]
+    pub sftp_concurrency: usize, // SFTP read requests kept in flight while downloading; 1 reads a block at a time
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        SshConfig {
+            read_timeout: SshConfig::default_read_timeout(),
+            default_username: None,
+            sftp_concurrency: SshConfig::default_sftp_concurrency(),
+        }
+    }
+}
+
+impl SshConfig {
+    fn default_read_timeout() -> u64 {
+        60
+    }
+
+    fn default_sftp_concurrency() -> usize {
+        1
+    }
+}
+
+#[derive(Deserialize, Serialize, std::fmt::Debug)]
+/// ## ConnectionConfig
+///
+/// ConnectionConfig contains the configuration for the connections to the remote hosts
+pub struct ConnectionConfig {
+    #[serde(default = "ConnectionConfig::default_max_connections")]
+    pub max_connections: usize, // Connections which can be open to the same host at once; 0 is unlimited
+    #[serde(default)]
+    pub clock_skew_threshold: u64, // Seconds of remote clock skew which are warned about at connect; 0 disables the check
+    #[serde(default)]
+    pub clock_skew_compensate: bool, // Whether the measured skew is subtracted from remote times when syncing
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        ConnectionConfig {
+            max_connections: ConnectionConfig::default_max_connections(),
+            clock_skew_threshold: 0,
+            clock_skew_compensate: false,
+        }
+    }
+}
+
+impl ConnectionConfig {
+    fn default_max_connections() -> usize {
+        4
+    }
+}
+
+#[derive(Deserialize, Serialize, std::fmt::Debug)]
+/// ## TransferConfig
+///
+/// TransferConfig contains the configuration for file transfers
+pub struct TransferConfig {
+    #[serde(default = "TransferConfig::default_verify_uploads")]
+    pub verify_uploads: bool, // Compare remote file size with local size after upload
+    #[serde(default)]
+    pub verify_retries: u8, // Amount of times an upload is retried if verification fails
+    pub post_download_command: Option<String>, // Command run after each successful download (security-sensitive; opt-in)
+    #[serde(default)]
+    pub create_parents: bool, // Create missing parent directories of the transfer destination
+    #[serde(default)]
+    pub create_download_dir: bool, // Create the local download directory if it doesn't exist
+    #[serde(default)]
+    pub confirm_size: u64, // Transfers bigger than this amount of bytes must be confirmed; 0 never asks
+    #[serde(default)]
+    pub max_depth: usize, // Directory levels walked by recursive transfers and deletes; 0 is unlimited
+    #[serde(default)]
+    pub buffer_auto_tune: bool, // Adjust the transfer buffer size according to the observed throughput
+    #[serde(default = "TransferConfig::default_buffer_max_size")]
+    pub buffer_max_size: usize, // Size (bytes) the auto-tuned transfer buffer never exceeds
+    pub remote_line_ending: Option<LineEnding>, // Convert the line endings of text files to this convention on upload; None never converts
+    #[serde(default)]
+    pub preserve_paths: bool, // Keep marks across directories and recreate their paths relative to the working directory
+    #[serde(default)]
+    pub follow_symlinks: bool, // Walk the directories symlinks point to during recursive downloads, instead of recreating the links
+    #[serde(default)]
+    pub resume_uploads: bool, // Append to a smaller, newer remote file instead of uploading the whole file again
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        TransferConfig {
+            verify_uploads: TransferConfig::default_verify_uploads(),
+            verify_retries: 0,
+            post_download_command: None,
+            create_parents: false,
+            create_download_dir: false,
+            confirm_size: 0,
+            max_depth: 0,
+            buffer_auto_tune: false,
+            buffer_max_size: TransferConfig::default_buffer_max_size(),
+            remote_line_ending: None,
+            preserve_paths: false,
+            follow_symlinks: false,
+            resume_uploads: false,
+        }
+    }
+}
+
+impl TransferConfig {
+    fn default_verify_uploads() -> bool {
+        true
+    }
+
+    fn default_buffer_max_size() -> usize {
+        4194304
+    }
+}
+
+#[derive(Deserialize, Serialize, std::fmt::Debug)]
+/// ## UiConfig
+///
+/// UiConfig contains the preferences for the user interface
+pub struct UiConfig {
+    #[serde(default = "UiConfig::default_show_log")]
+    pub show_log: bool, // Whether the log panel is visible in the explorer view
+    #[serde(default = "UiConfig::default_wrap_selection")]
+    pub wrap_selection: bool, // Whether explorer selection wraps around at the list boundaries
+    #[serde(default = "UiConfig::default_terminal_title")]
+    pub terminal_title: bool, // Whether the terminal title reports the transfer progress
+    #[serde(default = "UiConfig::default_log_size")]
+    pub log_size: usize, // Maximum amount of records kept in the log panel
+    #[serde(default)]
+    pub log_file: Option<PathBuf>, // File every log record is appended to, as soon as it's written
+    #[serde(default = "UiConfig::default_log_file_max_size")]
+    pub log_file_max_size: u64, // Size (bytes) the log file is rotated at; 0 is unlimited
+    #[serde(default)]
+    pub relative_time: bool, // Whether times in explorers and log are shown relative to now (e.g. `2 hours ago`)
+    #[serde(default = "UiConfig::default_show_link_target")]
+    pub show_link_target: bool, // Whether symlinks are displayed with their target (`name -> target`)
+    #[serde(default = "UiConfig::default_redraw_interval")]
+    pub redraw_interval: RedrawInterval, // How often the progress bar is redrawn during a transfer
+    #[serde(default)]
+    pub show_path_line: bool, // Whether a status line shows the absolute path of the selected entry
+    #[serde(default = "UiConfig::default_abort_key")]
+    pub abort_key: KeyBinding, // Key which aborts the running transfer or scan
+    #[serde(default = "UiConfig::default_show_banner")]
+    pub show_banner: bool, // Whether the server banner / welcome message is written to the log at connect
+    #[serde(default = "UiConfig::default_group_dirs")]
+    pub group_dirs: GroupDirs, // Where directories are listed in the explorers, whatever the sort key
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        UiConfig {
+            show_log: UiConfig::default_show_log(),
+            wrap_selection: UiConfig::default_wrap_selection(),
+            terminal_title: UiConfig::default_terminal_title(),
+            log_size: UiConfig::default_log_size(),
+            log_file: None,
+            log_file_max_size: UiConfig::default_log_file_max_size(),
+            relative_time: false,
+            show_link_target: UiConfig::default_show_link_target(),
+            redraw_interval: UiConfig::default_redraw_interval(),
+            show_path_line: false,
+            abort_key: UiConfig::default_abort_key(),
+            show_banner: UiConfig::default_show_banner(),
+            group_dirs: UiConfig::default_group_dirs(),
+        }
+    }
+}
+
+impl UiConfig {
+    fn default_show_log() -> bool {
+        true
+    }
+
+    fn default_wrap_selection() -> bool {
+        false
+    }
+
+    fn default_terminal_title() -> bool {
+        true
+    }
+
+    fn default_log_size() -> usize {
+        256
+    }
+
+    fn default_log_file_max_size() -> u64 {
+        10485760
+    }
+
+    fn default_show_link_target() -> bool {
+        true
+    }
+
+    fn default_redraw_interval() -> RedrawInterval {
+        RedrawInterval::Percent(1.0)
+    }
+
+    fn default_show_banner() -> bool {
+        true
+    }
+
+    fn default_group_dirs() -> GroupDirs {
+        GroupDirs::First
+    }
+
+    fn default_abort_key() -> KeyBinding {
+        KeyBinding {
+            ctrl: true,
+            alt: false,
+            key: KeyName::Char('c'),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, std::fmt::Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+/// ## GroupDirs
+///
+/// GroupDirs describes where directories are listed in the explorers (`first`, `last` or `mixed`),
+/// independently of the key the entries are sorted by
+pub enum GroupDirs {
+    First, // Directories before files
+    Last,  // Directories after files
+    Mixed, // Directories sorted along with files
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, std::fmt::Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+/// ## LineEnding
+///
+/// LineEnding describes a line ending convention (`lf` or `crlf`)
+pub enum LineEnding {
+    Lf,   // Unix
+    Crlf, // Windows
+}
+
+impl std::fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::Crlf => write!(f, "CRLF"),
+        }
+    }
+}
+
+impl LineEnding {
+    /// ### local
+    ///
+    /// Returns the line ending convention of this host
+    pub fn local() -> LineEnding {
+        match cfg!(windows) {
+            true => LineEnding::Crlf,
+            false => LineEnding::Lf,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, std::fmt::Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+/// ## RedrawInterval
+///
+/// RedrawInterval describes how often the progress bar is redrawn during a transfer.
+/// It's written as a percentage (e.g. `1%`) or as a time (e.g. `100ms`, `1s`)
+pub enum RedrawInterval {
+    Percent(f64), // Redraw each time the progress advances by this percentage
+    Millis(u64),  // Redraw each time this amount of milliseconds has elapsed
+}
+
+impl TryFrom<String> for RedrawInterval {
+    type Error = String;
+
+    fn try_from(interval: String) -> Result<Self, Self::Error> {
+        let interval: &str = interval.trim();
+        let bad_interval = || {
+            format!(
+                "Bad redraw interval '{}' (expected a percentage, e.g. `1%`, or a time, e.g. `100ms`)",
+                interval
+            )
+        };
+        if let Some(percent) = interval.strip_suffix('%') {
+            match percent.trim().parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => {
+                    Ok(RedrawInterval::Percent(percent))
+                }
+                _ => Err(bad_interval()),
+            }
+        } else if let Some(millis) = interval.strip_suffix("ms") {
+            millis
+                .trim()
+                .parse::<u64>()
+                .map(RedrawInterval::Millis)
+                .map_err(|_| bad_interval())
+        } else if let Some(secs) = interval.strip_suffix('s') {
+            secs.trim()
+                .parse::<u64>()
+                .ok()
+                .and_then(|secs| secs.checked_mul(1000))
+                .map(RedrawInterval::Millis)
+                .ok_or_else(bad_interval)
+        } else {
+            Err(bad_interval())
+        }
+    }
+}
+
+impl From<RedrawInterval> for String {
+    fn from(interval: RedrawInterval) -> Self {
+        match interval {
+            RedrawInterval::Percent(percent) => format!("{}%", percent),
+            RedrawInterval::Millis(millis) => format!("{}ms", millis),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, std::fmt::Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+/// ## KeyBinding
+///
+/// KeyBinding describes a key, optionally combined with modifiers.
+/// It's written as the key name, preceded by the modifiers (e.g. `CTRL+C`, `ALT+X`, `ESC`, `F2`)
+pub struct KeyBinding {
+    pub ctrl: bool,   // CTRL must be pressed
+    pub alt: bool,    // ALT must be pressed
+    pub key: KeyName, // The key
+}
+
+#[derive(Clone, Copy, std::fmt::Debug, PartialEq)]
+/// ## KeyName
+///
+/// KeyName describes the key of a key binding
+pub enum KeyName {
+    Char(char), // Character key; letters are always lowercase
+    Delete,
+    Esc,
+    Function(u8), // F1-F12
+    Tab,
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(binding: String) -> Result<Self, Self::Error> {
+        let bad_binding = || {
+            format!(
+                "Bad key binding '{}' (expected a key with optional modifiers, e.g. `CTRL+C`)",
+                binding
+            )
+        };
+        let mut parts: Vec<String> = binding
+            .split('+')
+            .map(|x| x.trim().to_uppercase())
+            .collect();
+        let key: String = parts.pop().unwrap_or_default();
+        let (mut ctrl, mut alt): (bool, bool) = (false, false);
+        for modifier in parts.iter() {
+            match modifier.as_str() {
+                "CTRL" => ctrl = true,
+                "ALT" => alt = true,
+                _ => return Err(bad_binding()),
+            }
+        }
+        let key: KeyName = match key.as_str() {
+            "DEL" | "DELETE" => KeyName::Delete,
+            "ESC" => KeyName::Esc,
+            "TAB" => KeyName::Tab,
+            key if key.chars().count() == 1 => {
+                KeyName::Char(key.chars().flat_map(|x| x.to_lowercase()).next().unwrap())
+            }
+            key => match key.strip_prefix('F').map(|x| x.parse::<u8>()) {
+                Some(Ok(n)) if (1..=12).contains(&n) => KeyName::Function(n),
+                _ => return Err(bad_binding()),
+            },
+        };
+        Ok(KeyBinding { ctrl, alt, key })
+    }
+}
+
+impl From<KeyBinding> for String {
+    fn from(binding: KeyBinding) -> Self {
+        let key: String = match binding.key {
+            KeyName::Char(ch) => ch.to_uppercase().collect(),
+            KeyName::Delete => String::from("DEL"),
+            KeyName::Esc => String::from("ESC"),
+            KeyName::Function(n) => format!("F{}", n),
+            KeyName::Tab => String::from("TAB"),
+        };
+        match (binding.ctrl, binding.alt) {
+            (true, true) => format!("CTRL+ALT+{}", key),
+            (true, false) => format!("CTRL+{}", key),
+            (false, true) => format!("ALT+{}", key),
+            (false, false) => key,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", String::from(*self))
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_config_mod_new() {
+        let config: UserConfig = UserConfig::default();
+        assert!(config.ftp.list_args.is_none());
+        assert_eq!(config.ftp.flush_interval, 0);
+        assert!(config.ftp.default_username.is_none());
+        assert_eq!(config.ssh.read_timeout, 60);
+        assert!(config.ssh.default_username.is_none());
+        assert_eq!(config.ssh.sftp_concurrency, 1);
+        assert_eq!(config.connection.max_connections, 4);
+        assert_eq!(config.connection.clock_skew_threshold, 0);
+        assert!(!config.connection.clock_skew_compensate);
+        assert!(config.transfer.verify_uploads);
+        assert_eq!(config.transfer.verify_retries, 0);
+        assert!(config.transfer.post_download_command.is_none());
+        assert!(!config.transfer.create_parents);
+        assert!(!config.transfer.create_download_dir);
+        assert_eq!(config.transfer.confirm_size, 0);
+        assert_eq!(config.transfer.max_depth, 0);
+        assert!(!config.transfer.buffer_auto_tune);
+        assert_eq!(config.transfer.buffer_max_size, 4194304);
+        assert!(config.transfer.remote_line_ending.is_none());
+        assert!(!config.transfer.preserve_paths);
+        assert!(!config.transfer.follow_symlinks);
+        assert!(!config.transfer.resume_uploads);
+        assert!(config.ui.show_log);
+        assert!(!config.ui.wrap_selection);
+        assert!(config.ui.terminal_title);
+        assert_eq!(config.ui.log_size, 256);
+        assert!(config.ui.log_file.is_none());
+        assert_eq!(config.ui.log_file_max_size, 10485760);
+        assert!(!config.ui.relative_time);
+        assert!(config.ui.show_link_target);
+        assert_eq!(config.ui.redraw_interval, RedrawInterval::Percent(1.0));
+        assert!(!config.ui.show_path_line);
+        assert_eq!(config.ui.abort_key.to_string().as_str(), "CTRL+C");
+        assert!(config.ui.show_banner);
+        assert_eq!(config.ui.group_dirs, GroupDirs::First);
+        assert!(config.selections.is_empty());
+        let mut selections: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        selections.insert(
+            String::from("audit"),
+            vec![PathBuf::from("/var/log/syslog")],
+        );
+        let config: UserConfig = UserConfig {
+            ftp: FtpConfig {
+                list_args: Some(String::from("-a")),
+                flush_interval: 1048576,
+                default_username: Some(String::from("anonymous")),
+            },
+            ssh: SshConfig {
+                read_timeout: 0,
+                default_username: Some(String::from("deploy")),
+                sftp_concurrency: 16,
+            },
+            connection: ConnectionConfig {
+                max_connections: 2,
+                clock_skew_threshold: 5,
+                clock_skew_compensate: true,
+            },
+            transfer: TransferConfig {
+                verify_uploads: false,
+                verify_retries: 2,
+                post_download_command: Some(String::from("chmod 600 {path}")),
+                create_parents: true,
+                create_download_dir: true,
+                confirm_size: 1073741824,
+                max_depth: 3,
+                buffer_auto_tune: true,
+                buffer_max_size: 1048576,
+                remote_line_ending: Some(LineEnding::Crlf),
+                preserve_paths: true,
+                follow_symlinks: true,
+                resume_uploads: true,
+            },
+            ui: UiConfig {
+                show_log: false,
+                wrap_selection: true,
+                terminal_title: false,
+                log_size: 1024,
+                log_file: Some(PathBuf::from("/var/log/gateway.log")),
+                log_file_max_size: 0,
+                relative_time: true,
+                show_link_target: false,
+                redraw_interval: RedrawInterval::Millis(250),
+                show_path_line: true,
+                abort_key: KeyBinding {
+                    ctrl: false,
+                    alt: false,
+                    key: KeyName::Esc,
+                },
+                show_banner: false,
+                group_dirs: GroupDirs::Mixed,
+            },
+            selections,
+        };
+        assert_eq!(config.ftp.list_args.as_deref().unwrap(), "-a");
+        assert_eq!(config.ftp.flush_interval, 1048576);
+        assert_eq!(config.ftp.default_username.as_deref().unwrap(), "anonymous");
+        assert_eq!(config.ssh.read_timeout, 0);
+        assert_eq!(config.ssh.default_username.as_deref().unwrap(), "deploy");
+        assert_eq!(config.ssh.sftp_concurrency, 16);
+        assert_eq!(config.connection.max_connections, 2);
+        assert_eq!(config.connection.clock_skew_threshold, 5);
+        assert!(config.connection.clock_skew_compensate);
+        assert!(!config.transfer.verify_uploads);
+        assert_eq!(config.transfer.verify_retries, 2);
+        assert_eq!(
+            config.transfer.post_download_command.as_deref().unwrap(),
+            "chmod 600 {path}"
+        );
+        assert!(config.transfer.create_parents);
+        assert!(config.transfer.create_download_dir);
+        assert_eq!(config.transfer.confirm_size, 1073741824);
+        assert_eq!(config.transfer.max_depth, 3);
+        assert!(config.transfer.buffer_auto_tune);
+        assert_eq!(config.transfer.buffer_max_size, 1048576);
+        assert_eq!(config.transfer.remote_line_ending, Some(LineEnding::Crlf));
+        assert!(config.transfer.preserve_paths);
+        assert!(config.transfer.follow_symlinks);
+        assert!(config.transfer.resume_uploads);
+        assert!(!config.ui.show_log);
+        assert!(config.ui.wrap_selection);
+        assert!(!config.ui.terminal_title);
+        assert_eq!(config.ui.log_size, 1024);
+        assert_eq!(
+            config.ui.log_file.as_deref().unwrap(),
+            std::path::Path::new("/var/log/gateway.log")
+        );
+        assert_eq!(config.ui.log_file_max_size, 0);
+        assert!(config.ui.relative_time);
+        assert!(!config.ui.show_link_target);
+        assert_eq!(config.ui.redraw_interval, RedrawInterval::Millis(250));
+        assert!(config.ui.show_path_line);
+        assert_eq!(config.ui.abort_key.key, KeyName::Esc);
+        assert!(!config.ui.show_banner);
+        assert_eq!(config.ui.group_dirs, GroupDirs::Mixed);
+        assert_eq!(
+            config.selections.get("audit").unwrap(),
+            &vec![PathBuf::from("/var/log/syslog")]
+        );
+    }
+    #[test]
+    fn test_config_mod_redraw_interval() {
+        assert_eq!(
+            RedrawInterval::try_from(String::from("1%")).ok().unwrap(),
+            RedrawInterval::Percent(1.0)
+        );
+        assert_eq!(
+            RedrawInterval::try_from(String::from(" 0.5 %"))
+                .ok()
+                .unwrap(),
+            RedrawInterval::Percent(0.5)
+        );
+        assert_eq!(
+            RedrawInterval::try_from(String::from("100ms"))
+                .ok()
+                .unwrap(),
+            RedrawInterval::Millis(100)
+        );
+        assert_eq!(
+            RedrawInterval::try_from(String::from("2s")).ok().unwrap(),
+            RedrawInterval::Millis(2000)
+        );
+        // Bad intervals
+        assert!(RedrawInterval::try_from(String::from("")).is_err());
+        assert!(RedrawInterval::try_from(String::from("1")).is_err());
+        assert!(RedrawInterval::try_from(String::from("101%")).is_err());
+        assert!(RedrawInterval::try_from(String::from("-1%")).is_err());
+        assert!(RedrawInterval::try_from(String::from("1.5ms")).is_err());
+        assert!(RedrawInterval::try_from(String::from("fast")).is_err());
+        // To string
+        assert_eq!(String::from(RedrawInterval::Percent(1.0)), "1%");
+        assert_eq!(String::from(RedrawInterval::Percent(2.5)), "2.5%");
+        assert_eq!(String::from(RedrawInterval::Millis(100)), "100ms");
+    }
+
+    #[test]
+    fn test_config_mod_key_binding() {
+        let parse = |binding: &str| KeyBinding::try_from(String::from(binding));
+        assert_eq!(
+            parse("CTRL+C").ok().unwrap(),
+            KeyBinding {
+                ctrl: true,
+                alt: false,
+                key: KeyName::Char('c')
+            }
+        );
+        assert_eq!(
+            parse("ctrl + alt + X").ok().unwrap(),
+            KeyBinding {
+                ctrl: true,
+                alt: true,
+                key: KeyName::Char('x')
+            }
+        );
+        assert_eq!(parse("esc").ok().unwrap().key, KeyName::Esc);
+        assert_eq!(parse("F12").ok().unwrap().key, KeyName::Function(12));
+        assert_eq!(parse("Del").ok().unwrap().key, KeyName::Delete);
+        assert_eq!(parse("Tab").ok().unwrap().key, KeyName::Tab);
+        assert_eq!(parse("F").ok().unwrap().key, KeyName::Char('f'));
+        // Bad bindings
+        assert!(parse("").is_err());
+        assert!(parse("CTRL+").is_err());
+        assert!(parse("SHIFT+A").is_err());
+        assert!(parse("F13").is_err());
+        assert!(parse("ENTER").is_err());
+        // To string
+        assert_eq!(parse("ctrl+c").ok().unwrap().to_string().as_str(), "CTRL+C");
+        assert_eq!(
+            parse("alt+ctrl+f2").ok().unwrap().to_string().as_str(),
+            "CTRL+ALT+F2"
+        );
+        assert_eq!(parse("del").ok().unwrap().to_string().as_str(), "DEL");
+    }
+}
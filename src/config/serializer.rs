@@ -0,0 +1,369 @@
+//! ## Serializer
+//!
+//! `serializer` is the module which provides the serializer/deserializer for configuration
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+use super::UserConfig;
+use crate::bookmarks::{SerializerError, SerializerErrorKind};
+
+use std::io::{Read, Write};
+
+pub struct ConfigSerializer {}
+
+impl ConfigSerializer {
+    /// ### serialize
+    ///
+    /// Serialize `UserConfig` into TOML and write content to writable
+    pub fn serialize(
+        &self,
+        mut writable: Box<dyn Write>,
+        cfg: &UserConfig,
+    ) -> Result<(), SerializerError> {
+        // Serialize content
+        let data: String = match toml::ser::to_string(cfg) {
+            Ok(dt) => dt,
+            Err(err) => {
+                return Err(SerializerError::new_ex(
+                    SerializerErrorKind::SerializationError,
+                    err.to_string(),
+                ))
+            }
+        };
+        // Write file
+        match writable.write_all(data.as_bytes()) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(SerializerError::new_ex(
+                SerializerErrorKind::IoError,
+                err.to_string(),
+            )),
+        }
+    }
+
+    /// ### deserialize
+    ///
+    /// Read data from readable and deserialize its content as TOML
+    pub fn deserialize(&self, mut readable: Box<dyn Read>) -> Result<UserConfig, SerializerError> {
+        // Read file content
+        let mut data: String = String::new();
+        if let Err(err) = readable.read_to_string(&mut data) {
+            return Err(SerializerError::new_ex(
+                SerializerErrorKind::IoError,
+                err.to_string(),
+            ));
+        }
+        // Deserialize
+        match toml::de::from_str(data.as_str()) {
+            Ok(config) => Ok(config),
+            Err(err) => Err(SerializerError::new_ex(
+                SerializerErrorKind::SyntaxError,
+                err.to_string(),
+            )),
+        }
+    }
+}
+
+// Tests
+
+#[cfg(test)]
+mod tests {
+
+    use super::super::{
+        ConnectionConfig, FtpConfig, GroupDirs, KeyBinding, KeyName, LineEnding, RedrawInterval,
+        SshConfig, TransferConfig, UiConfig,
+    };
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::io::{Seek, SeekFrom};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_config_serializer_deserialize_ok() {
+        let toml_file: tempfile::NamedTempFile = create_good_toml();
+        toml_file.as_file().sync_all().unwrap();
+        toml_file.as_file().seek(SeekFrom::Start(0)).unwrap();
+        // Parse
+        let deserializer: ConfigSerializer = ConfigSerializer {};
+        let config = deserializer.deserialize(Box::new(toml_file));
+        assert!(config.is_ok());
+        let config: UserConfig = config.ok().unwrap();
+        // Verify configuration
+        assert_eq!(*config.ftp.list_args.as_ref().unwrap(), String::from("-a"));
+        assert_eq!(config.ftp.flush_interval, 262144);
+        assert_eq!(config.ftp.default_username.as_deref().unwrap(), "anonymous");
+        assert_eq!(config.ssh.read_timeout, 30);
+        assert_eq!(config.ssh.default_username.as_deref().unwrap(), "deploy");
+        assert_eq!(config.ssh.sftp_concurrency, 8);
+        assert_eq!(config.connection.max_connections, 1);
+        assert_eq!(config.connection.clock_skew_threshold, 10);
+        assert!(config.connection.clock_skew_compensate);
+        assert!(!config.transfer.verify_uploads);
+        assert_eq!(config.transfer.verify_retries, 3);
+        assert_eq!(
+            config.transfer.post_download_command.as_deref().unwrap(),
+            "clamscan {path}"
+        );
+        assert!(config.transfer.create_parents);
+        assert!(config.transfer.create_download_dir);
+        assert_eq!(config.transfer.confirm_size, 1073741824);
+        assert_eq!(config.transfer.max_depth, 8);
+        assert!(config.transfer.buffer_auto_tune);
+        assert_eq!(config.transfer.buffer_max_size, 16777216);
+        assert_eq!(config.transfer.remote_line_ending, Some(LineEnding::Crlf));
+        assert!(config.transfer.preserve_paths);
+        assert!(config.transfer.follow_symlinks);
+        assert!(config.transfer.resume_uploads);
+        assert!(!config.ui.show_log);
+        assert!(config.ui.wrap_selection);
+        assert!(!config.ui.terminal_title);
+        assert_eq!(config.ui.log_size, 1024);
+        assert_eq!(
+            config.ui.log_file.as_deref().unwrap(),
+            std::path::Path::new("/home/omar/gateway.log")
+        );
+        assert_eq!(config.ui.log_file_max_size, 1048576);
+        assert!(config.ui.relative_time);
+        assert!(!config.ui.show_link_target);
+        assert_eq!(config.ui.redraw_interval, RedrawInterval::Millis(100));
+        assert!(config.ui.show_path_line);
+        assert_eq!(
+            config.ui.abort_key,
+            KeyBinding {
+                ctrl: false,
+                alt: true,
+                key: KeyName::Char('q')
+            }
+        );
+        assert!(!config.ui.show_banner);
+        assert_eq!(config.ui.group_dirs, GroupDirs::Last);
+        assert_eq!(
+            config.selections.get("audit").unwrap(),
+            &vec![PathBuf::from("/var/log/syslog"), PathBuf::from("auth.log")]
+        );
+    }
+
+    #[test]
+    fn test_config_serializer_deserialize_empty() {
+        let mut toml_file: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        toml_file.write_all(b"\n").unwrap();
+        toml_file.as_file().sync_all().unwrap();
+        toml_file.as_file().seek(SeekFrom::Start(0)).unwrap();
+        // Parse; missing sections fall back to default
+        let deserializer: ConfigSerializer = ConfigSerializer {};
+        let config: UserConfig = deserializer.deserialize(Box::new(toml_file)).ok().unwrap();
+        assert!(config.ftp.list_args.is_none());
+        assert_eq!(config.ftp.flush_interval, 0);
+        assert!(config.ftp.default_username.is_none());
+        assert_eq!(config.ssh.read_timeout, 60);
+        assert!(config.ssh.default_username.is_none());
+        assert_eq!(config.ssh.sftp_concurrency, 1);
+        assert_eq!(config.connection.max_connections, 4);
+        assert_eq!(config.connection.clock_skew_threshold, 0);
+        assert!(!config.connection.clock_skew_compensate);
+        assert!(config.transfer.verify_uploads);
+        assert_eq!(config.transfer.verify_retries, 0);
+        assert!(config.transfer.post_download_command.is_none());
+        assert!(!config.transfer.create_parents);
+        assert!(!config.transfer.create_download_dir);
+        assert_eq!(config.transfer.confirm_size, 0);
+        assert_eq!(config.transfer.max_depth, 0);
+        assert!(!config.transfer.buffer_auto_tune);
+        assert_eq!(config.transfer.buffer_max_size, 4194304);
+        assert!(config.transfer.remote_line_ending.is_none());
+        assert!(!config.transfer.preserve_paths);
+        assert!(!config.transfer.follow_symlinks);
+        assert!(!config.transfer.resume_uploads);
+        assert!(config.ui.show_log);
+        assert!(!config.ui.wrap_selection);
+        assert!(config.ui.terminal_title);
+        assert_eq!(config.ui.log_size, 256);
+        assert!(config.ui.log_file.is_none());
+        assert_eq!(config.ui.log_file_max_size, 10485760);
+        assert!(!config.ui.relative_time);
+        assert!(config.ui.show_link_target);
+        assert_eq!(config.ui.redraw_interval, RedrawInterval::Percent(1.0));
+        assert!(!config.ui.show_path_line);
+        assert_eq!(config.ui.abort_key.to_string().as_str(), "CTRL+C");
+        assert!(config.ui.show_banner);
+        assert_eq!(config.ui.group_dirs, GroupDirs::First);
+        assert!(config.selections.is_empty());
+    }
+
+    #[test]
+    fn test_config_serializer_deserialize_nok() {
+        let toml_file: tempfile::NamedTempFile = create_bad_toml();
+        toml_file.as_file().sync_all().unwrap();
+        toml_file.as_file().seek(SeekFrom::Start(0)).unwrap();
+        // Parse
+        let deserializer: ConfigSerializer = ConfigSerializer {};
+        assert!(deserializer.deserialize(Box::new(toml_file)).is_err());
+        // Bad redraw interval
+        let mut toml_file: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        toml_file
+            .write_all(b"[ui]\nredraw_interval = \"often\"\n")
+            .unwrap();
+        toml_file.as_file().seek(SeekFrom::Start(0)).unwrap();
+        assert!(deserializer.deserialize(Box::new(toml_file)).is_err());
+        // Bad abort key
+        let mut toml_file: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        toml_file
+            .write_all(b"[ui]\nabort_key = \"SHIFT+ENTER\"\n")
+            .unwrap();
+        toml_file.as_file().seek(SeekFrom::Start(0)).unwrap();
+        assert!(deserializer.deserialize(Box::new(toml_file)).is_err());
+        // Bad directory grouping
+        let mut toml_file: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        toml_file
+            .write_all(b"[ui]\ngroup_dirs = \"middle\"\n")
+            .unwrap();
+        toml_file.as_file().seek(SeekFrom::Start(0)).unwrap();
+        assert!(deserializer.deserialize(Box::new(toml_file)).is_err());
+    }
+
+    #[test]
+    fn test_config_serializer_serialize() {
+        let config: UserConfig = UserConfig {
+            ftp: FtpConfig {
+                list_args: Some(String::from("-la")),
+                flush_interval: 0,
+                default_username: None,
+            },
+            ssh: SshConfig {
+                read_timeout: 60,
+                default_username: Some(String::from("deploy")),
+                sftp_concurrency: 1,
+            },
+            connection: ConnectionConfig {
+                max_connections: 4,
+                clock_skew_threshold: 5,
+                clock_skew_compensate: false,
+            },
+            transfer: TransferConfig {
+                verify_uploads: true,
+                verify_retries: 1,
+                post_download_command: None,
+                create_parents: false,
+                create_download_dir: false,
+                confirm_size: 0,
+                max_depth: 0,
+                buffer_auto_tune: false,
+                buffer_max_size: 4194304,
+                remote_line_ending: None,
+                preserve_paths: false,
+                follow_symlinks: false,
+                resume_uploads: true,
+            },
+            ui: UiConfig {
+                show_log: true,
+                wrap_selection: true,
+                terminal_title: true,
+                log_size: 256,
+                log_file: Some(PathBuf::from("/home/omar/gateway.log")),
+                log_file_max_size: 10485760,
+                relative_time: false,
+                show_link_target: true,
+                redraw_interval: RedrawInterval::Percent(1.0),
+                show_path_line: false,
+                abort_key: KeyBinding {
+                    ctrl: true,
+                    alt: false,
+                    key: KeyName::Char('c'),
+                },
+                show_banner: true,
+                group_dirs: GroupDirs::First,
+            },
+            selections: HashMap::new(),
+        };
+        let tmpfile: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        // Serialize
+        let serializer: ConfigSerializer = ConfigSerializer {};
+        assert!(serializer.serialize(Box::new(tmpfile), &config).is_ok());
+    }
+
+    fn create_good_toml() -> tempfile::NamedTempFile {
+        // Write
+        let mut tmpfile: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        let file_content: &str = r#"
+        [ftp]
+        list_args = "-a"
+        flush_interval = 262144
+        default_username = "anonymous"
+
+        [ssh]
+        read_timeout = 30
+        default_username = "deploy"
+        sftp_concurrency = 8
+
+        [connection]
+        max_connections = 1
+        clock_skew_threshold = 10
+        clock_skew_compensate = true
+
+        [transfer]
+        verify_uploads = false
+        verify_retries = 3
+        post_download_command = "clamscan {path}"
+        create_parents = true
+        create_download_dir = true
+        confirm_size = 1073741824
+        max_depth = 8
+        buffer_auto_tune = true
+        buffer_max_size = 16777216
+        remote_line_ending = "crlf"
+        preserve_paths = true
+        follow_symlinks = true
+        resume_uploads = true
+
+        [ui]
+        show_log = false
+        wrap_selection = true
+        terminal_title = false
+        log_size = 1024
+        log_file = "/home/omar/gateway.log"
+        log_file_max_size = 1048576
+        relative_time = true
+        show_link_target = false
+        redraw_interval = "100ms"
+        show_path_line = true
+        abort_key = "ALT+Q"
+        show_banner = false
+        group_dirs = "last"
+
+        [selections]
+        audit = ["/var/log/syslog", "auth.log"]
+        "#;
+        tmpfile.write_all(file_content.as_bytes()).unwrap();
+        tmpfile
+    }
+
+    fn create_bad_toml() -> tempfile::NamedTempFile {
+        // Write
+        let mut tmpfile: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        let file_content: &str = r#"
+        [ftp]
+        list_args = 1
+        "#;
+        tmpfile.write_all(file_content.as_bytes()).unwrap();
+        tmpfile
+    }
+}
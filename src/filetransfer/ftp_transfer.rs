@@ -30,15 +30,20 @@ extern crate regex;
 
 use super::{FileTransfer, FileTransferError, FileTransferErrorType};
 use crate::fs::{FsDirectory, FsEntry, FsFile};
-use crate::utils::parser::parse_lstime;
+use crate::utils::parser::{parse_lstime, parse_lstime_iso};
 
 // Includes
 use ftp4::native_tls::TlsConnector;
-use ftp4::FtpStream;
+use ftp4::types::{FileType, FormatControl};
+use ftp4::{status, FtpError, FtpStream};
 use regex::Regex;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// Amount of times opening the data connection is retried
+const DATA_CONNECTION_RETRIES: u8 = 2;
 
 /// ## FtpFileTransfer
 ///
@@ -46,6 +51,10 @@ use std::time::SystemTime;
 pub struct FtpFileTransfer {
     stream: Option<FtpStream>,
     ftps: bool,
+    list_args: Option<String>,
+    data_retries: u8,
+    timeout: Duration,
+    warnings: Vec<String>,
 }
 
 impl FtpFileTransfer {
@@ -53,7 +62,206 @@ impl FtpFileTransfer {
     ///
     /// Instantiates a new `FtpFileTransfer`
     pub fn new(ftps: bool) -> FtpFileTransfer {
-        FtpFileTransfer { stream: None, ftps }
+        FtpFileTransfer {
+            stream: None,
+            ftps,
+            list_args: None,
+            data_retries: DATA_CONNECTION_RETRIES,
+            timeout: Duration::from_secs(0),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// ### set_timeout
+    ///
+    /// Set how long a reply on the control connection may be waited for before the operation fails.
+    /// Zero means no timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// ### read_timeout
+    ///
+    /// Get the read timeout of the control connection; None if disabled
+    fn read_timeout(&self) -> Option<Duration> {
+        match self.timeout.as_secs() == 0 && self.timeout.subsec_nanos() == 0 {
+            true => None,
+            false => Some(self.timeout),
+        }
+    }
+
+    /// ### set_list_args
+    ///
+    /// Set arguments to prepend to the path in LIST command (e.g. `-a` to show hidden files).
+    /// Support for these arguments depends on the server; if they are rejected, plain LIST is used
+    pub fn set_list_args(&mut self, args: Option<String>) {
+        self.list_args = match args {
+            Some(args) if !args.trim().is_empty() => Some(String::from(args.trim())),
+            _ => None,
+        };
+    }
+
+    /// ### list_cmd_arg
+    ///
+    /// Make the argument for the LIST command for provided path
+    fn list_cmd_arg(&self, path: &Path) -> String {
+        match &self.list_args {
+            Some(args) => format!("{} {}", args, path.display()),
+            None => format!("{}", path.display()),
+        }
+    }
+
+    /// ### is_no_such_file
+    ///
+    /// Returns whether the error returned by `stat_file` is a clear "no such file" reply to SIZE
+    fn is_no_such_file(err: &FileTransferError) -> bool {
+        let msg: String = err.to_string().to_lowercase();
+        msg.contains("got response: 550")
+            && [
+                "no such file",
+                "not found",
+                "does not exist",
+                "doesn't exist",
+            ]
+            .iter()
+            .any(|x| msg.contains(x))
+    }
+
+    /// ### is_listed
+    ///
+    /// Returns whether `file` is listed in its parent directory; an error is returned if the directory can't be listed
+    fn is_listed(&mut self, file: &Path) -> Result<bool, FileTransferError> {
+        // Relative names are listed in the working directory
+        let parent: PathBuf = match file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => PathBuf::from(parent),
+            _ => PathBuf::from("."),
+        };
+        match self.list_dir(parent.as_path()) {
+            Ok(entries) => Ok(entries.iter().any(|x| {
+                file.file_name()
+                    .map(|name| name.to_string_lossy() == x.get_name().as_str())
+                    .unwrap_or(false)
+            })),
+            Err(err) => Err(FileTransferError::new_ex(
+                FileTransferErrorType::FileCreateDenied,
+                format!(
+                    "could not check whether \"{}\" already exists: {}",
+                    file.display(),
+                    err
+                ),
+            )),
+        }
+    }
+
+    /// ### with_data_retry
+    ///
+    /// Run an operation which opens a data connection.
+    /// If the data connection fails (e.g. passive connection dropped by NAT), the operation is retried up to `retries` times;
+    /// each retry is reported into `warnings`
+    fn with_data_retry<T, F>(
+        stream: &mut FtpStream,
+        retries: u8,
+        warnings: &mut Vec<String>,
+        mut op: F,
+    ) -> Result<T, FtpError>
+    where
+        F: FnMut(&mut FtpStream) -> Result<T, FtpError>,
+    {
+        let mut attempt: u8 = 0;
+        let timeout: Option<Duration> = stream.get_ref().read_timeout().ok().flatten();
+        loop {
+            match op(stream) {
+                Err(FtpError::ConnectionError(err)) if attempt < retries => {
+                    attempt += 1;
+                    warnings.push(format!(
+                        "Could not open data connection ({}); retrying ({}/{})",
+                        err, attempt, retries
+                    ));
+                    // Consume the reply to the failed command, so the control channel stays in sync
+                    let reply_timeout: Duration = timeout
+                        .unwrap_or(Duration::from_secs(5))
+                        .min(Duration::from_secs(5));
+                    let _ = stream.get_ref().set_read_timeout(Some(reply_timeout));
+                    let _ = stream.read_response_in(&[
+                        status::CANNOT_OPEN_DATA_CONNECTION,
+                        status::TRANSER_ABORTED,
+                    ]);
+                    let _ = stream.get_ref().set_read_timeout(timeout);
+                    thread::sleep(Duration::from_millis(500));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// ### stat_file
+    ///
+    /// Stat file at `path` using `SIZE` and `MDTM`.
+    /// Some servers refuse `SIZE` in ASCII mode (e.g. `550 SIZE not allowed in ASCII mode`), so the queries
+    /// are issued in binary mode (`TYPE I`); the ASCII type, which is the session default used by transfers,
+    /// is restored afterwards. If `SIZE` fails, the entry may be a directory, which can't be told without
+    /// listing its parent: the error is then reported as unsupported, so callers fall back to listing
+    fn stat_file(stream: &mut FtpStream, path: &Path) -> Result<FsEntry, FileTransferError> {
+        let pathname: String = path.to_string_lossy().to_string();
+        if let Err(err) = stream.transfer_type(FileType::Binary) {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::ProtocolError,
+                format!("TYPE I: {}", err),
+            ));
+        }
+        let size: Result<Option<usize>, FtpError> = stream.size(pathname.as_str());
+        let mtime: Option<SystemTime> = match size {
+            Ok(Some(_)) => stream
+                .mdtm(pathname.as_str())
+                .ok()
+                .flatten()
+                .map(SystemTime::from),
+            _ => None,
+        };
+        let restored: Result<(), FtpError> =
+            stream.transfer_type(FileType::Ascii(FormatControl::Default));
+        let size: usize = match size {
+            Ok(Some(size)) => size,
+            Ok(None) => {
+                return Err(FileTransferError::new_ex(
+                    FileTransferErrorType::ProtocolError,
+                    String::from("SIZE: unexpected response"),
+                ))
+            }
+            Err(err) => {
+                return Err(FileTransferError::new_ex(
+                    FileTransferErrorType::UnsupportedFeature,
+                    format!("SIZE: {}", err),
+                ))
+            }
+        };
+        if let Err(err) = restored {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::ProtocolError,
+                format!("TYPE A: {}", err),
+            ));
+        }
+        let mtime: SystemTime = mtime.unwrap_or(SystemTime::UNIX_EPOCH);
+        Ok(FsEntry::File(FsFile {
+            name: path
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            abs_path: PathBuf::from(path),
+            last_change_time: mtime,
+            last_access_time: mtime,
+            creation_time: mtime,
+            size,
+            ftype: path.extension().map(|s| String::from(s.to_string_lossy())),
+            readonly: false,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex: None,
+            nlink: None,
+            inode: None,
+        }))
     }
 
     /// ### parse_list_line
@@ -63,7 +271,7 @@ impl FtpFileTransfer {
         // Prepare list regex
         // NOTE: about this damn regex <https://stackoverflow.com/questions/32480890/is-there-a-regex-to-parse-the-values-from-an-ftp-directory-listing>
         lazy_static! {
-            static ref LS_RE: Regex = Regex::new(r#"^([\-ld])([\-rwxs]{9})\s+(\d+)\s+(\w+)\s+(\w+)\s+(\d+)\s+(\w{3}\s+\d{1,2}\s+(?:\d{1,2}:\d{1,2}|\d{4}))\s+(.+)$"#).unwrap();
+            static ref LS_RE: Regex = Regex::new(r#"^([\-ld])([\-rwxs]{9})\s+(\d+)\s+(\w+)\s+(\w+)\s+(\d+)\s+(\w{3}\s+\d{1,2}\s+(?:\d{1,2}:\d{1,2}|\d{4})|\d{4}-\d{2}-\d{2}(?:\s+\d{1,2}:\d{2}(?::\d{2}(?:\.\d+)?(?:\s+[+\-]\d{4})?)?)?)\s+(.+)$"#).unwrap();
         }
         // Apply regex to result
         match LS_RE.captures(line) {
@@ -141,15 +349,16 @@ impl FtpFileTransfer {
                     };
                     (owner_pex, group_pex, others_pex)
                 };
-                // Parse mtime and convert to SystemTime
-                let mtime: SystemTime = match parse_lstime(
-                    metadata.get(7).unwrap().as_str(),
-                    "%b %d %Y",
-                    "%b %d %H:%M",
-                ) {
+                // Parse mtime and convert to SystemTime; try month name syntax first, then numeric ISO
+                let mtime_str: &str = metadata.get(7).unwrap().as_str();
+                let mtime: SystemTime = match parse_lstime(mtime_str, "%b %d %Y", "%b %d %H:%M")
+                    .or_else(|_| parse_lstime_iso(mtime_str))
+                {
                     Ok(t) => t,
                     Err(_) => SystemTime::UNIX_EPOCH,
                 };
+                // Get link count
+                let nlink: Option<u64> = metadata.get(3).unwrap().as_str().parse::<u64>().ok();
                 // Get uid
                 let uid: Option<u32> = match metadata.get(4).unwrap().as_str().parse::<u32>() {
                     Ok(uid) => Some(uid),
@@ -187,9 +396,12 @@ impl FtpFileTransfer {
                         creation_time: mtime,
                         readonly: false,
                         symlink: None,
+                        link_target: None,
                         user: uid,
                         group: gid,
                         unix_pex: Some(unix_pex),
+                        nlink,
+                        inode: None,
                     }),
                     false => FsEntry::File(FsFile {
                         name: file_name,
@@ -201,15 +413,59 @@ impl FtpFileTransfer {
                         ftype: extension,
                         readonly: false,
                         symlink: None,
+                        link_target: None,
                         user: uid,
                         group: gid,
                         unix_pex: Some(unix_pex),
+                        nlink,
+                        inode: None,
                     }),
                 })
             }
             None => Err(()),
         }
     }
+
+    /// ### parse_list_line_loose
+    ///
+    /// Fallback for LIST lines which `parse_list_line` can't parse (e.g. unusual server formats).
+    /// Only the file name (last token of the line) is extracted; the entry is returned as a file without metadata.
+    /// Empty lines, `total` lines, special files and `.`/`..` are ignored
+    fn parse_list_line_loose(&self, path: &Path, line: &str) -> Result<FsEntry, ()> {
+        lazy_static! {
+            static ref SPECIAL_RE: Regex = Regex::new(r#"^[bcps][\-rwxsStT]{9}"#).unwrap();
+        }
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with("total ") || SPECIAL_RE.is_match(line) {
+            return Err(());
+        }
+        let file_name: String = match line.split_whitespace().last() {
+            Some(".") | Some("..") | None => return Err(()),
+            Some(name) => String::from(name),
+        };
+        let mut abs_path: PathBuf = PathBuf::from(path);
+        abs_path.push(file_name.as_str());
+        let extension: Option<String> = abs_path
+            .extension()
+            .map(|s| String::from(s.to_string_lossy()));
+        Ok(FsEntry::File(FsFile {
+            name: file_name,
+            abs_path,
+            last_change_time: SystemTime::UNIX_EPOCH,
+            last_access_time: SystemTime::UNIX_EPOCH,
+            creation_time: SystemTime::UNIX_EPOCH,
+            size: 0,
+            ftype: extension,
+            readonly: false,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex: None,
+            nlink: None,
+            inode: None,
+        }))
+    }
 }
 
 impl FileTransfer for FtpFileTransfer {
@@ -225,7 +481,7 @@ impl FileTransfer for FtpFileTransfer {
         password: Option<String>,
     ) -> Result<Option<String>, FileTransferError> {
         // Get stream
-        let mut stream: FtpStream = match FtpStream::connect(format!("{}:{}", address, port)) {
+        let mut stream: FtpStream = match FtpStream::connect((address.as_str(), port)) {
             Ok(stream) => stream,
             Err(err) => {
                 return Err(FileTransferError::new_ex(
@@ -234,6 +490,13 @@ impl FileTransfer for FtpFileTransfer {
                 ))
             }
         };
+        // Replies which never come fail the operation instead of blocking forever
+        if let Err(err) = stream.get_ref().set_read_timeout(self.read_timeout()) {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::ConnectionError,
+                format!("{}", err),
+            ));
+        }
         // If SSL, open secure session
         if self.ftps {
             let ctx = match TlsConnector::builder()
@@ -349,17 +612,47 @@ impl FileTransfer for FtpFileTransfer {
     /// List directory entries
 
     fn list_dir(&mut self, path: &Path) -> Result<Vec<FsEntry>, FileTransferError> {
+        let list_arg: String = self.list_cmd_arg(path);
+        let has_args: bool = self.list_args.is_some();
+        let retries: u8 = self.data_retries;
+        let warnings: &mut Vec<String> = &mut self.warnings;
         match &mut self.stream {
-            Some(stream) => match stream.list(Some(&path.to_string_lossy())) {
+            Some(stream) => match Self::with_data_retry(stream, retries, warnings, |stream| {
+                stream.list(Some(list_arg.as_str()))
+            })
+            .or_else(|err| {
+                // If server rejected arguments, fall back to plain LIST
+                match has_args {
+                    true => Self::with_data_retry(stream, retries, warnings, |stream| {
+                        stream.list(Some(&path.to_string_lossy()))
+                    }),
+                    false => Err(err),
+                }
+            }) {
                 Ok(entries) => {
                     // Prepare result
                     let mut result: Vec<FsEntry> = Vec::with_capacity(entries.len());
+                    let mut loose: usize = 0;
                     // Iterate over entries
                     for entry in entries.iter() {
-                        if let Ok(file) = self.parse_list_line(path, entry) {
-                            result.push(file);
+                        match self.parse_list_line(path, entry) {
+                            Ok(file) => result.push(file),
+                            Err(_) => {
+                                // Show at least the file name
+                                if let Ok(file) = self.parse_list_line_loose(path, entry) {
+                                    loose += 1;
+                                    result.push(file);
+                                }
+                            }
                         }
                     }
+                    if loose > 0 {
+                        self.warnings.push(format!(
+                            "Could not parse {} entries of \"{}\" listing; they're shown without metadata",
+                            loose,
+                            path.display()
+                        ));
+                    }
                     Ok(result)
                 }
                 Err(err) => Err(FileTransferError::new_ex(
@@ -391,6 +684,45 @@ impl FileTransfer for FtpFileTransfer {
         }
     }
 
+    /// ### touch
+    ///
+    /// Create an empty file, storing no data; since STOR overwrites, the file is created only if it surely doesn't exist:
+    /// either the server replies to SIZE that there's no such file, or the file isn't listed in its parent directory
+    fn touch(&mut self, file: &Path) -> Result<(), FileTransferError> {
+        let exists: bool = match &mut self.stream {
+            Some(stream) => match Self::stat_file(stream, file) {
+                Ok(_) => true,
+                Err(err) if Self::is_no_such_file(&err) => false,
+                // Inconclusive reply (e.g. `550 Could not get file size`): look for the file in the listing
+                Err(_) => self.is_listed(file)?,
+            },
+            None => {
+                return Err(FileTransferError::new(
+                    FileTransferErrorType::UninitializedSession,
+                ))
+            }
+        };
+        if exists {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::FileCreateDenied,
+                format!("\"{}\" already exists", file.display()),
+            ));
+        }
+        let name: String = file.to_string_lossy().to_string();
+        match self
+            .stream
+            .as_mut()
+            .unwrap()
+            .put(name.as_str(), &mut std::io::empty())
+        {
+            Ok(_) => Ok(()),
+            Err(err) => Err(FileTransferError::new_ex(
+                FileTransferErrorType::FileCreateDenied,
+                format!("{}", err),
+            )),
+        }
+    }
+
     /// ### remove
     ///
     /// Remove a file or a directory
@@ -478,14 +810,22 @@ impl FileTransfer for FtpFileTransfer {
         }
     }
 
+    /// ### hardlink
+    ///
+    /// Create a hard link; not supported by FTP
+    fn hardlink(&mut self, _target: &Path, _link: &Path) -> Result<(), FileTransferError> {
+        Err(FileTransferError::new_ex(
+            FileTransferErrorType::UnsupportedFeature,
+            String::from("FTP doesn't support hard links"),
+        ))
+    }
+
     /// ### stat
     ///
     /// Stat file and return FsEntry
-    fn stat(&mut self, _path: &Path) -> Result<FsEntry, FileTransferError> {
+    fn stat(&mut self, path: &Path) -> Result<FsEntry, FileTransferError> {
         match &mut self.stream {
-            Some(_) => Err(FileTransferError::new(
-                FileTransferErrorType::UnsupportedFeature,
-            )),
+            Some(stream) => Self::stat_file(stream, path),
             None => Err(FileTransferError::new(
                 FileTransferErrorType::UninitializedSession,
             )),
@@ -503,8 +843,12 @@ impl FileTransfer for FtpFileTransfer {
         _local: &FsFile,
         file_name: &Path,
     ) -> Result<Box<dyn Write>, FileTransferError> {
+        let retries: u8 = self.data_retries;
+        let warnings: &mut Vec<String> = &mut self.warnings;
         match &mut self.stream {
-            Some(stream) => match stream.put_with_stream(&file_name.to_string_lossy()) {
+            Some(stream) => match Self::with_data_retry(stream, retries, warnings, |stream| {
+                stream.put_with_stream(&file_name.to_string_lossy())
+            }) {
                 Ok(writer) => Ok(Box::new(writer)), // NOTE: don't use BufWriter here, since already returned by the library
                 Err(err) => Err(FileTransferError::new_ex(
                     FileTransferErrorType::FileCreateDenied,
@@ -522,8 +866,12 @@ impl FileTransfer for FtpFileTransfer {
     /// Receive file from remote with provided name
     /// Returns file and its size
     fn recv_file(&mut self, file: &FsFile) -> Result<Box<dyn Read>, FileTransferError> {
+        let retries: u8 = self.data_retries;
+        let warnings: &mut Vec<String> = &mut self.warnings;
         match &mut self.stream {
-            Some(stream) => match stream.get(&file.abs_path.as_path().to_string_lossy()) {
+            Some(stream) => match Self::with_data_retry(stream, retries, warnings, |stream| {
+                stream.get(&file.abs_path.as_path().to_string_lossy())
+            }) {
                 Ok(reader) => Ok(Box::new(reader)), // NOTE: don't use BufReader here, since already returned by the library
                 Err(err) => Err(FileTransferError::new_ex(
                     FileTransferErrorType::NoSuchFileOrDirectory,
@@ -579,6 +927,13 @@ impl FileTransfer for FtpFileTransfer {
             )),
         }
     }
+
+    /// ### take_warnings
+    ///
+    /// Take the warnings collected since the last call (e.g. retried data connections)
+    fn take_warnings(&mut self) -> Vec<String> {
+        self.warnings.drain(..).collect()
+    }
 }
 
 #[cfg(test)]
@@ -598,6 +953,386 @@ mod tests {
         assert!(ftp.stream.is_none());
     }
 
+    #[test]
+    fn test_filetransfer_ftp_data_retry() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+        // Get a port nobody is listening on, for the passive data connection
+        let closed_port: u16 = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        // Fake server, which never accepts data connections
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut control, _) = listener.accept().unwrap();
+            control.write_all(b"220 ready\r\n").unwrap();
+            let reader = BufReader::new(control.try_clone().unwrap());
+            for line in reader.lines().map_while(Result::ok) {
+                let reply: String = match line.as_str() {
+                    "PASV" => format!(
+                        "227 Entering Passive Mode (127,0,0,1,{},{})\r\n",
+                        closed_port >> 8,
+                        closed_port & 0xff
+                    ),
+                    "LIST" => String::from("425 Can't open data connection\r\n"),
+                    _ => break,
+                };
+                if control.write_all(reply.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        assert_eq!(ftp.data_retries, DATA_CONNECTION_RETRIES);
+        let mut stream: FtpStream = FtpStream::connect(format!("127.0.0.1:{}", port)).unwrap();
+        // Every attempt fails; operation is retried and each retry is reported
+        assert!(FtpFileTransfer::with_data_retry(
+            &mut stream,
+            ftp.data_retries,
+            &mut ftp.warnings,
+            |stream| stream.list(None)
+        )
+        .is_err());
+        assert_eq!(ftp.take_warnings().len(), DATA_CONNECTION_RETRIES as usize);
+        assert!(ftp.take_warnings().is_empty());
+        drop(stream);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_read_timeout() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+        // Fake server, which never replies to PWD
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut control, _) = listener.accept().unwrap();
+            control.write_all(b"220 ready\r\n").unwrap();
+            let reader = BufReader::new(control.try_clone().unwrap());
+            for line in reader.lines().map_while(Result::ok) {
+                let reply: &str = match line.as_str() {
+                    "USER omar" => "331 Password required\r\n",
+                    "PASS secret" => "230 Logged in\r\n",
+                    "PWD" => continue,
+                    _ => break,
+                };
+                if control.write_all(reply.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        assert!(ftp.read_timeout().is_none());
+        ftp.set_timeout(Duration::from_millis(500));
+        assert_eq!(ftp.read_timeout(), Some(Duration::from_millis(500)));
+        assert!(ftp
+            .connect(
+                String::from("127.0.0.1"),
+                port,
+                Some(String::from("omar")),
+                Some(String::from("secret"))
+            )
+            .is_ok());
+        // The reply never comes: operation fails instead of blocking forever
+        let started: std::time::Instant = std::time::Instant::now();
+        assert!(ftp.pwd().is_err());
+        assert!(started.elapsed() < Duration::from_secs(10));
+        ftp.stream = None;
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_touch_existing() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+        // Get a port nobody is listening on, for the passive data connection
+        let closed_port: u16 = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        // Fake server, which refuses SIZE in ASCII mode and can't tell whether `new.txt` exists
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel::<String>();
+        let server = thread::spawn(move || {
+            let (mut control, _) = listener.accept().unwrap();
+            control.write_all(b"220 ready\r\n").unwrap();
+            let reader = BufReader::new(control.try_clone().unwrap());
+            let mut binary: bool = false;
+            for line in reader.lines().map_while(Result::ok) {
+                let reply: String = match line.as_str() {
+                    "TYPE I" => {
+                        binary = true;
+                        String::from("200 Switching to Binary mode.\r\n")
+                    }
+                    "TYPE A N" => {
+                        binary = false;
+                        String::from("200 Switching to ASCII mode.\r\n")
+                    }
+                    "SIZE /tmp/omar.txt" if binary => String::from("213 8192\r\n"),
+                    "SIZE /tmp/omar.txt" => String::from("550 SIZE not allowed in ASCII mode.\r\n"),
+                    "MDTM /tmp/omar.txt" => String::from("213 20210301120000\r\n"),
+                    "SIZE /tmp/new.txt" => String::from("550 Could not get file size.\r\n"),
+                    "PASV" => format!(
+                        "227 Entering Passive Mode (127,0,0,1,{},{})\r\n",
+                        closed_port >> 8,
+                        closed_port & 0xff
+                    ),
+                    cmd if cmd.starts_with("STOR ") => String::from("553 Not allowed\r\n"),
+                    _ => break,
+                };
+                tx.send(line.clone()).unwrap();
+                if control.write_all(reply.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        ftp.data_retries = 0;
+        ftp.stream = Some(FtpStream::connect(format!("127.0.0.1:{}", port)).unwrap());
+        // Existing file is detected in binary mode and never overwritten
+        let err: FileTransferError = ftp.touch(Path::new("/tmp/omar.txt")).err().unwrap();
+        assert!(err.to_string().contains("already exists"));
+        // Existence can't be told (SIZE is inconclusive and the directory can't be listed): file is not created
+        let err: FileTransferError = ftp.touch(Path::new("/tmp/new.txt")).err().unwrap();
+        assert!(err.to_string().contains("could not check whether"));
+        ftp.stream = None;
+        server.join().unwrap();
+        let commands: Vec<String> = rx.iter().collect();
+        assert!(commands.iter().all(|x| !x.starts_with("STOR ")));
+        assert!(commands.iter().any(|x| x.as_str() == "PASV"));
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_no_such_file() {
+        let not_found: FileTransferError = FileTransferError::new_ex(
+            FileTransferErrorType::UnsupportedFeature,
+            String::from("SIZE: Expected code [213], got response: 550 /tmp/a.txt: No such file or directory"),
+        );
+        assert!(FtpFileTransfer::is_no_such_file(&not_found));
+        let inconclusive: FileTransferError = FileTransferError::new_ex(
+            FileTransferErrorType::UnsupportedFeature,
+            String::from("SIZE: Expected code [213], got response: 550 Could not get file size."),
+        );
+        assert!(!FtpFileTransfer::is_no_such_file(&inconclusive));
+        let ascii: FileTransferError = FileTransferError::new_ex(
+            FileTransferErrorType::UnsupportedFeature,
+            String::from(
+                "SIZE: Expected code [213], got response: 550 SIZE not allowed in ASCII mode.",
+            ),
+        );
+        assert!(!FtpFileTransfer::is_no_such_file(&ascii));
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_stat_binary_type() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+        // Fake server, which refuses SIZE in ASCII mode
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel::<String>();
+        let server = thread::spawn(move || {
+            let (mut control, _) = listener.accept().unwrap();
+            control.write_all(b"220 ready\r\n").unwrap();
+            let reader = BufReader::new(control.try_clone().unwrap());
+            let mut binary: bool = false;
+            for line in reader.lines().map_while(Result::ok) {
+                let reply: &str = match line.as_str() {
+                    "TYPE I" => {
+                        binary = true;
+                        "200 Switching to Binary mode.\r\n"
+                    }
+                    "TYPE A N" => {
+                        binary = false;
+                        "200 Switching to ASCII mode.\r\n"
+                    }
+                    "SIZE /tmp/omar.txt" if binary => "213 8192\r\n",
+                    "SIZE /tmp/omar.txt" => "550 SIZE not allowed in ASCII mode.\r\n",
+                    "MDTM /tmp/omar.txt" => "213 20210301120000\r\n",
+                    "SIZE /tmp" => "550 Could not get file size.\r\n",
+                    _ => break,
+                };
+                tx.send(line.clone()).unwrap();
+                if control.write_all(reply.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut stream: FtpStream = FtpStream::connect(format!("127.0.0.1:{}", port)).unwrap();
+        match FtpFileTransfer::stat_file(&mut stream, Path::new("/tmp/omar.txt")) {
+            Ok(FsEntry::File(file)) => {
+                assert_eq!(file.name.as_str(), "omar.txt");
+                assert_eq!(file.size, 8192);
+                assert_eq!(file.ftype.as_deref().unwrap(), "txt");
+                assert_eq!(
+                    file.last_change_time
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap(),
+                    Duration::from_secs(1614600000)
+                );
+            }
+            _ => panic!("Expected file"),
+        }
+        // SIZE fails on directories: fall back is up to the caller
+        assert!(matches!(
+            FtpFileTransfer::stat_file(&mut stream, Path::new("/tmp"))
+                .err()
+                .unwrap()
+                .kind(),
+            FileTransferErrorType::UnsupportedFeature
+        ));
+        drop(stream);
+        server.join().unwrap();
+        // TYPE I precedes SIZE; ASCII is restored afterwards
+        let commands: Vec<String> = rx.iter().collect();
+        assert_eq!(
+            commands,
+            vec![
+                "TYPE I",
+                "SIZE /tmp/omar.txt",
+                "MDTM /tmp/omar.txt",
+                "TYPE A N",
+                "TYPE I",
+                "SIZE /tmp",
+                "TYPE A N"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_list_args() {
+        let mut ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        assert!(ftp.list_args.is_none());
+        assert_eq!(ftp.list_cmd_arg(Path::new("/tmp")), String::from("/tmp"));
+        // Set args
+        ftp.set_list_args(Some(String::from(" -a ")));
+        assert_eq!(ftp.list_args.as_deref().unwrap(), "-a");
+        assert_eq!(ftp.list_cmd_arg(Path::new("/tmp")), String::from("-a /tmp"));
+        // Empty args are ignored
+        ftp.set_list_args(Some(String::from("  ")));
+        assert!(ftp.list_args.is_none());
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_parse_list_line_nlink() {
+        let ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        // Link count is the column after permissions
+        let fs_entry: FsEntry = ftp
+            .parse_list_line(
+                PathBuf::from("/tmp").as_path(),
+                "-rw-r--r-- 3 root  root  512 Nov 5 2018 hardlinked.txt",
+            )
+            .ok()
+            .unwrap();
+        assert_eq!(fs_entry.get_nlink(), Some(3));
+        // Inode is not reported by LIST
+        assert_eq!(fs_entry.get_inode(), None);
+        let fs_entry: FsEntry = ftp
+            .parse_list_line(
+                PathBuf::from("/tmp").as_path(),
+                "drwxr-xr-x 12 0  9  4096 Nov 5 16:32 docs",
+            )
+            .ok()
+            .unwrap();
+        assert!(fs_entry.is_dir());
+        assert_eq!(fs_entry.get_nlink(), Some(12));
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_parse_list_line_loose() {
+        let ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        let path: &Path = Path::new("/tmp");
+        // Lines the regex can't parse
+        assert!(ftp.parse_list_line(path, "omar.txt").is_err());
+        let fs_entry: FsEntry = ftp.parse_list_line_loose(path, "omar.txt").ok().unwrap();
+        assert_eq!(fs_entry.get_name(), String::from("omar.txt"));
+        assert_eq!(fs_entry.get_abs_path(), PathBuf::from("/tmp/omar.txt"));
+        assert!(!fs_entry.is_dir());
+        assert_eq!(fs_entry.get_size(), 0);
+        assert_eq!(fs_entry.get_last_change_time(), SystemTime::UNIX_EPOCH);
+        assert!(fs_entry.get_unix_pex().is_none());
+        let fs_entry: FsEntry = ftp
+            .parse_list_line_loose(path, "07-14-21  11:20AM       <DIR>          Backups")
+            .ok()
+            .unwrap();
+        assert_eq!(fs_entry.get_name(), String::from("Backups"));
+        // Ignored lines
+        assert!(ftp.parse_list_line_loose(path, "   ").is_err());
+        assert!(ftp.parse_list_line_loose(path, "total 24").is_err());
+        assert!(ftp
+            .parse_list_line_loose(path, "drwxr-xr-x 2 0 0 4096 Nov 5 2018 ..")
+            .is_err());
+        assert!(ftp
+            .parse_list_line_loose(path, "crw-rw-rw- 1 0 0 1, 3 Nov 5 2018 null")
+            .is_err());
+    }
+
+    #[test]
+    fn test_filetransfer_ftp_parse_list_line_iso_date() {
+        let ftp: FtpFileTransfer = FtpFileTransfer::new(false);
+        // long-iso
+        let fs_entry: FsEntry = ftp
+            .parse_list_line(
+                PathBuf::from("/tmp").as_path(),
+                "-rw-r--r-- 1 root  root  1234 2018-11-05 16:32 report 2018.csv",
+            )
+            .ok()
+            .unwrap();
+        assert_eq!(fs_entry.get_name(), String::from("report 2018.csv"));
+        assert_eq!(fs_entry.get_size(), 1234);
+        assert_eq!(
+            fs_entry
+                .get_last_change_time()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .unwrap(),
+            Duration::from_secs(1541435520)
+        );
+        // full-iso
+        let fs_entry: FsEntry = ftp
+            .parse_list_line(
+                PathBuf::from("/tmp").as_path(),
+                "drwxr-xr-x 2 0  0  4096 2018-11-05 16:32:10.123456789 +0100 docs",
+            )
+            .ok()
+            .unwrap();
+        assert!(fs_entry.is_dir());
+        assert_eq!(fs_entry.get_name(), String::from("docs"));
+        assert_eq!(
+            fs_entry
+                .get_last_change_time()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .unwrap(),
+            Duration::from_secs(1541435530)
+        );
+        // Date only
+        let fs_entry: FsEntry = ftp
+            .parse_list_line(
+                PathBuf::from("/tmp").as_path(),
+                "-rw-r--r-- 1 root  root  8 2018-11-05 omar.txt",
+            )
+            .ok()
+            .unwrap();
+        assert_eq!(fs_entry.get_name(), String::from("omar.txt"));
+        assert_eq!(
+            fs_entry
+                .get_last_change_time()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .unwrap(),
+            Duration::from_secs(1541376000)
+        );
+    }
+
     #[test]
     fn test_filetransfer_ftp_parse_list_line() {
         let ftp: FtpFileTransfer = FtpFileTransfer::new(false);
@@ -877,9 +1612,12 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         let mut ftp: FtpFileTransfer = FtpFileTransfer::new(false);
         assert!(ftp.change_dir(Path::new("/tmp")).is_err());
@@ -890,5 +1628,19 @@ mod tests {
         assert!(ftp.stat(Path::new("/tmp")).is_err());
         assert!(ftp.recv_file(&file).is_err());
         assert!(ftp.send_file(&file, Path::new("/tmp/omar.txt")).is_err());
+        assert!(matches!(
+            ftp.hardlink(Path::new("/tmp/omar.txt"), Path::new("/tmp/link.txt"))
+                .err()
+                .unwrap()
+                .kind(),
+            FileTransferErrorType::UnsupportedFeature
+        ));
+        assert!(matches!(
+            ftp.chmod(Path::new("/tmp/omar.txt"), (6, 4, 4))
+                .err()
+                .unwrap()
+                .kind(),
+            FileTransferErrorType::UnsupportedFeature
+        ));
     }
 }
@@ -0,0 +1,382 @@
+//! ## KnownHosts
+//!
+//! `known_hosts` is the module which verifies the SSH server host key against the OpenSSH `known_hosts` file
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Dependencies
+extern crate data_encoding;
+extern crate dirs;
+extern crate ring;
+extern crate ssh2;
+
+// Locals
+use super::{FileTransferError, FileTransferErrorType};
+use crate::utils::select::glob_match;
+
+// Includes
+use data_encoding::BASE64;
+use ring::hmac;
+use ssh2::Session;
+use std::path::{Path, PathBuf};
+
+/// ## HostKeyCheck
+///
+/// HostKeyCheck describes the result of the host key lookup in known hosts
+#[derive(std::fmt::Debug, PartialEq)]
+pub enum HostKeyCheck {
+    Match,    // Host is known with this key
+    Mismatch, // Host is known with a different key of the same type
+    NotFound, // Host is not known (or not with this key type)
+    Revoked,  // Key has been revoked (`@revoked`) for this host
+}
+
+/// ## HostPattern
+///
+/// HostPattern describes the hosts field of a known hosts entry
+#[derive(std::fmt::Debug)]
+enum HostPattern {
+    Hashed(Vec<u8>, Vec<u8>), // Salt; HMAC-SHA1 of host name (`|1|salt|hash`)
+    Plain(Vec<String>), // Comma separated host name patterns (`*` and `?` wildcards, `!` negation)
+}
+
+/// ## KnownHost
+///
+/// KnownHost is a single entry of the known hosts file
+#[derive(std::fmt::Debug)]
+struct KnownHost {
+    hosts: HostPattern,
+    key_type: String,
+    key: Vec<u8>,
+    revoked: bool, // Marked with `@revoked`: the key must be refused
+}
+
+/// ## KnownHosts
+///
+/// KnownHosts contains the entries of an OpenSSH `known_hosts` file
+#[derive(std::fmt::Debug)]
+pub struct KnownHosts {
+    entries: Vec<KnownHost>,
+}
+
+impl KnownHosts {
+    /// ### parse
+    ///
+    /// Parse known hosts file content. Invalid lines, comments and `@cert-authority` lines are ignored
+    pub fn parse(data: &str) -> KnownHosts {
+        KnownHosts {
+            entries: data.lines().filter_map(KnownHosts::parse_line).collect(),
+        }
+    }
+
+    /// ### read
+    ///
+    /// Read and parse known hosts file at `path`
+    pub fn read(path: &Path) -> std::io::Result<KnownHosts> {
+        Ok(KnownHosts::parse(std::fs::read_to_string(path)?.as_str()))
+    }
+
+    /// ### check
+    ///
+    /// Look for `host:port` in known hosts and compare its key with `key` (SSH wire format blob).
+    /// A revoked key is reported as such, whatever the other entries say
+    pub fn check(&self, host: &str, port: u16, key: &[u8]) -> HostKeyCheck {
+        let name: String = match port {
+            22 => String::from(host),
+            _ => format!("[{}]:{}", host, port),
+        };
+        let matching = || self.entries.iter().filter(|x| x.matches(name.as_str()));
+        if matching().any(|x| x.revoked && x.key.as_slice() == key) {
+            return HostKeyCheck::Revoked;
+        }
+        let key_type: Option<String> = blob_key_type(key);
+        let mut result: HostKeyCheck = HostKeyCheck::NotFound;
+        for entry in matching().filter(|x| !x.revoked) {
+            if entry.key.as_slice() == key {
+                return HostKeyCheck::Match;
+            }
+            if key_type.as_deref() == Some(entry.key_type.as_str()) {
+                result = HostKeyCheck::Mismatch;
+            }
+        }
+        result
+    }
+
+    /// ### parse_line
+    ///
+    /// Parse a line of known hosts file (`[@revoked] hosts key_type base64_key [comment]`)
+    fn parse_line(line: &str) -> Option<KnownHost> {
+        let line: &str = line.trim();
+        let (line, revoked): (&str, bool) = match line.strip_prefix("@revoked") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => (rest.trim_start(), true),
+            _ => (line, false),
+        };
+        if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+            return None;
+        }
+        let mut fields = line.split_whitespace();
+        let hosts: &str = fields.next()?;
+        let key_type: &str = fields.next()?;
+        let key: Vec<u8> = BASE64.decode(fields.next()?.as_bytes()).ok()?;
+        let hosts: HostPattern = match hosts.strip_prefix("|1|") {
+            Some(hashed) => {
+                let mut tokens = hashed.split('|');
+                let salt: Vec<u8> = BASE64.decode(tokens.next()?.as_bytes()).ok()?;
+                let hash: Vec<u8> = BASE64.decode(tokens.next()?.as_bytes()).ok()?;
+                HostPattern::Hashed(salt, hash)
+            }
+            None => HostPattern::Plain(hosts.split(',').map(String::from).collect()),
+        };
+        Some(KnownHost {
+            hosts,
+            key_type: String::from(key_type),
+            key,
+            revoked,
+        })
+    }
+}
+
+impl KnownHost {
+    /// ### matches
+    ///
+    /// Returns whether the entry refers to the host name (`host` or `[host]:port`).
+    /// Plain patterns match if any of them matches and no negated one (`!pattern`) does
+    fn matches(&self, name: &str) -> bool {
+        match &self.hosts {
+            HostPattern::Hashed(salt, hash) => {
+                let key: hmac::Key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, salt);
+                hmac::verify(&key, name.as_bytes(), hash).is_ok()
+            }
+            HostPattern::Plain(hosts) => {
+                let mut matched: bool = false;
+                for pattern in hosts.iter() {
+                    match pattern.strip_prefix('!') {
+                        Some(negated) if glob_match(negated, name) => return false,
+                        Some(_) => {}
+                        None => matched |= glob_match(pattern, name),
+                    }
+                }
+                matched
+            }
+        }
+    }
+}
+
+/// ### blob_key_type
+///
+/// Get key type name (e.g. `ssh-ed25519`) from key blob, which starts with it as an SSH string
+fn blob_key_type(key: &[u8]) -> Option<String> {
+    if key.len() < 4 {
+        return None;
+    }
+    let len: usize = u32::from_be_bytes([key[0], key[1], key[2], key[3]]) as usize;
+    key.get(4..4 + len)
+        .map(|x| String::from_utf8_lossy(x).to_string())
+}
+
+/// ### known_hosts_path
+///
+/// Get path of the user known hosts file (`~/.ssh/known_hosts`)
+pub fn known_hosts_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|mut p| {
+        p.push(".ssh");
+        p.push("known_hosts");
+        p
+    })
+}
+
+/// ### verify_host_key
+///
+/// Verify the host key of the session against the user known hosts file.
+/// Returns error if the host is known with a different key or if the key has been revoked; unknown hosts are accepted
+pub fn verify_host_key(session: &Session, host: &str, port: u16) -> Result<(), FileTransferError> {
+    match known_hosts_path() {
+        Some(path) => verify_host_key_in(session, host, port, path.as_path()),
+        None => Ok(()),
+    }
+}
+
+/// ### verify_host_key_in
+///
+/// Verify the host key of the session against the known hosts file at `path` (see `verify_host_key`)
+pub fn verify_host_key_in(
+    session: &Session,
+    host: &str,
+    port: u16,
+    path: &Path,
+) -> Result<(), FileTransferError> {
+    let key: &[u8] = match session.host_key() {
+        Some((key, _)) => key,
+        None => return Ok(()),
+    };
+    let known_hosts: KnownHosts = match KnownHosts::read(path) {
+        Ok(known_hosts) => known_hosts,
+        Err(_) => return Ok(()), // Missing or unreadable file
+    };
+    match known_hosts.check(host, port, key) {
+        HostKeyCheck::Mismatch => Err(FileTransferError::new_ex(
+            FileTransferErrorType::ConnectionError,
+            format!(
+                "Host key of '{}' doesn't match the one in {}; the host may be impersonated",
+                host,
+                path.display()
+            ),
+        )),
+        HostKeyCheck::Revoked => Err(FileTransferError::new_ex(
+            FileTransferErrorType::ConnectionError,
+            format!(
+                "Host key of '{}' has been revoked in {}",
+                host,
+                path.display()
+            ),
+        )),
+        HostKeyCheck::Match | HostKeyCheck::NotFound => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // ssh-ed25519 blobs
+    const KEY_A: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+    const KEY_B: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIAEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEB";
+    // ssh-rsa blob
+    const KEY_RSA: &str = "AAAAB3NzaC1yc2EAAAAgAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI=";
+
+    fn decode(key: &str) -> Vec<u8> {
+        BASE64.decode(key.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_filetransfer_known_hosts_hashed() {
+        // Hashed entries for `example.com` and `[example.com]:2222`
+        let known_hosts: KnownHosts = KnownHosts::parse(
+            format!(
+                "|1|AQIDBAUGBwgJCgsMDQ4PEBESExQ=|qvtG0DaqrsqPDhV2Ni+wmYohchA= ssh-ed25519 {}\n|1|AQIDBAUGBwgJCgsMDQ4PEBESExQ=|uVLj+YL3GsbtNOcCoC7AMM/WVh0= ssh-ed25519 {}\n",
+                KEY_A, KEY_B
+            )
+            .as_str(),
+        );
+        assert_eq!(known_hosts.entries.len(), 2);
+        assert_eq!(
+            known_hosts.check("example.com", 22, decode(KEY_A).as_slice()),
+            HostKeyCheck::Match
+        );
+        assert_eq!(
+            known_hosts.check("example.com", 22, decode(KEY_B).as_slice()),
+            HostKeyCheck::Mismatch
+        );
+        assert_eq!(
+            known_hosts.check("example.com", 2222, decode(KEY_B).as_slice()),
+            HostKeyCheck::Match
+        );
+        // Non matching host name
+        assert_eq!(
+            known_hosts.check("example.org", 22, decode(KEY_B).as_slice()),
+            HostKeyCheck::NotFound
+        );
+        // Different key type is not a mismatch
+        assert_eq!(
+            known_hosts.check("example.com", 22, decode(KEY_RSA).as_slice()),
+            HostKeyCheck::NotFound
+        );
+    }
+
+    #[test]
+    fn test_filetransfer_known_hosts_plain() {
+        let known_hosts: KnownHosts = KnownHosts::parse(
+            format!(
+                "# Comment\n\nexample.com,192.168.1.31 ssh-ed25519 {} omar@pc\n[bastion]:2222 ssh-rsa {}\n@cert-authority * ssh-ed25519 {}\nbad line\n",
+                KEY_A, KEY_RSA, KEY_B
+            )
+            .as_str(),
+        );
+        assert_eq!(known_hosts.entries.len(), 2);
+        assert_eq!(
+            known_hosts.check("192.168.1.31", 22, decode(KEY_A).as_slice()),
+            HostKeyCheck::Match
+        );
+        assert_eq!(
+            known_hosts.check("192.168.1.31", 22, decode(KEY_B).as_slice()),
+            HostKeyCheck::Mismatch
+        );
+        assert_eq!(
+            known_hosts.check("bastion", 2222, decode(KEY_RSA).as_slice()),
+            HostKeyCheck::Match
+        );
+        assert_eq!(
+            known_hosts.check("bastion", 22, decode(KEY_RSA).as_slice()),
+            HostKeyCheck::NotFound
+        );
+    }
+
+    #[test]
+    fn test_filetransfer_known_hosts_revoked() {
+        let known_hosts: KnownHosts = KnownHosts::parse(
+            format!(
+                "example.com ssh-ed25519 {}\n@revoked * ssh-ed25519 {}\n@revoked *.example.org,!safe.example.org ssh-rsa {}\n",
+                KEY_A, KEY_B, KEY_RSA
+            )
+            .as_str(),
+        );
+        assert_eq!(known_hosts.entries.len(), 3);
+        // Revoked for every host, even if it isn't otherwise known
+        assert_eq!(
+            known_hosts.check("example.com", 22, decode(KEY_B).as_slice()),
+            HostKeyCheck::Revoked
+        );
+        assert_eq!(
+            known_hosts.check("example.net", 2222, decode(KEY_B).as_slice()),
+            HostKeyCheck::Revoked
+        );
+        // Other keys are checked as usual
+        assert_eq!(
+            known_hosts.check("example.com", 22, decode(KEY_A).as_slice()),
+            HostKeyCheck::Match
+        );
+        // Wildcards and negated patterns
+        assert_eq!(
+            known_hosts.check("www.example.org", 22, decode(KEY_RSA).as_slice()),
+            HostKeyCheck::Revoked
+        );
+        assert_eq!(
+            known_hosts.check("safe.example.org", 22, decode(KEY_RSA).as_slice()),
+            HostKeyCheck::NotFound
+        );
+    }
+
+    #[test]
+    fn test_filetransfer_known_hosts_blob_key_type() {
+        assert_eq!(
+            blob_key_type(decode(KEY_A).as_slice()).unwrap().as_str(),
+            "ssh-ed25519"
+        );
+        assert_eq!(
+            blob_key_type(decode(KEY_RSA).as_slice()).unwrap().as_str(),
+            "ssh-rsa"
+        );
+        assert!(blob_key_type(&[0, 0]).is_none());
+        assert!(blob_key_type(&[0, 0, 0, 8, 1]).is_none());
+    }
+}
@@ -25,13 +25,25 @@
 
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use crate::fs::{FsEntry, FsFile};
 
 // Transfers
+#[cfg(feature = "ftp")]
 pub mod ftp_transfer;
+#[cfg(feature = "ssh")]
+pub mod known_hosts;
+pub mod proxy_jump;
+#[cfg(feature = "ssh")]
 pub mod scp_transfer;
+#[cfg(feature = "ssh")]
 pub mod sftp_transfer;
+#[cfg(feature = "ssh")]
+pub mod ssh_agent;
+
+#[cfg(not(any(feature = "ftp", feature = "ssh")))]
+compile_error!("At least one protocol must be enabled: build with feature \"ftp\" and/or \"ssh\"");
 
 /// ## FileTransferProtocol
 ///
@@ -44,10 +56,97 @@ pub enum FileTransferProtocol {
     Ftp(bool), // Bool is for secure (true => ftps)
 }
 
+impl FileTransferProtocol {
+    /// ### is_enabled
+    ///
+    /// Returns whether the protocol has been compiled in this build (see "ftp" and "ssh" features)
+    pub fn is_enabled(self) -> bool {
+        match self {
+            FileTransferProtocol::Sftp | FileTransferProtocol::Scp => cfg!(feature = "ssh"),
+            FileTransferProtocol::Ftp(_) => cfg!(feature = "ftp"),
+        }
+    }
+}
+
+impl Default for FileTransferProtocol {
+    /// SFTP, or FTP if SSH protocols are disabled
+    fn default() -> Self {
+        match cfg!(feature = "ssh") {
+            true => FileTransferProtocol::Sftp,
+            false => FileTransferProtocol::Ftp(false),
+        }
+    }
+}
+
+/// ## SshIdentity
+///
+/// SshIdentity describes the private key SSH based protocols authenticate with, before trying the SSH agent and the password
+#[derive(std::fmt::Debug, Clone, PartialEq)]
+pub struct SshIdentity {
+    pub path: PathBuf,
+    pub passphrase: Option<String>, // Passphrase the key is encrypted with, if any
+}
+
+impl SshIdentity {
+    /// ### new
+    ///
+    /// Instantiates a new SshIdentity
+    pub fn new(path: PathBuf, passphrase: Option<String>) -> SshIdentity {
+        SshIdentity { path, passphrase }
+    }
+}
+
+/// ### new_client
+///
+/// Instantiates the file transfer client for the provided protocol.
+/// Jump hosts and `identity` are used by SSH based protocols only, `sftp_concurrency` (read requests kept in flight
+/// while receiving files) by SFTP only, while `ftp_list_args` is used by FTP only. `read_timeout` applies to every protocol.
+/// Protocol must be enabled in this build (see `FileTransferProtocol::is_enabled`)
+#[allow(unused_variables)]
+pub fn new_client(
+    protocol: FileTransferProtocol,
+    jump_hosts: &[proxy_jump::JumpHost],
+    identity: Option<&SshIdentity>,
+    ftp_list_args: Option<String>,
+    read_timeout: Duration,
+    sftp_concurrency: usize,
+) -> Box<dyn FileTransfer> {
+    match protocol {
+        #[cfg(feature = "ssh")]
+        FileTransferProtocol::Sftp => {
+            let mut client: sftp_transfer::SftpFileTransfer =
+                sftp_transfer::SftpFileTransfer::new();
+            client.set_jump_hosts(jump_hosts.to_vec());
+            client.set_identity(identity.cloned());
+            client.set_timeout(read_timeout);
+            client.set_read_concurrency(sftp_concurrency);
+            Box::new(client)
+        }
+        #[cfg(feature = "ftp")]
+        FileTransferProtocol::Ftp(ftps) => {
+            let mut client: ftp_transfer::FtpFileTransfer =
+                ftp_transfer::FtpFileTransfer::new(ftps);
+            client.set_list_args(ftp_list_args);
+            client.set_timeout(read_timeout);
+            Box::new(client)
+        }
+        #[cfg(feature = "ssh")]
+        FileTransferProtocol::Scp => {
+            let mut client: scp_transfer::ScpFileTransfer = scp_transfer::ScpFileTransfer::new();
+            client.set_jump_hosts(jump_hosts.to_vec());
+            client.set_identity(identity.cloned());
+            client.set_timeout(read_timeout);
+            Box::new(client)
+        }
+        // NOTE: disabled protocols are rejected before getting here
+        #[allow(unreachable_patterns)]
+        _ => unreachable!("Protocol {:?} is not enabled in this build", protocol),
+    }
+}
+
 /// ## FileTransferError
 ///
 /// FileTransferError defines the possible errors available for a file transfer
-#[derive(std::fmt::Debug)]
 pub struct FileTransferError {
     code: FileTransferErrorType,
     msg: Option<String>,
@@ -89,6 +188,34 @@ impl FileTransferError {
         err.msg = Some(msg);
         err
     }
+
+    /// ### kind
+    ///
+    /// Returns the error type
+    pub fn kind(&self) -> &FileTransferErrorType {
+        &self.code
+    }
+}
+
+// libssh2 error code for blocking functions which timed out
+#[cfg(feature = "ssh")]
+const LIBSSH2_ERROR_TIMEOUT: std::os::raw::c_int = -9;
+
+#[cfg(feature = "ssh")]
+impl FileTransferError {
+    /// ### from_ssh
+    ///
+    /// Instantiates a FileTransferError of type `code` from an SSH error.
+    /// Blocking operations which timed out are reported as connection errors
+    pub(crate) fn from_ssh(code: FileTransferErrorType, err: ssh2::Error) -> FileTransferError {
+        match err.code() {
+            ssh2::ErrorCode::Session(LIBSSH2_ERROR_TIMEOUT) => FileTransferError::new_ex(
+                FileTransferErrorType::ConnectionError,
+                String::from("operation timed out"),
+            ),
+            _ => FileTransferError::new_ex(code, format!("{}", err)),
+        }
+    }
 }
 
 impl std::fmt::Display for FileTransferError {
@@ -116,11 +243,72 @@ impl std::fmt::Display for FileTransferError {
     }
 }
 
+impl std::fmt::Debug for FileTransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut dbg = f.debug_struct("FileTransferError");
+        dbg.field("kind", &self.code);
+        if let Some(msg) = &self.msg {
+            dbg.field("msg", msg);
+        }
+        dbg.finish()
+    }
+}
+
+impl std::error::Error for FileTransferError {
+    /// ### source
+    ///
+    /// Returns the underlying IO error, if any
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.code {
+            FileTransferErrorType::IoErr(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// ## StreamFlusher
+///
+/// StreamFlusher flushes a write stream each time a certain amount of bytes has been written to it
+pub struct StreamFlusher {
+    interval: u64,  // Bytes to write between flushes; 0 never flushes
+    unflushed: u64, // Bytes written since the last flush
+}
+
+impl StreamFlusher {
+    /// ### new
+    ///
+    /// Instantiates a new StreamFlusher, which flushes each `interval` bytes (0 never flushes)
+    pub fn new(interval: u64) -> StreamFlusher {
+        StreamFlusher {
+            interval,
+            unflushed: 0,
+        }
+    }
+
+    /// ### written
+    ///
+    /// Account `bytes` written to `stream`, flushing it if the interval has been reached.
+    /// Returns whether the stream has been flushed
+    pub fn written(&mut self, stream: &mut dyn Write, bytes: usize) -> std::io::Result<bool> {
+        if self.interval == 0 {
+            return Ok(false);
+        }
+        self.unflushed += bytes as u64;
+        if self.unflushed < self.interval {
+            return Ok(false);
+        }
+        stream.flush()?;
+        self.unflushed = 0;
+        Ok(true)
+    }
+}
+
 /// ## FileTransfer
 ///
-/// File transfer trait must be implemented by all the file transfers and defines the method used by a generic file transfer
+/// File transfer trait must be implemented by all the file transfers and defines the method used by a generic file transfer.
+/// Clients must be `Send`, so that blocking operations can run on a worker thread
 
-pub trait FileTransfer {
+pub trait FileTransfer: Send {
     /// ### connect
     ///
     /// Connect to the remote server
@@ -169,6 +357,12 @@ pub trait FileTransfer {
     /// You must return error in case the directory already exists
     fn mkdir(&mut self, dir: &Path) -> Result<(), FileTransferError>;
 
+    /// ### touch
+    ///
+    /// Create an empty file
+    /// You must return error in case the file already exists, which must never be overwritten
+    fn touch(&mut self, file: &Path) -> Result<(), FileTransferError>;
+
     /// ### remove
     ///
     /// Remove a file or a directory
@@ -179,6 +373,22 @@ pub trait FileTransfer {
     /// Rename file or a directory
     fn rename(&mut self, file: &FsEntry, dst: &Path) -> Result<(), FileTransferError>;
 
+    /// ### hardlink
+    ///
+    /// Create a hard link at `link` to the `target` file.
+    /// Returns `UnsupportedFeature` if the protocol or the server doesn't support hard links
+    fn hardlink(&mut self, target: &Path, link: &Path) -> Result<(), FileTransferError>;
+
+    /// ### chmod
+    ///
+    /// Change the UNIX permissions (user, group, others) of the remote file or directory at `path`.
+    /// Returns `UnsupportedFeature` if the protocol can't change permissions
+    fn chmod(&mut self, _path: &Path, _mode: (u8, u8, u8)) -> Result<(), FileTransferError> {
+        Err(FileTransferError::new(
+            FileTransferErrorType::UnsupportedFeature,
+        ))
+    }
+
     /// ### stat
     ///
     /// Stat file and return FsEntry
@@ -196,12 +406,59 @@ pub trait FileTransfer {
         file_name: &Path,
     ) -> Result<Box<dyn Write>, FileTransferError>;
 
+    /// ### send_file_at
+    ///
+    /// Send file to remote, appending the data from byte `offset` of the local file to the remote file,
+    /// which already contains the data before it (e.g. an interrupted upload).
+    /// Returns the stream and the offset the upload actually starts from: the default implementation
+    /// sends the entire file, starting from 0; protocols which can seek should override it.
+    /// As for `send_file`, the stream must then be finalized with `on_sent`
+    fn send_file_at(
+        &mut self,
+        local: &FsFile,
+        file_name: &Path,
+        _offset: usize,
+    ) -> Result<(Box<dyn Write>, usize), FileTransferError> {
+        Ok((self.send_file(local, file_name)?, 0))
+    }
+
+    /// ### can_resume_upload
+    ///
+    /// Returns whether `send_file_at` can append to a partial remote file
+    fn can_resume_upload(&self) -> bool {
+        false
+    }
+
     /// ### recv_file
     ///
     /// Receive file from remote with provided name
     /// Returns file and its size
     fn recv_file(&mut self, file: &FsFile) -> Result<Box<dyn Read>, FileTransferError>;
 
+    /// ### recv_file_at
+    ///
+    /// Receive file from remote, starting from byte `offset`.
+    /// The default implementation receives the entire file and discards the data before `offset`;
+    /// protocols which can seek should override it.
+    /// As for `recv_file`, the stream must then be finalized with `on_recv`
+    fn recv_file_at(
+        &mut self,
+        file: &FsFile,
+        offset: usize,
+    ) -> Result<Box<dyn Read>, FileTransferError> {
+        let mut reader: Box<dyn Read> = self.recv_file(file)?;
+        if let Err(err) =
+            std::io::copy(&mut (&mut reader).take(offset as u64), &mut std::io::sink())
+        {
+            let _ = self.on_recv(reader);
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::IoErr(err),
+                String::from("Could not skip data before offset"),
+            ));
+        }
+        Ok(reader)
+    }
+
     /// ### on_sent
     ///
     /// Finalize send method.
@@ -219,6 +476,22 @@ pub trait FileTransfer {
     /// This mighe be necessary for some protocols.
     /// You must call this method each time you want to finalize the read of the remote file.
     fn on_recv(&mut self, readable: Box<dyn Read>) -> Result<(), FileTransferError>;
+
+    /// ### take_warnings
+    ///
+    /// Take the warnings collected by the file transfer since the last call.
+    /// Warnings report recovered issues (e.g. retried operations) which should be logged to the user
+    fn take_warnings(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// ### get_auth_method
+    ///
+    /// Get the description of the method which authenticated the current session (e.g. the SSH agent identity),
+    /// if the protocol reports it
+    fn get_auth_method(&self) -> Option<String> {
+        None
+    }
 }
 
 // Tests
@@ -228,6 +501,52 @@ mod tests {
 
     use super::*;
 
+    /// Writer which counts flushes
+    struct FlushCounter {
+        written: usize,
+        flushes: usize,
+    }
+
+    impl Write for FlushCounter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_filetransfer_mod_stream_flusher() {
+        let buffer: Vec<u8> = vec![0; 1048576];
+        let mut stream: FlushCounter = FlushCounter {
+            written: 0,
+            flushes: 0,
+        };
+        // Flush each 100000 bytes, writing chunks of 64KB
+        let mut flusher: StreamFlusher = StreamFlusher::new(100000);
+        for chunk in buffer.chunks(65536) {
+            stream.write_all(chunk).unwrap();
+            flusher.written(&mut stream, chunk.len()).unwrap();
+        }
+        assert_eq!(stream.written, 1048576);
+        assert_eq!(stream.flushes, 8);
+        // Never flush
+        let mut stream: FlushCounter = FlushCounter {
+            written: 0,
+            flushes: 0,
+        };
+        let mut flusher: StreamFlusher = StreamFlusher::new(0);
+        for chunk in buffer.chunks(65536) {
+            stream.write_all(chunk).unwrap();
+            assert!(!flusher.written(&mut stream, chunk.len()).unwrap());
+        }
+        assert_eq!(stream.flushes, 0);
+    }
+
     #[test]
     fn test_filetransfer_mod_protocol() {
         assert_eq!(
@@ -238,6 +557,106 @@ mod tests {
             FileTransferProtocol::Ftp(false),
             FileTransferProtocol::Ftp(false)
         );
+        // Enabled protocols
+        assert_eq!(
+            FileTransferProtocol::Sftp.is_enabled(),
+            cfg!(feature = "ssh")
+        );
+        assert_eq!(
+            FileTransferProtocol::Scp.is_enabled(),
+            cfg!(feature = "ssh")
+        );
+        assert_eq!(
+            FileTransferProtocol::Ftp(true).is_enabled(),
+            cfg!(feature = "ftp")
+        );
+        assert!(FileTransferProtocol::default().is_enabled());
+    }
+
+    #[test]
+    fn test_filetransfer_mod_new_client() {
+        let client: Box<dyn FileTransfer> = new_client(
+            FileTransferProtocol::default(),
+            &[],
+            Some(&SshIdentity::new(
+                PathBuf::from("/home/omar/.ssh/id_ed25519"),
+                None,
+            )),
+            Some(String::from("-a")),
+            Duration::from_secs(60),
+            4,
+        );
+        assert!(!client.is_connected());
+    }
+
+    #[test]
+    #[cfg(feature = "ssh")]
+    fn test_filetransfer_mod_error_from_ssh() {
+        // Timeouts are connection errors
+        let err: FileTransferError = FileTransferError::from_ssh(
+            FileTransferErrorType::ProtocolError,
+            ssh2::Error::new(ssh2::ErrorCode::Session(-9), "timed out"),
+        );
+        assert!(matches!(err.kind(), FileTransferErrorType::ConnectionError));
+        assert_eq!(
+            err.to_string(),
+            String::from("Connection error (operation timed out)")
+        );
+        // Other errors keep the provided type
+        let err: FileTransferError = FileTransferError::from_ssh(
+            FileTransferErrorType::NoSuchFileOrDirectory,
+            ssh2::Error::new(ssh2::ErrorCode::SFTP(2), "no such file"),
+        );
+        assert!(matches!(
+            err.kind(),
+            FileTransferErrorType::NoSuchFileOrDirectory
+        ));
+    }
+
+    #[test]
+    fn test_filetransfer_mod_error_source() {
+        use std::error::Error;
+        let err: FileTransferError = FileTransferError::new_ex(
+            FileTransferErrorType::IoErr(std::io::Error::from(std::io::ErrorKind::AddrInUse)),
+            String::from("could not bind"),
+        );
+        let source: &std::io::Error = err
+            .source()
+            .unwrap()
+            .downcast_ref::<std::io::Error>()
+            .unwrap();
+        assert_eq!(source.kind(), std::io::ErrorKind::AddrInUse);
+        assert!(FileTransferError::new(FileTransferErrorType::ProtocolError)
+            .source()
+            .is_none());
+        // Chain is preserved through `?` and boxed errors
+        let boxed = || -> Result<(), Box<dyn Error>> {
+            Err(FileTransferError::new(FileTransferErrorType::IoErr(
+                std::io::Error::from(std::io::ErrorKind::TimedOut),
+            )))?;
+            Ok(())
+        };
+        let err: Box<dyn Error> = boxed().err().unwrap();
+        assert_eq!(err.to_string(), String::from("IO error: timed out"));
+        assert!(err.source().unwrap().is::<std::io::Error>());
+        // Debug
+        assert_eq!(
+            format!(
+                "{:?}",
+                FileTransferError::new_ex(
+                    FileTransferErrorType::NoSuchFileOrDirectory,
+                    String::from("/tmp/a.txt")
+                )
+            ),
+            String::from("FileTransferError { kind: NoSuchFileOrDirectory, msg: \"/tmp/a.txt\" }")
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                FileTransferError::new(FileTransferErrorType::ConnectionError)
+            ),
+            String::from("FileTransferError { kind: ConnectionError }")
+        );
     }
 
     #[test]
@@ -247,6 +666,7 @@ mod tests {
             String::from("non va una mazza"),
         );
         assert_eq!(*err.msg.as_ref().unwrap(), String::from("non va una mazza"));
+        assert!(matches!(err.kind(), FileTransferErrorType::IoErr(_)));
         assert_eq!(
             format!("{}", err),
             String::from("IO error: address in use (non va una mazza)")
@@ -329,4 +749,4 @@ mod tests {
             String::from("Unsupported feature")
         );
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,399 @@
+//! ## ProxyJump
+//!
+//! `proxy_jump` is the module which provides the tunneling through SSH jump hosts for SSH based file transfers
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Dependencies
+#[cfg(feature = "ssh")]
+extern crate ssh2;
+#[cfg(feature = "ssh")]
+extern crate whoami;
+
+// Locals
+#[cfg(feature = "ssh")]
+use super::known_hosts;
+#[cfg(feature = "ssh")]
+use super::{FileTransferError, FileTransferErrorType};
+
+// Includes
+#[cfg(feature = "ssh")]
+use ssh2::{Channel, Session};
+#[cfg(feature = "ssh")]
+use std::io::{ErrorKind, Read, Write};
+#[cfg(feature = "ssh")]
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+#[cfg(feature = "ssh")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "ssh")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "ssh")]
+use std::thread;
+
+/// ## JumpHost
+///
+/// JumpHost describes an SSH host used to reach the target host (as OpenSSH's `ProxyJump`)
+#[derive(std::fmt::Debug, Clone, PartialEq)]
+pub struct JumpHost {
+    pub address: String,
+    pub port: u16,
+    pub username: Option<String>,
+}
+
+impl std::fmt::Display for JumpHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(username) = &self.username {
+            write!(f, "{}@", username)?;
+        }
+        // IPv6 addresses are enclosed in brackets, as in the `-J` option
+        match self.address.contains(':') {
+            true => write!(f, "[{}]:{}", self.address, self.port),
+            false => write!(f, "{}:{}", self.address, self.port),
+        }
+    }
+}
+
+/// ### connect_tcp
+///
+/// Open a TCP stream to `address:port`.
+/// If jump hosts are provided, the stream is tunneled through each of them, in order;
+/// the returned stream is then connected to a local proxy which forwards data through the last jump host.
+/// Jump host keys are verified against the user known hosts file; authentication on jump hosts is performed using the SSH agent
+#[cfg(feature = "ssh")]
+pub fn connect_tcp(
+    jump_hosts: &[JumpHost],
+    address: &str,
+    port: u16,
+) -> Result<TcpStream, FileTransferError> {
+    // Connect to first host (jump host or target)
+    let (first_address, first_port): (&str, u16) = match jump_hosts.first() {
+        Some(jump) => (jump.address.as_str(), jump.port),
+        None => (address, port),
+    };
+    // NOTE: connect to (address, port), so IPv6 addresses don't need brackets
+    let mut tcp: TcpStream = match TcpStream::connect((first_address, first_port)) {
+        Ok(stream) => stream,
+        Err(err) => {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::BadAddress,
+                match jump_hosts.first() {
+                    Some(jump) => format!("Could not connect to jump host '{}': {}", jump, err),
+                    None => format!("{}", err),
+                },
+            ))
+        }
+    };
+    // Tunnel through each jump host to the next one
+    let known_hosts: Option<PathBuf> = known_hosts::known_hosts_path();
+    for (i, jump) in jump_hosts.iter().enumerate() {
+        let (next_address, next_port): (&str, u16) = match jump_hosts.get(i + 1) {
+            Some(next) => (next.address.as_str(), next.port),
+            None => (address, port),
+        };
+        tcp = tunnel(tcp, jump, next_address, next_port, known_hosts.as_deref())?;
+    }
+    Ok(tcp)
+}
+
+/// ### tunnel
+///
+/// Establish an SSH session with the jump host over `tcp` and open a direct-tcpip channel to `address:port`.
+/// The jump host key is verified against the `known_hosts` file, if any, before authenticating.
+/// Returns a local TCP stream forwarded to the channel
+#[cfg(feature = "ssh")]
+fn tunnel(
+    tcp: TcpStream,
+    jump: &JumpHost,
+    address: &str,
+    port: u16,
+    known_hosts: Option<&Path>,
+) -> Result<TcpStream, FileTransferError> {
+    let io_err = |err: std::io::Error| {
+        FileTransferError::new_ex(
+            FileTransferErrorType::IoErr(err),
+            format!("Could not setup proxy for jump host '{}'", jump),
+        )
+    };
+    // Keep a handle to the socket, to wait for the data sent by the jump host
+    let socket: TcpStream = tcp.try_clone().map_err(io_err)?;
+    // Create session
+    let mut session: Session = match Session::new() {
+        Ok(s) => s,
+        Err(err) => {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::ConnectionError,
+                format!("Could not create session for jump host '{}': {}", jump, err),
+            ))
+        }
+    };
+    session.set_tcp_stream(tcp);
+    if let Err(err) = session.handshake() {
+        return Err(FileTransferError::new_ex(
+            FileTransferErrorType::ConnectionError,
+            format!("Handshake with jump host '{}' failed: {}", jump, err),
+        ));
+    }
+    // Verify host key
+    if let Some(path) = known_hosts {
+        known_hosts::verify_host_key_in(&session, jump.address.as_str(), jump.port, path)?;
+    }
+    // Authenticate with agent
+    let username: String = match &jump.username {
+        Some(u) => u.clone(),
+        None => whoami::username(),
+    };
+    if let Err(err) = session.userauth_agent(username.as_str()) {
+        return Err(FileTransferError::new_ex(
+            FileTransferErrorType::AuthenticationFailed,
+            format!("Could not authenticate on jump host '{}': {}", jump, err),
+        ));
+    }
+    // Open channel to next host
+    let channel: Channel = match session.channel_direct_tcpip(address, port, None) {
+        Ok(channel) => channel,
+        Err(err) => {
+            return Err(FileTransferError::new_ex(
+                FileTransferErrorType::ConnectionError,
+                format!(
+                    "Jump host '{}' could not open a channel to {}:{}: {}",
+                    jump, address, port, err
+                ),
+            ))
+        }
+    };
+    // Create local proxy
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").map_err(io_err)?;
+    let client: TcpStream =
+        TcpStream::connect(listener.local_addr().map_err(io_err)?).map_err(io_err)?;
+    // Any local process can connect to the listener: only the connection from `client` gets the tunnel
+    let client_addr: SocketAddr = client.local_addr().map_err(io_err)?;
+    let proxy: TcpStream = loop {
+        let (stream, peer): (TcpStream, SocketAddr) = listener.accept().map_err(io_err)?;
+        if peer == client_addr {
+            break stream;
+        }
+    };
+    let upstream: TcpStream = proxy.try_clone().map_err(io_err)?;
+    let tunnel: Arc<Mutex<Tunnel>> = Arc::new(Mutex::new(Tunnel {
+        session,
+        channel,
+        socket: socket.try_clone().map_err(io_err)?,
+    }));
+    let downstream: Arc<Mutex<Tunnel>> = tunnel.clone();
+    thread::spawn(move || forward_upstream(tunnel, upstream));
+    thread::spawn(move || forward_downstream(downstream, proxy, socket));
+    Ok(client)
+}
+
+/// ## Tunnel
+///
+/// Tunnel holds the session with a jump host and the channel opened through it, shared by the two forwarding threads.
+/// The session is blocking, except while the channel is drained
+#[cfg(feature = "ssh")]
+struct Tunnel {
+    session: Session,
+    channel: Channel,
+    socket: TcpStream, // Stream to the jump host
+}
+
+#[cfg(feature = "ssh")]
+impl Tunnel {
+    /// ### close
+    ///
+    /// Close the channel, the proxy stream and the stream to the jump host, which stops both forwarding threads
+    fn close(&mut self, proxy: &TcpStream) {
+        let _ = self.channel.close();
+        let _ = proxy.shutdown(Shutdown::Both);
+        let _ = self.socket.shutdown(Shutdown::Both);
+    }
+}
+
+/// ### forward_upstream
+///
+/// Forward the data read from the local proxy stream to the channel, until the proxy stream gets closed
+#[cfg(feature = "ssh")]
+fn forward_upstream(tunnel: Arc<Mutex<Tunnel>>, mut proxy: TcpStream) {
+    let mut buffer: [u8; 65536] = [0; 65536];
+    loop {
+        let bytes: usize = match proxy.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(bytes) => bytes,
+        };
+        let mut tunnel = match tunnel.lock() {
+            Ok(tunnel) => tunnel,
+            Err(_) => break,
+        };
+        // NOTE: don't flush; flushing a channel discards its pending read data
+        // While writing, the session may have received the data the downstream thread is waiting for
+        if tunnel.channel.write_all(&buffer[..bytes]).is_err()
+            || !matches!(drain(&mut tunnel, &mut proxy), Ok(false))
+        {
+            tunnel.close(&proxy);
+            return;
+        }
+    }
+    // The proxy stream was closed: the other end of the channel may still send data
+    if let Ok(mut tunnel) = tunnel.lock() {
+        let _ = tunnel.channel.send_eof();
+    }
+}
+
+/// ### forward_downstream
+///
+/// Forward the data received on the channel to the local proxy stream, until the channel gets closed.
+/// The thread waits on `socket`, the stream to the jump host, so the session isn't held while idle
+#[cfg(feature = "ssh")]
+fn forward_downstream(tunnel: Arc<Mutex<Tunnel>>, mut proxy: TcpStream, socket: TcpStream) {
+    let mut byte: [u8; 1] = [0];
+    loop {
+        // Block until the jump host sends something (or closes the connection)
+        match socket.peek(&mut byte) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let mut tunnel = match tunnel.lock() {
+            Ok(tunnel) => tunnel,
+            Err(_) => break,
+        };
+        if !matches!(drain(&mut tunnel, &mut proxy), Ok(false)) {
+            break;
+        }
+    }
+    if let Ok(mut tunnel) = tunnel.lock() {
+        tunnel.close(&proxy);
+    }
+}
+
+/// ### drain
+///
+/// Write the data the session already received on the channel to the proxy stream, without waiting for more.
+/// Returns whether the channel reached EOF
+#[cfg(feature = "ssh")]
+fn drain(tunnel: &mut Tunnel, proxy: &mut TcpStream) -> std::io::Result<bool> {
+    let mut buffer: [u8; 65536] = [0; 65536];
+    tunnel.session.set_blocking(false);
+    let result: std::io::Result<bool> = loop {
+        match tunnel.channel.read(&mut buffer) {
+            Ok(0) => break Ok(tunnel.channel.eof()),
+            Ok(bytes) => {
+                if let Err(err) = proxy.write_all(&buffer[..bytes]) {
+                    break Err(err);
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break Ok(false),
+            Err(err) => break Err(err),
+        }
+    };
+    tunnel.session.set_blocking(true);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_filetransfer_proxy_jump_fmt() {
+        let jump: JumpHost = JumpHost {
+            address: String::from("bastion"),
+            port: 2222,
+            username: Some(String::from("root")),
+        };
+        assert_eq!(format!("{}", jump), String::from("root@bastion:2222"));
+        let jump: JumpHost = JumpHost {
+            address: String::from("bastion"),
+            port: 22,
+            username: None,
+        };
+        assert_eq!(format!("{}", jump), String::from("bastion:22"));
+        let jump: JumpHost = JumpHost {
+            address: String::from("fe80::1"),
+            port: 22,
+            username: None,
+        };
+        assert_eq!(format!("{}", jump), String::from("[fe80::1]:22"));
+    }
+
+    #[test]
+    #[cfg(feature = "ssh")]
+    fn test_filetransfer_proxy_jump_connect_direct() {
+        // Without jump hosts, stream is connected directly to target
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        assert!(connect_tcp(&[], "127.0.0.1", port).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "ssh")]
+    fn test_filetransfer_proxy_jump_connect_bad_jump() {
+        // Jump host doesn't accept connections
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        drop(listener);
+        let jump: JumpHost = JumpHost {
+            address: String::from("127.0.0.1"),
+            port,
+            username: None,
+        };
+        assert!(connect_tcp(&[jump], "127.0.0.1", 22).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "ssh")]
+    fn test_filetransfer_proxy_jump_tunnel_host_key_mismatch() {
+        // Jump host is known with other keys, for every key type it may offer
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = tmpdir.path().join("known_hosts");
+        let lines: Vec<String> = [
+            "ssh-ed25519",
+            "ssh-rsa",
+            "ecdsa-sha2-nistp256",
+            "ecdsa-sha2-nistp384",
+            "ecdsa-sha2-nistp521",
+        ]
+        .iter()
+        .map(|key_type| {
+            let mut blob: Vec<u8> = (key_type.len() as u32).to_be_bytes().to_vec();
+            blob.extend_from_slice(key_type.as_bytes());
+            blob.extend_from_slice(&[0; 32]);
+            format!(
+                "test.rebex.net {} {}",
+                key_type,
+                data_encoding::BASE64.encode(blob.as_slice())
+            )
+        })
+        .collect();
+        std::fs::write(path.as_path(), lines.join("\n")).unwrap();
+        let jump: JumpHost = JumpHost {
+            address: String::from("test.rebex.net"),
+            port: 22,
+            username: Some(String::from("demo")),
+        };
+        let tcp: TcpStream = TcpStream::connect(("test.rebex.net", 22)).unwrap();
+        // Must fail before authenticating
+        let err: FileTransferError = tunnel(tcp, &jump, "127.0.0.1", 22, Some(path.as_path()))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("doesn't match"));
+    }
+}
@@ -28,7 +28,10 @@ extern crate regex;
 extern crate ssh2;
 
 // Locals
-use super::{FileTransfer, FileTransferError, FileTransferErrorType};
+use super::known_hosts;
+use super::proxy_jump::{self, JumpHost};
+use super::ssh_agent;
+use super::{FileTransfer, FileTransferError, FileTransferErrorType, SshIdentity};
 use crate::fs::{FsDirectory, FsEntry, FsFile};
 use crate::utils::parser::parse_lstime;
 
@@ -38,13 +41,17 @@ use ssh2::{Channel, Session};
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::net::TcpStream;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// ## ScpFileTransfer
 ///
 /// SCP file transfer structure
 pub struct ScpFileTransfer {
     session: Option<Session>,
+    jump_hosts: Vec<JumpHost>,
+    identity: Option<SshIdentity>,
+    timeout: Duration,
+    auth_method: Option<String>,
     wrkdir: PathBuf,
 }
 
@@ -61,10 +68,37 @@ impl ScpFileTransfer {
     pub fn new() -> ScpFileTransfer {
         ScpFileTransfer {
             session: None,
+            jump_hosts: Vec::new(),
+            identity: None,
+            timeout: Duration::from_secs(0),
+            auth_method: None,
             wrkdir: PathBuf::from("~"),
         }
     }
 
+    /// ### set_jump_hosts
+    ///
+    /// Set the jump hosts to go through to reach the remote host
+    pub fn set_jump_hosts(&mut self, jump_hosts: Vec<JumpHost>) {
+        self.jump_hosts = jump_hosts;
+    }
+
+    /// ### set_identity
+    ///
+    /// Set the private key to authenticate with, before trying the SSH agent and the password
+    pub fn set_identity(&mut self, identity: Option<SshIdentity>) {
+        self.identity = identity;
+    }
+
+    /// ### set_timeout
+    ///
+    /// Set how long a blocking operation (e.g. reading file data) may wait for the server before failing.
+    /// The timeout applies to each operation, so a slow transfer which is still progressing is not interrupted.
+    /// Zero means no timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
     /// ### parse_ls_output
     ///
     /// Parse a line of `ls -l` output and tokenize the output into a `FsEntry`
@@ -159,6 +193,8 @@ impl ScpFileTransfer {
                     Ok(t) => t,
                     Err(_) => SystemTime::UNIX_EPOCH,
                 };
+                // Get link count
+                let nlink: Option<u64> = metadata.get(3).unwrap().as_str().parse::<u64>().ok();
                 // Get uid
                 let uid: Option<u32> = match metadata.get(4).unwrap().as_str().parse::<u32>() {
                     Ok(uid) => Some(uid),
@@ -179,10 +215,10 @@ impl ScpFileTransfer {
                     true => self.get_name_and_link(metadata.get(8).unwrap().as_str()),
                     false => (String::from(metadata.get(8).unwrap().as_str()), None),
                 };
-                // Get symlink
-                let symlink: Option<Box<FsEntry>> = match symlink_path {
+                // Get symlink; relative targets are resolved against the directory of the link
+                let symlink: Option<Box<FsEntry>> = match symlink_path.as_ref() {
                     None => None,
-                    Some(p) => match self.stat(p.as_path()) {
+                    Some(p) => match self.stat(path.join(p).as_path()) {
                         Ok(e) => Some(Box::new(e)),
                         Err(_) => None, // Ignore errors
                     },
@@ -208,9 +244,12 @@ impl ScpFileTransfer {
                         creation_time: mtime,
                         readonly: false,
                         symlink,
+                        link_target: symlink_path,
                         user: uid,
                         group: gid,
                         unix_pex: Some(unix_pex),
+                        nlink,
+                        inode: None,
                     }),
                     false => FsEntry::File(FsFile {
                         name: file_name,
@@ -222,9 +261,12 @@ impl ScpFileTransfer {
                         ftype: extension,
                         readonly: false,
                         symlink,
+                        link_target: symlink_path,
                         user: uid,
                         group: gid,
                         unix_pex: Some(unix_pex),
+                        nlink,
+                        inode: None,
                     }),
                 })
             }
@@ -245,6 +287,25 @@ impl ScpFileTransfer {
         (filename, symlink)
     }
 
+    /// ### stat_cmd
+    ///
+    /// Returns the directory to run the stat command from and the command which lists the metadata of `path`:
+    /// the command is run from its parent, so the name printed by ls is the file name.
+    /// `-d` lists directories themselves, instead of their content
+    fn stat_cmd(path: &Path) -> (PathBuf, String) {
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => (
+                PathBuf::from(parent),
+                format!("unset LANG; ls -ld \"{}\"", name.to_string_lossy()),
+            ),
+            // Root directory
+            _ => (
+                PathBuf::from(path),
+                format!("unset LANG; ls -ld \"{}\"", path.display()),
+            ),
+        }
+    }
+
     /// ### perform_shell_cmd_with
     ///
     /// Perform a shell command, but change directory to specified path first
@@ -313,51 +374,42 @@ impl FileTransfer for ScpFileTransfer {
         password: Option<String>,
     ) -> Result<Option<String>, FileTransferError> {
         // Setup tcp stream
-        let tcp: TcpStream = match TcpStream::connect(format!("{}:{}", address, port)) {
-            Ok(stream) => stream,
-            Err(err) => {
-                return Err(FileTransferError::new_ex(
-                    FileTransferErrorType::BadAddress,
-                    format!("{}", err),
-                ))
-            }
-        };
+        let tcp: TcpStream =
+            proxy_jump::connect_tcp(self.jump_hosts.as_slice(), address.as_str(), port)?;
         // Create session
         let mut session: Session = match Session::new() {
             Ok(s) => s,
             Err(err) => {
-                return Err(FileTransferError::new_ex(
+                return Err(FileTransferError::from_ssh(
                     FileTransferErrorType::ConnectionError,
-                    format!("{}", err),
+                    err,
                 ))
             }
         };
+        // Don't let blocking operations hang forever if the server stalls
+        session.set_timeout(self.timeout.as_millis().min(u32::MAX as u128) as u32);
         // Set TCP stream
         session.set_tcp_stream(tcp);
         // Open connection
         if let Err(err) = session.handshake() {
-            return Err(FileTransferError::new_ex(
+            return Err(FileTransferError::from_ssh(
                 FileTransferErrorType::ConnectionError,
-                format!("{}", err),
+                err,
             ));
         }
+        // Verify host key against known hosts
+        known_hosts::verify_host_key(&session, address.as_str(), port)?;
         let username: String = match username {
             Some(u) => u,
             None => String::from(""),
         };
-        // Try authenticating with user agent
-        if session.userauth_agent(username.as_str()).is_err() {
-            // Try authentication with password then
-            if let Err(err) = session.userauth_password(
-                username.as_str(),
-                password.unwrap_or_else(|| String::from("")).as_str(),
-            ) {
-                return Err(FileTransferError::new_ex(
-                    FileTransferErrorType::AuthenticationFailed,
-                    format!("{}", err),
-                ));
-            }
-        }
+        // Authenticate with the identity file, the identities of the user agent (queried again at each connection), or with password
+        self.auth_method = Some(ssh_agent::authenticate(
+            &session,
+            username.as_str(),
+            password.as_deref(),
+            self.identity.as_ref(),
+        )?);
         // Get banner
         let banner: Option<String> = match session.banner() {
             Some(s) => Some(String::from(s)),
@@ -386,9 +438,9 @@ impl FileTransfer for ScpFileTransfer {
                         self.session = None;
                         Ok(())
                     }
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::ConnectionError,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -542,6 +594,37 @@ impl FileTransfer for ScpFileTransfer {
         }
     }
 
+    /// ### touch
+    ///
+    /// Create an empty file; with `noclobber` set, the redirection fails if the file already exists
+    fn touch(&mut self, file: &Path) -> Result<(), FileTransferError> {
+        match self.is_connected() {
+            true => {
+                let p: PathBuf = self.wrkdir.clone();
+                match self.perform_shell_cmd_with_path(
+                    p.as_path(),
+                    format!("set -C; : > \"{}\"; echo $?", file.display()).as_str(),
+                ) {
+                    Ok(output) => match output.as_str().trim() == "0" {
+                        true => Ok(()), // File created
+                        false => Err(FileTransferError::new_ex(
+                            // Could not create file (e.g. it already exists)
+                            FileTransferErrorType::FileCreateDenied,
+                            format!("\"{}\"", file.display()),
+                        )),
+                    },
+                    Err(err) => Err(FileTransferError::new_ex(
+                        FileTransferErrorType::ProtocolError,
+                        format!("{}", err),
+                    )),
+                }
+            }
+            false => Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            )),
+        }
+    }
+
     /// ### remove
     ///
     /// Remove a file or a directory
@@ -620,16 +703,49 @@ impl FileTransfer for ScpFileTransfer {
         }
     }
 
+    /// ### hardlink
+    ///
+    /// Create a hard link at `link` to the `target` file, running `ln` on the remote host
+    fn hardlink(&mut self, target: &Path, link: &Path) -> Result<(), FileTransferError> {
+        match self.is_connected() {
+            true => {
+                let p: PathBuf = self.wrkdir.clone();
+                match self.perform_shell_cmd_with_path(
+                    p.as_path(),
+                    format!(
+                        "ln \"{}\" \"{}\"; echo $?",
+                        target.display(),
+                        link.display()
+                    )
+                    .as_str(),
+                ) {
+                    Ok(output) => {
+                        // Check if output is 0
+                        match output.as_str().trim() == "0" {
+                            true => Ok(()), // Link created
+                            false => Err(FileTransferError::new_ex(
+                                // Could not create link
+                                FileTransferErrorType::FileCreateDenied,
+                                format!("\"{}\"", link.display()),
+                            )),
+                        }
+                    }
+                    Err(err) => Err(FileTransferError::new_ex(
+                        FileTransferErrorType::ProtocolError,
+                        format!("{}", err),
+                    )),
+                }
+            }
+            false => Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            )),
+        }
+    }
+
     /// ### stat
     ///
-    /// Stat file and return FsEntry
+    /// Stat file or directory and return FsEntry; its metadata are parsed from `ls -ld`, as `list_dir` does
     fn stat(&mut self, path: &Path) -> Result<FsEntry, FileTransferError> {
-        if path.is_dir() {
-            return Err(FileTransferError::new_ex(
-                FileTransferErrorType::UnsupportedFeature,
-                String::from("stat is not supported for directories"),
-            ));
-        }
         let path: PathBuf = match path.is_absolute() {
             true => PathBuf::from(path),
             false => {
@@ -640,26 +756,18 @@ impl FileTransfer for ScpFileTransfer {
         };
         match self.is_connected() {
             true => {
-                let p: PathBuf = self.wrkdir.clone();
-                match self.perform_shell_cmd_with_path(
-                    p.as_path(),
-                    format!("ls -l \"{}\"", path.display()).as_str(),
-                ) {
+                let (parent, cmd): (PathBuf, String) = ScpFileTransfer::stat_cmd(path.as_path());
+                match self.perform_shell_cmd_with_path(parent.as_path(), cmd.as_str()) {
+                    // NOTE: ls writes nothing to stdout if the path doesn't exist
+                    Ok(line) if line.trim().is_empty() => Err(FileTransferError::new(
+                        FileTransferErrorType::NoSuchFileOrDirectory,
+                    )),
                     Ok(line) => {
-                        // Parse ls line
-                        let parent: PathBuf = match path.as_path().parent() {
-                            Some(p) => PathBuf::from(p),
-                            None => {
-                                return Err(FileTransferError::new_ex(
-                                    FileTransferErrorType::UnsupportedFeature,
-                                    String::from("Path has no parent"),
-                                ))
-                            }
-                        };
                         match self.parse_ls_output(parent.as_path(), line.as_str().trim()) {
                             Ok(entry) => Ok(entry),
-                            Err(_) => Err(FileTransferError::new(
-                                FileTransferErrorType::NoSuchFileOrDirectory,
+                            Err(_) => Err(FileTransferError::new_ex(
+                                FileTransferErrorType::ProtocolError,
+                                format!("Could not parse ls output: {}", line.trim()),
                             )),
                         }
                     }
@@ -715,9 +823,9 @@ impl FileTransfer for ScpFileTransfer {
                 };
                 match session.scp_send(file_name, mode, local.size as u64, Some(times)) {
                     Ok(channel) => Ok(Box::new(BufWriter::with_capacity(8192, channel))),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::ProtocolError,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -738,9 +846,9 @@ impl FileTransfer for ScpFileTransfer {
                 session.set_blocking(true);
                 match session.scp_recv(file.abs_path.as_path()) {
                     Ok(reader) => Ok(Box::new(BufReader::with_capacity(8192, reader.0))),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::ProtocolError,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -773,6 +881,13 @@ impl FileTransfer for ScpFileTransfer {
         // Nothing to do
         Ok(())
     }
+
+    /// ### get_auth_method
+    ///
+    /// Get the SSH agent identity (or password) which authenticated the session
+    fn get_auth_method(&self) -> Option<String> {
+        self.auth_method.clone()
+    }
 }
 
 #[cfg(test)]
@@ -785,6 +900,7 @@ mod tests {
         let client: ScpFileTransfer = ScpFileTransfer::new();
         assert!(client.session.is_none());
         assert_eq!(client.is_connected(), false);
+        assert!(client.get_auth_method().is_none());
     }
 
     #[test]
@@ -943,9 +1059,44 @@ mod tests {
             .unwrap();
         if let FsEntry::File(file) = file {
             assert_eq!(file.abs_path, PathBuf::from("/readme.txt"));
+            assert_eq!(file.name.as_str(), "readme.txt");
+            assert!(file.unix_pex.is_some());
+            assert!(file.size > 0);
         } else {
             panic!("Expected readme.txt to be a file");
         }
+        // Directories are stat themselves
+        let dir: FsEntry = client.stat(PathBuf::from("/pub").as_path()).ok().unwrap();
+        if let FsEntry::Directory(dir) = dir {
+            assert_eq!(dir.abs_path, PathBuf::from("/pub"));
+            assert_eq!(dir.name.as_str(), "pub");
+        } else {
+            panic!("Expected pub to be a directory");
+        }
+        // No such file
+        assert!(matches!(
+            client
+                .stat(PathBuf::from("/omar.txt").as_path())
+                .err()
+                .unwrap()
+                .kind(),
+            FileTransferErrorType::NoSuchFileOrDirectory
+        ));
+    }
+
+    #[test]
+    fn test_filetransfer_scp_stat_cmd() {
+        assert_eq!(
+            ScpFileTransfer::stat_cmd(Path::new("/home/omar/readme.txt")),
+            (
+                PathBuf::from("/home/omar"),
+                String::from("unset LANG; ls -ld \"readme.txt\"")
+            )
+        );
+        assert_eq!(
+            ScpFileTransfer::stat_cmd(Path::new("/")),
+            (PathBuf::from("/"), String::from("unset LANG; ls -ld \"/\""))
+        );
     }
 
     #[test]
@@ -971,9 +1122,12 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         // Receive file
         assert!(client.recv_file(&file).is_ok());
@@ -1004,9 +1158,12 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         assert!(client.recv_file(&file).is_err());
         // Disconnect
@@ -1042,9 +1199,12 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         let mut scp: ScpFileTransfer = ScpFileTransfer::new();
         assert!(scp.change_dir(Path::new("/tmp")).is_err());
@@ -1055,5 +1215,8 @@ mod tests {
         assert!(scp.stat(Path::new("/tmp")).is_err());
         assert!(scp.recv_file(&file).is_err());
         assert!(scp.send_file(&file, Path::new("/tmp/omar.txt")).is_err());
+        assert!(scp
+            .hardlink(Path::new("/tmp/omar.txt"), Path::new("/tmp/link.txt"))
+            .is_err());
     }
 }
@@ -27,23 +27,35 @@
 extern crate ssh2;
 
 // Locals
-use super::{FileTransfer, FileTransferError, FileTransferErrorType};
+use super::known_hosts;
+use super::proxy_jump::{self, JumpHost};
+use super::ssh_agent;
+use super::{FileTransfer, FileTransferError, FileTransferErrorType, SshIdentity};
 use crate::fs::{FsDirectory, FsEntry, FsFile};
 
 // Includes
-use ssh2::{FileStat, OpenFlags, OpenType, Session, Sftp};
-use std::io::{BufReader, BufWriter, Read, Write};
+use ssh2::{Channel, FileStat, OpenFlags, OpenType, Session, Sftp};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::net::TcpStream;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
+/// Size of each read request libssh2 sends to the server. While reading a file, libssh2 keeps up to
+/// four times the size of each `read` in flight, split into requests of this size
+const SFTP_READ_REQUEST_SIZE: usize = 30000;
+
 /// ## SftpFileTransfer
 ///
 /// SFTP file transfer structure
 pub struct SftpFileTransfer {
     session: Option<Session>,
+    jump_hosts: Vec<JumpHost>,
+    identity: Option<SshIdentity>,
+    timeout: Duration,
+    auth_method: Option<String>,
     sftp: Option<Sftp>,
     wrkdir: PathBuf,
+    read_concurrency: usize, // Read requests kept in flight while receiving files
 }
 
 impl Default for SftpFileTransfer {
@@ -59,8 +71,68 @@ impl SftpFileTransfer {
     pub fn new() -> SftpFileTransfer {
         SftpFileTransfer {
             session: None,
+            jump_hosts: Vec::new(),
+            identity: None,
+            timeout: Duration::from_secs(0),
+            auth_method: None,
             sftp: None,
             wrkdir: PathBuf::from("~"),
+            read_concurrency: 1,
+        }
+    }
+
+    /// ### set_jump_hosts
+    ///
+    /// Set the jump hosts to go through to reach the remote host
+    pub fn set_jump_hosts(&mut self, jump_hosts: Vec<JumpHost>) {
+        self.jump_hosts = jump_hosts;
+    }
+
+    /// ### set_identity
+    ///
+    /// Set the private key to authenticate with, before trying the SSH agent and the password
+    pub fn set_identity(&mut self, identity: Option<SshIdentity>) {
+        self.identity = identity;
+    }
+
+    /// ### set_timeout
+    ///
+    /// Set how long a blocking operation (e.g. reading file data) may wait for the server before failing.
+    /// The timeout applies to each operation, so a slow transfer which is still progressing is not interrupted.
+    /// Zero means no timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// ### set_read_concurrency
+    ///
+    /// Set how many read requests are kept in flight while receiving a file.
+    /// The server answers the requests while the previous data is still travelling, so on high latency links
+    /// the throughput grows with the concurrency; the data is still returned in order.
+    /// 1 (or 0) reads the file as before, one buffer at a time
+    pub fn set_read_concurrency(&mut self, concurrency: usize) {
+        self.read_concurrency = concurrency;
+    }
+
+    /// ### file_reader
+    ///
+    /// Wrap the remote file into the reader returned by `recv_file`.
+    /// With a read concurrency greater than 1, the file is read in windows big enough to make libssh2
+    /// keep `read_concurrency` requests in flight (at least; larger reads by the caller keep more)
+    fn file_reader(&self, file: ssh2::File) -> Box<dyn Read> {
+        Box::new(BufReader::with_capacity(
+            SftpFileTransfer::read_window(self.read_concurrency),
+            file,
+        ))
+    }
+
+    /// ### read_window
+    ///
+    /// Returns the size of the reads which keep `concurrency` read requests in flight
+    fn read_window(concurrency: usize) -> usize {
+        match concurrency {
+            0 | 1 => 8192,
+            n => n * SFTP_READ_REQUEST_SIZE / 4,
         }
     }
 
@@ -75,23 +147,23 @@ impl SftpFileTransfer {
                 match self.sftp.as_ref().unwrap().realpath(root.as_path()) {
                     Ok(p) => match self.sftp.as_ref().unwrap().stat(p.as_path()) {
                         Ok(_) => Ok(p),
-                        Err(err) => Err(FileTransferError::new_ex(
+                        Err(err) => Err(FileTransferError::from_ssh(
                             FileTransferErrorType::NoSuchFileOrDirectory,
-                            format!("{}", err),
+                            err,
                         )),
                     },
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::NoSuchFileOrDirectory,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
             false => match self.sftp.as_ref().unwrap().realpath(p) {
                 Ok(p) => match self.sftp.as_ref().unwrap().stat(p.as_path()) {
                     Ok(_) => Ok(p),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::NoSuchFileOrDirectory,
-                        format!("{}", err),
+                        err,
                     )),
                 },
                 Err(_) => Err(FileTransferError::new(
@@ -149,18 +221,23 @@ impl SftpFileTransfer {
             .unwrap_or(SystemTime::UNIX_EPOCH);
         // Check if symlink
         let is_symlink: bool = metadata.file_type().is_symlink();
-        let symlink: Option<Box<FsEntry>> = match is_symlink {
-            true => {
-                // Read symlink
-                match self.sftp.as_ref().unwrap().readlink(path) {
-                    Ok(p) => match self.stat(p.as_path()) {
-                        Ok(entry) => Some(Box::new(entry)),
-                        Err(_) => None, // Ignore errors
-                    },
-                    Err(_) => None,
+        let link_target: Option<PathBuf> = match is_symlink {
+            true => self.sftp.as_ref().unwrap().readlink(path).ok(),
+            false => None,
+        };
+        // Stat symlink target; relative targets are resolved against the directory of the link
+        let symlink: Option<Box<FsEntry>> = match link_target.as_ref() {
+            Some(p) => {
+                let target: PathBuf = match path.parent() {
+                    Some(parent) => parent.join(p),
+                    None => p.clone(),
+                };
+                match self.stat(target.as_path()) {
+                    Ok(entry) => Some(Box::new(entry)),
+                    Err(_) => None, // Ignore errors
                 }
             }
-            false => None,
+            None => None,
         };
         // Is a directory?
         match metadata.is_dir() {
@@ -172,9 +249,12 @@ impl SftpFileTransfer {
                 creation_time: SystemTime::UNIX_EPOCH,
                 readonly: false,
                 symlink,
+                link_target,
                 user: uid,
                 group: gid,
                 unix_pex: pex,
+                nlink: None,
+                inode: None,
             }),
             false => FsEntry::File(FsFile {
                 name: file_name,
@@ -186,9 +266,12 @@ impl SftpFileTransfer {
                 creation_time: SystemTime::UNIX_EPOCH,
                 readonly: false,
                 symlink,
+                link_target,
                 user: uid,
                 group: gid,
                 unix_pex: pex,
+                nlink: None,
+                inode: None,
             }),
         }
     }
@@ -206,60 +289,51 @@ impl FileTransfer for SftpFileTransfer {
         password: Option<String>,
     ) -> Result<Option<String>, FileTransferError> {
         // Setup tcp stream
-        let tcp: TcpStream = match TcpStream::connect(format!("{}:{}", address, port)) {
-            Ok(stream) => stream,
-            Err(err) => {
-                return Err(FileTransferError::new_ex(
-                    FileTransferErrorType::BadAddress,
-                    format!("{}", err),
-                ))
-            }
-        };
+        let tcp: TcpStream =
+            proxy_jump::connect_tcp(self.jump_hosts.as_slice(), address.as_str(), port)?;
         // Create session
         let mut session: Session = match Session::new() {
             Ok(s) => s,
             Err(err) => {
-                return Err(FileTransferError::new_ex(
+                return Err(FileTransferError::from_ssh(
                     FileTransferErrorType::ConnectionError,
-                    format!("{}", err),
+                    err,
                 ))
             }
         };
+        // Don't let blocking operations hang forever if the server stalls
+        session.set_timeout(self.timeout.as_millis().min(u32::MAX as u128) as u32);
         // Set TCP stream
         session.set_tcp_stream(tcp);
         // Open connection
         if let Err(err) = session.handshake() {
-            return Err(FileTransferError::new_ex(
+            return Err(FileTransferError::from_ssh(
                 FileTransferErrorType::ConnectionError,
-                format!("{}", err),
+                err,
             ));
         }
+        // Verify host key against known hosts
+        known_hosts::verify_host_key(&session, address.as_str(), port)?;
         let username: String = match username {
             Some(u) => u,
             None => String::from(""),
         };
-        // Try authenticating with user agent
-        if session.userauth_agent(username.as_str()).is_err() {
-            // Try authentication with password then
-            if let Err(err) = session.userauth_password(
-                username.as_str(),
-                password.unwrap_or_else(|| String::from("")).as_str(),
-            ) {
-                return Err(FileTransferError::new_ex(
-                    FileTransferErrorType::AuthenticationFailed,
-                    format!("{}", err),
-                ));
-            }
-        }
+        // Authenticate with the identity file, the identities of the user agent (queried again at each connection), or with password
+        self.auth_method = Some(ssh_agent::authenticate(
+            &session,
+            username.as_str(),
+            password.as_deref(),
+            self.identity.as_ref(),
+        )?);
         // Set blocking to true
         session.set_blocking(true);
         // Get Sftp client
         let sftp: Sftp = match session.sftp() {
             Ok(s) => s,
             Err(err) => {
-                return Err(FileTransferError::new_ex(
+                return Err(FileTransferError::from_ssh(
                     FileTransferErrorType::ProtocolError,
-                    format!("{}", err),
+                    err,
                 ))
             }
         };
@@ -267,9 +341,9 @@ impl FileTransfer for SftpFileTransfer {
         self.wrkdir = match sftp.realpath(PathBuf::from(".").as_path()) {
             Ok(p) => p,
             Err(err) => {
-                return Err(FileTransferError::new_ex(
+                return Err(FileTransferError::from_ssh(
                     FileTransferErrorType::ProtocolError,
-                    format!("{}", err),
+                    err,
                 ))
             }
         };
@@ -298,9 +372,9 @@ impl FileTransfer for SftpFileTransfer {
                         self.sftp = None;
                         Ok(())
                     }
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::ConnectionError,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -361,9 +435,9 @@ impl FileTransfer for SftpFileTransfer {
                 };
                 // Get files
                 match sftp.readdir(dir.as_path()) {
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::DirStatFailed,
-                        format!("{}", err),
+                        err,
                     )),
                     Ok(files) => {
                         // Allocate vector
@@ -392,9 +466,36 @@ impl FileTransfer for SftpFileTransfer {
                 let path: PathBuf = self.get_abs_path(PathBuf::from(dir).as_path());
                 match sftp.mkdir(path.as_path(), 0o775) {
                     Ok(_) => Ok(()),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
+                        FileTransferErrorType::FileCreateDenied,
+                        err,
+                    )),
+                }
+            }
+            None => Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            )),
+        }
+    }
+
+    /// ### touch
+    ///
+    /// Create an empty file; the file is opened exclusively, so an existing file is never overwritten
+    fn touch(&mut self, file: &Path) -> Result<(), FileTransferError> {
+        match self.sftp.as_ref() {
+            Some(sftp) => {
+                let path: PathBuf = self.get_abs_path(file);
+                // The file is closed as soon as the handle is dropped
+                match sftp.open_mode(
+                    path.as_path(),
+                    OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+                    0o644,
+                    OpenType::File,
+                ) {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::FileCreateDenied,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -419,9 +520,9 @@ impl FileTransfer for SftpFileTransfer {
                 // Remove file
                 match self.sftp.as_ref().unwrap().unlink(f.abs_path.as_path()) {
                     Ok(_) => Ok(()),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::PexError,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -440,9 +541,9 @@ impl FileTransfer for SftpFileTransfer {
                 // Finally remove directory
                 match self.sftp.as_ref().unwrap().rmdir(d.abs_path.as_path()) {
                     Ok(_) => Ok(()),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::PexError,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -464,15 +565,95 @@ impl FileTransfer for SftpFileTransfer {
                 let abs_src: PathBuf = file.get_abs_path();
                 match sftp.rename(abs_src.as_path(), abs_dst.as_path(), None) {
                     Ok(_) => Ok(()),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::FileCreateDenied,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
         }
     }
 
+    /// ### hardlink
+    ///
+    /// Create a hard link at `link` to the `target` file.
+    /// libssh2 doesn't provide the `hardlink@openssh.com` extension, so `ln` is executed on a channel of the session;
+    /// if the server doesn't allow executing commands, `UnsupportedFeature` is returned
+    fn hardlink(&mut self, target: &Path, link: &Path) -> Result<(), FileTransferError> {
+        if self.sftp.is_none() {
+            return Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            ));
+        }
+        let target: PathBuf = self.get_abs_path(target);
+        let link: PathBuf = self.get_abs_path(link);
+        let session: &Session = match self.session.as_ref() {
+            Some(session) => session,
+            None => {
+                return Err(FileTransferError::new(
+                    FileTransferErrorType::UninitializedSession,
+                ))
+            }
+        };
+        let mut channel: Channel = match session.channel_session() {
+            Ok(channel) => channel,
+            Err(err) => {
+                return Err(FileTransferError::from_ssh(
+                    FileTransferErrorType::UnsupportedFeature,
+                    err,
+                ))
+            }
+        };
+        if let Err(err) = channel
+            .exec(format!("ln \"{}\" \"{}\" 2>&1", target.display(), link.display()).as_str())
+        {
+            return Err(FileTransferError::from_ssh(
+                FileTransferErrorType::UnsupportedFeature,
+                err,
+            ));
+        }
+        let mut output: String = String::new();
+        let _ = channel.read_to_string(&mut output);
+        let _ = channel.wait_close();
+        match channel.exit_status() {
+            Ok(0) => Ok(()),
+            _ => Err(FileTransferError::new_ex(
+                FileTransferErrorType::FileCreateDenied,
+                String::from(output.trim()),
+            )),
+        }
+    }
+
+    /// ### chmod
+    ///
+    /// Change the UNIX permissions of the remote file or directory at `path`, with a setstat request
+    fn chmod(&mut self, path: &Path, mode: (u8, u8, u8)) -> Result<(), FileTransferError> {
+        match self.sftp.as_ref() {
+            Some(sftp) => {
+                let path: PathBuf = self.get_abs_path(path);
+                let (u, g, o): (u8, u8, u8) = mode;
+                let stat: FileStat = FileStat {
+                    size: None,
+                    uid: None,
+                    gid: None,
+                    perm: Some(((u as u32) << 6) + ((g as u32) << 3) + (o as u32)),
+                    atime: None,
+                    mtime: None,
+                };
+                match sftp.setstat(path.as_path(), stat) {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(FileTransferError::from_ssh(
+                        FileTransferErrorType::PexError,
+                        err,
+                    )),
+                }
+            }
+            None => Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            )),
+        }
+    }
+
     /// ### stat
     ///
     /// Stat file and return FsEntry
@@ -487,9 +668,9 @@ impl FileTransfer for SftpFileTransfer {
                 // Get file
                 match sftp.stat(dir.as_path()) {
                     Ok(metadata) => Ok(self.make_fsentry(dir.as_path(), &metadata)),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::NoSuchFileOrDirectory,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -527,15 +708,63 @@ impl FileTransfer for SftpFileTransfer {
                     OpenType::File,
                 ) {
                     Ok(file) => Ok(Box::new(BufWriter::with_capacity(65536, file))),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::FileCreateDenied,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
         }
     }
 
+    /// ### send_file_at
+    ///
+    /// Send file to remote, writing the remote file from byte `offset` on, without truncating it
+    fn send_file_at(
+        &mut self,
+        local: &FsFile,
+        file_name: &Path,
+        offset: usize,
+    ) -> Result<(Box<dyn Write>, usize), FileTransferError> {
+        if offset == 0 {
+            return Ok((self.send_file(local, file_name)?, 0));
+        }
+        match self.sftp.as_ref() {
+            None => Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            )),
+            Some(sftp) => {
+                let remote_path: PathBuf = self.get_abs_path(file_name);
+                // Open remote file and seek to offset
+                match sftp.open_mode(
+                    remote_path.as_path(),
+                    OpenFlags::WRITE,
+                    0o644,
+                    OpenType::File,
+                ) {
+                    Ok(mut file) => match file.seek(SeekFrom::Start(offset as u64)) {
+                        Ok(_) => Ok((Box::new(BufWriter::with_capacity(65536, file)), offset)),
+                        Err(err) => Err(FileTransferError::new_ex(
+                            FileTransferErrorType::IoErr(err),
+                            format!("Could not seek to {}", offset),
+                        )),
+                    },
+                    Err(err) => Err(FileTransferError::from_ssh(
+                        FileTransferErrorType::FileCreateDenied,
+                        err,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// ### can_resume_upload
+    ///
+    /// Returns whether `send_file_at` can append to a partial remote file
+    fn can_resume_upload(&self) -> bool {
+        true
+    }
+
     /// ### recv_file
     ///
     /// Receive file from remote with provided name
@@ -552,10 +781,43 @@ impl FileTransfer for SftpFileTransfer {
                 };
                 // Open remote file
                 match sftp.open(remote_path.as_path()) {
-                    Ok(file) => Ok(Box::new(BufReader::with_capacity(8192, file))),
-                    Err(err) => Err(FileTransferError::new_ex(
+                    Ok(file) => Ok(self.file_reader(file)),
+                    Err(err) => Err(FileTransferError::from_ssh(
+                        FileTransferErrorType::NoSuchFileOrDirectory,
+                        err,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// ### recv_file_at
+    ///
+    /// Receive file from remote, starting from byte `offset`
+    fn recv_file_at(
+        &mut self,
+        file: &FsFile,
+        offset: usize,
+    ) -> Result<Box<dyn Read>, FileTransferError> {
+        match self.sftp.as_ref() {
+            None => Err(FileTransferError::new(
+                FileTransferErrorType::UninitializedSession,
+            )),
+            Some(sftp) => {
+                // Get remote file name
+                let remote_path: PathBuf = self.get_remote_path(file.abs_path.as_path())?;
+                // Open remote file and seek to offset
+                match sftp.open(remote_path.as_path()) {
+                    Ok(mut file) => match file.seek(SeekFrom::Start(offset as u64)) {
+                        Ok(_) => Ok(self.file_reader(file)),
+                        Err(err) => Err(FileTransferError::new_ex(
+                            FileTransferErrorType::IoErr(err),
+                            format!("Could not seek to {}", offset),
+                        )),
+                    },
+                    Err(err) => Err(FileTransferError::from_ssh(
                         FileTransferErrorType::NoSuchFileOrDirectory,
-                        format!("{}", err),
+                        err,
                     )),
                 }
             }
@@ -581,6 +843,13 @@ impl FileTransfer for SftpFileTransfer {
     fn on_recv(&mut self, _readable: Box<dyn Read>) -> Result<(), FileTransferError> {
         Ok(())
     }
+
+    /// ### get_auth_method
+    ///
+    /// Get the SSH agent identity (or password) which authenticated the session
+    fn get_auth_method(&self) -> Option<String> {
+        self.auth_method.clone()
+    }
 }
 
 #[cfg(test)]
@@ -595,6 +864,20 @@ mod tests {
         assert!(client.sftp.is_none());
         assert_eq!(client.wrkdir, PathBuf::from("~"));
         assert_eq!(client.is_connected(), false);
+        assert!(client.get_auth_method().is_none());
+        assert_eq!(client.read_concurrency, 1);
+    }
+
+    #[test]
+    fn test_filetransfer_sftp_read_window() {
+        // 1 (and 0) keep the simple path
+        assert_eq!(SftpFileTransfer::read_window(0), 8192);
+        assert_eq!(SftpFileTransfer::read_window(1), 8192);
+        // libssh2 keeps 4 times the read size in flight
+        assert_eq!(
+            SftpFileTransfer::read_window(8) * 4,
+            8 * SFTP_READ_REQUEST_SIZE
+        );
     }
 
     #[test]
@@ -798,12 +1081,27 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         // Receive file
         assert!(client.recv_file(&file).is_ok());
+        // Pipelined reads return the same data
+        let mut data: Vec<u8> = Vec::new();
+        let mut reader: Box<dyn Read> = client.recv_file(&file).ok().unwrap();
+        assert!(reader.read_to_end(&mut data).is_ok());
+        assert!(client.on_recv(reader).is_ok());
+        client.set_read_concurrency(8);
+        let mut pipelined: Vec<u8> = Vec::new();
+        let mut reader: Box<dyn Read> = client.recv_file(&file).ok().unwrap();
+        assert!(reader.read_to_end(&mut pipelined).is_ok());
+        assert!(client.on_recv(reader).is_ok());
+        assert!(!data.is_empty());
+        assert_eq!(data, pipelined);
         // Disconnect
         assert!(client.disconnect().is_ok());
     }
@@ -833,9 +1131,12 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         assert!(client.recv_file(&file).is_err());
         // Disconnect
@@ -872,9 +1173,12 @@ mod tests {
             ftype: Some(String::from("txt")), // File type
             readonly: true,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         };
         let mut sftp: SftpFileTransfer = SftpFileTransfer::new();
         assert!(sftp.change_dir(Path::new("/tmp")).is_err());
@@ -885,5 +1189,9 @@ mod tests {
         assert!(sftp.stat(Path::new("/tmp")).is_err());
         assert!(sftp.recv_file(&file).is_err());
         assert!(sftp.send_file(&file, Path::new("/tmp/omar.txt")).is_err());
+        assert!(sftp
+            .hardlink(Path::new("/tmp/omar.txt"), Path::new("/tmp/link.txt"))
+            .is_err());
+        assert!(sftp.chmod(Path::new("/tmp/omar.txt"), (6, 4, 4)).is_err());
     }
 }
@@ -0,0 +1,533 @@
+//! ## Archive
+//!
+//! `archive` is the module which lists and extracts the members of archive files (tar and zip),
+//! so they can be browsed as directories
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Without the `archives` feature, archives are never read
+#![cfg_attr(not(feature = "archives"), allow(dead_code, unused_imports))]
+
+// Dependencies
+#[cfg(feature = "archives")]
+extern crate flate2;
+#[cfg(feature = "archives")]
+extern crate tar;
+#[cfg(feature = "archives")]
+extern crate zip;
+
+use super::{FsDirectory, FsEntry, FsFile};
+
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "archives")]
+use std::fs::File;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// ## ArchiveKind
+///
+/// ArchiveKind describes the format of an archive file
+#[derive(Clone, Copy, std::fmt::Debug, PartialEq)]
+pub enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    /// ### from_name
+    ///
+    /// Get the archive kind from the file name extension (`.tar`, `.tar.gz`, `.tgz`, `.zip`).
+    /// Returns `None` if the file is not a supported archive, or if Gateway has been built without the `archives` feature
+    pub fn from_name(name: &str) -> Option<ArchiveKind> {
+        if !cfg!(feature = "archives") {
+            return None;
+        }
+        let name: String = name.to_lowercase();
+        if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// ## Archive
+///
+/// Archive contains the members of an archive file, as file system entries.
+/// Entries paths are absolute paths inside the archive (e.g. `/docs/readme.txt`);
+/// directories which are not stored in the archive, but contain members, are listed too
+pub struct Archive {
+    path: PathBuf,                     // Archive file
+    kind: ArchiveKind,                 // Archive format
+    entries: Vec<FsEntry>,             // Archive members
+    members: HashMap<PathBuf, String>, // Name of the member in the archive, by entry path
+    paths: HashSet<PathBuf>,           // Paths of the entries
+}
+
+impl Archive {
+    /// ### open
+    ///
+    /// Read the list of members of the archive file at `path`
+    pub fn open(path: &Path, kind: ArchiveKind) -> Result<Archive, String> {
+        let mut archive: Archive = Archive {
+            path: PathBuf::from(path),
+            kind,
+            entries: Vec::new(),
+            members: HashMap::new(),
+            paths: HashSet::new(),
+        };
+        match kind {
+            ArchiveKind::Tar | ArchiveKind::TarGz => archive.read_tar()?,
+            ArchiveKind::Zip => archive.read_zip()?,
+        }
+        Ok(archive)
+    }
+
+    /// ### list_dir
+    ///
+    /// Get the entries contained in directory `dir` of the archive
+    pub fn list_dir(&self, dir: &Path) -> Vec<FsEntry> {
+        self.entries
+            .iter()
+            .filter(|x| x.get_abs_path().parent() == Some(dir))
+            .cloned()
+            .collect()
+    }
+
+    /// ### extract
+    ///
+    /// Write the content of the archive member `file` into `dst`. Returns the amount of bytes written
+    pub fn extract(&self, file: &FsFile, dst: &mut dyn Write) -> Result<u64, String> {
+        let member: &str = match self.members.get(&file.abs_path) {
+            Some(member) => member.as_str(),
+            None => return Err(format!("\"{}\": no such member", file.abs_path.display())),
+        };
+        match self.kind {
+            ArchiveKind::Tar | ArchiveKind::TarGz => self.extract_tar(member, dst),
+            ArchiveKind::Zip => self.extract_zip(member, dst),
+        }
+    }
+
+    /// ### push_member
+    ///
+    /// Add a member of the archive named `name` to the entries; its parent directories are added too, if missing.
+    /// Members which would be extracted outside of the archive root (e.g. `../x`) are ignored
+    fn push_member(
+        &mut self,
+        name: &str,
+        is_dir: bool,
+        size: usize,
+        mtime: SystemTime,
+        mode: Option<u32>,
+    ) {
+        let path: PathBuf = match member_path(name) {
+            Some(path) => path,
+            None => return,
+        };
+        // Add missing parent directories
+        let mut ancestors: Vec<&Path> = path.ancestors().skip(1).collect();
+        ancestors.pop(); // Root
+        for dir in ancestors.into_iter().rev() {
+            if self.paths.insert(PathBuf::from(dir)) {
+                self.entries
+                    .push(archive_entry(dir, true, 0, mtime, Some(0o755)));
+            }
+        }
+        // Directories may have been already added as parent of a previous member
+        if !self.paths.insert(path.clone()) && is_dir {
+            return;
+        }
+        if !is_dir {
+            self.members.insert(path.clone(), String::from(name));
+        }
+        self.entries
+            .push(archive_entry(path.as_path(), is_dir, size, mtime, mode));
+    }
+
+    /// ### tar_reader
+    ///
+    /// Open the tar archive, decompressing it if gzipped
+    #[cfg(feature = "archives")]
+    fn tar_reader(&self) -> Result<tar::Archive<Box<dyn std::io::Read>>, String> {
+        let file: File = File::open(self.path.as_path())
+            .map_err(|err| format!("Could not open archive: {}", err))?;
+        let reader: Box<dyn std::io::Read> = match self.kind {
+            ArchiveKind::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+            _ => Box::new(file),
+        };
+        Ok(tar::Archive::new(reader))
+    }
+
+    /// ### read_tar
+    ///
+    /// Read members of tar archive
+    #[cfg(feature = "archives")]
+    fn read_tar(&mut self) -> Result<(), String> {
+        let mut archive: tar::Archive<Box<dyn std::io::Read>> = self.tar_reader()?;
+        let members = archive
+            .entries()
+            .map_err(|err| format!("Could not read archive: {}", err))?;
+        for member in members {
+            let member = member.map_err(|err| format!("Could not read archive: {}", err))?;
+            let header: &tar::Header = member.header();
+            let is_dir: bool = match header.entry_type() {
+                tar::EntryType::Directory => true,
+                tar::EntryType::Regular | tar::EntryType::Continuous => false,
+                // Links and special files can't be extracted
+                _ => continue,
+            };
+            let name: String = String::from_utf8_lossy(&member.path_bytes()).to_string();
+            let mtime: SystemTime =
+                SystemTime::UNIX_EPOCH + Duration::from_secs(header.mtime().unwrap_or(0));
+            self.push_member(
+                name.as_str(),
+                is_dir,
+                header.size().unwrap_or(0) as usize,
+                mtime,
+                header.mode().ok(),
+            );
+        }
+        Ok(())
+    }
+
+    /// ### extract_tar
+    ///
+    /// Write the content of tar archive member `member` into `dst`
+    #[cfg(feature = "archives")]
+    fn extract_tar(&self, member: &str, dst: &mut dyn Write) -> Result<u64, String> {
+        let mut archive: tar::Archive<Box<dyn std::io::Read>> = self.tar_reader()?;
+        let entries = archive
+            .entries()
+            .map_err(|err| format!("Could not read archive: {}", err))?;
+        for entry in entries {
+            let mut entry = entry.map_err(|err| format!("Could not read archive: {}", err))?;
+            if entry.path_bytes().as_ref() == member.as_bytes() {
+                return std::io::copy(&mut entry, dst)
+                    .map_err(|err| format!("Could not extract \"{}\": {}", member, err));
+            }
+        }
+        Err(format!("\"{}\": no such member", member))
+    }
+
+    /// ### zip_reader
+    ///
+    /// Open the zip archive
+    #[cfg(feature = "archives")]
+    fn zip_reader(&self) -> Result<zip::ZipArchive<File>, String> {
+        let file: File = File::open(self.path.as_path())
+            .map_err(|err| format!("Could not open archive: {}", err))?;
+        zip::ZipArchive::new(file).map_err(|err| format!("Could not read archive: {}", err))
+    }
+
+    /// ### read_zip
+    ///
+    /// Read members of zip archive
+    #[cfg(feature = "archives")]
+    fn read_zip(&mut self) -> Result<(), String> {
+        let mut archive: zip::ZipArchive<File> = self.zip_reader()?;
+        for i in 0..archive.len() {
+            let member = archive
+                .by_index(i)
+                .map_err(|err| format!("Could not read archive: {}", err))?;
+            let name: String = String::from(member.name());
+            let is_dir: bool = member.is_dir();
+            let size: usize = member.size() as usize;
+            let mtime: SystemTime = zip_time(member.last_modified());
+            let mode: Option<u32> = member.unix_mode();
+            drop(member);
+            self.push_member(name.as_str(), is_dir, size, mtime, mode);
+        }
+        Ok(())
+    }
+
+    /// ### extract_zip
+    ///
+    /// Write the content of zip archive member `member` into `dst`
+    #[cfg(feature = "archives")]
+    fn extract_zip(&self, member: &str, dst: &mut dyn Write) -> Result<u64, String> {
+        let mut archive: zip::ZipArchive<File> = self.zip_reader()?;
+        let mut file = archive
+            .by_name(member)
+            .map_err(|err| format!("\"{}\": {}", member, err))?;
+        std::io::copy(&mut file, dst)
+            .map_err(|err| format!("Could not extract \"{}\": {}", member, err))
+    }
+
+    #[cfg(not(feature = "archives"))]
+    fn read_tar(&mut self) -> Result<(), String> {
+        Err(String::from("Archive support is not enabled"))
+    }
+
+    #[cfg(not(feature = "archives"))]
+    fn extract_tar(&self, _member: &str, _dst: &mut dyn Write) -> Result<u64, String> {
+        Err(String::from("Archive support is not enabled"))
+    }
+
+    #[cfg(not(feature = "archives"))]
+    fn read_zip(&mut self) -> Result<(), String> {
+        Err(String::from("Archive support is not enabled"))
+    }
+
+    #[cfg(not(feature = "archives"))]
+    fn extract_zip(&self, _member: &str, _dst: &mut dyn Write) -> Result<u64, String> {
+        Err(String::from("Archive support is not enabled"))
+    }
+}
+
+/// ### member_path
+///
+/// Get the absolute path inside the archive of the member named `name` (e.g. `./docs/` => `/docs`).
+/// Returns `None` for the root and for names which escape it
+fn member_path(name: &str) -> Option<PathBuf> {
+    let mut path: PathBuf = PathBuf::from("/");
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir | Component::RootDir => { /* Nothing to do */ }
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    match path.as_path() == Path::new("/") {
+        true => None,
+        false => Some(path),
+    }
+}
+
+/// ### archive_entry
+///
+/// Make the file system entry of an archive member
+fn archive_entry(
+    path: &Path,
+    is_dir: bool,
+    size: usize,
+    mtime: SystemTime,
+    mode: Option<u32>,
+) -> FsEntry {
+    let name: String = path
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let unix_pex: Option<(u8, u8, u8)> = mode.map(|mode| {
+        (
+            ((mode >> 6) & 0x7) as u8,
+            ((mode >> 3) & 0x7) as u8,
+            (mode & 0x7) as u8,
+        )
+    });
+    match is_dir {
+        true => FsEntry::Directory(FsDirectory {
+            name,
+            abs_path: PathBuf::from(path),
+            last_change_time: mtime,
+            last_access_time: mtime,
+            creation_time: mtime,
+            readonly: true,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex,
+            nlink: None,
+            inode: None,
+        }),
+        false => FsEntry::File(FsFile {
+            ftype: path.extension().map(|x| x.to_string_lossy().to_string()),
+            name,
+            abs_path: PathBuf::from(path),
+            last_change_time: mtime,
+            last_access_time: mtime,
+            creation_time: mtime,
+            size,
+            readonly: true,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex,
+            nlink: None,
+            inode: None,
+        }),
+    }
+}
+
+/// ### zip_time
+///
+/// Convert the (MS-DOS) modification time of a zip member into a `SystemTime`; invalid times are reported as UNIX epoch
+#[cfg(feature = "archives")]
+fn zip_time(time: zip::DateTime) -> SystemTime {
+    chrono::NaiveDate::from_ymd_opt(time.year() as i32, time.month() as u32, time.day() as u32)
+        .and_then(|date| {
+            date.and_hms_opt(
+                time.hour() as u32,
+                time.minute() as u32,
+                time.second() as u32,
+            )
+        })
+        .map(|datetime| datetime.timestamp())
+        .filter(|secs| *secs > 0)
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64))
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(all(test, feature = "archives"))]
+mod tests {
+
+    use super::*;
+
+    use std::io::Cursor;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_fs_archive_kind() {
+        assert_eq!(ArchiveKind::from_name("a.tar"), Some(ArchiveKind::Tar));
+        assert_eq!(ArchiveKind::from_name("a.TAR.GZ"), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::from_name("a.tgz"), Some(ArchiveKind::TarGz));
+        assert_eq!(ArchiveKind::from_name("a.zip"), Some(ArchiveKind::Zip));
+        assert!(ArchiveKind::from_name("a.txt").is_none());
+        assert!(ArchiveKind::from_name("tar").is_none());
+        // Member paths
+        assert_eq!(member_path("a/b.txt").unwrap(), PathBuf::from("/a/b.txt"));
+        assert_eq!(member_path("./a/").unwrap(), PathBuf::from("/a"));
+        assert!(member_path("./").is_none());
+        assert!(member_path("../a").is_none());
+    }
+
+    #[test]
+    fn test_fs_archive_tar() {
+        let tmpfile: NamedTempFile = NamedTempFile::new().unwrap();
+        {
+            let mut builder = tar::Builder::new(tmpfile.reopen().unwrap());
+            append_tar(&mut builder, "./readme.txt", b"hello");
+            append_tar(&mut builder, "docs/a/guide.md", b"# Guide\n");
+            builder.finish().unwrap();
+        }
+        let archive: Archive = Archive::open(tmpfile.path(), ArchiveKind::Tar)
+            .ok()
+            .unwrap();
+        // Root contains readme and the implicit docs directory
+        let mut names: Vec<String> = archive
+            .list_dir(Path::new("/"))
+            .iter()
+            .map(|x| x.get_name())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![String::from("docs"), String::from("readme.txt")]
+        );
+        assert!(archive.list_dir(Path::new("/docs"))[0].is_dir());
+        let guide: FsFile = match &archive.list_dir(Path::new("/docs/a"))[0] {
+            FsEntry::File(file) => file.clone(),
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        assert_eq!(guide.abs_path, PathBuf::from("/docs/a/guide.md"));
+        assert_eq!(guide.size, 8);
+        assert_eq!(guide.unix_pex, Some((6, 4, 4)));
+        // Extract
+        let mut data: Vec<u8> = Vec::new();
+        assert_eq!(archive.extract(&guide, &mut data).ok().unwrap(), 8);
+        assert_eq!(data.as_slice(), b"# Guide\n");
+        let mut readme: FsFile = guide;
+        readme.abs_path = PathBuf::from("/readme.txt");
+        let mut data: Vec<u8> = Vec::new();
+        assert!(archive.extract(&readme, &mut data).is_ok());
+        assert_eq!(data.as_slice(), b"hello");
+        // Directories can't be extracted
+        readme.abs_path = PathBuf::from("/docs");
+        assert!(archive.extract(&readme, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_fs_archive_tar_gz() {
+        let tmpfile: NamedTempFile = NamedTempFile::new().unwrap();
+        {
+            let encoder = flate2::write::GzEncoder::new(
+                tmpfile.reopen().unwrap(),
+                flate2::Compression::default(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            append_tar(&mut builder, "readme.txt", b"hello");
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+        let archive: Archive = Archive::open(tmpfile.path(), ArchiveKind::TarGz)
+            .ok()
+            .unwrap();
+        let entries: Vec<FsEntry> = archive.list_dir(Path::new("/"));
+        assert_eq!(entries.len(), 1);
+        if let FsEntry::File(file) = &entries[0] {
+            let mut data: Vec<u8> = Vec::new();
+            assert!(archive.extract(file, &mut data).is_ok());
+            assert_eq!(data.as_slice(), b"hello");
+        } else {
+            panic!("Expected file");
+        }
+        // Not an archive
+        assert!(Archive::open(tmpfile.path(), ArchiveKind::Zip).is_err());
+    }
+
+    #[test]
+    fn test_fs_archive_zip() {
+        let tmpfile: NamedTempFile = NamedTempFile::new().unwrap();
+        {
+            let mut writer = zip::ZipWriter::new(tmpfile.reopen().unwrap());
+            let options = zip::write::FileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored)
+                .unix_permissions(0o600);
+            writer.add_directory("empty/", options).unwrap();
+            writer.start_file("src/main.rs", options).unwrap();
+            writer.write_all(b"fn main() {}").unwrap();
+            writer.finish().unwrap();
+        }
+        let archive: Archive = Archive::open(tmpfile.path(), ArchiveKind::Zip)
+            .ok()
+            .unwrap();
+        assert_eq!(archive.list_dir(Path::new("/")).len(), 2);
+        assert!(archive.list_dir(Path::new("/empty")).is_empty());
+        let main: FsFile = match &archive.list_dir(Path::new("/src"))[0] {
+            FsEntry::File(file) => file.clone(),
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        assert_eq!(main.name.as_str(), "main.rs");
+        assert_eq!(main.size, 12);
+        assert_eq!(main.unix_pex, Some((6, 0, 0)));
+        let mut data: Vec<u8> = Vec::new();
+        assert_eq!(archive.extract(&main, &mut data).ok().unwrap(), 12);
+        assert_eq!(data.as_slice(), b"fn main() {}");
+    }
+
+    fn append_tar<W: Write>(builder: &mut tar::Builder<W>, path: &str, data: &[u8]) {
+        let mut header: tar::Header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(1612164210);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, Cursor::new(data))
+            .unwrap();
+    }
+}
@@ -23,11 +23,14 @@
 *
 */
 
+pub mod archive;
+pub mod preview;
+
 extern crate bytesize;
 #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
 extern crate users;
 
-use crate::utils::fmt::{fmt_pex, fmt_time};
+use crate::utils::fmt::{fmt_pex, fmt_relative_time, fmt_time};
 
 use bytesize::ByteSize;
 use std::path::PathBuf;
@@ -58,9 +61,12 @@ pub struct FsDirectory {
     pub creation_time: SystemTime,
     pub readonly: bool,
     pub symlink: Option<Box<FsEntry>>,  // UNIX only
+    pub link_target: Option<PathBuf>,   // UNIX only; symlink target as read (may be relative)
     pub user: Option<u32>,              // UNIX only
     pub group: Option<u32>,             // UNIX only
     pub unix_pex: Option<(u8, u8, u8)>, // UNIX only
+    pub nlink: Option<u64>,             // UNIX only; hard links count
+    pub inode: Option<u64>,             // UNIX only
 }
 
 /// ### FsFile
@@ -78,9 +84,12 @@ pub struct FsFile {
     pub ftype: Option<String>, // File type
     pub readonly: bool,
     pub symlink: Option<Box<FsEntry>>,  // UNIX only
+    pub link_target: Option<PathBuf>,   // UNIX only; symlink target as read (may be relative)
     pub user: Option<u32>,              // UNIX only
     pub group: Option<u32>,             // UNIX only
     pub unix_pex: Option<(u8, u8, u8)>, // UNIX only
+    pub nlink: Option<u64>,             // UNIX only; hard links count
+    pub inode: Option<u64>,             // UNIX only
 }
 
 impl FsEntry {
@@ -184,6 +193,26 @@ impl FsEntry {
         }
     }
 
+    /// ### get_nlink
+    ///
+    /// Get hard links count from `FsEntry`
+    pub fn get_nlink(&self) -> Option<u64> {
+        match self {
+            FsEntry::Directory(dir) => dir.nlink,
+            FsEntry::File(file) => file.nlink,
+        }
+    }
+
+    /// ### get_inode
+    ///
+    /// Get inode number from `FsEntry`
+    pub fn get_inode(&self) -> Option<u64> {
+        match self {
+            FsEntry::Directory(dir) => dir.inode,
+            FsEntry::File(file) => file.inode,
+        }
+    }
+
     /// ### is_symlink
     ///
     /// Returns whether the `FsEntry` is a symlink
@@ -225,11 +254,13 @@ impl FsEntry {
     }
 }
 
-impl std::fmt::Display for FsEntry {
+impl FsEntry {
     /// ### fmt_ls
     ///
-    /// Format File Entry as `ls` does
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    /// Format File Entry as `ls` does.
+    /// If `relative_time` is true, the last change time is formatted as the elapsed time from now.
+    /// If `link_target` is true, the target of symlinks is appended to the name (`name -> target`)
+    pub fn fmt_ls(&self, relative_time: bool, link_target: bool) -> String {
         // Create mode string
         let mut mode: String = String::with_capacity(10);
         let file_type: char = match self.is_symlink() {
@@ -271,21 +302,41 @@ impl std::fmt::Display for FsEntry {
         // Get byte size
         let size: ByteSize = ByteSize(self.get_size() as u64);
         // Get date
-        let datetime: String = fmt_time(self.get_last_change_time(), "%b %d %Y %H:%M");
-        // Set file name (or elide if too long)
-        let name: String = self.get_name();
-        let name: String = match name.len() >= 24 {
+        let datetime: String = match relative_time {
+            true => fmt_relative_time(self.get_last_change_time()),
+            false => fmt_time(self.get_last_change_time(), "%b %d %Y %H:%M"),
+        };
+        // Set file name, with link target (or elide if too long)
+        let symlink: Option<&FsEntry> = match self {
+            FsEntry::Directory(dir) => dir.symlink.as_deref(),
+            FsEntry::File(file) => file.symlink.as_deref(),
+        };
+        let name: String = match (symlink, link_target) {
+            (Some(target), true) => {
+                format!("{} -> {}", self.get_name(), target.get_abs_path().display())
+            }
+            _ => self.get_name(),
+        };
+        let name: String = match name.chars().count() >= 24 {
             false => name,
-            true => format!("{}...", &name.as_str()[0..20]),
+            true => format!("{}...", name.chars().take(20).collect::<String>()),
         };
-        write!(
-            f,
+        format!(
             "{:24}\t{:12}\t{:12}\t{:10}\t{:17}",
             name, mode, username, size, datetime
         )
     }
 }
 
+impl std::fmt::Display for FsEntry {
+    /// ### fmt
+    ///
+    /// Format File Entry as `ls` does
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.fmt_ls(false, true))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -302,9 +353,12 @@ mod tests {
             creation_time: t_now,
             readonly: false,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((7, 5, 5)), // UNIX only
+            nlink: Some(2),            // UNIX only
+            inode: Some(1048577),      // UNIX only
         });
         assert_eq!(entry.get_abs_path(), PathBuf::from("/foo"));
         assert_eq!(entry.get_name(), String::from("foo"));
@@ -319,6 +373,8 @@ mod tests {
         assert_eq!(entry.is_dir(), true);
         assert_eq!(entry.is_file(), false);
         assert_eq!(entry.get_unix_pex(), Some((7, 5, 5)));
+        assert_eq!(entry.get_nlink(), Some(2));
+        assert_eq!(entry.get_inode(), Some(1048577));
     }
 
     #[test]
@@ -334,9 +390,12 @@ mod tests {
             readonly: false,
             ftype: Some(String::from("txt")),
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         assert_eq!(entry.get_abs_path(), PathBuf::from("/bar.txt"));
         assert_eq!(entry.get_name(), String::from("bar.txt"));
@@ -348,6 +407,8 @@ mod tests {
         assert_eq!(entry.get_user(), Some(0));
         assert_eq!(entry.get_group(), Some(0));
         assert_eq!(entry.get_unix_pex(), Some((6, 4, 4)));
+        assert_eq!(entry.get_nlink(), None);
+        assert_eq!(entry.get_inode(), None);
         assert_eq!(entry.is_symlink(), false);
         assert_eq!(entry.is_dir(), false);
         assert_eq!(entry.is_file(), true);
@@ -367,9 +428,12 @@ mod tests {
             readonly: false,
             ftype: Some(String::from("txt")),
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         // Symlink is None...
         assert_eq!(
@@ -385,9 +449,12 @@ mod tests {
             creation_time: t_now,
             readonly: false,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((7, 5, 5)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         assert_eq!(entry.get_realfile().get_abs_path(), PathBuf::from("/foo"));
     }
@@ -405,9 +472,12 @@ mod tests {
             creation_time: t_now,
             readonly: false,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((7, 7, 7)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         let entry_child: FsEntry = FsEntry::Directory(FsDirectory {
             name: String::from("projects"),
@@ -417,9 +487,12 @@ mod tests {
             creation_time: t_now,
             readonly: false,
             symlink: Some(Box::new(entry_target)),
+            link_target: None,
             user: Some(0),
             group: Some(0),
             unix_pex: Some((7, 7, 7)),
+            nlink: None,
+            inode: None,
         });
         let entry_root: FsEntry = FsEntry::File(FsFile {
             name: String::from("projects"),
@@ -431,9 +504,12 @@ mod tests {
             readonly: false,
             ftype: None,
             symlink: Some(Box::new(entry_child)),
+            link_target: None,
             user: Some(0),
             group: Some(0),
             unix_pex: Some((7, 7, 7)),
+            nlink: None,
+            inode: None,
         });
         assert_eq!(entry_root.is_symlink(), true);
         // get real file
@@ -458,9 +534,12 @@ mod tests {
             readonly: false,
             ftype: Some(String::from("txt")),
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
         assert_eq!(
@@ -478,6 +557,8 @@ mod tests {
                 fmt_time(t, "%b %d %Y %H:%M")
             )
         );
+        // Relative time
+        assert!(entry.fmt_ls(true, true).ends_with("\tjust now         "));
         // Elide name
         let entry: FsEntry = FsEntry::File(FsFile {
             name: String::from("piroparoporoperoperupupu.txt"),
@@ -489,9 +570,12 @@ mod tests {
             readonly: false,
             ftype: Some(String::from("txt")),
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
         assert_eq!(
@@ -519,10 +603,13 @@ mod tests {
             size: 8192,
             readonly: false,
             ftype: Some(String::from("txt")),
-            symlink: None,  // UNIX only
-            user: Some(0),  // UNIX only
-            group: Some(0), // UNIX only
-            unix_pex: None, // UNIX only
+            symlink: None,     // UNIX only
+            link_target: None, // UNIX only
+            user: Some(0),     // UNIX only
+            group: Some(0),    // UNIX only
+            unix_pex: None,    // UNIX only
+            nlink: None,       // UNIX only
+            inode: None,       // UNIX only
         });
         #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
         assert_eq!(
@@ -550,10 +637,13 @@ mod tests {
             size: 8192,
             readonly: false,
             ftype: Some(String::from("txt")),
-            symlink: None,  // UNIX only
-            user: None,     // UNIX only
-            group: Some(0), // UNIX only
-            unix_pex: None, // UNIX only
+            symlink: None,     // UNIX only
+            link_target: None, // UNIX only
+            user: None,        // UNIX only
+            group: Some(0),    // UNIX only
+            unix_pex: None,    // UNIX only
+            nlink: None,       // UNIX only
+            inode: None,       // UNIX only
         });
         #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
         assert_eq!(
@@ -584,9 +674,12 @@ mod tests {
             creation_time: t_now,
             readonly: false,
             symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
             user: Some(0),             // UNIX only
             group: Some(0),            // UNIX only
             unix_pex: Some((7, 5, 5)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
         });
         #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
         assert_eq!(
@@ -612,10 +705,13 @@ mod tests {
             last_access_time: t_now,
             creation_time: t_now,
             readonly: false,
-            symlink: None,  // UNIX only
-            user: None,     // UNIX only
-            group: Some(0), // UNIX only
-            unix_pex: None, // UNIX only
+            symlink: None,     // UNIX only
+            link_target: None, // UNIX only
+            user: None,        // UNIX only
+            group: Some(0),    // UNIX only
+            unix_pex: None,    // UNIX only
+            nlink: None,       // UNIX only
+            inode: None,       // UNIX only
         });
         #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
         assert_eq!(
@@ -634,4 +730,60 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_fs_fmt_symlink() {
+        let t: SystemTime = SystemTime::now();
+        let target: FsEntry = FsEntry::File(FsFile {
+            name: String::from("b.txt"),
+            abs_path: PathBuf::from("/b.txt"),
+            last_change_time: t,
+            last_access_time: t,
+            creation_time: t,
+            size: 8192,
+            readonly: false,
+            ftype: Some(String::from("txt")),
+            symlink: None,             // UNIX only
+            link_target: None,         // UNIX only
+            user: Some(0),             // UNIX only
+            group: Some(0),            // UNIX only
+            unix_pex: Some((6, 4, 4)), // UNIX only
+            nlink: None,               // UNIX only
+            inode: None,               // UNIX only
+        });
+        let link = |name: &str| {
+            FsEntry::File(FsFile {
+                name: String::from(name),
+                abs_path: PathBuf::from("/a.txt"),
+                last_change_time: t,
+                last_access_time: t,
+                creation_time: t,
+                size: 8192,
+                readonly: false,
+                ftype: Some(String::from("txt")),
+                symlink: Some(Box::new(target.clone())), // UNIX only
+                link_target: None,                       // UNIX only
+                user: Some(0),                           // UNIX only
+                group: Some(0),                          // UNIX only
+                unix_pex: Some((7, 7, 7)),               // UNIX only
+                nlink: None,                             // UNIX only
+                inode: None,                             // UNIX only
+            })
+        };
+        // Target is appended to name
+        assert!(
+            format!("{}", link("a.txt")).starts_with("a.txt -> /b.txt         \tlrwxrwxrwx  \t")
+        );
+        assert!(link("a.txt")
+            .fmt_ls(false, true)
+            .starts_with("a.txt -> /b.txt         \t"));
+        // Name and target are elided together
+        assert!(link("configuration.txt")
+            .fmt_ls(false, true)
+            .starts_with("configuration.txt ->... \t"));
+        // Link target hidden
+        assert!(link("a.txt")
+            .fmt_ls(false, false)
+            .starts_with("a.txt                   \t"));
+    }
 }
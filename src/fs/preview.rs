@@ -0,0 +1,223 @@
+//! ## Preview
+//!
+//! `preview` is the module which decodes image files into a downscaled preview,
+//! to be rendered on the terminal with half-block characters
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Without the `images` feature, images are never decoded
+#![cfg_attr(not(feature = "images"), allow(dead_code, unused_imports))]
+
+// Dependencies
+#[cfg(feature = "images")]
+extern crate image;
+
+use std::path::Path;
+
+// Images with more pixels than this are not decoded (4096x4096), to bound the memory used
+const IMAGE_MAX_PIXELS: u64 = 16777216;
+// Decoded images are downscaled to fit in a square of this side (pixels)
+const PREVIEW_MAX_SIZE: u32 = 512;
+
+/// ### Rgb
+///
+/// Color of a pixel (red, green, blue)
+pub type Rgb = [u8; 3];
+
+/// ### is_image
+///
+/// Returns whether the file name has the extension of a supported image format (`.png`, `.jpg`, `.jpeg`, `.gif`, `.bmp`).
+/// Always false if Gateway has been built without the `images` feature
+pub fn is_image(name: &str) -> bool {
+    if !cfg!(feature = "images") {
+        return false;
+    }
+    let name: String = name.to_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".bmp"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+}
+
+/// ## ImagePreview
+///
+/// ImagePreview contains an image, downscaled to fit in `PREVIEW_MAX_SIZE` pixels per side
+pub struct ImagePreview {
+    pub width: u32,   // Width of the original image
+    pub height: u32,  // Height of the original image
+    columns: u32,     // Width of the downscaled image
+    rows: u32,        // Height of the downscaled image
+    pixels: Vec<Rgb>, // Pixels of the downscaled image, row by row
+}
+
+impl ImagePreview {
+    /// ### open
+    ///
+    /// Decode the image file at `path`; images bigger than `IMAGE_MAX_PIXELS` are refused before being decoded
+    #[cfg(feature = "images")]
+    pub fn open(path: &Path) -> Result<ImagePreview, String> {
+        let reader = || -> Result<image::io::Reader<std::io::BufReader<std::fs::File>>, String> {
+            image::io::Reader::open(path)
+                .and_then(|x| x.with_guessed_format())
+                .map_err(|err| format!("Could not open image: {}", err))
+        };
+        let (width, height): (u32, u32) = reader()?
+            .into_dimensions()
+            .map_err(|err| format!("Unsupported image: {}", err))?;
+        if width as u64 * height as u64 > IMAGE_MAX_PIXELS {
+            return Err(format!(
+                "Image is too big to be previewed ({}x{})",
+                width, height
+            ));
+        }
+        let image: image::DynamicImage = reader()?
+            .decode()
+            .map_err(|err| format!("Could not decode image: {}", err))?;
+        let preview: image::RgbImage = match width > PREVIEW_MAX_SIZE || height > PREVIEW_MAX_SIZE {
+            true => image
+                .thumbnail(PREVIEW_MAX_SIZE, PREVIEW_MAX_SIZE)
+                .to_rgb8(),
+            false => image.to_rgb8(),
+        };
+        Ok(ImagePreview {
+            width,
+            height,
+            columns: preview.width(),
+            rows: preview.height(),
+            pixels: preview.pixels().map(|x| x.0).collect(),
+        })
+    }
+
+    /// ### open
+    ///
+    /// Images can't be decoded without the `images` feature
+    #[cfg(not(feature = "images"))]
+    pub fn open(_path: &Path) -> Result<ImagePreview, String> {
+        Err(String::from(
+            "Gateway has been built without the `images` feature",
+        ))
+    }
+
+    /// ### cells
+    ///
+    /// Scale the image to fit in `columns` x `rows` terminal cells, keeping its aspect ratio.
+    /// Each cell holds two pixels, one above the other; the bottom one is `None` if the image has an odd height
+    pub fn cells(&self, columns: u32, rows: u32) -> Vec<Vec<(Rgb, Option<Rgb>)>> {
+        if columns == 0 || rows == 0 || self.pixels.is_empty() {
+            return Vec::new();
+        }
+        // Each cell is about twice as high as wide: 2 pixels per cell make them square
+        let scale: f64 = f64::min(
+            columns as f64 / self.columns as f64,
+            (rows * 2) as f64 / self.rows as f64,
+        );
+        let width: u32 = ((self.columns as f64 * scale) as u32).max(1);
+        let height: u32 = ((self.rows as f64 * scale) as u32).max(1);
+        let pixel = |x: u32, y: u32| -> Rgb {
+            let src_x: u32 = x * self.columns / width;
+            let src_y: u32 = y * self.rows / height;
+            self.pixels[(src_y * self.columns + src_x) as usize]
+        };
+        (0..height)
+            .step_by(2)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let bottom: Option<Rgb> = match y + 1 < height {
+                            true => Some(pixel(x, y + 1)),
+                            false => None,
+                        };
+                        (pixel(x, y), bottom)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_fs_preview_is_image() {
+        assert_eq!(is_image("photo.JPG"), cfg!(feature = "images"));
+        assert_eq!(is_image("icon.png"), cfg!(feature = "images"));
+        assert!(!is_image("notes.txt"));
+        assert!(!is_image("png"));
+    }
+
+    #[test]
+    fn test_fs_preview_cells() {
+        // 4x2 image: left half red, right half blue
+        let red: Rgb = [255, 0, 0];
+        let blue: Rgb = [0, 0, 255];
+        let preview: ImagePreview = ImagePreview {
+            width: 4,
+            height: 2,
+            columns: 4,
+            rows: 2,
+            pixels: vec![red, red, blue, blue, red, red, blue, blue],
+        };
+        // Upscaled to 10x5 pixels: last row of cells has no bottom pixel
+        let cells: Vec<Vec<(Rgb, Option<Rgb>)>> = preview.cells(10, 10);
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0].len(), 10);
+        assert_eq!(cells[2][0], (red, None));
+        assert_eq!(cells[2][9], (blue, None));
+        // Same size
+        let cells: Vec<Vec<(Rgb, Option<Rgb>)>> = preview.cells(4, 10);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(
+            cells[0],
+            vec![
+                (red, Some(red)),
+                (red, Some(red)),
+                (blue, Some(blue)),
+                (blue, Some(blue))
+            ]
+        );
+        // Downscaled to 2x1 pixels: one row of cells without bottom pixel
+        let cells: Vec<Vec<(Rgb, Option<Rgb>)>> = preview.cells(2, 10);
+        assert_eq!(cells, vec![vec![(red, None), (blue, None)]]);
+        assert!(preview.cells(0, 10).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn test_fs_preview_open() {
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: std::path::PathBuf = tmp_dir.path().join("dot.png");
+        let mut image: image::RgbImage = image::RgbImage::new(1024, 8);
+        image.put_pixel(0, 0, image::Rgb([10, 20, 30]));
+        image.save(path.as_path()).unwrap();
+        let preview: ImagePreview = ImagePreview::open(path.as_path()).ok().unwrap();
+        assert_eq!((preview.width, preview.height), (1024, 8));
+        // Downscaled
+        assert_eq!((preview.columns, preview.rows), (512, 4));
+        assert_eq!(preview.pixels.len(), 2048);
+        // Not an image
+        let path: std::path::PathBuf = tmp_dir.path().join("fake.png");
+        std::fs::write(path.as_path(), b"hello").unwrap();
+        assert!(ImagePreview::open(path.as_path()).is_err());
+    }
+}
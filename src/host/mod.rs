@@ -28,9 +28,9 @@ use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 // Metadata ext
 #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
-use std::os::unix::fs::{MetadataExt, PermissionsExt};
-#[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
 use std::fs::set_permissions;
+#[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
 
 // Locals
 use crate::fs::{FsDirectory, FsEntry, FsFile};
@@ -47,6 +47,7 @@ pub enum HostErrorType {
     FileAlreadyExists,
     CouldNotCreateFile,
     DeleteFailed,
+    NotRegularFile,
 }
 
 /// ### HostError
@@ -80,6 +81,7 @@ impl std::fmt::Display for HostError {
             HostErrorType::FileAlreadyExists => "File already exists",
             HostErrorType::CouldNotCreateFile => "Could not create file",
             HostErrorType::DeleteFailed => "Could not delete file",
+            HostErrorType::NotRegularFile => "Not a regular file (FIFO, socket or device)",
         };
         match &self.ioerr {
             Some(err) => write!(f, "{}: {}", code_str, err),
@@ -88,13 +90,32 @@ impl std::fmt::Display for HostError {
     }
 }
 
+/// ## DirIter
+///
+/// DirIter iterates over the entries of a local directory, yielding a `FsEntry` for each of them.
+/// Entries which can't be read from the directory are skipped
+pub struct DirIter {
+    entries: std::fs::ReadDir,
+}
+
+impl Iterator for DirIter {
+    type Item = Result<FsEntry, HostError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Ok(entry) = self.entries.next()? {
+                return Some(Localhost::stat_path(entry.path().as_path()));
+            }
+        }
+    }
+}
+
 /// ## Localhost
 ///
 /// Localhost is the entity which holds the information about the current directory and host.
 /// It provides functions to navigate across the local host file system
 pub struct Localhost {
     wrkdir: PathBuf,
-    files: Vec<FsEntry>,
 }
 
 impl Localhost {
@@ -102,19 +123,13 @@ impl Localhost {
     ///
     /// Instantiates a new Localhost struct
     pub fn new(wrkdir: PathBuf) -> Result<Localhost, HostError> {
-        let mut host: Localhost = Localhost {
-            wrkdir,
-            files: Vec::new(),
-        };
+        let host: Localhost = Localhost { wrkdir };
         // Check if dir exists
         if !host.file_exists(host.wrkdir.as_path()) {
             return Err(HostError::new(HostErrorType::NoSuchFileOrDirectory, None));
         }
-        // Retrieve files for provided path
-        host.files = match host.scan_dir(host.wrkdir.as_path()) {
-            Ok(files) => files,
-            Err(err) => return Err(err),
-        };
+        // Check if dir is accessible
+        host.iter_dir(host.wrkdir.as_path())?;
         Ok(host)
     }
 
@@ -130,7 +145,7 @@ impl Localhost {
     /// List files in current directory
     #[allow(dead_code)]
     pub fn list_dir(&self) -> Vec<FsEntry> {
-        self.files.clone()
+        self.scan_dir(self.wrkdir.as_path()).unwrap_or_default()
     }
 
     /// ### change_wrkdir
@@ -141,24 +156,16 @@ impl Localhost {
         if !self.file_exists(new_dir.as_path()) {
             return Err(HostError::new(HostErrorType::NoSuchFileOrDirectory, None));
         }
-        let prev_dir: PathBuf = self.wrkdir.clone(); // Backup location
-                                                     // Update working directory
+        // Check whether directory is accessible; entries are not read here
+        self.iter_dir(new_dir.as_path())?;
+        // Update working directory
         self.wrkdir = new_dir;
-        // Scan new directory
-        self.files = match self.scan_dir(self.wrkdir.as_path()) {
-            Ok(files) => files,
-            Err(err) => {
-                // Restore directory
-                self.wrkdir = prev_dir;
-                return Err(err);
-            }
-        };
         Ok(self.wrkdir.clone())
     }
 
     /// ### mkdir
     ///
-    /// Make a directory at path and update the file list (only if relative)
+    /// Make a directory at path
     pub fn mkdir(&mut self, dir_name: &Path) -> Result<(), HostError> {
         self.mkdir_ex(dir_name, false)
     }
@@ -184,20 +191,53 @@ impl Localhost {
             }
         }
         match std::fs::create_dir(dir_path) {
-            Ok(_) => {
-                // Update dir
-                if dir_name.is_relative() {
-                    self.files = match self.scan_dir(self.wrkdir.as_path()) {
-                        Ok(f) => f,
-                        Err(err) => return Err(err),
-                    };
-                }
-                Ok(())
+            Ok(_) => Ok(()),
+            Err(err) => Err(HostError::new(HostErrorType::CouldNotCreateFile, Some(err))),
+        }
+    }
+
+    /// ### touch
+    ///
+    /// Create an empty file at path; an existing file is never overwritten
+    pub fn touch(&self, file_name: &Path) -> Result<(), HostError> {
+        let file_path: PathBuf = match file_name.is_absolute() {
+            true => PathBuf::from(file_name),
+            false => self.wrkdir.join(file_name),
+        };
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(file_path)
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(HostError::new(HostErrorType::FileAlreadyExists, Some(err)))
             }
             Err(err) => Err(HostError::new(HostErrorType::CouldNotCreateFile, Some(err))),
         }
     }
 
+    /// ### mkdir_all
+    ///
+    /// Make a directory at path, creating its missing parents too, as `mkdir -p` does.
+    /// Returns the directories which have been created, from the outermost; nothing is created if the directory already exists
+    pub fn mkdir_all(&mut self, dir_name: &Path) -> Result<Vec<PathBuf>, HostError> {
+        let dir_path: PathBuf = match dir_name.is_absolute() {
+            true => PathBuf::from(dir_name),
+            false => self.wrkdir.join(dir_name),
+        };
+        let mut missing: Vec<PathBuf> = dir_path
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+            .map(PathBuf::from)
+            .collect();
+        missing.reverse();
+        for dir in missing.iter() {
+            self.mkdir_ex(dir.as_path(), true)?;
+        }
+        Ok(missing)
+    }
+
     /// ### remove
     ///
     /// Remove file entry
@@ -210,14 +250,7 @@ impl Localhost {
                 }
                 // Remove
                 match std::fs::remove_dir_all(dir.abs_path.as_path()) {
-                    Ok(_) => {
-                        // Update dir
-                        self.files = match self.scan_dir(self.wrkdir.as_path()) {
-                            Ok(f) => f,
-                            Err(err) => return Err(err),
-                        };
-                        Ok(())
-                    }
+                    Ok(_) => Ok(()),
                     Err(err) => Err(HostError::new(HostErrorType::DeleteFailed, Some(err))),
                 }
             }
@@ -228,14 +261,7 @@ impl Localhost {
                 }
                 // Remove
                 match std::fs::remove_file(file.abs_path.as_path()) {
-                    Ok(_) => {
-                        // Update dir
-                        self.files = match self.scan_dir(self.wrkdir.as_path()) {
-                            Ok(f) => f,
-                            Err(err) => return Err(err),
-                        };
-                        Ok(())
-                    }
+                    Ok(_) => Ok(()),
                     Err(err) => Err(HostError::new(HostErrorType::DeleteFailed, Some(err))),
                 }
             }
@@ -248,28 +274,56 @@ impl Localhost {
     pub fn rename(&mut self, entry: &FsEntry, dst_path: &Path) -> Result<(), HostError> {
         let abs_path: PathBuf = entry.get_abs_path();
         match std::fs::rename(abs_path.as_path(), dst_path) {
-            Ok(_) => {
-                // Scan dir
-                self.files = match self.scan_dir(self.wrkdir.as_path()) {
-                    Ok(f) => f,
-                    Err(err) => return Err(err),
-                };
-                Ok(())
-            }
+            Ok(_) => Ok(()),
             Err(err) => Err(HostError::new(HostErrorType::CouldNotCreateFile, Some(err))),
         }
     }
 
+    /// ### is_same_file
+    ///
+    /// Returns whether `a` and `b` are the same file (same device and inode, so hard links are detected too).
+    /// False if any of them doesn't exist
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    pub fn is_same_file(a: &Path, b: &Path) -> bool {
+        match (fs::metadata(a), fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+
+    /// ### is_same_file
+    ///
+    /// Returns whether `a` and `b` are the same file (same canonical path).
+    /// False if any of them doesn't exist
+    #[cfg(target_os = "windows")]
+    pub fn is_same_file(a: &Path, b: &Path) -> bool {
+        match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+
     /// ### stat
     ///
     /// Stat file and create a FsEntry
-    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     pub fn stat(&self, path: &Path) -> Result<FsEntry, HostError> {
+        Localhost::stat_path(path)
+    }
+
+    /// ### stat_path
+    ///
+    /// Stat file and create a FsEntry
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    fn stat_path(path: &Path) -> Result<FsEntry, HostError> {
         let attr: Metadata = match fs::metadata(path) {
             Ok(metadata) => metadata,
             Err(err) => return Err(HostError::new(HostErrorType::FileNotAccessible, Some(err))),
         };
-        let file_name: String = String::from(path.file_name().unwrap().to_str().unwrap_or(""));
+        // NOTE: name is only displayed; abs_path keeps the original (possibly non-UTF8) path for operations
+        let file_name: String = match path.file_name() {
+            Some(name) => String::from(name.to_string_lossy()),
+            None => String::from(path.to_string_lossy()),
+        };
         // Match dir / file
         Ok(match path.is_dir() {
             true => FsEntry::Directory(FsDirectory {
@@ -280,22 +334,23 @@ impl Localhost {
                 creation_time: attr.created().unwrap_or(SystemTime::UNIX_EPOCH),
                 readonly: attr.permissions().readonly(),
                 symlink: match fs::read_link(path) {
-                    Ok(p) => match self.stat(p.as_path()) {
+                    Ok(p) => match Localhost::stat_path(p.as_path()) {
                         Ok(entry) => Some(Box::new(entry)),
                         Err(_) => None,
                     },
                     Err(_) => None,
                 },
+                link_target: fs::read_link(path).ok(),
                 user: Some(attr.uid()),
                 group: Some(attr.gid()),
-                unix_pex: Some(self.u32_to_mode(attr.mode())),
+                unix_pex: Some(Localhost::u32_to_mode(attr.mode())),
+                nlink: Some(attr.nlink()),
+                inode: Some(attr.ino()),
             }),
             false => {
                 // Is File
-                let extension: Option<String> = match path.extension() {
-                    Some(s) => Some(String::from(s.to_str().unwrap_or(""))),
-                    None => None,
-                };
+                let extension: Option<String> =
+                    path.extension().map(|s| String::from(s.to_string_lossy()));
                 FsEntry::File(FsFile {
                     name: file_name,
                     abs_path: PathBuf::from(path),
@@ -306,31 +361,38 @@ impl Localhost {
                     size: attr.len() as usize,
                     ftype: extension,
                     symlink: match fs::read_link(path) {
-                        Ok(p) => match self.stat(p.as_path()) {
+                        Ok(p) => match Localhost::stat_path(p.as_path()) {
                             Ok(entry) => Some(Box::new(entry)),
                             Err(_) => None,
                         },
                         Err(_) => None, // Ignore errors
                     },
+                    link_target: fs::read_link(path).ok(),
                     user: Some(attr.uid()),
                     group: Some(attr.gid()),
-                    unix_pex: Some(self.u32_to_mode(attr.mode())),
+                    unix_pex: Some(Localhost::u32_to_mode(attr.mode())),
+                    nlink: Some(attr.nlink()),
+                    inode: Some(attr.ino()),
                 })
             }
         })
     }
 
-    /// ### stat
+    /// ### stat_path
     ///
     /// Stat file and create a FsEntry
     #[cfg(target_os = "windows")]
     #[cfg(not(tarpaulin_include))]
-    pub fn stat(&self, path: &Path) -> Result<FsEntry, HostError> {
+    fn stat_path(path: &Path) -> Result<FsEntry, HostError> {
         let attr: Metadata = match fs::metadata(path) {
             Ok(metadata) => metadata,
             Err(err) => return Err(HostError::new(HostErrorType::FileNotAccessible, Some(err))),
         };
-        let file_name: String = String::from(path.file_name().unwrap().to_str().unwrap_or(""));
+        // NOTE: name is only displayed; abs_path keeps the original (possibly non-UTF8) path for operations
+        let file_name: String = match path.file_name() {
+            Some(name) => String::from(name.to_string_lossy()),
+            None => String::from(path.to_string_lossy()),
+        };
         // Match dir / file
         Ok(match path.is_dir() {
             true => FsEntry::Directory(FsDirectory {
@@ -341,22 +403,23 @@ impl Localhost {
                 creation_time: attr.created().unwrap_or(SystemTime::UNIX_EPOCH),
                 readonly: attr.permissions().readonly(),
                 symlink: match fs::read_link(path) {
-                    Ok(p) => match self.stat(p.as_path()) {
+                    Ok(p) => match Localhost::stat_path(p.as_path()) {
                         Ok(entry) => Some(Box::new(entry)),
                         Err(_) => None, // Ignore errors
                     },
                     Err(_) => None,
                 },
+                link_target: fs::read_link(path).ok(),
                 user: None,
                 group: None,
                 unix_pex: None,
+                nlink: None,
+                inode: None,
             }),
             false => {
                 // Is File
-                let extension: Option<String> = match path.extension() {
-                    Some(s) => Some(String::from(s.to_str().unwrap_or(""))),
-                    None => None,
-                };
+                let extension: Option<String> =
+                    path.extension().map(|s| String::from(s.to_string_lossy()));
                 FsEntry::File(FsFile {
                     name: file_name,
                     abs_path: PathBuf::from(path),
@@ -367,15 +430,18 @@ impl Localhost {
                     size: attr.len() as usize,
                     ftype: extension,
                     symlink: match fs::read_link(path) {
-                        Ok(p) => match self.stat(p.as_path()) {
+                        Ok(p) => match Localhost::stat_path(p.as_path()) {
                             Ok(entry) => Some(Box::new(entry)),
                             Err(_) => None,
                         },
                         Err(_) => None,
                     },
+                    link_target: fs::read_link(path).ok(),
                     user: None,
                     group: None,
                     unix_pex: None,
+                    nlink: None,
+                    inode: None,
                 })
             }
         })
@@ -400,6 +466,20 @@ impl Localhost {
         }
     }
 
+    /// ### symlink
+    ///
+    /// Create a symlink at `path`, pointing to `target`
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    pub fn symlink(&self, path: &Path, target: &Path) -> Result<(), HostError> {
+        match std::os::unix::fs::symlink(target, path) {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(HostError::new(HostErrorType::FileAlreadyExists, Some(err)))
+            }
+            Err(err) => Err(HostError::new(HostErrorType::CouldNotCreateFile, Some(err))),
+        }
+    }
+
     /// ### open_file_read
     ///
     /// Open file for read
@@ -407,6 +487,10 @@ impl Localhost {
         if !self.file_exists(file) {
             return Err(HostError::new(HostErrorType::NoSuchFileOrDirectory, None));
         }
+        // Opening a FIFO blocks until a writer shows up; devices and sockets can't be transferred either
+        if self.is_special_file(file) {
+            return Err(HostError::new(HostErrorType::NotRegularFile, None));
+        }
         match OpenOptions::new()
             .create(false)
             .read(true)
@@ -443,31 +527,106 @@ impl Localhost {
         path.exists()
     }
 
+    /// ### is_special_file
+    ///
+    /// Returns whether provided path is a FIFO, a socket or a device file (symlinks are followed)
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    pub fn is_special_file(&self, path: &Path) -> bool {
+        match fs::metadata(path) {
+            Ok(attr) => {
+                let ftype: fs::FileType = attr.file_type();
+                ftype.is_fifo()
+                    || ftype.is_socket()
+                    || ftype.is_block_device()
+                    || ftype.is_char_device()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// ### is_special_file
+    ///
+    /// Returns whether provided path is a FIFO, a socket or a device file; there are none on Windows
+    #[cfg(target_os = "windows")]
+    pub fn is_special_file(&self, _path: &Path) -> bool {
+        false
+    }
+
+    /// ### get_xattrs
+    ///
+    /// Get the extended attributes (name, value) of provided path, sorted by name.
+    /// Returns None if extended attributes are not supported by the file system
+    #[cfg(all(feature = "xattrs", unix))]
+    pub fn get_xattrs(&self, path: &Path) -> Option<Vec<(String, Vec<u8>)>> {
+        let mut attrs: Vec<(String, Vec<u8>)> = xattr::list(path)
+            .ok()?
+            .filter_map(|name| match xattr::get(path, &name) {
+                Ok(Some(value)) => Some((name.to_string_lossy().to_string(), value)),
+                _ => None, // Removed meanwhile or not readable
+            })
+            .collect();
+        attrs.sort();
+        Some(attrs)
+    }
+
+    /// ### get_xattrs
+    ///
+    /// Get the extended attributes of provided path; not supported by this build
+    #[cfg(not(all(feature = "xattrs", unix)))]
+    pub fn get_xattrs(&self, _path: &Path) -> Option<Vec<(String, Vec<u8>)>> {
+        None
+    }
+
     /// ### scan_dir
     ///
-    /// Get content of the current directory as a list of fs entry (Windows)
+    /// Get content of the current directory as a list of fs entry
     pub fn scan_dir(&self, dir: &Path) -> Result<Vec<FsEntry>, HostError> {
-        let entries = match std::fs::read_dir(dir) {
-            Ok(e) => e,
-            Err(err) => return Err(HostError::new(HostErrorType::DirNotAccessible, Some(err))),
-        };
-        let mut fs_entries: Vec<FsEntry> = Vec::new();
-        for entry in entries {
-            if let Ok(entry) = entry {
-                fs_entries.push(match self.stat(entry.path().as_path()) {
-                    Ok(entry) => entry,
-                    Err(err) => return Err(err),
-                });
+        self.iter_dir(dir)?.collect()
+    }
+
+    /// ### iter_dir
+    ///
+    /// Get an iterator over the content of the provided directory.
+    /// Entries are stat one at a time while iterating, so the caller can start processing them
+    /// without waiting for the entire directory to be read
+    pub fn iter_dir(&self, dir: &Path) -> Result<DirIter, HostError> {
+        match std::fs::read_dir(dir) {
+            Ok(entries) => Ok(DirIter { entries }),
+            Err(err) => Err(HostError::new(HostErrorType::DirNotAccessible, Some(err))),
+        }
+    }
+
+    /// ### du
+    ///
+    /// Returns the size of the files in the directory at `path` and in its subdirectories.
+    /// Symlinks to directories aren't followed, to avoid cycles; entries and subdirectories which can't be
+    /// read (e.g. permission denied) are skipped, so the total is a best effort.
+    /// Fails only if `path` itself can't be read
+    pub fn du(&self, path: &Path) -> Result<u64, HostError> {
+        let mut total: u64 = 0;
+        for entry in self.iter_dir(path)?.flatten() {
+            match entry {
+                FsEntry::File(file) => total += file.size as u64,
+                FsEntry::Directory(dir) => {
+                    // NOTE: `symlink` is None if the target of a relative link can't be stat; check the link itself too
+                    let is_link: bool = dir.symlink.is_some()
+                        || fs::symlink_metadata(dir.abs_path.as_path())
+                            .map(|x| x.file_type().is_symlink())
+                            .unwrap_or(true);
+                    if !is_link {
+                        total += self.du(dir.abs_path.as_path()).unwrap_or(0);
+                    }
+                }
             }
         }
-        Ok(fs_entries)
+        Ok(total)
     }
 
     /// ### u32_to_mode
     ///
     /// Return string with format xxxxxx to tuple of permissions (user, group, others)
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
-    fn u32_to_mode(&self, mode: u32) -> (u8, u8, u8) {
+    fn u32_to_mode(mode: u32) -> (u8, u8, u8) {
         let user: u8 = ((mode >> 6) & 0x7) as u8;
         let group: u8 = ((mode >> 3) & 0x7) as u8;
         let others: u8 = (mode & 0x7) as u8;
@@ -489,11 +648,15 @@ mod tests {
     use super::*;
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     use std::fs::File;
+    use std::io::Read;
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     use std::io::Write;
 
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
-    use std::os::unix::fs::{symlink, PermissionsExt};
+    use std::os::unix::{
+        ffi::OsStrExt,
+        fs::{symlink, PermissionsExt},
+    };
 
     #[test]
     fn test_host_error_new() {
@@ -513,7 +676,7 @@ mod tests {
         for _ in entries {
             counter = counter + 1;
         }
-        assert_eq!(host.files.len(), counter);
+        assert_eq!(host.list_dir().len(), counter);
     }
 
     #[test]
@@ -527,7 +690,7 @@ mod tests {
         for _ in entries {
             counter = counter + 1;
         }
-        assert_eq!(host.files.len(), counter);
+        assert_eq!(host.list_dir().len(), counter);
     }
 
     #[test]
@@ -558,6 +721,25 @@ mod tests {
         assert_eq!(host.list_dir().len(), counter);
     }
 
+    #[test]
+    fn test_host_localhost_iter_dir() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"].iter() {
+            let mut file_path: PathBuf = PathBuf::from(tmpdir.path());
+            file_path.push(name);
+            assert!(File::create(file_path.as_path()).is_ok());
+        }
+        let host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        // Iterate
+        let mut entries: DirIter = host.iter_dir(tmpdir.path()).ok().unwrap();
+        assert!(entries.next().unwrap().is_ok());
+        assert_eq!(entries.count(), 2);
+        // Must match scan_dir
+        assert_eq!(host.scan_dir(tmpdir.path()).ok().unwrap().len(), 3);
+        // Bad directory
+        assert!(host.iter_dir(Path::new("/omar/gabber/123/456")).is_err());
+    }
+
     #[test]
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     fn test_host_localhost_change_dir() {
@@ -571,7 +753,7 @@ mod tests {
         for _ in entries {
             counter = counter + 1;
         }
-        assert_eq!(host.files.len(), counter);
+        assert_eq!(host.list_dir().len(), counter);
     }
 
     #[test]
@@ -632,6 +814,94 @@ mod tests {
         //fs::set_permissions(file.path(), perms)?;
         assert!(host.open_file_write(file.path()).is_err());
     }
+
+    #[test]
+    fn test_host_localhost_zero_bytes_file() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        let mut file_path: PathBuf = PathBuf::from(tmpdir.path());
+        file_path.push("empty.txt");
+        // Write (download); nothing is written, but file must exist
+        let fhnd: File = host.open_file_write(file_path.as_path()).ok().unwrap();
+        drop(fhnd);
+        assert!(host.file_exists(file_path.as_path()));
+        match host.stat(file_path.as_path()).ok().unwrap() {
+            FsEntry::File(f) => assert_eq!(f.size, 0),
+            FsEntry::Directory(_) => panic!("Expected file, got directory"),
+        }
+        // Read (upload); stream must be at EOF immediately
+        let mut fhnd: File = host.open_file_read(file_path.as_path()).ok().unwrap();
+        let mut buffer: [u8; 8] = [0; 8];
+        assert_eq!(fhnd.read(&mut buffer).ok().unwrap(), 0);
+    }
+
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    #[test]
+    fn test_host_localhost_special_files() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        let fifo_path: PathBuf = tmpdir.path().join("pipe");
+        let file_path: PathBuf = tmpdir.path().join("regular.txt");
+        assert!(std::process::Command::new("mkfifo")
+            .arg(fifo_path.as_path())
+            .status()
+            .unwrap()
+            .success());
+        assert!(host.open_file_write(file_path.as_path()).is_ok());
+        // Directory containing the FIFO can be read as usual (as when uploading it)
+        let entries: Vec<FsEntry> = host.scan_dir(tmpdir.path()).ok().unwrap();
+        assert_eq!(entries.len(), 2);
+        for entry in entries.iter() {
+            let path: PathBuf = entry.get_abs_path();
+            match path == fifo_path {
+                true => {
+                    assert!(host.is_special_file(path.as_path()));
+                    // Must fail instead of blocking until a writer opens the FIFO
+                    assert_eq!(
+                        host.open_file_read(path.as_path()).err().unwrap().error,
+                        HostErrorType::NotRegularFile
+                    );
+                }
+                false => {
+                    assert!(!host.is_special_file(path.as_path()));
+                    assert!(host.open_file_read(path.as_path()).is_ok());
+                }
+            }
+        }
+        // Device files
+        assert!(host.is_special_file(Path::new("/dev/null")));
+        assert!(!host.is_special_file(tmpdir.path()));
+    }
+
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    #[test]
+    fn test_host_localhost_non_utf8_name() {
+        use std::ffi::OsStr;
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        // Create file with invalid UTF-8 bytes in its name
+        let mut file_path: PathBuf = PathBuf::from(tmpdir.path());
+        file_path.push(OsStr::from_bytes(b"omar\xff\xfe.txt"));
+        let mut file: File = File::create(file_path.as_path()).unwrap();
+        assert!(writeln!(file, "Hello world!").is_ok());
+        drop(file);
+        // Scan
+        let host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        let files: Vec<FsEntry> = host.list_dir();
+        assert_eq!(files.len(), 1);
+        match files.first().unwrap() {
+            FsEntry::File(f) => {
+                // Display name is lossy, but path is preserved
+                assert_eq!(f.name, String::from("omar\u{FFFD}\u{FFFD}.txt"));
+                assert_eq!(f.abs_path, file_path);
+                assert_eq!(f.ftype.as_deref().unwrap(), "txt");
+                assert_eq!(f.size, 13);
+                // File can be opened for transfer
+                assert!(host.open_file_read(f.abs_path.as_path()).is_ok());
+            }
+            FsEntry::Directory(_) => panic!("Expected file, got directory"),
+        }
+    }
+
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     #[test]
     fn test_host_localhost_symlinks() {
@@ -680,6 +950,30 @@ mod tests {
         };
     }
 
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    #[test]
+    fn test_host_localhost_du() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let root: &Path = tmpdir.path();
+        std::fs::create_dir_all(root.join("sub/deep")).unwrap();
+        std::fs::write(root.join("a.txt"), b"hello").unwrap();
+        std::fs::write(root.join("sub/b.txt"), b"abc").unwrap();
+        std::fs::write(root.join("sub/deep/c.txt"), b"xy").unwrap();
+        // Symlinked directories (relative too) aren't followed, otherwise this would never end
+        assert!(symlink("..", root.join("sub/parent")).is_ok());
+        assert!(symlink(root.join("sub"), root.join("sub_link")).is_ok());
+        // Unreadable directories are skipped
+        std::fs::create_dir(root.join("locked")).unwrap();
+        std::fs::write(root.join("locked/empty.txt"), b"").unwrap();
+        fs::set_permissions(root.join("locked"), PermissionsExt::from_mode(0o000)).unwrap();
+        let host: Localhost = Localhost::new(PathBuf::from(root)).ok().unwrap();
+        assert_eq!(host.du(root).ok().unwrap(), 10);
+        assert_eq!(host.du(root.join("sub").as_path()).ok().unwrap(), 5);
+        fs::set_permissions(root.join("locked"), PermissionsExt::from_mode(0o755)).unwrap();
+        // Directory which doesn't exist
+        assert!(host.du(root.join("missing").as_path()).is_err());
+    }
+
     #[test]
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     fn test_host_localhost_mkdir() {
@@ -698,6 +992,61 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn test_host_localhost_touch() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        // Relative path
+        assert!(host.touch(Path::new("a.txt")).is_ok());
+        assert_eq!(
+            std::fs::metadata(tmpdir.path().join("a.txt"))
+                .unwrap()
+                .len(),
+            0
+        );
+        // Existing file is never overwritten
+        std::fs::write(tmpdir.path().join("b.txt"), b"hello").unwrap();
+        assert_eq!(
+            host.touch(Path::new("b.txt")).err().unwrap().error,
+            HostErrorType::FileAlreadyExists
+        );
+        assert_eq!(
+            std::fs::read(tmpdir.path().join("b.txt")).unwrap(),
+            b"hello"
+        );
+        // Absolute path, with a missing parent
+        assert!(host.touch(tmpdir.path().join("c.txt").as_path()).is_ok());
+        assert_eq!(
+            host.touch(tmpdir.path().join("d/e.txt").as_path())
+                .err()
+                .unwrap()
+                .error,
+            HostErrorType::CouldNotCreateFile
+        );
+    }
+
+    #[test]
+    fn test_host_localhost_mkdir_all() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let mut host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        // Relative path
+        assert_eq!(
+            host.mkdir_all(Path::new("a/b")).ok().unwrap(),
+            vec![tmpdir.path().join("a"), tmpdir.path().join("a/b")]
+        );
+        assert!(tmpdir.path().join("a/b").is_dir());
+        // Only missing directories are created
+        let dir: PathBuf = tmpdir.path().join("a/c/d");
+        assert_eq!(
+            host.mkdir_all(dir.as_path()).ok().unwrap(),
+            vec![tmpdir.path().join("a/c"), dir.clone()]
+        );
+        assert!(host.mkdir_all(dir.as_path()).ok().unwrap().is_empty());
+        // A file is in the way
+        assert!(File::create(tmpdir.path().join("f")).is_ok());
+        assert!(host.mkdir_all(Path::new("f/g")).is_err());
+    }
+
     #[test]
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     fn test_host_localhost_remove() {
@@ -749,6 +1098,31 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    fn test_host_localhost_is_same_file() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let a: PathBuf = tmpdir.path().join("a.txt");
+        let b: PathBuf = tmpdir.path().join("b.txt");
+        std::fs::write(a.as_path(), b"a").unwrap();
+        std::fs::write(b.as_path(), b"a").unwrap();
+        assert!(Localhost::is_same_file(a.as_path(), a.as_path()));
+        assert!(Localhost::is_same_file(
+            a.as_path(),
+            tmpdir.path().join("./a.txt").as_path()
+        ));
+        assert!(!Localhost::is_same_file(a.as_path(), b.as_path()));
+        // Hard link
+        let link: PathBuf = tmpdir.path().join("link.txt");
+        std::fs::hard_link(a.as_path(), link.as_path()).unwrap();
+        assert!(Localhost::is_same_file(a.as_path(), link.as_path()));
+        // Missing file
+        assert!(!Localhost::is_same_file(
+            a.as_path(),
+            tmpdir.path().join("c.txt").as_path()
+        ));
+    }
+
     #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
     #[test]
     fn test_host_chmod() {
@@ -768,6 +1142,67 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    fn test_host_symlink() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let host: Localhost = Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap();
+        let link: PathBuf = tmpdir.path().join("link");
+        // The target doesn't need to exist
+        assert!(host.symlink(link.as_path(), Path::new("/srv/data")).is_ok());
+        assert_eq!(
+            std::fs::read_link(link.as_path()).unwrap(),
+            PathBuf::from("/srv/data")
+        );
+        // Link already exists
+        assert_eq!(
+            host.symlink(link.as_path(), Path::new("/srv/data"))
+                .err()
+                .unwrap()
+                .error,
+            HostErrorType::FileAlreadyExists
+        );
+        // Parent doesn't exist
+        assert_eq!(
+            host.symlink(Path::new("/tmp/krgiogoiegj/kwrgnoerig"), Path::new("/srv"))
+                .err()
+                .unwrap()
+                .error,
+            HostErrorType::CouldNotCreateFile
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "xattrs", unix))]
+    fn test_host_localhost_get_xattrs() {
+        let tmpfile: tempfile::NamedTempFile = create_sample_file();
+        let host: Localhost = Localhost::new(PathBuf::from("/dev")).ok().unwrap();
+        if xattr::set(tmpfile.path(), "user.gateway.origin", b"omar@192.168.1.31").is_err() {
+            // File system doesn't support user extended attributes
+            assert!(host
+                .get_xattrs(tmpfile.path())
+                .map(|x| x.is_empty())
+                .unwrap_or(true));
+            return;
+        }
+        assert!(xattr::set(tmpfile.path(), "user.gateway.checksum", b"1a2b3c").is_ok());
+        let attrs: Vec<(String, Vec<u8>)> = host.get_xattrs(tmpfile.path()).unwrap();
+        assert_eq!(
+            attrs,
+            vec![
+                (String::from("user.gateway.checksum"), b"1a2b3c".to_vec()),
+                (
+                    String::from("user.gateway.origin"),
+                    b"omar@192.168.1.31".to_vec()
+                ),
+            ]
+        );
+        // Not existing file
+        assert!(host
+            .get_xattrs(Path::new("/home/omar/nonexisting"))
+            .is_none());
+    }
+
     #[test]
     fn test_host_fmt_error() {
         let err: HostError = HostError::new(
@@ -28,17 +28,21 @@ extern crate getopts;
 extern crate lazy_static;
 #[macro_use]
 extern crate magic_crypt;
-extern crate rpassword;
 
 // External libs
+use bytesize::ByteSize;
 use getopts::Options;
+use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
 
 // Include
 mod activity_manager;
 mod bookmarks;
+mod config;
 mod filetransfer;
 mod fs;
 mod host;
@@ -48,18 +52,729 @@ mod utils;
 
 // namespaces
 use activity_manager::{ActivityManager, NextActivity};
-use filetransfer::FileTransferProtocol;
+use filetransfer::proxy_jump::JumpHost;
+use filetransfer::{FileTransfer, FileTransferProtocol, SshIdentity};
+use fs::{FsEntry, FsFile};
+use host::Localhost;
+use system::config_client::ConfigClient;
+use ui::activities::filetransfer_activity::{LogLevel, TransferStates};
+use ui::prompt::PasswordInput;
+
+/// ## ConnectArgs
+///
+/// ConnectArgs holds the connection options given on the command line to the subcommands which run without the UI
+struct ConnectArgs<'a> {
+    password: Option<String>,
+    jump_hosts: &'a [JumpHost],
+    identity: Option<&'a SshIdentity>,
+    buffer_size: usize, // Size of the buffer files are copied with
+    config: Option<&'a ConfigClient>,
+}
 
 /// ### print_usage
 ///
 /// Print usage
 
 fn print_usage(opts: Options) {
-    let brief = String::from("Usage: gateway [options]... [protocol://user@address:port]");
+    let brief = String::from(
+        "Usage: gateway [options]... [protocol://user@address:port]\n       gateway [options]... test <protocol://user@address:port>\n       gateway [options]... mirror <url-list> <local-dir>\n       gateway [options]... put <local-file> <url>\n       gateway [options]... get <url> <local-path>\n       gateway [options]... bench <protocol://user@address:port>",
+    );
     print!("{}", opts.usage(&brief));
     println!("\nPlease, report issues to <https://github.com/warrengalyen/Gateway>");
 }
 
+// Amount of data transferred in each direction by bench, if not specified
+const BENCH_DEFAULT_SIZE: usize = 16777216;
+// Size of the buffer files are copied with when running without the UI, if not specified
+const BUFFER_DEFAULT_SIZE: usize = 65536;
+
+/// ### default_username
+///
+/// Get the username to use for `protocol` when the connection string omits it, from the configuration
+fn default_username(
+    config: Option<&ConfigClient>,
+    protocol: FileTransferProtocol,
+) -> Option<String> {
+    let config: &ConfigClient = config?;
+    match protocol {
+        FileTransferProtocol::Ftp(_) => config.get_ftp_default_username(),
+        FileTransferProtocol::Sftp | FileTransferProtocol::Scp => config.get_ssh_default_username(),
+    }
+}
+
+/// ### test_connection
+///
+/// Connect to the remote host, print the working directory and disconnect, without starting the UI.
+/// Prints `OK` followed by the server banner on success, the error otherwise.
+/// Returns the process exit code
+fn test_connection(
+    address: String,
+    port: u16,
+    protocol: FileTransferProtocol,
+    username: Option<String>,
+    args: &ConnectArgs,
+) -> i32 {
+    let mut client: Box<dyn FileTransfer> = filetransfer::new_client(
+        protocol,
+        args.jump_hosts,
+        args.identity,
+        None,
+        Duration::from_secs(60),
+        1,
+    );
+    let banner: Option<String> =
+        match client.connect(address.clone(), port, username, args.password.clone()) {
+            Ok(banner) => banner,
+            Err(err) => {
+                eprintln!("FAILED {}:{}: {}", address, port, err);
+                return 1;
+            }
+        };
+    let result: i32 = match client.pwd() {
+        Ok(wrkdir) => {
+            println!(
+                "OK {}:{} (working directory: {})",
+                address,
+                port,
+                wrkdir.display()
+            );
+            if let Some(banner) = banner {
+                println!("{}", banner.trim_end());
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("FAILED {}:{}: {}", address, port, err);
+            1
+        }
+    };
+    // Disconnect; failures here don't affect the result
+    let _ = client.disconnect();
+    result
+}
+
+/// ### mirror_urls
+///
+/// Download each remote URL (`protocol://user@address:port/path`) listed in the file at `list` into `dest`,
+/// without starting the UI. Connections are opened when a host is first met and reused by the following URLs of the same host.
+/// Empty lines and lines starting with `#` are ignored.
+/// If `dest` doesn't exist, it's created only if enabled in configuration (`transfer.create_download_dir`).
+/// The password of each host is read from `GATEWAY_PASSWORD_<HOST>`, `-P` or `GATEWAY_PASSWORD`, in this order.
+/// If `json_progress` is set, the progress of each download is printed on stderr as JSON lines.
+/// Prints the result of each URL and returns the process exit code, which is `1` if any URL failed
+fn mirror_urls(list: &Path, dest: &Path, args: &ConnectArgs, json_progress: bool) -> i32 {
+    let urls: String = match std::fs::read_to_string(list) {
+        Ok(urls) => urls,
+        Err(err) => {
+            eprintln!("Could not read URL list '{}': {}", list.display(), err);
+            return 1;
+        }
+    };
+    // Create destination directory, if enabled
+    let create_dir: bool = args
+        .config
+        .map(|config| config.get_create_download_dir())
+        .unwrap_or(false);
+    if create_dir && !dest.exists() {
+        let created: Result<Vec<PathBuf>, String> = env::current_dir()
+            .map_err(|err| err.to_string())
+            .and_then(|wrkdir| Localhost::new(wrkdir).map_err(|err| err.to_string()))
+            .and_then(|mut host| host.mkdir_all(dest).map_err(|err| err.to_string()));
+        match created {
+            Ok(created) => {
+                for dir in created.iter() {
+                    println!("Created directory '{}'", dir.display());
+                }
+            }
+            Err(err) => {
+                eprintln!("Could not create '{}': {}", dest.display(), err);
+                return 1;
+            }
+        }
+    }
+    // Connected clients (or connection error) by host
+    let mut clients: HashMap<String, Result<Box<dyn FileTransfer>, String>> = HashMap::new();
+    let mut failed: usize = 0;
+    let mut total: usize = 0;
+    for url in urls
+        .lines()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty() && !x.starts_with('#'))
+    {
+        total += 1;
+        match mirror_url(url, dest, &mut clients, args, json_progress) {
+            Ok(local) => println!("OK {} -> {}", url, local.display()),
+            Err(err) => {
+                eprintln!("FAILED {}: {}", url, err);
+                failed += 1;
+            }
+        }
+    }
+    // Disconnect; failures here don't affect the result
+    for client in clients.values_mut() {
+        if let Ok(client) = client {
+            let _ = client.disconnect();
+        }
+    }
+    println!("{} of {} URL(s) downloaded", total - failed, total);
+    match failed {
+        0 => 0,
+        _ => 1,
+    }
+}
+
+/// ### mirror_url
+///
+/// Download the remote URL into `dest`, connecting to its host if there's no client for it yet.
+/// If `json_progress` is set, the transfer states are printed on stderr as JSON every second and once the download completes.
+/// Returns the path of the downloaded file
+fn mirror_url(
+    url: &str,
+    dest: &Path,
+    clients: &mut HashMap<String, Result<Box<dyn FileTransfer>, String>>,
+    args: &ConnectArgs,
+    json_progress: bool,
+) -> Result<PathBuf, String> {
+    let (address, port, protocol, username, path) =
+        utils::parser::parse_remote_url(url, |protocol| default_username(args.config, protocol))?;
+    let host: String = format!(
+        "{:?}://{}@{}:{}",
+        protocol,
+        username.as_deref().unwrap_or_default(),
+        address,
+        port
+    );
+    let client: &mut Box<dyn FileTransfer> = clients
+        .entry(host)
+        .or_insert_with(|| {
+            let password: Option<String> =
+                env::var(system::environment::host_password_env(address.as_str()))
+                    .ok()
+                    .or_else(|| args.password.clone())
+                    .or_else(|| env::var(system::environment::PASSWORD_ENV).ok());
+            let mut client: Box<dyn FileTransfer> = filetransfer::new_client(
+                protocol,
+                args.jump_hosts,
+                args.identity,
+                None,
+                Duration::from_secs(60),
+                args.config
+                    .map(|cli| cli.get_sftp_concurrency())
+                    .unwrap_or(1),
+            );
+            match client.connect(address.clone(), port, username, password) {
+                Ok(_) => Ok(client),
+                Err(err) => Err(format!(
+                    "could not connect to {}:{}: {}",
+                    address, port, err
+                )),
+            }
+        })
+        .as_mut()
+        .map_err(|err| err.clone())?;
+    let file = match client.stat(path.as_path()).map_err(|err| err.to_string())? {
+        FsEntry::File(file) => file,
+        FsEntry::Directory(_) => return Err(String::from("not a file")),
+    };
+    let local: PathBuf = dest.join(file.name.as_str());
+    // Open the remote file first, so the local one isn't truncated if it can't be read
+    let mut reader: Box<dyn Read> = client.recv_file(&file).map_err(|err| err.to_string())?;
+    let mut local_file: File = match File::create(local.as_path()) {
+        Ok(local_file) => local_file,
+        Err(err) => {
+            let _ = client.on_recv(reader);
+            return Err(format!("could not create '{}': {}", local.display(), err));
+        }
+    };
+    copy_with_progress(
+        reader.as_mut(),
+        &mut local_file,
+        file.size,
+        args.buffer_size,
+        json_progress,
+        url,
+        local.display().to_string().as_str(),
+    )?;
+    client.on_recv(reader).map_err(|err| err.to_string())?;
+    Ok(local)
+}
+
+/// ### copy_with_progress
+///
+/// Copy `reader` to `writer` through a buffer of `buffer_size` bytes; they are named `src` and `dest` in errors and
+/// `size` is the size of the file being copied.
+/// If `json_progress` is set, the transfer states are printed on stderr as JSON every second and once the copy completes.
+/// Returns the amount of bytes copied
+fn copy_with_progress(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    size: usize,
+    buffer_size: usize,
+    json_progress: bool,
+    src: &str,
+    dest: &str,
+) -> Result<usize, String> {
+    let mut states: TransferStates = TransferStates::default();
+    states.set_progress(0, size);
+    let mut last_report: Instant = Instant::now();
+    let mut buffer: Vec<u8> = vec![0; buffer_size];
+    loop {
+        let bytes: usize = reader
+            .read(buffer.as_mut_slice())
+            .map_err(|err| format!("could not read '{}': {}", src, err))?;
+        if bytes == 0 {
+            break;
+        }
+        writer
+            .write_all(&buffer[0..bytes])
+            .map_err(|err| format!("could not write '{}': {}", dest, err))?;
+        states.set_progress(states.bytes_written + bytes, size);
+        if json_progress && last_report.elapsed() >= Duration::from_secs(1) {
+            eprintln!("{}", states.to_json());
+            last_report = Instant::now();
+        }
+    }
+    if json_progress {
+        eprintln!("{}", states.to_json());
+    }
+    Ok(states.bytes_written)
+}
+
+/// ### put_file
+///
+/// Upload the local file `src` to the remote URL (`protocol://user@address:port/path`), without starting the UI.
+/// The remote path is resolved by `parse_transfer_target`: if it ends with '/' or is an existing directory,
+/// the file keeps its name in it; otherwise the file is renamed.
+/// If `json_progress` is set, the transfer states are printed on stderr as JSON. Returns the process exit code
+fn put_file(src: &Path, url: &str, args: &ConnectArgs, json_progress: bool) -> i32 {
+    let result: Result<PathBuf, String> = connect_url(url, args).and_then(|(mut client, path)| {
+        let result: Result<PathBuf, String> =
+            put_file_to(client.as_mut(), src, path, args.buffer_size, json_progress);
+        // Disconnect; failures here don't affect the result
+        let _ = client.disconnect();
+        result
+    });
+    match result {
+        Ok(target) => {
+            println!("OK {} -> {}", src.display(), target.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("FAILED {}: {}", url, err);
+            1
+        }
+    }
+}
+
+/// ### put_file_to
+///
+/// Upload `src` to `dest` (see `put_file`). Returns the remote path of the uploaded file
+fn put_file_to(
+    client: &mut dyn FileTransfer,
+    src: &Path,
+    dest: PathBuf,
+    buffer_size: usize,
+    json_progress: bool,
+) -> Result<PathBuf, String> {
+    let parent: PathBuf = match src.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let local: FsFile = match Localhost::new(parent)
+        .and_then(|host| host.stat(src))
+        .map_err(|err| format!("could not stat '{}': {}", src.display(), err))?
+    {
+        FsEntry::File(file) => file,
+        FsEntry::Directory(_) => return Err(format!("'{}' is not a file", src.display())),
+    };
+    let target: PathBuf = utils::parser::parse_transfer_target(
+        dest.to_string_lossy().as_ref(),
+        local.name.as_str(),
+        |path| matches!(client.stat(path), Ok(FsEntry::Directory(_))),
+    );
+    let mut reader: File =
+        File::open(src).map_err(|err| format!("could not open '{}': {}", src.display(), err))?;
+    let mut writer: Box<dyn Write> = client
+        .send_file(&local, target.as_path())
+        .map_err(|err| format!("upload failed: {}", err))?;
+    copy_with_progress(
+        &mut reader,
+        writer.as_mut(),
+        local.size,
+        buffer_size,
+        json_progress,
+        src.display().to_string().as_str(),
+        target.display().to_string().as_str(),
+    )
+    .map_err(|err| format!("upload failed: {}", err))?;
+    client
+        .on_sent(writer)
+        .map_err(|err| format!("upload failed: {}", err))?;
+    Ok(target)
+}
+
+/// ### get_file
+///
+/// Download the remote URL (`protocol://user@address:port/path`) to `dest`, without starting the UI.
+/// `dest` is resolved by `parse_transfer_target`: if it ends with '/' or is an existing directory,
+/// the file keeps its name in it; otherwise the file is renamed.
+/// If `json_progress` is set, the transfer states are printed on stderr as JSON. Returns the process exit code
+fn get_file(url: &str, dest: &str, args: &ConnectArgs, json_progress: bool) -> i32 {
+    let result: Result<PathBuf, String> = connect_url(url, args).and_then(|(mut client, path)| {
+        let result: Result<PathBuf, String> =
+            get_file_to(client.as_mut(), path, dest, args.buffer_size, json_progress);
+        // Disconnect; failures here don't affect the result
+        let _ = client.disconnect();
+        result
+    });
+    match result {
+        Ok(target) => {
+            println!("OK {} -> {}", url, target.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("FAILED {}: {}", url, err);
+            1
+        }
+    }
+}
+
+/// ### get_file_to
+///
+/// Download the remote file at `src` to `dest` (see `get_file`). Returns the local path of the downloaded file
+fn get_file_to(
+    client: &mut dyn FileTransfer,
+    src: PathBuf,
+    dest: &str,
+    buffer_size: usize,
+    json_progress: bool,
+) -> Result<PathBuf, String> {
+    let file: FsFile = match client.stat(src.as_path()).map_err(|err| err.to_string())? {
+        FsEntry::File(file) => file,
+        FsEntry::Directory(_) => return Err(String::from("not a file")),
+    };
+    let target: PathBuf =
+        utils::parser::parse_transfer_target(dest, file.name.as_str(), |path| path.is_dir());
+    // Open the remote file first, so the local one isn't truncated if it can't be read
+    let mut reader: Box<dyn Read> = client
+        .recv_file(&file)
+        .map_err(|err| format!("download failed: {}", err))?;
+    let mut writer: File = match File::create(target.as_path()) {
+        Ok(writer) => writer,
+        Err(err) => {
+            let _ = client.on_recv(reader);
+            return Err(format!("could not create '{}': {}", target.display(), err));
+        }
+    };
+    copy_with_progress(
+        reader.as_mut(),
+        &mut writer,
+        file.size,
+        buffer_size,
+        json_progress,
+        src.display().to_string().as_str(),
+        target.display().to_string().as_str(),
+    )
+    .map_err(|err| format!("download failed: {}", err))?;
+    client
+        .on_recv(reader)
+        .map_err(|err| format!("download failed: {}", err))?;
+    Ok(target)
+}
+
+/// ### connect_url
+///
+/// Connect to the host of the remote URL, for `put` and `get`. Returns the client and the remote path of the URL.
+/// The password is taken from the host password variable, then from the option, then from the password variable;
+/// otherwise it's asked on the terminal, unless an identity file is provided
+fn connect_url(url: &str, args: &ConnectArgs) -> Result<(Box<dyn FileTransfer>, PathBuf), String> {
+    let (address, port, protocol, username, path) =
+        utils::parser::parse_remote_url(url, |protocol| default_username(args.config, protocol))?;
+    let password: Option<String> =
+        env::var(system::environment::host_password_env(address.as_str()))
+            .ok()
+            .or_else(|| args.password.clone())
+            .or_else(|| env::var(system::environment::PASSWORD_ENV).ok())
+            .or_else(|| match args.identity {
+                Some(_) => None,
+                None => prompt_password(),
+            });
+    let mut client: Box<dyn FileTransfer> = filetransfer::new_client(
+        protocol,
+        args.jump_hosts,
+        args.identity,
+        None,
+        Duration::from_secs(60),
+        args.config
+            .map(|cli| cli.get_sftp_concurrency())
+            .unwrap_or(1),
+    );
+    match client.connect(address.clone(), port, username, password) {
+        Ok(_) => Ok((client, path)),
+        Err(err) => Err(format!(
+            "could not connect to {}:{}: {}",
+            address, port, err
+        )),
+    }
+}
+
+/// ### bench
+///
+/// Connect to the remote host and measure the throughput of the transfers, without starting the UI.
+/// `size` bytes generated in memory are uploaded to a temporary file in the remote working directory,
+/// which is then downloaded and removed; the local disk is never used, so network and disk bottlenecks can be told apart.
+/// Prints the throughput of each direction and returns the process exit code
+fn bench(
+    address: String,
+    port: u16,
+    protocol: FileTransferProtocol,
+    username: Option<String>,
+    args: &ConnectArgs,
+    size: usize,
+    sftp_concurrency: usize,
+) -> i32 {
+    let mut client: Box<dyn FileTransfer> = filetransfer::new_client(
+        protocol,
+        args.jump_hosts,
+        args.identity,
+        None,
+        Duration::from_secs(60),
+        sftp_concurrency,
+    );
+    if protocol == FileTransferProtocol::Sftp {
+        println!("SFTP concurrency: {}", sftp_concurrency.max(1));
+    }
+    if let Err(err) = client.connect(address.clone(), port, username, args.password.clone()) {
+        eprintln!("FAILED {}:{}: {}", address, port, err);
+        return 1;
+    }
+    let result: Result<(), String> = bench_transfers(client.as_mut(), size, args.buffer_size);
+    // Disconnect; failures here don't affect the result
+    let _ = client.disconnect();
+    match result {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("FAILED {}:{}: {}", address, port, err);
+            1
+        }
+    }
+}
+
+/// ### bench_transfers
+///
+/// Upload and download `size` bytes to a temporary file in the remote working directory, through a buffer of `buffer_size` bytes,
+/// printing the throughput of each direction. The temporary file is removed even if a transfer fails
+fn bench_transfers(
+    client: &mut dyn FileTransfer,
+    size: usize,
+    buffer_size: usize,
+) -> Result<(), String> {
+    let wrkdir: PathBuf = client.pwd().map_err(|err| err.to_string())?;
+    let name: String = format!(".gateway-bench-{}", std::process::id());
+    let now: SystemTime = SystemTime::now();
+    let file: FsFile = FsFile {
+        name: name.clone(),
+        abs_path: wrkdir.join(name.as_str()),
+        last_change_time: now,
+        last_access_time: now,
+        creation_time: now,
+        size,
+        ftype: None,
+        readonly: false,
+        symlink: None,
+        link_target: None,
+        user: None,
+        group: None,
+        unix_pex: Some((6, 0, 0)),
+        nlink: None,
+        inode: None,
+    };
+    println!(
+        "Benchmarking {} in each direction with '{}'",
+        ByteSize(size as u64),
+        file.abs_path.display()
+    );
+    let result: Result<(), String> = bench_upload(client, &file, buffer_size)
+        .and_then(|elapsed| {
+            print_throughput("Upload", size, elapsed);
+            bench_download(client, &file, buffer_size)
+        })
+        .map(|elapsed| print_throughput("Download", size, elapsed));
+    // Remove temporary file; if a transfer failed, it may not exist at all
+    if let Err(err) = client.remove(&FsEntry::File(file.clone())) {
+        if result.is_ok() {
+            return Err(format!(
+                "could not remove '{}': {}",
+                file.abs_path.display(),
+                err
+            ));
+        }
+    }
+    result
+}
+
+/// ### bench_upload
+///
+/// Upload `file.size` bytes of generated data to `file`, `buffer_size` bytes at a time. Returns the time the upload took
+fn bench_upload(
+    client: &mut dyn FileTransfer,
+    file: &FsFile,
+    buffer_size: usize,
+) -> Result<Duration, String> {
+    let buffer: Vec<u8> = bench_data(buffer_size);
+    let started: Instant = Instant::now();
+    let mut writer: Box<dyn Write> = client
+        .send_file(file, file.abs_path.as_path())
+        .map_err(|err| format!("upload failed: {}", err))?;
+    let mut written: usize = 0;
+    while written < file.size {
+        let bytes: usize = buffer.len().min(file.size - written);
+        writer
+            .write_all(&buffer[0..bytes])
+            .map_err(|err| format!("upload failed: {}", err))?;
+        written += bytes;
+    }
+    client
+        .on_sent(writer)
+        .map_err(|err| format!("upload failed: {}", err))?;
+    Ok(started.elapsed())
+}
+
+/// ### bench_download
+///
+/// Download `file`, `buffer_size` bytes at a time, checking its data against the uploaded one (so pipelined reads
+/// returning data out of order would be caught) and then discarding it. Returns the time the download took
+fn bench_download(
+    client: &mut dyn FileTransfer,
+    file: &FsFile,
+    buffer_size: usize,
+) -> Result<Duration, String> {
+    let expected: Vec<u8> = bench_data(buffer_size);
+    let mut buffer: Vec<u8> = vec![0; expected.len()];
+    let started: Instant = Instant::now();
+    let mut reader: Box<dyn Read> = client
+        .recv_file(file)
+        .map_err(|err| format!("download failed: {}", err))?;
+    let mut read: usize = 0;
+    loop {
+        let bytes: usize = match reader.read(buffer.as_mut_slice()) {
+            Ok(0) => break,
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(format!("download failed: {}", err)),
+        };
+        // Compare with the pattern, which repeats every `expected.len()` bytes
+        for (i, byte) in buffer[0..bytes].iter().enumerate() {
+            if *byte != expected[(read + i) % expected.len()] {
+                return Err(format!(
+                    "downloaded data differs from the uploaded data at byte {}",
+                    read + i
+                ));
+            }
+        }
+        read += bytes;
+    }
+    client
+        .on_recv(reader)
+        .map_err(|err| format!("download failed: {}", err))?;
+    match read == file.size {
+        true => Ok(started.elapsed()),
+        false => Err(format!(
+            "downloaded {} bytes, but {} were uploaded",
+            read, file.size
+        )),
+    }
+}
+
+/// ### bench_data
+///
+/// Returns `size` bytes of data uploaded (repeatedly) by bench.
+/// Data is pseudo random, so compression can't skew the results
+fn bench_data(size: usize) -> Vec<u8> {
+    let mut seed: u32 = 0x2545_f491;
+    let mut buffer: Vec<u8> = vec![0; size];
+    for byte in buffer.iter_mut() {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        *byte = seed as u8;
+    }
+    buffer
+}
+
+/// ### print_throughput
+///
+/// Print the throughput of a transfer of `size` bytes which took `elapsed`
+fn print_throughput(direction: &str, size: usize, elapsed: Duration) {
+    let secs: f64 = elapsed.as_secs_f64();
+    let rate: u64 = match secs > 0.0 {
+        true => (size as f64 / secs) as u64,
+        false => size as u64,
+    };
+    println!(
+        "{}: {} in {}s ({}/s)",
+        direction,
+        ByteSize(size as u64),
+        utils::fmt::fmt_millis(elapsed),
+        ByteSize(rate)
+    );
+}
+
+/// ### prompt_password
+///
+/// Ask the password on the terminal. Returns `None` if an empty password has been typed;
+/// exits if the prompt has been interrupted, since nothing has been set up yet
+fn prompt_password() -> Option<String> {
+    prompt_secret("Password: ", "password")
+}
+
+/// ### unlock_identity
+///
+/// Ask the passphrase of the private key of `identity` on the terminal, if the key is encrypted.
+/// Keys which can't be read are left as they are: connecting reports why they can't be used
+fn unlock_identity(identity: Option<SshIdentity>) -> Option<SshIdentity> {
+    let mut identity: SshIdentity = identity?;
+    #[cfg(feature = "ssh")]
+    let encrypted: bool = std::fs::read_to_string(identity.path.as_path())
+        .map(|key| filetransfer::ssh_agent::is_key_encrypted(key.as_str()))
+        .unwrap_or(false);
+    #[cfg(not(feature = "ssh"))]
+    let encrypted: bool = false;
+    if encrypted {
+        identity.passphrase = prompt_secret(
+            format!("Passphrase for '{}': ", identity.path.display()).as_str(),
+            "passphrase",
+        );
+    }
+    Some(identity)
+}
+
+/// ### prompt_secret
+///
+/// Print `prompt` and read a secret (`what`, e.g. "password") on the terminal. Returns `None` if nothing has been typed;
+/// exits if the prompt has been interrupted, since nothing has been set up yet
+fn prompt_secret(prompt: &str, what: &str) -> Option<String> {
+    match ui::prompt::read_password(prompt) {
+        Ok(PasswordInput::Password(p)) => Some(p),
+        Ok(PasswordInput::Empty) => {
+            eprintln!("No {} provided; trying without {}", what, what);
+            None
+        }
+        Ok(PasswordInput::Interrupted) => {
+            eprintln!("Interrupted");
+            std::process::exit(255);
+        }
+        Ok(PasswordInput::Eof) => {
+            eprintln!("No {} read (EOF)", what);
+            std::process::exit(255);
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(255);
+        }
+    }
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     //Program CLI options
@@ -67,8 +782,9 @@ fn main() {
     let mut port: u16 = 22; // Default port
     let mut username: Option<String> = None; // Default username
     let mut password: Option<String> = None; // Default password
-    let mut protocol: FileTransferProtocol = FileTransferProtocol::Sftp; // Default protocol
+    let mut protocol: FileTransferProtocol = FileTransferProtocol::default(); // Default protocol
     let mut ticks: Duration = Duration::from_millis(10);
+    let mut jump_hosts: Vec<JumpHost> = Vec::new();
     //Process options
     let mut opts = Options::new();
     opts.optopt(
@@ -77,7 +793,71 @@ fn main() {
         "Provide password from CLI (use at your own risk)",
         "<password>",
     );
+    opts.optopt(
+        "J",
+        "jump",
+        "Connect through SSH jump host(s), comma separated (SFTP/SCP only)",
+        "<[user@]host[:port],...>",
+    );
+    opts.optopt(
+        "i",
+        "identity",
+        "Authenticate with the provided private key before the SSH agent and the password (SFTP/SCP only)",
+        "<path>",
+    );
+    opts.optflag(
+        "",
+        "readonly",
+        "Disable operations which modify files (only browsing and downloading allowed)",
+    );
+    opts.optflag(
+        "",
+        "trash",
+        "Move local deletes to a trash directory and back up overwritten remote files, so they can be undone",
+    );
     opts.optopt("T", "ticks", "Set UI ticks; default 10ms", "<ms>");
+    opts.optopt(
+        "",
+        "progress",
+        "Print transfer progress on stderr in mirror, put and get mode (`json`)",
+        "<format>",
+    );
+    opts.optopt(
+        "",
+        "bench-size",
+        "Amount of data transferred in each direction by bench (e.g. `64M`); default 16M",
+        "<size>",
+    );
+    opts.optopt(
+        "",
+        "sftp-concurrency",
+        "SFTP read requests kept in flight by bench; default `ssh.sftp_concurrency`",
+        "<n>",
+    );
+    opts.optopt(
+        "",
+        "buffer-size",
+        "Size of the transfer buffer (4-8192 KB), also in mirror, put, get and bench mode; default 64, or auto-tuned",
+        "<kb>",
+    );
+    opts.optopt(
+        "",
+        "log-file",
+        "Append log records to this file; default `ui.log_file`, or `gateway.log` in the configuration directory",
+        "<path>",
+    );
+    opts.optopt(
+        "",
+        "log-level",
+        "Least severe level written to the log file (`error`, `warn`, `info`); default info",
+        "<level>",
+    );
+    opts.optopt(
+        "",
+        "config-dir",
+        "Use a custom directory for configuration and bookmarks",
+        "<dir>",
+    );
     opts.optflag("v", "version", "");
     opts.optflag("h", "help", "Print this menu");
     let matches = match opts.parse(&args[1..]) {
@@ -100,10 +880,29 @@ fn main() {
         );
         std::process::exit(255);
     }
+    // Match readonly mode
+    let readonly: bool = matches.opt_present("readonly");
+    // Match trash mode
+    let trash: bool = matches.opt_present("trash");
     // Match password
     if let Some(passwd) = matches.opt_str("P") {
         password = Some(passwd);
     }
+    // Match identity file; its passphrase is asked only when needed
+    let mut identity: Option<SshIdentity> = matches
+        .opt_str("i")
+        .map(|path| SshIdentity::new(PathBuf::from(path), None));
+    // Match jump hosts
+    if let Some(spec) = matches.opt_str("J") {
+        match utils::parser::parse_jump_hosts(spec.as_str()) {
+            Ok(hosts) => jump_hosts = hosts,
+            Err(err) => {
+                eprintln!("Bad jump host option: {}", err);
+                print_usage(opts);
+                std::process::exit(255);
+            }
+        }
+    }
     // Match ticks
     if let Some(val) = matches.opt_str("T") {
         match val.parse::<usize>() {
@@ -115,11 +914,161 @@ fn main() {
             }
         }
     }
+    // Match progress format; only JSON lines are supported, default is quiet
+    let json_progress: bool = match matches.opt_str("progress").as_deref() {
+        None => false,
+        Some("json") => true,
+        Some(format) => {
+            eprintln!("Unknown progress format '{}'", format);
+            print_usage(opts);
+            std::process::exit(255);
+        }
+    };
+    // Match bench size
+    let bench_size: usize = match matches.opt_str("bench-size") {
+        None => BENCH_DEFAULT_SIZE,
+        Some(size) => match utils::parser::parse_bytesize(size.as_str()) {
+            Ok(size) => size,
+            Err(err) => {
+                eprintln!("Bad bench size option: {}", err);
+                print_usage(opts);
+                std::process::exit(255);
+            }
+        },
+    };
+    // Match transfer buffer size
+    let buffer_size: Option<usize> = match matches.opt_str("buffer-size") {
+        None => None,
+        Some(kb) => match utils::parser::parse_buffer_size(kb.as_str()) {
+            Ok(size) => Some(size),
+            Err(err) => {
+                eprintln!("Bad buffer size option: {}", err);
+                print_usage(opts);
+                std::process::exit(255);
+            }
+        },
+    };
+    // Match log file and level
+    let log_file: Option<PathBuf> = matches.opt_str("log-file").map(PathBuf::from);
+    let log_level: LogLevel = match matches.opt_str("log-level").as_deref() {
+        None | Some("info") => LogLevel::Info,
+        Some("warn") => LogLevel::Warn,
+        Some("error") => LogLevel::Error,
+        Some(level) => {
+            eprintln!("Unknown log level '{}'", level);
+            print_usage(opts);
+            std::process::exit(255);
+        }
+    };
+    // Match configuration directory; the environment variable is read when initializing the directory
+    if let Some(dir) = matches.opt_str("config-dir") {
+        env::set_var(system::environment::CONFIG_DIR_ENV, dir);
+    }
+    // Read configuration, for the default usernames; if it fails, default configuration will be used
+    let config_client: Option<ConfigClient> = match system::environment::init_config_dir() {
+        Ok(Some(mut config_path)) => {
+            config_path.push("config.toml");
+            ConfigClient::new(config_path.as_path()).ok()
+        }
+        _ => None,
+    };
+    // Match SFTP concurrency
+    let sftp_concurrency: usize = match matches.opt_str("sftp-concurrency") {
+        None => config_client
+            .as_ref()
+            .map(|cli| cli.get_sftp_concurrency())
+            .unwrap_or(1),
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) => n,
+            Err(err) => {
+                eprintln!("Bad SFTP concurrency option: {}", err);
+                print_usage(opts);
+                std::process::exit(255);
+            }
+        },
+    };
     // Check free args
-    let extra_args: Vec<String> = matches.free;
+    let mut extra_args: Vec<String> = matches.free;
+    // Connection test subcommand (`test <remote>`)
+    let test_mode: bool = matches!(extra_args.get(0).map(|x| x.as_str()), Some("test"));
+    if test_mode {
+        if extra_args.len() < 2 {
+            eprintln!("Missing remote address for test");
+            print_usage(opts);
+            std::process::exit(255);
+        }
+        extra_args.remove(0);
+    }
+    // Throughput benchmark subcommand (`bench <remote>`)
+    let bench_mode: bool = matches!(extra_args.get(0).map(|x| x.as_str()), Some("bench"));
+    if bench_mode {
+        if extra_args.len() < 2 {
+            eprintln!("Missing remote address for bench");
+            print_usage(opts);
+            std::process::exit(255);
+        }
+        extra_args.remove(0);
+    }
+    // Mirror subcommand (`mirror <url-list> <local-dir>`); password is never prompted here
+    if matches!(extra_args.get(0).map(|x| x.as_str()), Some("mirror")) {
+        if extra_args.len() < 3 {
+            eprintln!("Missing URL list or local directory for mirror");
+            print_usage(opts);
+            std::process::exit(255);
+        }
+        // Identity isn't used in mirror mode, since its passphrase would have to be prompted
+        let args: ConnectArgs = ConnectArgs {
+            password,
+            jump_hosts: jump_hosts.as_slice(),
+            identity: None,
+            buffer_size: buffer_size.unwrap_or(BUFFER_DEFAULT_SIZE),
+            config: config_client.as_ref(),
+        };
+        std::process::exit(mirror_urls(
+            Path::new(extra_args[1].as_str()),
+            Path::new(extra_args[2].as_str()),
+            &args,
+            json_progress,
+        ));
+    }
+    // One-shot transfer subcommands (`put <local-file> <url>`, `get <url> <local-path>`)
+    match extra_args.get(0).map(|x| x.as_str()) {
+        Some("put") | Some("get") if extra_args.len() < 3 => {
+            eprintln!("Missing source or destination for {}", extra_args[0]);
+            print_usage(opts);
+            std::process::exit(255);
+        }
+        Some(cmd @ "put") | Some(cmd @ "get") => {
+            let identity: Option<SshIdentity> = unlock_identity(identity);
+            let args: ConnectArgs = ConnectArgs {
+                password,
+                jump_hosts: jump_hosts.as_slice(),
+                identity: identity.as_ref(),
+                buffer_size: buffer_size.unwrap_or(BUFFER_DEFAULT_SIZE),
+                config: config_client.as_ref(),
+            };
+            std::process::exit(match cmd {
+                "put" => put_file(
+                    Path::new(extra_args[1].as_str()),
+                    extra_args[2].as_str(),
+                    &args,
+                    json_progress,
+                ),
+                _ => get_file(
+                    extra_args[1].as_str(),
+                    extra_args[2].as_str(),
+                    &args,
+                    json_progress,
+                ),
+            })
+        }
+        _ => {}
+    }
     if let Some(remote) = extra_args.get(0) {
         // Parse address
-        match utils::parser::parse_remote_opt(remote) {
+        match utils::parser::parse_remote_opt_ex(remote, |protocol| {
+            default_username(config_client.as_ref(), protocol)
+        }) {
             Ok((addr, portn, proto, user)) => {
                 // Set params
                 address = Some(addr);
@@ -139,24 +1088,64 @@ fn main() {
         Ok(dir) => dir,
         Err(_) => PathBuf::from("/"),
     };
+    // Without address argument, look for a `.gateway` file in the working directory
+    if address.is_none() {
+        let gateway_file: Option<String> = std::fs::read_to_string(wrkdir.join(".gateway")).ok();
+        // Missing or malformed file: just show the authentication form
+        if let Some(Ok((addr, portn, proto, user))) =
+            gateway_file.map(|x| utils::parser::parse_gateway_file(x.as_str()))
+        {
+            address = Some(addr);
+            port = portn;
+            protocol = proto;
+            username = user;
+        }
+    }
+    // Run connection test, without starting the UI; password is never prompted here
+    if test_mode {
+        if let Some(address) = address {
+            let args: ConnectArgs = ConnectArgs {
+                password,
+                jump_hosts: jump_hosts.as_slice(),
+                identity: identity.as_ref(),
+                buffer_size: buffer_size.unwrap_or(BUFFER_DEFAULT_SIZE),
+                config: config_client.as_ref(),
+            };
+            std::process::exit(test_connection(address, port, protocol, username, &args));
+        }
+    }
+    // Run throughput benchmark, without starting the UI
+    if bench_mode {
+        if let Some(address) = address {
+            identity = unlock_identity(identity);
+            if password.is_none() && identity.is_none() {
+                password = prompt_password();
+            }
+            let args: ConnectArgs = ConnectArgs {
+                password,
+                jump_hosts: jump_hosts.as_slice(),
+                identity: identity.as_ref(),
+                buffer_size: buffer_size.unwrap_or(BUFFER_DEFAULT_SIZE),
+                config: config_client.as_ref(),
+            };
+            std::process::exit(bench(
+                address,
+                port,
+                protocol,
+                username,
+                &args,
+                bench_size,
+                sftp_concurrency,
+            ));
+        }
+    }
     // Initialize client if necessary
     let mut start_activity: NextActivity = NextActivity::Authentication;
+    identity = unlock_identity(identity);
     if address.is_some() {
-        if password.is_none() {
-            // Ask password if unspecified
-            password = match rpassword::read_password_from_tty(Some("Password: ")) {
-                Ok(p) => {
-                    if p.is_empty() {
-                        None
-                    } else {
-                        Some(p)
-                    }
-                }
-                Err(_) => {
-                    eprintln!("Could not read password from prompt");
-                    std::process::exit(255);
-                }
-            };
+        if password.is_none() && identity.is_none() {
+            // Ask password if unspecified and there's no identity file to authenticate with
+            password = prompt_password();
         }
         // In this case the first activity will be FileTransfer
         start_activity = NextActivity::FileTransfer;
@@ -169,6 +1158,22 @@ fn main() {
             std::process::exit(255);
         }
     };
+    manager.set_jump_hosts(jump_hosts);
+    manager.set_identity(identity);
+    manager.set_readonly(readonly);
+    manager.set_buffer_size(buffer_size);
+    manager.set_log_file(log_file);
+    manager.set_log_level(log_level);
+    // Trash directory is in the configuration directory
+    if trash {
+        match system::environment::init_trash_dir() {
+            Ok(dir) => manager.set_trash_dir(Some(dir)),
+            Err(err) => {
+                eprintln!("Could not initialize trash directory: {}", err);
+                std::process::exit(255);
+            }
+        }
+    }
     // Set file transfer params if set
     if let Some(address) = address {
         manager.set_filetransfer_params(address, port, protocol, username, password);
@@ -178,3 +1183,116 @@ fn main() {
     // Then return
     std::process::exit(0);
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "ssh")]
+    fn test_main_get_file_to() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let args: ConnectArgs = ConnectArgs {
+            password: Some(String::from("password")),
+            jump_hosts: &[],
+            identity: None,
+            buffer_size: BUFFER_DEFAULT_SIZE,
+            config: None,
+        };
+        let (mut client, path) = connect_url("sftp://demo@test.rebex.net:22/readme.txt", &args)
+            .ok()
+            .unwrap();
+        assert_eq!(path, PathBuf::from("/readme.txt"));
+        // Into directory
+        let dest: String = format!("{}/", tmpdir.path().display());
+        let target: PathBuf = get_file_to(
+            client.as_mut(),
+            path.clone(),
+            dest.as_str(),
+            BUFFER_DEFAULT_SIZE,
+            false,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(target, tmpdir.path().join("readme.txt"));
+        assert!(std::fs::metadata(target.as_path()).unwrap().len() > 0);
+        // Renamed
+        let dest: PathBuf = tmpdir.path().join("renamed.txt");
+        let target: PathBuf = get_file_to(
+            client.as_mut(),
+            path,
+            dest.to_string_lossy().as_ref(),
+            4096,
+            true,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(target, dest);
+        assert!(std::fs::metadata(target.as_path()).unwrap().len() > 0);
+        // Not a file
+        assert!(get_file_to(
+            client.as_mut(),
+            PathBuf::from("/pub"),
+            dest.to_string_lossy().as_ref(),
+            BUFFER_DEFAULT_SIZE,
+            false
+        )
+        .is_err());
+        assert!(client.disconnect().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "ftp")]
+    fn test_main_put_file_to() {
+        let mut client: filetransfer::ftp_transfer::FtpFileTransfer =
+            filetransfer::ftp_transfer::FtpFileTransfer::new(false);
+        assert!(client
+            .connect(String::from("speedtest.tele2.net"), 21, None, None)
+            .is_ok());
+        let mut src: tempfile::NamedTempFile = tempfile::NamedTempFile::new().unwrap();
+        writeln!(src, "Gateway put test").unwrap();
+        let name: String = src
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        // Into directory
+        assert_eq!(
+            put_file_to(
+                &mut client,
+                src.path(),
+                PathBuf::from("/upload/"),
+                BUFFER_DEFAULT_SIZE,
+                false
+            )
+            .ok()
+            .unwrap(),
+            PathBuf::from("/upload").join(name.as_str())
+        );
+        // Renamed
+        assert_eq!(
+            put_file_to(
+                &mut client,
+                src.path(),
+                PathBuf::from("/upload/gateway-renamed.txt"),
+                4096,
+                true
+            )
+            .ok()
+            .unwrap(),
+            PathBuf::from("/upload/gateway-renamed.txt")
+        );
+        // Missing source
+        assert!(put_file_to(
+            &mut client,
+            Path::new("/this/file/does/not/exist.txt"),
+            PathBuf::from("/upload/"),
+            BUFFER_DEFAULT_SIZE,
+            false
+        )
+        .is_err());
+        assert!(client.disconnect().is_ok());
+    }
+}
@@ -95,6 +95,15 @@ impl BookmarksClient {
         Ok(client)
     }
 
+    /// ### set_passphrase
+    ///
+    /// Encrypt and decrypt the bookmark passwords with `passphrase`, instead of the key stored in the key file,
+    /// so they can't be decrypted with the key file alone.
+    /// Passwords saved with another key (or passphrase) can't be decrypted anymore and are loaded as unset
+    pub fn set_passphrase(&mut self, passphrase: &str) {
+        self.key = passphrase.to_string();
+    }
+
     /// ### iter_bookmarks
     ///
     /// Iterate over bookmarks keys
@@ -475,6 +484,41 @@ mod tests {
         assert_eq!(bookmark.3, String::from("pi"));
     }
 
+    #[test]
+    fn test_system_bookmarks_passphrase() {
+        let tmp_dir: tempfile::TempDir = create_tmp_dir();
+        let (cfg_path, key_path): (PathBuf, PathBuf) = get_paths(tmp_dir.path());
+        let mut client: BookmarksClient =
+            BookmarksClient::new(cfg_path.as_path(), key_path.as_path(), 16).unwrap();
+        client.set_passphrase("secret");
+        client.add_bookmark(
+            String::from("raspberry"),
+            String::from("192.168.1.31"),
+            22,
+            FileTransferProtocol::Sftp,
+            String::from("pi"),
+            Some(String::from("mypassword")),
+        );
+        assert!(client.write_bookmarks().is_ok());
+        // Same passphrase
+        let mut client: BookmarksClient =
+            BookmarksClient::new(cfg_path.as_path(), key_path.as_path(), 16).unwrap();
+        client.set_passphrase("secret");
+        assert_eq!(
+            client.get_bookmark("raspberry").unwrap().4,
+            Some(String::from("mypassword"))
+        );
+        // Key file only or another passphrase: the password isn't decrypted, the rest is loaded
+        let mut client: BookmarksClient =
+            BookmarksClient::new(cfg_path.as_path(), key_path.as_path(), 16).unwrap();
+        assert_eq!(client.get_bookmark("raspberry").unwrap().4, None);
+        client.set_passphrase("wrong");
+        let bookmark: (String, u16, FileTransferProtocol, String, Option<String>) =
+            client.get_bookmark("raspberry").unwrap();
+        assert_eq!(bookmark.0.as_str(), "192.168.1.31");
+        assert_eq!(bookmark.4, None);
+    }
+
     #[test]
     fn test_system_bookmarks_manipulate_bookmarks() {
         let tmp_dir: tempfile::TempDir = create_tmp_dir();
@@ -0,0 +1,557 @@
+//! ## ConfigClient
+//!
+//! `config_client` is the module which provides an API between the Config module and the system
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Local
+use crate::bookmarks::{SerializerError, SerializerErrorKind};
+use crate::config::serializer::ConfigSerializer;
+use crate::config::{GroupDirs, KeyBinding, LineEnding, RedrawInterval, UserConfig};
+// Ext
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+/// ## ConfigClient
+///
+/// ConfigClient provides a layer between the host system and the config module
+pub struct ConfigClient {
+    config: UserConfig,
+    config_path: PathBuf,
+}
+
+impl ConfigClient {
+    /// ### new
+    ///
+    /// Instantiate a new `ConfigClient` with provided path.
+    /// If the configuration file doesn't exist, it is created with default values
+    pub fn new(config_path: &Path) -> Result<ConfigClient, SerializerError> {
+        let mut client: ConfigClient = ConfigClient {
+            config: UserConfig::default(),
+            config_path: PathBuf::from(config_path),
+        };
+        // If config file doesn't exist, initialize it
+        match config_path.exists() {
+            true => client.read_config()?,   // Load configuration from file
+            false => client.write_config()?, // Write default configuration
+        }
+        Ok(client)
+    }
+
+    // Configuration
+
+    /// ### get_ftp_list_args
+    ///
+    /// Get arguments to pass to the FTP LIST command
+    pub fn get_ftp_list_args(&self) -> Option<String> {
+        self.config.ftp.list_args.clone()
+    }
+
+    /// ### get_ftp_flush_interval
+    ///
+    /// Get the amount of bytes after which the FTP upload stream is flushed (0 means only at the end)
+    pub fn get_ftp_flush_interval(&self) -> u64 {
+        self.config.ftp.flush_interval
+    }
+
+    /// ### get_ftp_default_username
+    ///
+    /// Get the username used for FTP when the connection string omits it
+    pub fn get_ftp_default_username(&self) -> Option<String> {
+        self.config.ftp.default_username.clone()
+    }
+
+    /// ### get_ssh_read_timeout
+    ///
+    /// Get the seconds a blocking SSH operation may wait for the server (0 means no timeout)
+    pub fn get_ssh_read_timeout(&self) -> u64 {
+        self.config.ssh.read_timeout
+    }
+
+    /// ### get_ssh_default_username
+    ///
+    /// Get the username used for SFTP/SCP when the connection string omits it (None means current user)
+    pub fn get_ssh_default_username(&self) -> Option<String> {
+        self.config.ssh.default_username.clone()
+    }
+
+    /// ### get_sftp_concurrency
+    ///
+    /// Get the amount of SFTP read requests kept in flight while downloading (1 means one at a time)
+    pub fn get_sftp_concurrency(&self) -> usize {
+        self.config.ssh.sftp_concurrency
+    }
+
+    /// ### get_max_connections
+    ///
+    /// Get the amount of connections which can be open to the same host at once (0 means unlimited)
+    pub fn get_max_connections(&self) -> usize {
+        self.config.connection.max_connections
+    }
+
+    /// ### get_clock_skew_threshold
+    ///
+    /// Get the seconds of remote clock skew which are warned about at connect (0 means the check is disabled)
+    pub fn get_clock_skew_threshold(&self) -> u64 {
+        self.config.connection.clock_skew_threshold
+    }
+
+    /// ### get_clock_skew_compensate
+    ///
+    /// Get whether the measured remote clock skew is compensated when comparing times in syncs
+    pub fn get_clock_skew_compensate(&self) -> bool {
+        self.config.connection.clock_skew_compensate
+    }
+
+    /// ### get_verify_uploads
+    ///
+    /// Get whether uploads must be verified by comparing the remote file size with the local one
+    pub fn get_verify_uploads(&self) -> bool {
+        self.config.transfer.verify_uploads
+    }
+
+    /// ### get_verify_retries
+    ///
+    /// Get the amount of times an upload is retried when verification fails
+    pub fn get_verify_retries(&self) -> u8 {
+        self.config.transfer.verify_retries
+    }
+
+    /// ### get_post_download_command
+    ///
+    /// Get the command template to run after each successful download
+    pub fn get_post_download_command(&self) -> Option<String> {
+        self.config.transfer.post_download_command.clone()
+    }
+
+    /// ### get_create_parents
+    ///
+    /// Get whether missing parent directories of the transfer destination must be created
+    pub fn get_create_parents(&self) -> bool {
+        self.config.transfer.create_parents
+    }
+
+    /// ### get_create_download_dir
+    ///
+    /// Get whether the local download directory must be created if it doesn't exist
+    pub fn get_create_download_dir(&self) -> bool {
+        self.config.transfer.create_download_dir
+    }
+
+    /// ### get_confirm_size
+    ///
+    /// Get the size (bytes) above which a transfer must be confirmed (0 means never)
+    pub fn get_confirm_size(&self) -> u64 {
+        self.config.transfer.confirm_size
+    }
+
+    /// ### get_max_depth
+    ///
+    /// Get the amount of directory levels walked by recursive transfers and deletes (0 means unlimited)
+    pub fn get_max_depth(&self) -> usize {
+        self.config.transfer.max_depth
+    }
+
+    /// ### get_buffer_auto_tune
+    ///
+    /// Get whether the transfer buffer size is adjusted according to the observed throughput
+    pub fn get_buffer_auto_tune(&self) -> bool {
+        self.config.transfer.buffer_auto_tune
+    }
+
+    /// ### get_buffer_max_size
+    ///
+    /// Get the size (bytes) the auto-tuned transfer buffer never exceeds
+    pub fn get_buffer_max_size(&self) -> usize {
+        self.config.transfer.buffer_max_size
+    }
+
+    /// ### get_remote_line_ending
+    ///
+    /// Get the line ending convention of the remote text files; if set, line endings are converted on transfer
+    pub fn get_remote_line_ending(&self) -> Option<LineEnding> {
+        self.config.transfer.remote_line_ending
+    }
+
+    /// ### get_preserve_paths
+    ///
+    /// Get whether marks are kept across directories and transferred with their path relative to the working directory
+    pub fn get_preserve_paths(&self) -> bool {
+        self.config.transfer.preserve_paths
+    }
+
+    /// ### get_follow_symlinks
+    ///
+    /// Get whether recursive downloads walk the directories symlinks point to, instead of recreating the links
+    pub fn get_follow_symlinks(&self) -> bool {
+        self.config.transfer.follow_symlinks
+    }
+
+    /// ### get_resume_uploads
+    ///
+    /// Get whether uploads are resumed when the remote file looks like an interrupted upload
+    pub fn get_resume_uploads(&self) -> bool {
+        self.config.transfer.resume_uploads
+    }
+
+    /// ### get_show_log
+    ///
+    /// Get whether the log panel is visible
+    pub fn get_show_log(&self) -> bool {
+        self.config.ui.show_log
+    }
+
+    /// ### set_show_log
+    ///
+    /// Set whether the log panel is visible
+    pub fn set_show_log(&mut self, show: bool) {
+        self.config.ui.show_log = show;
+    }
+
+    /// ### get_wrap_selection
+    ///
+    /// Get whether the explorer selection wraps around at the list boundaries
+    pub fn get_wrap_selection(&self) -> bool {
+        self.config.ui.wrap_selection
+    }
+
+    /// ### get_terminal_title
+    ///
+    /// Get whether the terminal title reports the transfer progress
+    pub fn get_terminal_title(&self) -> bool {
+        self.config.ui.terminal_title
+    }
+
+    /// ### get_log_file
+    ///
+    /// Get the path of the file every log record is appended to, if any
+    pub fn get_log_file(&self) -> Option<PathBuf> {
+        self.config.ui.log_file.clone()
+    }
+
+    /// ### get_log_file_max_size
+    ///
+    /// Get the size (bytes) the log file is rotated at (0 means unlimited)
+    pub fn get_log_file_max_size(&self) -> u64 {
+        self.config.ui.log_file_max_size
+    }
+
+    /// ### get_log_size
+    ///
+    /// Get the maximum amount of records kept in the log panel
+    pub fn get_log_size(&self) -> usize {
+        self.config.ui.log_size
+    }
+
+    /// ### get_relative_time
+    ///
+    /// Get whether times are shown relative to now
+    pub fn get_relative_time(&self) -> bool {
+        self.config.ui.relative_time
+    }
+
+    /// ### set_relative_time
+    ///
+    /// Set whether times are shown relative to now
+    pub fn set_relative_time(&mut self, relative: bool) {
+        self.config.ui.relative_time = relative;
+    }
+
+    /// ### get_show_link_target
+    ///
+    /// Get whether symlinks are displayed with their target in the explorers
+    pub fn get_show_link_target(&self) -> bool {
+        self.config.ui.show_link_target
+    }
+
+    /// ### get_redraw_interval
+    ///
+    /// Get how often the progress bar is redrawn during a transfer
+    pub fn get_redraw_interval(&self) -> RedrawInterval {
+        self.config.ui.redraw_interval
+    }
+
+    /// ### get_show_path_line
+    ///
+    /// Get whether the absolute path of the selected entry is shown in a status line
+    pub fn get_show_path_line(&self) -> bool {
+        self.config.ui.show_path_line
+    }
+
+    /// ### get_show_banner
+    ///
+    /// Get whether the server banner is written to the log at connect
+    pub fn get_show_banner(&self) -> bool {
+        self.config.ui.show_banner
+    }
+
+    /// ### get_group_dirs
+    ///
+    /// Get where directories are listed in the explorers (first, last or mixed with files)
+    pub fn get_group_dirs(&self) -> GroupDirs {
+        self.config.ui.group_dirs
+    }
+
+    /// ### get_abort_key
+    ///
+    /// Get the key which aborts the running transfer or scan
+    pub fn get_abort_key(&self) -> KeyBinding {
+        self.config.ui.abort_key
+    }
+
+    /// ### get_selection_profile
+    ///
+    /// Get the paths saved in the selection profile with provided name
+    pub fn get_selection_profile(&self, name: &str) -> Option<Vec<PathBuf>> {
+        self.config.selections.get(name).cloned()
+    }
+
+    /// ### set_selection_profile
+    ///
+    /// Save paths into the selection profile with provided name; an existing profile is replaced
+    pub fn set_selection_profile(&mut self, name: &str, paths: Vec<PathBuf>) {
+        self.config.selections.insert(String::from(name), paths);
+    }
+
+    // I/O
+
+    /// ### write_config
+    ///
+    /// Write configuration to file
+    pub fn write_config(&self) -> Result<(), SerializerError> {
+        // Open file
+        match OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(self.config_path.as_path())
+        {
+            Ok(writer) => {
+                let serializer: ConfigSerializer = ConfigSerializer {};
+                serializer.serialize(Box::new(writer), &self.config)
+            }
+            Err(err) => Err(SerializerError::new_ex(
+                SerializerErrorKind::IoError,
+                err.to_string(),
+            )),
+        }
+    }
+
+    /// ### read_config
+    ///
+    /// Read configuration from file
+    fn read_config(&mut self) -> Result<(), SerializerError> {
+        // Open config file for read
+        match OpenOptions::new()
+            .read(true)
+            .open(self.config_path.as_path())
+        {
+            Ok(reader) => {
+                // Deserialize
+                let deserializer: ConfigSerializer = ConfigSerializer {};
+                match deserializer.deserialize(Box::new(reader)) {
+                    Ok(config) => {
+                        self.config = config;
+                        Ok(())
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+            Err(err) => Err(SerializerError::new_ex(
+                SerializerErrorKind::IoError,
+                err.to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::KeyName;
+
+    #[test]
+    fn test_system_config_new() {
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().ok().unwrap();
+        let cfg_path: PathBuf = get_config_path(tmp_dir.path());
+        // Initialize a new config client
+        let client: ConfigClient = ConfigClient::new(cfg_path.as_path()).ok().unwrap();
+        // File must have been created
+        assert!(cfg_path.exists());
+        assert_eq!(client.config_path, cfg_path);
+        assert!(client.get_ftp_list_args().is_none());
+        assert_eq!(client.get_ftp_flush_interval(), 0);
+        assert!(client.get_ftp_default_username().is_none());
+        assert_eq!(client.get_ssh_read_timeout(), 60);
+        assert!(client.get_ssh_default_username().is_none());
+        assert_eq!(client.get_sftp_concurrency(), 1);
+        assert_eq!(client.get_max_connections(), 4);
+        assert_eq!(client.get_clock_skew_threshold(), 0);
+        assert!(!client.get_clock_skew_compensate());
+        assert!(client.get_verify_uploads());
+        assert_eq!(client.get_verify_retries(), 0);
+        assert!(client.get_post_download_command().is_none());
+        assert!(!client.get_create_parents());
+        assert!(!client.get_create_download_dir());
+        assert_eq!(client.get_confirm_size(), 0);
+        assert_eq!(client.get_max_depth(), 0);
+        assert!(!client.get_buffer_auto_tune());
+        assert_eq!(client.get_buffer_max_size(), 4194304);
+        assert!(client.get_remote_line_ending().is_none());
+        assert!(!client.get_preserve_paths());
+        assert!(!client.get_follow_symlinks());
+        assert!(!client.get_resume_uploads());
+        assert!(client.get_show_log());
+        assert!(!client.get_wrap_selection());
+        assert!(client.get_terminal_title());
+        assert_eq!(client.get_log_size(), 256);
+        assert!(client.get_log_file().is_none());
+        assert_eq!(client.get_log_file_max_size(), 10485760);
+        assert!(!client.get_relative_time());
+        assert!(client.get_show_link_target());
+        assert_eq!(client.get_redraw_interval(), RedrawInterval::Percent(1.0));
+        assert!(!client.get_show_path_line());
+        assert_eq!(client.get_abort_key().to_string().as_str(), "CTRL+C");
+        assert!(client.get_show_banner());
+        assert_eq!(client.get_group_dirs(), GroupDirs::First);
+        assert!(client.get_selection_profile("audit").is_none());
+    }
+
+    #[test]
+    fn test_system_config_new_err() {
+        assert!(ConfigClient::new(Path::new("/tmp/oifoif/omar/config.toml")).is_err());
+    }
+
+    #[test]
+    fn test_system_config_from_existing() {
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().ok().unwrap();
+        let cfg_path: PathBuf = get_config_path(tmp_dir.path());
+        let mut client: ConfigClient = ConfigClient::new(cfg_path.as_path()).ok().unwrap();
+        // Change configuration and write it
+        let mut config: UserConfig = UserConfig::default();
+        config.ftp.list_args = Some(String::from("-a"));
+        config.ftp.flush_interval = 1048576;
+        config.ftp.default_username = Some(String::from("anonymous"));
+        config.ssh.read_timeout = 0;
+        config.ssh.default_username = Some(String::from("deploy"));
+        config.ssh.sftp_concurrency = 16;
+        config.connection.max_connections = 0;
+        config.connection.clock_skew_threshold = 5;
+        config.connection.clock_skew_compensate = true;
+        config.transfer.verify_uploads = false;
+        config.transfer.verify_retries = 2;
+        config.transfer.post_download_command = Some(String::from("chmod 600 {path}"));
+        config.transfer.create_parents = true;
+        config.transfer.create_download_dir = true;
+        config.transfer.confirm_size = 1073741824;
+        config.transfer.max_depth = 4;
+        config.transfer.buffer_auto_tune = true;
+        config.transfer.buffer_max_size = 1048576;
+        config.transfer.remote_line_ending = Some(LineEnding::Crlf);
+        config.transfer.preserve_paths = true;
+        config.transfer.follow_symlinks = true;
+        config.transfer.resume_uploads = true;
+        config.ui.wrap_selection = true;
+        config.ui.terminal_title = false;
+        config.ui.log_size = 1024;
+        config.ui.log_file = Some(PathBuf::from("/tmp/gateway.log"));
+        config.ui.log_file_max_size = 0;
+        config.ui.show_link_target = false;
+        config.ui.redraw_interval = RedrawInterval::Millis(500);
+        config.ui.show_path_line = true;
+        config.ui.show_banner = false;
+        config.ui.group_dirs = GroupDirs::Last;
+        config.ui.abort_key = KeyBinding {
+            ctrl: false,
+            alt: false,
+            key: KeyName::Function(2),
+        };
+        client.config = config;
+        client.set_show_log(false);
+        client.set_relative_time(true);
+        client.set_selection_profile("audit", vec![PathBuf::from("/var/log/syslog")]);
+        assert!(client.write_config().is_ok());
+        // Re-initialize client
+        let client: ConfigClient = ConfigClient::new(cfg_path.as_path()).ok().unwrap();
+        assert_eq!(client.get_ftp_list_args().unwrap(), String::from("-a"));
+        assert_eq!(client.get_ftp_flush_interval(), 1048576);
+        assert_eq!(
+            client.get_ftp_default_username().unwrap().as_str(),
+            "anonymous"
+        );
+        assert_eq!(client.get_ssh_read_timeout(), 0);
+        assert_eq!(
+            client.get_ssh_default_username().unwrap().as_str(),
+            "deploy"
+        );
+        assert_eq!(client.get_sftp_concurrency(), 16);
+        assert_eq!(client.get_max_connections(), 0);
+        assert_eq!(client.get_clock_skew_threshold(), 5);
+        assert!(client.get_clock_skew_compensate());
+        assert!(!client.get_verify_uploads());
+        assert_eq!(client.get_verify_retries(), 2);
+        assert_eq!(
+            client.get_post_download_command().unwrap(),
+            String::from("chmod 600 {path}")
+        );
+        assert!(client.get_create_parents());
+        assert!(client.get_create_download_dir());
+        assert_eq!(client.get_confirm_size(), 1073741824);
+        assert_eq!(client.get_max_depth(), 4);
+        assert!(client.get_buffer_auto_tune());
+        assert_eq!(client.get_buffer_max_size(), 1048576);
+        assert_eq!(client.get_remote_line_ending(), Some(LineEnding::Crlf));
+        assert!(client.get_preserve_paths());
+        assert!(client.get_follow_symlinks());
+        assert!(client.get_resume_uploads());
+        assert!(!client.get_show_log());
+        assert!(client.get_wrap_selection());
+        assert!(!client.get_terminal_title());
+        assert_eq!(client.get_log_size(), 1024);
+        assert_eq!(
+            client.get_log_file().unwrap(),
+            PathBuf::from("/tmp/gateway.log")
+        );
+        assert_eq!(client.get_log_file_max_size(), 0);
+        assert!(client.get_relative_time());
+        assert!(!client.get_show_link_target());
+        assert_eq!(client.get_redraw_interval(), RedrawInterval::Millis(500));
+        assert!(client.get_show_path_line());
+        assert_eq!(client.get_abort_key().to_string().as_str(), "F2");
+        assert!(!client.get_show_banner());
+        assert_eq!(client.get_group_dirs(), GroupDirs::Last);
+        assert_eq!(
+            client.get_selection_profile("audit").unwrap(),
+            vec![PathBuf::from("/var/log/syslog")]
+        );
+    }
+
+    /// ### get_config_path
+    ///
+    /// Get path for configuration file
+    fn get_config_path(dir: &Path) -> PathBuf {
+        let mut p: PathBuf = PathBuf::from(dir);
+        p.push("config.toml");
+        p
+    }
+}
@@ -27,32 +27,99 @@
 extern crate dirs;
 
 // Ext
+use std::env;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
-/// ### get_config_dir
+/// Environment variable which overrides the configuration directory (set by `--config-dir` too)
+pub const CONFIG_DIR_ENV: &str = "GATEWAY_CONFIG_DIR";
+
+/// Environment variable which provides the password in non-interactive modes
+pub const PASSWORD_ENV: &str = "GATEWAY_PASSWORD";
+
+/// Environment variable which provides the passphrase the passwords saved in bookmarks are encrypted with
+pub const BOOKMARKS_PASSPHRASE_ENV: &str = "GATEWAY_BOOKMARKS_PASSPHRASE";
+
+/// ### host_password_env
+///
+/// Get the name of the environment variable which provides the password for `address` in non-interactive modes
+/// (e.g. `GATEWAY_PASSWORD_MIRROR_EXAMPLE_COM` for `mirror.example.com`); it takes precedence over `GATEWAY_PASSWORD`
+pub fn host_password_env(address: &str) -> String {
+    let host: String = address
+        .chars()
+        .map(|x| match x.is_ascii_alphanumeric() {
+            true => x.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect();
+    format!("{}_{}", PASSWORD_ENV, host)
+}
+
+/// ### init_config_dir
+///
+/// Get gateway configuration directory path and create it if it doesn't exist.
+/// The directory is, in order of precedence:
+///
+/// - `GATEWAY_CONFIG_DIR` (used as is)
+/// - `XDG_CONFIG_HOME/gateway/`
+/// - `gateway/` in the system configuration directory
 ///
-/// Get gateway configuration directory path.
 /// Returns None, if it's not possible to get it
 pub fn init_config_dir() -> Result<Option<PathBuf>, String> {
-    // Get file
-    lazy_static! {
-        static ref CONF_DIR: Option<PathBuf> = dirs::config_dir();
+    match get_config_dir(env::var_os(CONFIG_DIR_ENV), env::var_os("XDG_CONFIG_HOME")) {
+        Some(p) => create_config_dir(p).map(Some),
+        None => Ok(None),
     }
-    if CONF_DIR.is_some() {
-        // Get path of bookmarks
-        let mut p: PathBuf = CONF_DIR.as_ref().unwrap().clone();
-        // Append gateway dir
-        p.push("gateway/");
-        // If directory doesn't exist, create it
-        match p.exists() {
-            true => Ok(Some(p)),
-            false => match std::fs::create_dir(p.as_path()) {
-                Ok(_) => Ok(Some(p)),
-                Err(err) => Err(err.to_string()),
-            },
-        }
-    } else {
-        Ok(None)
+}
+
+/// ### init_trash_dir
+///
+/// Get the trash directory (`trash/` in the configuration directory), where local deletes are moved in trash mode,
+/// and create it if it doesn't exist
+pub fn init_trash_dir() -> Result<PathBuf, String> {
+    match init_config_dir()? {
+        Some(p) => create_config_dir(p.join("trash")),
+        None => Err(String::from("configuration directory is not available")),
+    }
+}
+
+/// ### get_log_file_path
+///
+/// Get the path of the default log file (`gateway.log` in the configuration directory), if the directory is available
+pub fn get_log_file_path() -> Option<PathBuf> {
+    init_config_dir()
+        .ok()
+        .flatten()
+        .map(|p| p.join("gateway.log"))
+}
+
+/// ### get_config_dir
+///
+/// Get configuration directory path from the override directory and `XDG_CONFIG_HOME`.
+/// Empty values are ignored; `XDG_CONFIG_HOME` is ignored if relative too
+fn get_config_dir(override_dir: Option<OsString>, xdg_dir: Option<OsString>) -> Option<PathBuf> {
+    if let Some(dir) = override_dir.filter(|x| !x.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    let mut p: PathBuf = match xdg_dir.map(PathBuf::from).filter(|x| x.is_absolute()) {
+        Some(dir) => dir,
+        None => dirs::config_dir()?,
+    };
+    // Append gateway dir
+    p.push("gateway/");
+    Some(p)
+}
+
+/// ### create_config_dir
+///
+/// Create configuration directory (and its parents) if it doesn't exist
+fn create_config_dir(p: PathBuf) -> Result<PathBuf, String> {
+    match p.exists() {
+        true => Ok(p),
+        false => match std::fs::create_dir_all(p.as_path()) {
+            Ok(_) => Ok(p),
+            Err(err) => Err(err.to_string()),
+        },
     }
 }
 
@@ -71,6 +138,48 @@ mod tests {
         assert!(std::fs::remove_dir_all(conf_dir.as_path()).is_ok());
     }
 
+    #[test]
+    fn test_system_environment_get_config_dir_override() {
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let custom_dir: PathBuf = tmp_dir.path().join("portable/config");
+        // Override directory is used as is
+        let conf_dir: PathBuf = get_config_dir(
+            Some(OsString::from(custom_dir.as_os_str())),
+            Some(OsString::from("/home/omar/.xdg")),
+        )
+        .unwrap();
+        assert_eq!(conf_dir, custom_dir);
+        // Directory is created with its parents
+        assert_eq!(create_config_dir(conf_dir).unwrap(), custom_dir);
+        assert!(custom_dir.is_dir());
+        // XDG_CONFIG_HOME
+        assert_eq!(
+            get_config_dir(
+                Some(OsString::new()),
+                Some(OsString::from("/home/omar/.xdg"))
+            )
+            .unwrap(),
+            PathBuf::from("/home/omar/.xdg/gateway/")
+        );
+        // Relative XDG_CONFIG_HOME is ignored
+        assert_eq!(
+            get_config_dir(None, Some(OsString::from("xdg"))),
+            dirs::config_dir().map(|x| x.join("gateway/"))
+        );
+    }
+
+    #[test]
+    fn test_system_environment_host_password_env() {
+        assert_eq!(
+            host_password_env("mirror.example.com").as_str(),
+            "GATEWAY_PASSWORD_MIRROR_EXAMPLE_COM"
+        );
+        assert_eq!(
+            host_password_env("172.26.104.1").as_str(),
+            "GATEWAY_PASSWORD_172_26_104_1"
+        );
+    }
+
     #[test]
     fn test_system_environment_get_config_dir_err() {
         let mut conf_dir: PathBuf = dirs::config_dir().unwrap();
@@ -91,4 +200,4 @@ mod tests {
         // Remove file
         assert!(std::fs::remove_file(conf_dir.as_path()).is_ok());
     }
-}
\ No newline at end of file
+}
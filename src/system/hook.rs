@@ -0,0 +1,127 @@
+//! ## Hook
+//!
+//! `hook` is the module which runs the user commands configured to be executed after a file transfer
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Ext
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+
+/// ### render_command
+///
+/// Replace placeholders in the command template with the transferred file values:
+///
+/// - `{path}`: absolute path of the file (quoted)
+/// - `{name}`: file name (quoted)
+/// - `{size}`: file size in bytes
+///
+/// Path and name are quoted for the shell, since they may come from the remote host
+pub fn render_command(template: &str, path: &Path, size: usize) -> String {
+    let name: String = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => String::new(),
+    };
+    template
+        .replace("{path}", quote(path.to_string_lossy().as_ref()).as_str())
+        .replace("{name}", quote(name.as_str()).as_str())
+        .replace("{size}", size.to_string().as_str())
+}
+
+/// ### spawn_command
+///
+/// Run the command through the system shell, without waiting for it.
+/// Input and output are detached from the terminal, so the command can't mess up the user interface
+pub fn spawn_command(command: &str) -> std::io::Result<Child> {
+    #[cfg(target_os = "windows")]
+    let mut cmd: Command = {
+        let mut cmd: Command = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd: Command = {
+        let mut cmd: Command = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+/// ### quote
+///
+/// Quote argument for the system shell
+#[cfg(not(target_os = "windows"))]
+fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// ### quote
+///
+/// Quote argument for the system shell
+#[cfg(target_os = "windows")]
+fn quote(arg: &str) -> String {
+    // NOTE: double quotes are not allowed in Windows file names
+    format!("\"{}\"", arg.replace('"', ""))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_system_hook_render_command() {
+        assert_eq!(
+            render_command(
+                "chmod 600 {path} && echo {name} {size}",
+                Path::new("/home/omar/report.pdf"),
+                8192
+            )
+            .as_str(),
+            "chmod 600 '/home/omar/report.pdf' && echo 'report.pdf' 8192"
+        );
+        // Quotes in file name can't escape the argument
+        assert_eq!(
+            render_command("cat {path}", Path::new("/tmp/a'; rm -rf ~; '"), 0).as_str(),
+            "cat '/tmp/a'\\''; rm -rf ~; '\\'''"
+        );
+        // Without placeholders
+        assert_eq!(
+            render_command("notify-send done", Path::new("/tmp/a"), 0).as_str(),
+            "notify-send done"
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_system_hook_spawn_command() {
+        let mut child: Child = spawn_command("exit 3").unwrap();
+        assert_eq!(child.wait().unwrap().code(), Some(3));
+        let mut child: Child = spawn_command("true").unwrap();
+        assert!(child.wait().unwrap().success());
+    }
+}
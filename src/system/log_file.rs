@@ -0,0 +1,139 @@
+//! ## LogFile
+//!
+//! `log_file` is the module which appends the log records to a file as soon as they're written,
+//! so that the history isn't lost if Gateway crashes
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Ext
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// ## LogFile
+///
+/// LogFile appends lines to a file, flushing each one. When the file would exceed `max_size`,
+/// it is renamed to `<path>.1` (replacing the previous one) and a new file is started
+pub struct LogFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,     // Current size of the file
+    max_size: u64, // Size the file is rotated at; 0 is unlimited
+}
+
+impl LogFile {
+    /// ### open
+    ///
+    /// Open the log file at `path` for appending, creating it if it doesn't exist
+    pub fn open(path: &Path, max_size: u64) -> std::io::Result<LogFile> {
+        let file: File = LogFile::open_file(path)?;
+        let size: u64 = file.metadata()?.len();
+        Ok(LogFile {
+            path: PathBuf::from(path),
+            writer: BufWriter::new(file),
+            size,
+            max_size,
+        })
+    }
+
+    /// ### write_line
+    ///
+    /// Append `line` to the file and flush it, rotating the file first if it would exceed its maximum size
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len: u64 = line.len() as u64 + 1;
+        if self.max_size > 0 && self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// ### rotate
+    ///
+    /// Rename the file to `<path>.1` and start a new one
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        let mut rotated: OsString = self.path.clone().into_os_string();
+        rotated.push(".1");
+        std::fs::rename(self.path.as_path(), rotated)?;
+        self.writer = BufWriter::new(LogFile::open_file(self.path.as_path())?);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn open_file(path: &Path) -> std::io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_system_log_file() {
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = tmp_dir.path().join("gateway.log");
+        let mut log: LogFile = LogFile::open(path.as_path(), 0).unwrap();
+        log.write_line("first").unwrap();
+        // Written immediately
+        assert_eq!(std::fs::read_to_string(path.as_path()).unwrap(), "first\n");
+        drop(log);
+        // Appended when reopened
+        let mut log: LogFile = LogFile::open(path.as_path(), 0).unwrap();
+        log.write_line("second").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path.as_path()).unwrap(),
+            "first\nsecond\n"
+        );
+    }
+
+    #[test]
+    fn test_system_log_file_rotate() {
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = tmp_dir.path().join("gateway.log");
+        let rotated: PathBuf = tmp_dir.path().join("gateway.log.1");
+        let mut log: LogFile = LogFile::open(path.as_path(), 12).unwrap();
+        log.write_line("01234").unwrap();
+        log.write_line("56789").unwrap();
+        assert!(!rotated.exists());
+        // 12 bytes would be exceeded: rotate
+        log.write_line("abcde").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(rotated.as_path()).unwrap(),
+            "01234\n56789\n"
+        );
+        assert_eq!(std::fs::read_to_string(path.as_path()).unwrap(), "abcde\n");
+        // Lines longer than the maximum size are written anyway
+        log.write_line("0123456789abcdef").unwrap();
+        log.write_line("last").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(rotated.as_path()).unwrap(),
+            "0123456789abcdef\n"
+        );
+        assert_eq!(std::fs::read_to_string(path.as_path()).unwrap(), "last\n");
+    }
+}
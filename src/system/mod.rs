@@ -25,4 +25,7 @@
 
 // modules
 pub mod bookmarks_client;
-pub mod environment;
\ No newline at end of file
+pub mod config_client;
+pub mod environment;
+pub mod hook;
+pub mod log_file;
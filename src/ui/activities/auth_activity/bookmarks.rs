@@ -232,7 +232,17 @@ impl AuthActivity {
                     key_file.push(".bookmarks.key"); // key file is hidden
                                                      // Initialize client
                     match BookmarksClient::new(bookmarks_file.as_path(), key_file.as_path(), 16) {
-                        Ok(cli) => self.bookmarks_client = Some(cli),
+                        Ok(mut cli) => {
+                            // Passwords are encrypted with the passphrase, if provided
+                            if let Ok(passphrase) =
+                                std::env::var(environment::BOOKMARKS_PASSPHRASE_ENV)
+                            {
+                                if !passphrase.is_empty() {
+                                    cli.set_passphrase(passphrase.as_str());
+                                }
+                            }
+                            self.bookmarks_client = Some(cli);
+                        }
                         Err(err) => {
                             self.input_mode = InputMode::Popup(PopupType::Alert(
                                 Color::Red,
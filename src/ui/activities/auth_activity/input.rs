@@ -87,6 +87,14 @@ impl AuthActivity {
                         ));
                         return;
                     }
+                    // Check protocol (bookmarks may refer to a protocol not enabled in this build)
+                    if !self.protocol.is_enabled() {
+                        self.input_mode = InputMode::Popup(PopupType::Alert(
+                            Color::Red,
+                            String::from("Protocol is not supported by this build of Gateway"),
+                        ));
+                        return;
+                    }
                     // Check port
                     // Convert port to number
                     match self.port.parse::<usize>() {
@@ -121,6 +129,9 @@ impl AuthActivity {
                         InputField::Password => {
                             let _ = self.password.pop();
                         }
+                        InputField::IdentityFile => {
+                            let _ = self.identity_file.pop();
+                        }
                         InputField::Username => {
                             let _ = self.username.pop();
                         }
@@ -133,11 +144,12 @@ impl AuthActivity {
                 KeyCode::Up => {
                     // Move item up
                     self.selected_field = match self.selected_field {
-                        InputField::Address => InputField::Password, // End of list (wrap)
+                        InputField::Address => InputField::IdentityFile, // End of list (wrap)
                         InputField::Port => InputField::Address,
                         InputField::Protocol => InputField::Port,
                         InputField::Username => InputField::Protocol,
                         InputField::Password => InputField::Username,
+                        InputField::IdentityFile => InputField::Password,
                     }
                 }
                 KeyCode::Down => {
@@ -147,7 +159,8 @@ impl AuthActivity {
                         InputField::Port => InputField::Protocol,
                         InputField::Protocol => InputField::Username,
                         InputField::Username => InputField::Password,
-                        InputField::Password => InputField::Address, // End of list (wrap)
+                        InputField::Password => InputField::IdentityFile,
+                        InputField::IdentityFile => InputField::Address, // End of list (wrap)
                     }
                 }
                 KeyCode::Char(ch) => {
@@ -171,6 +184,7 @@ impl AuthActivity {
                         match self.selected_field {
                             InputField::Address => self.address.push(ch),
                             InputField::Password => self.password.push(ch),
+                            InputField::IdentityFile => self.identity_file.push(ch),
                             InputField::Username => self.username.push(ch),
                             InputField::Port => {
                                 // Value must be numeric
@@ -185,27 +199,39 @@ impl AuthActivity {
                 KeyCode::Left => {
                     // If current field is Protocol handle event... (move element left)
                     if self.selected_field == InputField::Protocol {
-                        self.protocol = match self.protocol {
-                            FileTransferProtocol::Sftp => FileTransferProtocol::Ftp(true), // End of list (wrap)
-                            FileTransferProtocol::Scp => FileTransferProtocol::Sftp,
-                            FileTransferProtocol::Ftp(ftps) => match ftps {
-                                false => FileTransferProtocol::Scp,
-                                true => FileTransferProtocol::Ftp(false),
-                            },
-                        };
+                        // Skip protocols which are not enabled in this build
+                        loop {
+                            self.protocol = match self.protocol {
+                                FileTransferProtocol::Sftp => FileTransferProtocol::Ftp(true), // End of list (wrap)
+                                FileTransferProtocol::Scp => FileTransferProtocol::Sftp,
+                                FileTransferProtocol::Ftp(ftps) => match ftps {
+                                    false => FileTransferProtocol::Scp,
+                                    true => FileTransferProtocol::Ftp(false),
+                                },
+                            };
+                            if self.protocol.is_enabled() {
+                                break;
+                            }
+                        }
                     }
                 }
                 KeyCode::Right => {
                     // If current field is Protocol handle event... ( move element right )
                     if self.selected_field == InputField::Protocol {
-                        self.protocol = match self.protocol {
-                            FileTransferProtocol::Sftp => FileTransferProtocol::Scp,
-                            FileTransferProtocol::Scp => FileTransferProtocol::Ftp(false),
-                            FileTransferProtocol::Ftp(ftps) => match ftps {
-                                false => FileTransferProtocol::Ftp(true),
-                                true => FileTransferProtocol::Sftp, // End of list (wrap)
-                            },
-                        };
+                        // Skip protocols which are not enabled in this build
+                        loop {
+                            self.protocol = match self.protocol {
+                                FileTransferProtocol::Sftp => FileTransferProtocol::Scp,
+                                FileTransferProtocol::Scp => FileTransferProtocol::Ftp(false),
+                                FileTransferProtocol::Ftp(ftps) => match ftps {
+                                    false => FileTransferProtocol::Ftp(true),
+                                    true => FileTransferProtocol::Sftp, // End of list (wrap)
+                                },
+                            };
+                            if self.protocol.is_enabled() {
+                                break;
+                            }
+                        }
                     }
                 }
                 _ => { /* Nothing to do */ }
@@ -361,8 +387,8 @@ impl AuthActivity {
                         self.input_mode = InputMode::Popup(PopupType::Help);
                     }
                     'S' | 's' => {
-                         // Default choice option to no
-                         self.choice_opt = DialogYesNoOption::No;
+                        // Default choice option to no
+                        self.choice_opt = DialogYesNoOption::No;
                         // Save bookmark as...
                         self.input_mode = InputMode::Popup(PopupType::SaveBookmark);
                     }
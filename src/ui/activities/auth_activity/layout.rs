@@ -67,6 +67,7 @@ impl AuthActivity {
                         Constraint::Length(3),
                         Constraint::Length(3),
                         Constraint::Length(3),
+                        Constraint::Length(3),
                     ]
                     .as_ref(),
                 )
@@ -85,8 +86,9 @@ impl AuthActivity {
             f.render_widget(self.draw_protocol_select(), auth_chunks[3]);
             f.render_widget(self.draw_protocol_username(), auth_chunks[4]);
             f.render_widget(self.draw_protocol_password(), auth_chunks[5]);
+            f.render_widget(self.draw_identity_file(), auth_chunks[6]);
             // Draw footer
-            f.render_widget(self.draw_footer(), auth_chunks[6]);
+            f.render_widget(self.draw_footer(), auth_chunks[7]);
             // Set cursor
             if let InputForm::AuthCredentials = self.input_form {
                 match self.selected_field {
@@ -106,6 +108,10 @@ impl AuthActivity {
                         auth_chunks[5].x + self.password_placeholder.width() as u16 + 1,
                         auth_chunks[5].y + 1,
                     ),
+                    InputField::IdentityFile => f.set_cursor(
+                        auth_chunks[6].x + self.identity_file.width() as u16 + 1,
+                        auth_chunks[6].y + 1,
+                    ),
                     _ => {}
                 }
             }
@@ -275,6 +281,23 @@ impl AuthActivity {
             )
     }
 
+    /// ### draw_identity_file
+    ///
+    /// Draw identity file block
+    fn draw_identity_file(&self) -> Paragraph {
+        Paragraph::new(self.identity_file.as_ref())
+            .style(match self.selected_field {
+                InputField::IdentityFile => Style::default().fg(Color::LightYellow),
+                _ => Style::default(),
+            })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title("Identity file (SFTP/SCP)"),
+            )
+    }
+
     /// ### draw_header
     ///
     /// Draw header
@@ -58,6 +58,7 @@ enum InputField {
     Protocol,
     Username,
     Password,
+    IdentityFile,
 }
 
 /// ### DialogYesNoOption
@@ -108,8 +109,9 @@ pub struct AuthActivity {
     pub protocol: FileTransferProtocol,
     pub username: String,
     pub password: String,
-    pub submit: bool, // becomes true after user has submitted fields
-    pub quit: bool,   // Becomes true if user has pressed esc
+    pub identity_file: String, // Private key to authenticate with (SFTP/SCP only); the password is its passphrase
+    pub submit: bool,          // becomes true after user has submitted fields
+    pub quit: bool,            // Becomes true if user has pressed esc
     context: Option<Context>,
     bookmarks_client: Option<BookmarksClient>,
     selected_field: InputField, // Selected field in AuthCredentials Form
@@ -137,9 +139,10 @@ impl AuthActivity {
         AuthActivity {
             address: String::new(),
             port: String::from("22"),
-            protocol: FileTransferProtocol::Sftp,
+            protocol: FileTransferProtocol::default(),
             username: String::new(),
             password: String::new(),
+            identity_file: String::new(),
             submit: false,
             quit: false,
             context: None,
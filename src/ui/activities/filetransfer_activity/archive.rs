@@ -0,0 +1,245 @@
+//! ## Archive
+//!
+//! `archive` is the module which browses the content of archive files and extracts their members
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Dependencies
+extern crate tempfile;
+
+// Locals
+use super::{FileExplorer, FileTransferActivity, InputMode, LogLevel, PopupType};
+use crate::fs::archive::{Archive, ArchiveKind};
+use crate::fs::{FsEntry, FsFile};
+
+// Ext
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// ## ArchiveState
+///
+/// ArchiveState contains the archive browsed in the archive popup
+pub(super) struct ArchiveState {
+    pub name: PathBuf,          // Path of the archive file (local or remote)
+    pub archive: Archive,       // Archive members
+    pub explorer: FileExplorer, // Entries of the archive directory being browsed
+    _tmpfile: Option<tempfile::NamedTempFile>, // Downloaded remote archive; removed with the state
+}
+
+impl ArchiveState {
+    /// ### changedir
+    ///
+    /// Browse directory `dir` of the archive
+    pub fn changedir(&mut self, dir: &Path) {
+        self.explorer.wrkdir = PathBuf::from(dir);
+        self.explorer.files = self.archive.list_dir(dir);
+        self.explorer.sort_files();
+        self.explorer.index = 0;
+    }
+}
+
+impl FileTransferActivity {
+    /// ### archive_open
+    ///
+    /// If `file` is a supported archive, browse its content in the archive popup.
+    /// Remote archives are downloaded to a temporary file first; other files are ignored
+    pub(super) fn archive_open(&mut self, file: &FsFile, local: bool) {
+        let kind: ArchiveKind = match ArchiveKind::from_name(file.name.as_str()) {
+            Some(kind) => kind,
+            None => return,
+        };
+        let tmpfile: Option<tempfile::NamedTempFile> = match local {
+            true => None,
+            false => match self.download_tmpfile(file) {
+                Ok(tmpfile) => Some(tmpfile),
+                Err(err) => {
+                    self.log_and_alert(LogLevel::Error, format!("Could not open archive: {}", err));
+                    return;
+                }
+            },
+        };
+        let path: PathBuf = match &tmpfile {
+            Some(tmpfile) => PathBuf::from(tmpfile.path()),
+            None => file.abs_path.clone(),
+        };
+        match Archive::open(path.as_path(), kind) {
+            Ok(archive) => {
+                let mut state: ArchiveState = ArchiveState {
+                    name: file.abs_path.clone(),
+                    archive,
+                    explorer: FileExplorer::new(self.local.wrap, self.local.group_dirs),
+                    _tmpfile: tmpfile,
+                };
+                state.changedir(Path::new("/"));
+                self.archive = Some(state);
+                self.input_mode = InputMode::Popup(PopupType::Archive);
+            }
+            Err(err) => self.log_and_alert(
+                LogLevel::Error,
+                format!("\"{}\": {}", file.abs_path.display(), err),
+            ),
+        }
+    }
+
+    /// ### download_tmpfile
+    ///
+    /// Download the remote file (e.g. an archive) to a temporary file
+    pub(super) fn download_tmpfile(
+        &mut self,
+        file: &FsFile,
+    ) -> Result<tempfile::NamedTempFile, String> {
+        let tmpfile: tempfile::NamedTempFile = tempfile::NamedTempFile::new()
+            .map_err(|err| format!("Could not create temporary file: {}", err))?;
+        self.filetransfer_recv_file(tmpfile.path(), file)?;
+        self.input_mode = InputMode::Explorer;
+        match std::mem::replace(&mut self.transfer.aborted, false) {
+            true => Err(String::from("download aborted")),
+            false => Ok(tmpfile),
+        }
+    }
+
+    /// ### archive_close
+    ///
+    /// Close the archive popup; the temporary copy of a remote archive is removed
+    pub(super) fn archive_close(&mut self) {
+        self.archive = None;
+        self.input_mode = InputMode::Explorer;
+    }
+
+    /// ### archive_enter
+    ///
+    /// Enter the selected directory of the archive
+    pub(super) fn archive_enter(&mut self) {
+        if let Some(state) = self.archive.as_mut() {
+            if let Some(FsEntry::Directory(dir)) = state.explorer.get_selected().cloned() {
+                state.changedir(dir.abs_path.as_path());
+            }
+        }
+    }
+
+    /// ### archive_parent
+    ///
+    /// Go to the parent directory of the archive directory being browsed
+    pub(super) fn archive_parent(&mut self) {
+        if let Some(state) = self.archive.as_mut() {
+            if let Some(parent) = state.explorer.wrkdir.parent().map(PathBuf::from) {
+                state.changedir(parent.as_path());
+            }
+        }
+    }
+
+    /// ### archive_extract
+    ///
+    /// Extract the selected file of the archive into the working directory of the local (or remote) explorer
+    pub(super) fn archive_extract(&mut self, local: bool) {
+        let file: FsFile = match self
+            .archive
+            .as_ref()
+            .and_then(|x| x.explorer.get_selected())
+        {
+            Some(FsEntry::File(file)) => file.clone(),
+            Some(FsEntry::Directory(_)) => {
+                self.log(LogLevel::Warn, "Only files can be extracted from archives");
+                return;
+            }
+            None => return,
+        };
+        if !local && self.readonly {
+            self.log_readonly();
+            return;
+        }
+        let result: Result<PathBuf, String> = match local {
+            true => self.archive_extract_local(&file),
+            false => self.archive_extract_remote(&file),
+        };
+        match result {
+            Ok(dst) => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "Extracted \"{}\" to \"{}\"",
+                        file.abs_path.display(),
+                        dst.display()
+                    )
+                    .as_str(),
+                );
+                match local {
+                    true => {
+                        let wrkdir: PathBuf = self.local.wrkdir.clone();
+                        self.local_scan(wrkdir.as_path());
+                    }
+                    false => self.reload_remote_dir(),
+                }
+            }
+            Err(err) => {
+                // The alert replaces the archive popup
+                self.archive = None;
+                self.log_and_alert(
+                    LogLevel::Error,
+                    format!("Could not extract \"{}\": {}", file.abs_path.display(), err),
+                );
+                return;
+            }
+        }
+        // Keep browsing the archive
+        self.input_mode = InputMode::Popup(PopupType::Archive);
+    }
+
+    /// ### archive_extract_local
+    ///
+    /// Extract archive member `file` into the local working directory
+    fn archive_extract_local(&mut self, file: &FsFile) -> Result<PathBuf, String> {
+        let dst: PathBuf = self.local.wrkdir.join(file.name.as_str());
+        let mut dst_file: File = self
+            .context
+            .as_ref()
+            .unwrap()
+            .local
+            .open_file_write(dst.as_path())
+            .map_err(|err| format!("\"{}\": {}", dst.display(), err))?;
+        self.archive
+            .as_ref()
+            .unwrap()
+            .archive
+            .extract(file, &mut dst_file)?;
+        Ok(dst)
+    }
+
+    /// ### archive_extract_remote
+    ///
+    /// Extract archive member `file` into a temporary file and upload it into the remote working directory
+    fn archive_extract_remote(&mut self, file: &FsFile) -> Result<PathBuf, String> {
+        let mut tmpfile: tempfile::NamedTempFile = tempfile::NamedTempFile::new()
+            .map_err(|err| format!("Could not create temporary file: {}", err))?;
+        self.archive
+            .as_ref()
+            .unwrap()
+            .archive
+            .extract(file, tmpfile.as_file_mut())?;
+        // Upload the temporary file with the name of the member
+        let mut local: FsFile = file.clone();
+        local.abs_path = PathBuf::from(tmpfile.path());
+        let dst: PathBuf = self.remote.wrkdir.join(file.name.as_str());
+        self.filetransfer_send_file(&local, dst.as_path())?;
+        Ok(dst)
+    }
+}
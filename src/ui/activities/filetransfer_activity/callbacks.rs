@@ -19,9 +19,19 @@
 *
 */
 
-use super::{FileExplorerTab, FileTransferActivity, FsEntry, LogLevel};
+use super::undo::UndoAction;
+use super::{
+    FileExplorerTab, FileTransferActivity, FileTransferParams, FsEntry, InputMode, LogLevel,
+    PopupType,
+};
+use crate::filetransfer::{FileTransferErrorType, FileTransferProtocol};
+use crate::host::Localhost;
+use crate::system::config_client::ConfigClient;
+use crate::utils::parser::{parse_remote_opt_ex, parse_unix_pex};
+use crate::utils::select::{glob_match, SelectExpr};
 
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 impl FileTransferActivity {
     /// ### callback_nothing_to_do
@@ -66,6 +76,24 @@ impl FileTransferActivity {
     ///
     /// Callback for MKDIR command (supports both local and remote)
     pub(super) fn callback_mkdir(&mut self, input: String) {
+        self.make_directory(input.as_str());
+    }
+
+    /// ### callback_mkdir_enter
+    ///
+    /// Callback for MKDIR and enter command (supports both local and remote):
+    /// once created, the directory becomes the working directory
+    pub(super) fn callback_mkdir_enter(&mut self, input: String) {
+        if self.make_directory(input.as_str()) {
+            self.callback_change_directory(input);
+        }
+    }
+
+    /// ### make_directory
+    ///
+    /// Create directory in the working directory of the current tab.
+    /// Returns whether the directory has been created
+    fn make_directory(&mut self, input: &str) -> bool {
         match self.tab {
             FileExplorerTab::Local => {
                 match self
@@ -73,7 +101,7 @@ impl FileTransferActivity {
                     .as_mut()
                     .unwrap()
                     .local
-                    .mkdir(PathBuf::from(input.as_str()).as_path())
+                    .mkdir(PathBuf::from(input).as_path())
                 {
                     Ok(_) => {
                         // Reload files
@@ -83,6 +111,7 @@ impl FileTransferActivity {
                         );
                         let wrkdir: PathBuf = self.local.wrkdir.clone();
                         self.local_scan(wrkdir.as_path());
+                        true
                     }
                     Err(err) => {
                         // Report err
@@ -90,15 +119,12 @@ impl FileTransferActivity {
                             LogLevel::Error,
                             format!("Could not create directory \"{}\": {}", input, err),
                         );
+                        false
                     }
                 }
             }
             FileExplorerTab::Remote => {
-                match self
-                    .client
-                    .as_mut()
-                    .mkdir(PathBuf::from(input.as_str()).as_path())
-                {
+                match self.client.as_mut().mkdir(PathBuf::from(input).as_path()) {
                     Ok(_) => {
                         // Reload files
                         self.log(
@@ -106,6 +132,7 @@ impl FileTransferActivity {
                             format!("Created directory \"{}\"", input).as_ref(),
                         );
                         self.reload_remote_dir();
+                        true
                     }
                     Err(err) => {
                         // Report err
@@ -113,12 +140,71 @@ impl FileTransferActivity {
                             LogLevel::Error,
                             format!("Could not create directory \"{}\": {}", input, err),
                         );
+                        false
                     }
                 }
             }
         }
     }
 
+    /// ### callback_touch
+    ///
+    /// Callback for TOUCH command: create an empty file in the working directory of the current tab.
+    /// An existing file is never overwritten
+    pub(super) fn callback_touch(&mut self, input: String) {
+        let exists: bool = match self.tab {
+            FileExplorerTab::Local => &self.local,
+            FileExplorerTab::Remote => &self.remote,
+        }
+        .files
+        .iter()
+        .any(|x| x.get_name() == input);
+        if exists {
+            self.log_and_alert(
+                LogLevel::Error,
+                format!("Could not create file \"{}\": file already exists", input),
+            );
+            return;
+        }
+        let result: Result<(), String> = match self.tab {
+            FileExplorerTab::Local => self
+                .context
+                .as_mut()
+                .unwrap()
+                .local
+                .touch(PathBuf::from(input.as_str()).as_path())
+                .map_err(|err| err.to_string()),
+            FileExplorerTab::Remote => self
+                .client
+                .as_mut()
+                .touch(PathBuf::from(input.as_str()).as_path())
+                .map_err(|err| err.to_string()),
+        };
+        match result {
+            Ok(_) => {
+                // Reload files
+                self.log(
+                    LogLevel::Info,
+                    format!("Created file \"{}\"", input).as_ref(),
+                );
+                match self.tab {
+                    FileExplorerTab::Local => {
+                        let wrkdir: PathBuf = self.local.wrkdir.clone();
+                        self.local_scan(wrkdir.as_path());
+                    }
+                    FileExplorerTab::Remote => self.reload_remote_dir(),
+                }
+            }
+            Err(err) => {
+                // Report err
+                self.log_and_alert(
+                    LogLevel::Error,
+                    format!("Could not create file \"{}\": {}", input, err),
+                );
+            }
+        }
+    }
+
     /// ### callback_rename
     ///
     /// Callback for RENAME command (supports borth local and remote)
@@ -133,8 +219,25 @@ impl FileTransferActivity {
                     dst_path = wrkdir;
                 }
                 // Check if file entry exists
-                if let Some(entry) = self.local.files.get(self.local.index) {
+                if let Some(entry) = self.local.get_selected() {
                     let full_path: PathBuf = entry.get_abs_path();
+                    // Moving a file onto another name of itself (e.g. a hard link) would do nothing;
+                    // changing the case of the name only is allowed, for case insensitive file systems
+                    if Localhost::is_same_file(full_path.as_path(), dst_path.as_path())
+                        && !full_path
+                            .to_string_lossy()
+                            .eq_ignore_ascii_case(dst_path.to_string_lossy().as_ref())
+                    {
+                        self.log_and_alert(
+                            LogLevel::Error,
+                            format!(
+                                "Could not rename file \"{}\": \"{}\" is the same file",
+                                full_path.display(),
+                                dst_path.display()
+                            ),
+                        );
+                        return;
+                    }
                     // Rename file or directory and report status as popup
                     match self
                         .context
@@ -144,6 +247,10 @@ impl FileTransferActivity {
                         .rename(entry, dst_path.as_path())
                     {
                         Ok(_) => {
+                            self.push_undo(UndoAction::LocalRename(
+                                full_path.clone(),
+                                dst_path.clone(),
+                            ));
                             // Reload files
                             let path: PathBuf = self.local.wrkdir.clone();
                             self.local_scan(path.as_path());
@@ -173,12 +280,15 @@ impl FileTransferActivity {
             }
             FileExplorerTab::Remote => {
                 // Check if file entry exists
-                if let Some(entry) = self.remote.files.get(self.remote.index) {
+                if let Some(entry) = self.remote.get_selected() {
                     let full_path: PathBuf = entry.get_abs_path();
                     // Rename file or directory and report status as popup
                     let dst_path: PathBuf = PathBuf::from(input);
                     match self.client.as_mut().rename(entry, dst_path.as_path()) {
                         Ok(_) => {
+                            // NOTE: relative destination is relative to the remote working directory
+                            let to: PathBuf = self.remote.wrkdir.join(dst_path.as_path());
+                            self.push_undo(UndoAction::RemoteRename(full_path.clone(), to));
                             // Reload files
                             let path: PathBuf = self.remote.wrkdir.clone();
                             self.remote_scan(path.as_path());
@@ -209,6 +319,101 @@ impl FileTransferActivity {
         }
     }
 
+    /// ### callback_hardlink
+    ///
+    /// Create a hard link with the provided name to the selected remote file
+    pub(super) fn callback_hardlink(&mut self, input: String) {
+        let target: PathBuf = match self.remote.get_selected() {
+            Some(FsEntry::File(file)) => file.abs_path.clone(),
+            Some(FsEntry::Directory(_)) => {
+                self.log_and_alert(
+                    LogLevel::Error,
+                    String::from("Could not create hard link: directories can't be hard linked"),
+                );
+                return;
+            }
+            None => return,
+        };
+        let link: PathBuf = PathBuf::from(input);
+        match self
+            .client
+            .as_mut()
+            .hardlink(target.as_path(), link.as_path())
+        {
+            Ok(_) => {
+                // Reload files
+                let path: PathBuf = self.remote.wrkdir.clone();
+                self.remote_scan(path.as_path());
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "Created hard link \"{}\" to \"{}\"",
+                        link.display(),
+                        target.display()
+                    )
+                    .as_ref(),
+                );
+            }
+            Err(err) => match err.kind() {
+                FileTransferErrorType::UnsupportedFeature => self.log_and_alert(
+                    LogLevel::Warn,
+                    format!("Hard links are not supported by the server: {}", err),
+                ),
+                _ => self.log_and_alert(
+                    LogLevel::Error,
+                    format!("Could not create hard link \"{}\": {}", link.display(), err),
+                ),
+            },
+        }
+    }
+
+    /// ### callback_chmod
+    ///
+    /// Callback for change mode popup: apply the octal mode in `input` to the selected remote entry
+    pub(super) fn callback_chmod(&mut self, input: String) {
+        let path: PathBuf = match self.remote.get_selected() {
+            Some(entry) => entry.get_abs_path(),
+            None => return,
+        };
+        let mode: (u8, u8, u8) = match parse_unix_pex(input.as_str()) {
+            Ok(mode) => mode,
+            Err(err) => {
+                self.log_and_alert(LogLevel::Error, err);
+                return;
+            }
+        };
+        match self.client.as_mut().chmod(path.as_path(), mode) {
+            Ok(_) => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "Changed mode of \"{}\" to {}{}{}",
+                        path.display(),
+                        mode.0,
+                        mode.1,
+                        mode.2
+                    )
+                    .as_ref(),
+                );
+                // Reload files
+                self.reload_remote_dir();
+            }
+            Err(err) => match err.kind() {
+                FileTransferErrorType::UnsupportedFeature => self.log_and_alert(
+                    LogLevel::Warn,
+                    format!(
+                        "Changing permissions is not supported by this protocol: {}",
+                        err
+                    ),
+                ),
+                _ => self.log_and_alert(
+                    LogLevel::Error,
+                    format!("Could not change mode of \"{}\": {}", path.display(), err),
+                ),
+            },
+        }
+    }
+
     /// ### callback_delete_fsentry
     ///
     /// Delete current selected fsentry in the currently selected TAB
@@ -217,19 +422,36 @@ impl FileTransferActivity {
         match self.tab {
             FileExplorerTab::Local => {
                 // Check if file entry exists
-                if let Some(entry) = self.local.files.get(self.local.index) {
+                if let Some(entry) = self.local.get_selected().cloned() {
                     let full_path: PathBuf = entry.get_abs_path();
-                    // Delete file or directory and report status as popup
-                    match self.context.as_mut().unwrap().local.remove(entry) {
+                    // Moving to trash isn't recursive; removing is
+                    if self.trash_dir.is_none() && self.remove_depth_exceeded(&entry, true) {
+                        return;
+                    }
+                    // Delete file or directory (or move it to trash) and report status as popup
+                    let result: Result<(), String> = match self.trash_dir.clone() {
+                        Some(trash_dir) => self
+                            .trash_local_entry(&entry, trash_dir.as_path())
+                            .map(|_| ()),
+                        None => self
+                            .context
+                            .as_mut()
+                            .unwrap()
+                            .local
+                            .remove(&entry)
+                            .map_err(|err| err.to_string()),
+                    };
+                    match result {
                         Ok(_) => {
                             // Reload files
                             let p: PathBuf = self.local.wrkdir.clone();
                             self.local_scan(p.as_path());
                             // Log
-                            self.log(
-                                LogLevel::Info,
-                                format!("Removed file \"{}\"", full_path.display()).as_ref(),
-                            );
+                            let msg: String = match self.trash_dir.is_some() {
+                                true => format!("Moved file \"{}\" to trash", full_path.display()),
+                                false => format!("Removed file \"{}\"", full_path.display()),
+                            };
+                            self.log(LogLevel::Info, msg.as_str());
                         }
                         Err(err) => {
                             self.log_and_alert(
@@ -246,10 +468,13 @@ impl FileTransferActivity {
             }
             FileExplorerTab::Remote => {
                 // Check if file entry exists
-                if let Some(entry) = self.remote.files.get(self.remote.index) {
+                if let Some(entry) = self.remote.get_selected().cloned() {
                     let full_path: PathBuf = entry.get_abs_path();
+                    if self.remove_depth_exceeded(&entry, false) {
+                        return;
+                    }
                     // Delete file
-                    match self.client.remove(entry) {
+                    match self.client.remove(&entry) {
                         Ok(_) => {
                             self.reload_remote_dir();
                             self.log(
@@ -283,21 +508,349 @@ impl FileTransferActivity {
                 // Get pwd
                 let wrkdir: PathBuf = self.remote.wrkdir.clone();
                 // Get file and clone (due to mutable / immutable stuff...)
-                if self.local.files.get(self.local.index).is_some() {
-                    let file: FsEntry = self.local.files.get(self.local.index).unwrap().clone();
+                if self.local.get_selected().is_some() {
+                    let file: FsEntry = self.local.get_selected().unwrap().clone();
                     // Call upload; pass realfile, keep link name
-                    self.filetransfer_send(&file.get_realfile(), wrkdir.as_path(), Some(input));
+                    self.filetransfer_request(
+                        vec![(file.get_realfile(), Some(input))],
+                        wrkdir.as_path(),
+                        true,
+                    );
                 }
             }
             FileExplorerTab::Remote => {
                 // Get file and clone (due to mutable / immutable stuff...)
-                if self.remote.files.get(self.remote.index).is_some() {
-                    let file: FsEntry = self.remote.files.get(self.remote.index).unwrap().clone();
+                if self.remote.get_selected().is_some() {
+                    let file: FsEntry = self.remote.get_selected().unwrap().clone();
                     // Call upload; pass realfile, keep link name
                     let wrkdir: PathBuf = self.local.wrkdir.clone();
-                    self.filetransfer_recv(&file.get_realfile(), wrkdir.as_path(), Some(input));
+                    self.filetransfer_request(
+                        vec![(file.get_realfile(), Some(input))],
+                        wrkdir.as_path(),
+                        false,
+                    );
+                }
+            }
+        }
+    }
+
+    /// ### callback_transfer_confirm
+    ///
+    /// Run the transfer waiting for confirmation
+    pub(super) fn callback_transfer_confirm(&mut self) {
+        if let Some(transfer) = self.pending_transfer.take() {
+            self.filetransfer_run(transfer);
+        }
+    }
+
+    /// ### callback_copy_overwrite_confirm
+    ///
+    /// Overwriting the destination entries has been confirmed: request the transfer waiting for confirmation
+    /// (its size may have to be confirmed too)
+    pub(super) fn callback_copy_overwrite_confirm(&mut self) {
+        if let Some(transfer) = self.pending_transfer.take() {
+            self.filetransfer_request(transfer.entries, transfer.dst.as_path(), transfer.upload);
+        }
+    }
+
+    /// ### callback_retry_confirm
+    ///
+    /// Retrying a failed transfer from the log has been confirmed: queue it again
+    pub(super) fn callback_retry_confirm(&mut self) {
+        if let Some(transfer) = self.pending_transfer.take() {
+            if let Some((entry, _)) = transfer.entries.first() {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "Re-queued {} of \"{}\" to \"{}\"",
+                        match transfer.upload {
+                            true => "upload",
+                            false => "download",
+                        },
+                        entry.get_abs_path().display(),
+                        transfer.dst.display()
+                    )
+                    .as_str(),
+                );
+            }
+            self.filetransfer_request(transfer.entries, transfer.dst.as_path(), transfer.upload);
+        }
+    }
+
+    /// ### callback_transfer_cancel
+    ///
+    /// Discard the transfer waiting for confirmation
+    pub(super) fn callback_transfer_cancel(&mut self) {
+        if self.pending_transfer.take().is_some() {
+            self.log(LogLevel::Info, "Transfer cancelled");
+        }
+    }
+
+    /// ### callback_sync_confirm
+    ///
+    /// Sync has been confirmed; ask whether extraneous destination entries must be deleted
+    pub(super) fn callback_sync_confirm(&mut self) {
+        if let Some(sync) = self.pending_sync.as_ref() {
+            self.input_mode = InputMode::Popup(PopupType::YesNo(
+                format!(
+                    "Delete {} entries which don't exist on {}?",
+                    match sync.upload {
+                        true => "remote",
+                        false => "local",
+                    },
+                    match sync.upload {
+                        true => "local",
+                        false => "remote",
+                    }
+                ),
+                FileTransferActivity::callback_sync_delete,
+                FileTransferActivity::callback_sync_keep,
+            ));
+        }
+    }
+
+    /// ### callback_sync_cancel
+    ///
+    /// Discard the pending sync
+    pub(super) fn callback_sync_cancel(&mut self) {
+        if self.pending_sync.take().is_some() {
+            self.log(LogLevel::Info, "Sync cancelled");
+        }
+    }
+
+    /// ### callback_sync_delete
+    ///
+    /// Run the pending sync, deleting extraneous destination entries
+    pub(super) fn callback_sync_delete(&mut self) {
+        self.sync_pending(true);
+    }
+
+    /// ### callback_sync_keep
+    ///
+    /// Run the pending sync, keeping extraneous destination entries
+    pub(super) fn callback_sync_keep(&mut self) {
+        self.sync_pending(false);
+    }
+
+    /// ### sync_pending
+    ///
+    /// Run the pending sync
+    fn sync_pending(&mut self, delete: bool) {
+        if let Some(sync) = self.pending_sync.take() {
+            if let Some((FsEntry::Directory(dir), _)) = sync.entries.first() {
+                self.filetransfer_sync(dir, sync.dst.as_path(), sync.upload, delete);
+            }
+        }
+    }
+
+    /// ### callback_save_selection_profile
+    ///
+    /// Save the marked entries of the current tab into the selection profile named as input
+    pub(super) fn callback_save_selection_profile(&mut self, input: String) {
+        let paths: Vec<PathBuf> = match self.tab {
+            FileExplorerTab::Local => &self.local,
+            FileExplorerTab::Remote => &self.remote,
+        }
+        .get_marked()
+        .iter()
+        .map(|x| x.get_abs_path())
+        .collect();
+        if paths.is_empty() {
+            self.log_and_alert(
+                LogLevel::Warn,
+                String::from("There are no marked entries to save"),
+            );
+            return;
+        }
+        let entries: usize = paths.len();
+        let result = match self
+            .context
+            .as_mut()
+            .and_then(|ctx| ctx.config_client.as_mut())
+        {
+            Some(config_client) => {
+                config_client.set_selection_profile(input.as_str(), paths);
+                config_client.write_config().map_err(|err| err.to_string())
+            }
+            None => Err(String::from("configuration is not available")),
+        };
+        match result {
+            Ok(_) => self.log(
+                LogLevel::Info,
+                format!(
+                    "Saved {} marked entries into selection profile \"{}\"",
+                    entries, input
+                )
+                .as_str(),
+            ),
+            Err(err) => self.log_and_alert(
+                LogLevel::Error,
+                format!("Could not save selection profile \"{}\": {}", input, err),
+            ),
+        }
+    }
+
+    /// ### callback_restore_selection_profile
+    ///
+    /// Mark the entries of the current tab saved in the selection profile named as input.
+    /// Paths which no longer exist are reported in the log
+    pub(super) fn callback_restore_selection_profile(&mut self, input: String) {
+        let paths: Vec<PathBuf> = match self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref())
+            .and_then(|config_client| config_client.get_selection_profile(input.as_str()))
+        {
+            Some(paths) => paths,
+            None => {
+                self.log_and_alert(
+                    LogLevel::Error,
+                    format!("No such selection profile \"{}\"", input),
+                );
+                return;
+            }
+        };
+        let missing: Vec<PathBuf> = match self.tab {
+            FileExplorerTab::Local => self.local.restore_marks(paths.as_slice()),
+            FileExplorerTab::Remote => self.remote.restore_marks(paths.as_slice()),
+        };
+        for path in missing.iter() {
+            self.log(
+                LogLevel::Warn,
+                format!("\"{}\" no longer exists; not marked", path.display()).as_str(),
+            );
+        }
+        self.log(
+            LogLevel::Info,
+            format!(
+                "Restored {} of {} entries from selection profile \"{}\"",
+                paths.len() - missing.len(),
+                paths.len(),
+                input
+            )
+            .as_str(),
+        );
+    }
+
+    /// ### callback_select_expression
+    ///
+    /// Callback for selection expression input: mark the files of the current directory matching the expression.
+    /// If the expression is invalid, the popup is displayed again with the error and the typed expression
+    pub(super) fn callback_select_expression(&mut self, input: String) {
+        let expr: SelectExpr = match SelectExpr::parse(input.as_str()) {
+            Ok(expr) => expr,
+            Err(err) => {
+                self.input_txt = input;
+                self.input_mode = InputMode::Popup(PopupType::Input(
+                    format!("Select files matching: {}", err),
+                    FileTransferActivity::callback_select_expression,
+                ));
+                return;
+            }
+        };
+        let now: SystemTime = SystemTime::now();
+        let matching: usize = match self.tab {
+            FileExplorerTab::Local => &mut self.local,
+            FileExplorerTab::Remote => &mut self.remote,
+        }
+        .mark_matching(|x| expr.matches(x, now));
+        self.log(
+            LogLevel::Info,
+            format!("Marked {} entries matching \"{}\"", matching, input).as_str(),
+        );
+    }
+
+    /// ### callback_select_glob
+    ///
+    /// Callback for glob input: mark the entries (directories too) of the current directory whose name matches the glob.
+    /// Names are case-sensitive, except in the local explorer on Windows
+    pub(super) fn callback_select_glob(&mut self, input: String) {
+        let ignore_case: bool =
+            cfg!(target_os = "windows") && matches!(self.tab, FileExplorerTab::Local);
+        let glob: String = match ignore_case {
+            true => input.to_lowercase(),
+            false => input.clone(),
+        };
+        let matching: usize = match self.tab {
+            FileExplorerTab::Local => &mut self.local,
+            FileExplorerTab::Remote => &mut self.remote,
+        }
+        .mark_matching(|x| match ignore_case {
+            true => glob_match(glob.as_str(), x.get_name().to_lowercase().as_str()),
+            false => glob_match(glob.as_str(), x.get_name().as_str()),
+        });
+        self.log(
+            LogLevel::Info,
+            format!("Marked {} entries matching \"{}\"", matching, input).as_str(),
+        );
+    }
+
+    /// ### callback_retry_username
+    ///
+    /// Callback for username input after an authentication failure; then asks for the password
+    pub(super) fn callback_retry_username(&mut self, input: String) {
+        self.params.username = match input.trim().is_empty() {
+            true => None,
+            false => Some(String::from(input.trim())),
+        };
+        self.input_mode = InputMode::Popup(PopupType::Secret(
+            String::from("Password"),
+            FileTransferActivity::callback_retry_password,
+        ));
+    }
+
+    /// ### callback_retry_password
+    ///
+    /// Callback for password input after an authentication failure, or for a new remote tab.
+    /// Once back in explorer mode, the connection is (re)tried with the new credentials
+    pub(super) fn callback_retry_password(&mut self, input: String) {
+        self.params.password = match input.is_empty() {
+            true => None,
+            false => Some(input),
+        };
+    }
+    /// ### callback_remote_tab_address
+    ///
+    /// Callback for the address of a new remote tab; the tab is opened and then the password is asked
+    pub(super) fn callback_remote_tab_address(&mut self, input: String) {
+        let config_client: Option<&ConfigClient> = self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref());
+        let default_username = |protocol: FileTransferProtocol| match (config_client, protocol) {
+            (None, _) => None,
+            (Some(config_client), FileTransferProtocol::Ftp(_)) => {
+                config_client.get_ftp_default_username()
+            }
+            (Some(config_client), _) => config_client.get_ssh_default_username(),
+        };
+        match parse_remote_opt_ex(input.trim(), default_username) {
+            Ok((address, port, protocol, username)) => {
+                let title: String = match &username {
+                    Some(username) => format!("Password for {}@{}", username, address),
+                    None => format!("Password for {}", address),
+                };
+                let opened: bool = self.remote_tab_open(FileTransferParams {
+                    address,
+                    port,
+                    protocol,
+                    username,
+                    password: None,
+                    jump_hosts: Vec::new(),
+                    identity: self.params.identity.clone(),
+                    buffer_size: self.params.buffer_size,
+                });
+                if opened {
+                    self.input_mode = InputMode::Popup(PopupType::Secret(
+                        title,
+                        FileTransferActivity::callback_retry_password,
+                    ));
                 }
             }
+            Err(err) => self.log_and_alert(
+                LogLevel::Error,
+                format!("Bad address \"{}\": {}", input, err),
+            ),
         }
     }
 }
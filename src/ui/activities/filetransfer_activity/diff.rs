@@ -0,0 +1,228 @@
+//! ## Diff
+//!
+//! `diff` is the module which compares the content of a local file with its remote counterpart
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Dependencies
+extern crate content_inspector;
+extern crate similar;
+extern crate tempfile;
+
+// Locals
+use super::{FileTransferActivity, InputMode, LogLevel, PopupType};
+use crate::fs::{FsEntry, FsFile};
+
+// Ext
+use bytesize::ByteSize;
+use similar::TextDiff;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Amount of bytes compared for each file; the rest of bigger files is ignored
+const DIFF_MAX_FILE_SIZE: usize = 1048576;
+// Maximum amount of lines displayed in the diff popup
+const DIFF_MAX_LINES: usize = 10000;
+// Amount of unchanged lines displayed around each change
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// ## DiffState
+///
+/// DiffState contains the diff displayed in the diff popup
+pub(super) struct DiffState {
+    pub title: String,      // Popup title
+    pub lines: Vec<String>, // Unified diff lines
+    pub scroll: usize,      // Index of the first line displayed
+}
+
+impl DiffState {
+    /// ### scroll_up
+    ///
+    /// Scroll diff up by `step` lines
+    pub fn scroll_up(&mut self, step: usize) {
+        self.scroll = self.scroll.saturating_sub(step);
+    }
+
+    /// ### scroll_down
+    ///
+    /// Scroll diff down by `step` lines, keeping the last line visible
+    pub fn scroll_down(&mut self, step: usize) {
+        self.scroll = (self.scroll + step).min(self.lines.len().saturating_sub(1));
+    }
+}
+
+/// ### diff_lines
+///
+/// Compute the line based unified diff between `local` and `remote` contents.
+/// At most `max_lines` lines are returned; returns whether the diff has been truncated too
+pub(super) fn diff_lines(local: &str, remote: &str, max_lines: usize) -> (Vec<String>, bool) {
+    let diff = TextDiff::from_lines(local, remote);
+    let text: String = diff
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header("local", "remote")
+        .to_string();
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let truncated: bool = lines.len() > max_lines;
+    lines.truncate(max_lines);
+    (lines, truncated)
+}
+
+/// ### read_text
+///
+/// Read at most `max` bytes of text file at `path`.
+/// Returns the text and whether the file has been truncated; binary files are refused
+fn read_text(path: &Path, max: usize) -> Result<(String, bool), String> {
+    let mut data: Vec<u8> = Vec::new();
+    File::open(path)
+        .and_then(|f| f.take(max as u64 + 1).read_to_end(&mut data))
+        .map_err(|err| format!("could not read file: {}", err))?;
+    let truncated: bool = data.len() > max;
+    data.truncate(max);
+    if content_inspector::inspect(data.as_slice()).is_binary() {
+        return Err(String::from("file is binary"));
+    }
+    Ok((
+        String::from_utf8_lossy(data.as_slice()).to_string(),
+        truncated,
+    ))
+}
+
+impl FileTransferActivity {
+    /// ### diff_selected
+    ///
+    /// Compare the selected file of the local (or remote) explorer with the file with the same name
+    /// in the working directory of the other explorer
+    pub(super) fn diff_selected(&mut self, local: bool) {
+        let (explorer, other) = match local {
+            true => (&self.local, &self.remote),
+            false => (&self.remote, &self.local),
+        };
+        let other_wrkdir: PathBuf = other.wrkdir.clone();
+        let file: FsFile = match explorer.get_selected().map(|x| x.get_realfile()) {
+            Some(FsEntry::File(file)) => file,
+            _ => return,
+        };
+        let counterpart: Option<FsFile> = other
+            .files
+            .iter()
+            .find(|x| x.get_name() == file.name)
+            .map(|x| x.get_realfile())
+            .and_then(|x| match x {
+                FsEntry::File(file) => Some(file),
+                FsEntry::Directory(_) => None,
+            });
+        let counterpart: FsFile = match counterpart {
+            Some(counterpart) => counterpart,
+            None => {
+                self.log_and_alert(
+                    LogLevel::Error,
+                    format!(
+                        "Could not diff \"{}\": no file with the same name in \"{}\"",
+                        file.name,
+                        other_wrkdir.display()
+                    ),
+                );
+                return;
+            }
+        };
+        let result: Result<(), String> = match local {
+            true => self.diff_files(&file, &counterpart),
+            false => self.diff_files(&counterpart, &file),
+        };
+        if let Err(err) = result {
+            self.log_and_alert(LogLevel::Error, format!("Could not diff: {}", err));
+        }
+    }
+
+    /// ### diff_files
+    ///
+    /// Download the remote file to a temporary file and show its diff with the local file.
+    /// If the files are identical, it's just logged
+    fn diff_files(&mut self, local: &FsFile, remote: &FsFile) -> Result<(), String> {
+        // Read local file first, so binary files are refused before downloading
+        let (local_text, local_truncated): (String, bool) =
+            read_text(local.abs_path.as_path(), DIFF_MAX_FILE_SIZE)
+                .map_err(|err| format!("\"{}\": {}", local.abs_path.display(), err))?;
+        let tmpfile: tempfile::NamedTempFile = tempfile::NamedTempFile::new()
+            .map_err(|err| format!("Could not create temporary file: {}", err))?;
+        self.filetransfer_recv_file(tmpfile.path(), remote)?;
+        self.input_mode = InputMode::Explorer;
+        if std::mem::replace(&mut self.transfer.aborted, false) {
+            return Err(String::from("download aborted"));
+        }
+        let (remote_text, remote_truncated): (String, bool) =
+            read_text(tmpfile.path(), DIFF_MAX_FILE_SIZE)
+                .map_err(|err| format!("\"{}\": {}", remote.abs_path.display(), err))?;
+        if local_truncated || remote_truncated {
+            self.log(
+                LogLevel::Warn,
+                format!(
+                    "\"{}\" is too big: only the first {} are compared",
+                    local.name,
+                    ByteSize(DIFF_MAX_FILE_SIZE as u64)
+                )
+                .as_str(),
+            );
+        }
+        let (lines, truncated): (Vec<String>, bool) =
+            diff_lines(local_text.as_str(), remote_text.as_str(), DIFF_MAX_LINES);
+        if lines.is_empty() {
+            self.log(
+                LogLevel::Info,
+                format!(
+                    "\"{}\" and \"{}\" are identical",
+                    local.abs_path.display(),
+                    remote.abs_path.display()
+                )
+                .as_str(),
+            );
+            return Ok(());
+        }
+        if truncated {
+            self.log(
+                LogLevel::Warn,
+                format!(
+                    "Diff of \"{}\" is too long: only the first {} lines are displayed",
+                    local.name, DIFF_MAX_LINES
+                )
+                .as_str(),
+            );
+        }
+        self.diff = Some(DiffState {
+            title: format!(
+                "\"{}\" (local) vs \"{}\" (remote){}",
+                local.abs_path.display(),
+                remote.abs_path.display(),
+                match local_truncated || remote_truncated || truncated {
+                    true => " [truncated]",
+                    false => "",
+                }
+            ),
+            lines,
+            scroll: 0,
+        });
+        self.input_mode = InputMode::Popup(PopupType::Diff);
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+//! ## DryRun
+//!
+//! `dry_run` is the module which simulates transfers: the directories which would be created
+//! and the files which would be transferred are logged, but nothing is written
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{FileTransferActivity, LogLevel};
+use crate::fs::{FsDirectory, FsEntry, FsFile};
+
+// Ext
+use bytesize::ByteSize;
+use std::path::{Path, PathBuf};
+
+impl FileTransferActivity {
+    /// ### toggle_dry_run
+    ///
+    /// Enable or disable the dry run mode
+    pub(super) fn toggle_dry_run(&mut self) {
+        self.dry_run = !self.dry_run;
+        let state: &str = match self.dry_run {
+            true => "enabled: transfers are only logged, nothing is written",
+            false => "disabled",
+        };
+        self.log(LogLevel::Info, format!("Dry run {}", state).as_str());
+    }
+
+    /// ### filetransfer_dry_run
+    ///
+    /// Log the directories which the transfer of `entry` into `dst` (with `dst_name`, if Some) would create
+    /// and the files it would transfer, without writing anything; entries are uploaded if `upload` is true,
+    /// downloaded otherwise. Directories are walked as by the actual transfer, up to the depth limit
+    pub(super) fn filetransfer_dry_run(
+        &mut self,
+        entry: &FsEntry,
+        dst: &Path,
+        dst_name: Option<String>,
+        upload: bool,
+    ) {
+        let (files, bytes): (usize, usize) = self.dry_run_entry(entry, dst, dst_name, upload, 0);
+        self.log(
+            LogLevel::Info,
+            format!(
+                "Dry run of \"{}\" completed: {} files ({}) would be {}",
+                entry.get_abs_path().display(),
+                files,
+                ByteSize(bytes as u64),
+                match upload {
+                    true => "uploaded",
+                    false => "downloaded",
+                }
+            )
+            .as_str(),
+        );
+    }
+
+    /// ### dry_run_entry
+    ///
+    /// Log the operations the transfer of `entry`, `depth` levels below the outermost entry, would perform.
+    /// Returns the amount of files and bytes which would be transferred
+    pub(super) fn dry_run_entry(
+        &mut self,
+        entry: &FsEntry,
+        dst: &Path,
+        dst_name: Option<String>,
+        upload: bool,
+        depth: usize,
+    ) -> (usize, usize) {
+        let dst_path: PathBuf = dst.join(dst_name.unwrap_or_else(|| entry.get_name()));
+        match entry {
+            FsEntry::File(FsFile {
+                symlink: Some(_),
+                link_target: Some(target),
+                ..
+            })
+            | FsEntry::Directory(FsDirectory {
+                symlink: Some(_),
+                link_target: Some(target),
+                ..
+            }) if !upload && depth > 0 && !self.follow_symlinks => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "[dry run] Would create symlink \"{}\" pointing to \"{}\"",
+                        dst_path.display(),
+                        target.display()
+                    )
+                    .as_str(),
+                );
+                (0, 0)
+            }
+            FsEntry::File(file)
+                if upload
+                    && matches!(self.context.as_ref(), Some(ctx) if ctx.local.is_special_file(file.abs_path.as_path())) =>
+            {
+                self.log(
+                    LogLevel::Warn,
+                    format!(
+                        "[dry run] Would skip \"{}\": not a regular file (FIFO, socket or device)",
+                        file.abs_path.display()
+                    )
+                    .as_str(),
+                );
+                (0, 0)
+            }
+            FsEntry::File(file) => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "[dry run] Would {} \"{}\" to \"{}\" ({})",
+                        match upload {
+                            true => "upload",
+                            false => "download",
+                        },
+                        file.abs_path.display(),
+                        dst_path.display(),
+                        ByteSize(file.size as u64)
+                    )
+                    .as_str(),
+                );
+                (1, file.size)
+            }
+            FsEntry::Directory(dir) => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "[dry run] Would create directory \"{}\"",
+                        dst_path.display()
+                    )
+                    .as_str(),
+                );
+                if self.max_depth > 0 && depth >= self.max_depth {
+                    self.log(
+                        LogLevel::Warn,
+                        format!(
+                            "[dry run] Depth limit ({}) reached: content of \"{}\" would be skipped",
+                            self.max_depth,
+                            dir.abs_path.display()
+                        )
+                        .as_str(),
+                    );
+                    return (0, 0);
+                }
+                let entries: Result<Vec<FsEntry>, String> = match upload {
+                    true => match self.context.as_ref() {
+                        Some(ctx) => ctx
+                            .local
+                            .scan_dir(dir.abs_path.as_path())
+                            .map_err(|err| err.to_string()),
+                        None => Ok(Vec::new()),
+                    },
+                    false => {
+                        let entries: Result<Vec<FsEntry>, String> = self
+                            .client
+                            .list_dir(dir.abs_path.as_path())
+                            .map_err(|err| err.to_string());
+                        self.log_client_warnings();
+                        entries
+                    }
+                };
+                match entries {
+                    Ok(entries) => entries.iter().fold((0, 0), |(files, bytes), entry| {
+                        let (f, b): (usize, usize) =
+                            self.dry_run_entry(entry, dst_path.as_path(), None, upload, depth + 1);
+                        (files + f, bytes + b)
+                    }),
+                    Err(err) => {
+                        self.log(
+                            LogLevel::Error,
+                            format!(
+                                "[dry run] Could not scan directory \"{}\": {}",
+                                dir.abs_path.display(),
+                                err
+                            )
+                            .as_str(),
+                        );
+                        (0, 0)
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ui::activities::filetransfer_activity::tests::{make_fs_entry, test_activity};
+    use crate::ui::activities::filetransfer_activity::{InputEvent, InputMode};
+
+    #[test]
+    fn test_ui_activities_filetransfer_dry_run() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        assert!(!activity.dry_run);
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+        )));
+        assert!(activity.dry_run);
+        // File is only logged, without popup
+        let mut file: FsEntry = make_fs_entry("omar.txt", false);
+        if let FsEntry::File(file) = &mut file {
+            file.abs_path = PathBuf::from("/home/omar/omar.txt");
+            file.size = 2048;
+        }
+        activity.filetransfer_send(&file, Path::new("/srv"), Some(String::from("a.txt")));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        assert_eq!(
+            activity.log_records[1].msg.as_str(),
+            "[dry run] Would upload \"/home/omar/omar.txt\" to \"/srv/a.txt\" (2.0 KB)"
+        );
+        assert_eq!(
+            activity.log_records[0].msg.as_str(),
+            "Dry run of \"/home/omar/omar.txt\" completed: 1 files (2.0 KB) would be uploaded"
+        );
+        // Directory creation is logged; depth limit is applied
+        let mut dir: FsEntry = make_fs_entry("docs", true);
+        if let FsEntry::Directory(dir) = &mut dir {
+            dir.abs_path = PathBuf::from("/srv/docs");
+        }
+        activity.max_depth = 1;
+        assert_eq!(
+            activity.dry_run_entry(&dir, Path::new("/home/omar"), None, false, 1),
+            (0, 0)
+        );
+        assert_eq!(
+            activity.log_records[1].msg.as_str(),
+            "[dry run] Would create directory \"/home/omar/docs\""
+        );
+        // Sync is refused
+        if let FsEntry::Directory(dir) = dir {
+            activity.filetransfer_sync_request(dir, Path::new("/home/omar"), false);
+        }
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        assert!(activity.pending_sync.is_none());
+        // Disable
+        activity.toggle_dry_run();
+        assert!(!activity.dry_run);
+    }
+}
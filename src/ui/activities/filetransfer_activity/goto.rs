@@ -0,0 +1,300 @@
+//! ## Goto
+//!
+//! `goto` is the module which completes the path typed in the goto popup
+//! with the directories of the focused host
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{ExplorerFilter, FileExplorerTab, FileTransferActivity, LogLevel};
+use crate::fs::FsEntry;
+
+// Ext
+use std::path::PathBuf;
+
+/// ## GotoCompletion
+///
+/// GotoCompletion contains the candidates of the last completion, which repeated <TAB> cycle through
+pub(super) struct GotoCompletion {
+    pub dir: String,             // Input before the completed component
+    pub candidates: Vec<String>, // Directories matching the completed component, sorted
+    pub index: Option<usize>,    // Candidate currently in the input; None until cycling starts
+    pub input: String, // Input after the last completion; if it has changed, candidates are listed again
+}
+
+impl FileTransferActivity {
+    /// ### goto_complete
+    ///
+    /// Complete the last component of the path typed in the goto popup with the directories
+    /// of its parent, listed on the focused host. If several directories match, the input is
+    /// completed up to their common prefix and the candidates are logged; pressing <TAB> again
+    /// cycles through them. If the parent can't be listed (e.g. permission denied), nothing is completed
+    pub(super) fn goto_complete(&mut self) {
+        let separator: char = self.goto_separator();
+        // Repeated <TAB>: next candidate
+        if let Some(completion) = self.goto_completion.as_mut() {
+            if completion.input == self.input_txt {
+                let index: usize = match completion.index {
+                    Some(index) => (index + 1) % completion.candidates.len(),
+                    None => 0,
+                };
+                completion.index = Some(index);
+                completion.input = format!(
+                    "{}{}{}",
+                    completion.dir, completion.candidates[index], separator
+                );
+                self.input_txt = completion.input.clone();
+                return;
+            }
+        }
+        self.goto_completion = None;
+        let (dir, prefix): (String, String) =
+            match self.input_txt.rfind(|c: char| std::path::is_separator(c)) {
+                Some(pos) => (
+                    self.input_txt[..=pos].to_string(),
+                    self.input_txt[pos + 1..].to_string(),
+                ),
+                None => (String::new(), self.input_txt.clone()),
+            };
+        let entries: Vec<FsEntry> = match self.tab {
+            FileExplorerTab::Local => {
+                let path: PathBuf = self.local.wrkdir.join(dir.as_str());
+                match self.context.as_ref() {
+                    Some(ctx) => ctx.local.scan_dir(path.as_path()).unwrap_or_default(),
+                    None => Vec::new(),
+                }
+            }
+            FileExplorerTab::Remote => {
+                let path: PathBuf = self.remote.wrkdir.join(dir.as_str());
+                let entries: Vec<FsEntry> =
+                    self.client.list_dir(path.as_path()).unwrap_or_default();
+                self.log_client_warnings();
+                entries
+            }
+        };
+        let candidates: Vec<String> =
+            FileTransferActivity::goto_candidates(entries.as_slice(), prefix.as_str());
+        match candidates.len() {
+            0 => {}
+            1 => self.input_txt = format!("{}{}{}", dir, candidates[0], separator),
+            _ => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "{} directories match: {}",
+                        candidates.len(),
+                        candidates.join("  ")
+                    )
+                    .as_str(),
+                );
+                // Complete up to the common prefix; if there's nothing to add, start cycling straight away
+                let common: String =
+                    FileTransferActivity::complete_name(candidates.as_slice()).unwrap_or_default();
+                let index: Option<usize> = match common.len() > prefix.len() {
+                    true => None,
+                    false => Some(0),
+                };
+                self.input_txt = match index {
+                    Some(index) => format!("{}{}{}", dir, candidates[index], separator),
+                    None => format!("{}{}", dir, common),
+                };
+                self.goto_completion = Some(GotoCompletion {
+                    dir,
+                    candidates,
+                    index,
+                    input: self.input_txt.clone(),
+                });
+            }
+        }
+    }
+
+    /// ### goto_candidates
+    ///
+    /// Returns the names of the directories (or links to directories) among `entries` which start with `prefix`, sorted.
+    /// Hidden directories are candidates too, as they're listed in the explorers
+    pub(super) fn goto_candidates(entries: &[FsEntry], prefix: &str) -> Vec<String> {
+        let mut candidates: Vec<String> = entries
+            .iter()
+            .filter(|x| ExplorerFilter::is_dir(x))
+            .map(|x| x.get_name())
+            .filter(|x| x.starts_with(prefix))
+            .collect();
+        candidates.sort();
+        candidates
+    }
+
+    /// ### complete_name
+    ///
+    /// Returns the longest common prefix of the candidate `names`; None if there are no candidates
+    pub(super) fn complete_name(names: &[String]) -> Option<String> {
+        let first: &String = names.first()?;
+        let mut common: &str = first.as_str();
+        for name in names.iter().skip(1) {
+            let len: usize = common
+                .char_indices()
+                .zip(name.chars())
+                .find(|((_, a), b)| a != b)
+                .map(|((i, _), _)| i)
+                .unwrap_or_else(|| common.len().min(name.len()));
+            common = &common[..len];
+        }
+        Some(common.to_string())
+    }
+
+    /// ### goto_separator
+    ///
+    /// Returns the separator appended to the completed directories
+    fn goto_separator(&self) -> char {
+        match self.tab {
+            FileExplorerTab::Local => std::path::MAIN_SEPARATOR,
+            FileExplorerTab::Remote => '/',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::host::Localhost;
+    use crate::ui::activities::filetransfer_activity::tests::test_activity;
+    use crate::ui::activities::filetransfer_activity::{InputEvent, InputMode, PopupType};
+
+    #[test]
+    fn test_ui_activities_filetransfer_goto() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        // Input is pre-filled with the working directory
+        activity.tab = FileExplorerTab::Remote;
+        activity.remote.wrkdir = PathBuf::from("/home/omar");
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('g')));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Goto)
+        ));
+        assert_eq!(activity.input_txt.as_str(), "/home/omar");
+        activity.handle_input_event(&key(KeyCode::Char('/')));
+        activity.handle_input_event(&key(KeyCode::Char('D')));
+        assert_eq!(activity.input_txt.as_str(), "/home/omar/D");
+        // Directory can't be listed (not connected): nothing to complete
+        activity.handle_input_event(&key(KeyCode::Tab));
+        assert_eq!(activity.input_txt.as_str(), "/home/omar/D");
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Goto)
+        ));
+        // Cancel
+        activity.handle_input_event(&key(KeyCode::Esc));
+        assert!(activity.input_txt.is_empty());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_complete_name() {
+        let names = |x: &[&str]| -> Vec<String> { x.iter().map(|x| x.to_string()).collect() };
+        assert!(FileTransferActivity::complete_name(&[]).is_none());
+        assert_eq!(
+            FileTransferActivity::complete_name(names(&["Documents"]).as_slice()).unwrap(),
+            "Documents"
+        );
+        assert_eq!(
+            FileTransferActivity::complete_name(
+                names(&["Documents", "Downloads", "Dox"]).as_slice()
+            )
+            .unwrap(),
+            "Do"
+        );
+        assert_eq!(
+            FileTransferActivity::complete_name(names(&["src", "src-old"]).as_slice()).unwrap(),
+            "src"
+        );
+        assert_eq!(
+            FileTransferActivity::complete_name(names(&["caff\u{e8}", "caff\u{e9}"]).as_slice())
+                .unwrap(),
+            "caff"
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_goto_candidates() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        for dir in ["docs", "downloads", ".dotfiles", "src"].iter() {
+            std::fs::create_dir(tmpdir.path().join(dir)).unwrap();
+        }
+        std::fs::write(tmpdir.path().join("dog.txt"), b"woof").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(tmpdir.path().join("src"), tmpdir.path().join("do-link"))
+            .unwrap();
+        let entries: Vec<FsEntry> = Localhost::new(PathBuf::from(tmpdir.path()))
+            .ok()
+            .unwrap()
+            .scan_dir(tmpdir.path())
+            .ok()
+            .unwrap();
+        // Files are never candidates; links to directories are
+        let mut expected: Vec<&str> = vec!["docs", "downloads"];
+        #[cfg(unix)]
+        expected.insert(0, "do-link");
+        assert_eq!(
+            FileTransferActivity::goto_candidates(entries.as_slice(), "do"),
+            expected
+        );
+        assert_eq!(
+            FileTransferActivity::goto_candidates(entries.as_slice(), "s"),
+            vec!["src"]
+        );
+        // Hidden directories are candidates too
+        assert_eq!(
+            FileTransferActivity::goto_candidates(entries.as_slice(), "."),
+            vec![".dotfiles"]
+        );
+        assert!(FileTransferActivity::goto_candidates(entries.as_slice(), "x").is_empty());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_goto_cycle() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.tab = FileExplorerTab::Remote;
+        activity.input_mode = InputMode::Popup(PopupType::Goto);
+        // Completed up to the common prefix of the candidates
+        activity.input_txt = String::from("/home/do");
+        activity.goto_completion = Some(GotoCompletion {
+            dir: String::from("/home/"),
+            candidates: vec![String::from("docs"), String::from("downloads")],
+            index: None,
+            input: String::from("/home/do"),
+        });
+        // Repeated <TAB> cycles through the candidates
+        activity.handle_input_event(&key(KeyCode::Tab));
+        assert_eq!(activity.input_txt.as_str(), "/home/docs/");
+        activity.handle_input_event(&key(KeyCode::Tab));
+        assert_eq!(activity.input_txt.as_str(), "/home/downloads/");
+        activity.handle_input_event(&key(KeyCode::Tab));
+        assert_eq!(activity.input_txt.as_str(), "/home/docs/");
+        // Typing ends the cycle
+        activity.handle_input_event(&key(KeyCode::Char('x')));
+        assert!(activity.goto_completion.is_none());
+        assert_eq!(activity.input_txt.as_str(), "/home/docs/x");
+    }
+}
@@ -22,11 +22,13 @@
 extern crate tempfile;
 
 use super::{
-    DialogCallback, DialogYesNoOption, FileExplorerTab, FileTransferActivity, FsEntry, InputEvent,
-    InputField, InputMode, LogLevel, OnInputSubmitCallback, PopupType,
+    DialogCallback, DialogYesNoOption, DisconnectRequest, FileExplorerTab, FileTransferActivity,
+    FsEntry, FsFile, InputEvent, InputField, InputMode, KeyName, LogLevel, OnInputSubmitCallback,
+    PopupType,
 };
+use crate::fs::preview::is_image;
 
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::path::PathBuf;
 
 impl FileTransferActivity {
@@ -76,6 +78,100 @@ impl FileTransferActivity {
     ///
     /// Input event handler for explorer mode
     pub(super) fn handle_input_event_mode_explorer(&mut self, ev: &InputEvent) {
+        // <ALT+LEFT>/<ALT+RIGHT> focus local/remote explorer, whatever is focused
+        if let InputEvent::Key(key) = ev {
+            if key.modifiers.intersects(KeyModifiers::ALT) {
+                let tab: Option<FileExplorerTab> = match key.code {
+                    KeyCode::Left => Some(FileExplorerTab::Local),
+                    KeyCode::Right => Some(FileExplorerTab::Remote),
+                    _ => None,
+                };
+                if let Some(tab) = tab {
+                    self.input_field = InputField::Explorer;
+                    self.tab = tab;
+                    return;
+                }
+            }
+            // <CTRL+N> new connection, whatever is focused
+            if matches!(key.code, KeyCode::Char('n') | KeyCode::Char('N'))
+                && key.modifiers.intersects(KeyModifiers::CONTROL)
+            {
+                self.new_connection();
+                return;
+            }
+            // <CTRL+Z> undo last action, whatever is focused
+            if matches!(key.code, KeyCode::Char('z') | KeyCode::Char('Z'))
+                && key.modifiers.intersects(KeyModifiers::CONTROL)
+            {
+                self.undo();
+                return;
+            }
+            // <CTRL+T> open a new remote tab, <CTRL+W> close the active one, <CTRL+S>/<CTRL+R> change sorting
+            if key.modifiers.intersects(KeyModifiers::CONTROL) {
+                match key.code {
+                    KeyCode::Char('t') | KeyCode::Char('T') => {
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("New remote tab (address)"),
+                            FileTransferActivity::callback_remote_tab_address,
+                        ));
+                        return;
+                    }
+                    KeyCode::Char('w') | KeyCode::Char('W') => {
+                        self.remote_tab_close();
+                        return;
+                    }
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        self.toggle_sync_browsing();
+                        return;
+                    }
+                    KeyCode::Char('b') | KeyCode::Char('B') => {
+                        self.process_queue();
+                        return;
+                    }
+                    KeyCode::Char('d') | KeyCode::Char('D') => {
+                        self.toggle_dry_run();
+                        return;
+                    }
+                    KeyCode::Char('s') | KeyCode::Char('S') => {
+                        // Sort the explorer of the current tab by the next sort mode
+                        match self.tab {
+                            FileExplorerTab::Local => self.local.cycle_sort(),
+                            FileExplorerTab::Remote => self.remote.cycle_sort(),
+                        }
+                        return;
+                    }
+                    KeyCode::Char('r') | KeyCode::Char('R') => {
+                        // Reverse the sort order of the explorer of the current tab
+                        match self.tab {
+                            FileExplorerTab::Local => self.local.toggle_sort_order(),
+                            FileExplorerTab::Remote => self.remote.toggle_sort_order(),
+                        }
+                        return;
+                    }
+                    KeyCode::Char('f') | KeyCode::Char('F') => {
+                        // Mark entries matching a glob
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Mark entries matching glob (e.g. *.log)"),
+                            FileTransferActivity::callback_select_glob,
+                        ));
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+            // <[> and <]> switch remote tab, whatever is focused
+            match key.code {
+                KeyCode::Char('[') => {
+                    self.remote_tab_switch(false);
+                    return;
+                }
+                KeyCode::Char(']') => {
+                    self.remote_tab_switch(true);
+                    return;
+                }
+                _ => {}
+            }
+        }
         // Match input field
         match self.input_field {
             InputField::Explorer => match self.tab {
@@ -93,6 +189,10 @@ impl FileTransferActivity {
     pub(super) fn handle_input_event_mode_explorer_tab_local(&mut self, ev: &InputEvent) {
         // Match events
         if let InputEvent::Key(key) = ev {
+            if self.readonly && FileTransferActivity::is_write_key(key.code, true) {
+                self.log_readonly();
+                return;
+            }
             match key.code {
                 KeyCode::Esc => {
                     // Handle quit event
@@ -101,42 +201,14 @@ impl FileTransferActivity {
                 }
                 KeyCode::Tab => self.switch_input_field(), // <TAB> switch tab
                 KeyCode::Right => self.tab = FileExplorerTab::Remote, // <RIGHT> switch to right tab
-                KeyCode::Up => {
-                    // Move index up; or move to the last element if 0
-                    self.local.index = match self.local.index {
-                        0 => self.local.files.len() - 1,
-                        _ => self.local.index - 1,
-                    };
-                }
-                KeyCode::Down => {
-                    // Move index down
-                    if self.local.index + 1 < self.local.files.len() {
-                        self.local.index += 1;
-                    } else {
-                        self.local.index = 0; // Move at the beginning of the list
-                    }
-                }
-                KeyCode::PageUp => {
-                    // Move index up (fast)
-                    if self.local.index > 8 {
-                        self.local.index -= 8; // Decrease by `8` if possible
-                    } else {
-                        self.local.index = 0; // Set to 0 otherwise
-                    }
-                }
-                KeyCode::PageDown => {
-                    // Move index down (fast)
-                    if self.local.index + 8 >= self.local.files.len() {
-                        // If overflows, set to size
-                        self.local.index = self.local.files.len() - 1;
-                    } else {
-                        self.local.index += 8; // Increase by `8`
-                    }
-                }
+                KeyCode::Up => self.local.move_index_up(1), // Move index up
+                KeyCode::Down => self.local.move_index_down(1), // Move index down
+                KeyCode::PageUp => self.local.move_index_up(8), // Move index up (fast)
+                KeyCode::PageDown => self.local.move_index_down(8), // Move index down (fast)
                 KeyCode::Enter => {
                     // Match selected file
-                    let local_files: Vec<FsEntry> = self.local.files.clone();
-                    if let Some(entry) = local_files.get(self.local.index) {
+                    let entry: Option<FsEntry> = self.local.get_selected().cloned();
+                    if let Some(entry) = &entry {
                         // If directory, enter directory, otherwise check if symlink
                         match entry {
                             FsEntry::Directory(dir) => {
@@ -144,16 +216,24 @@ impl FileTransferActivity {
                             }
                             FsEntry::File(file) => {
                                 // Check if symlink
-                                if let Some(symlink_entry) = &file.symlink {
+                                match &file.symlink {
                                     // If symlink entry is a directory, go to directory
-                                    if let FsEntry::Directory(dir) = &**symlink_entry {
-                                        self.local_changedir(dir.abs_path.as_path(), true)
+                                    Some(symlink_entry) if symlink_entry.is_dir() => self
+                                        .local_changedir(
+                                            symlink_entry.get_abs_path().as_path(),
+                                            true,
+                                        ),
+                                    // Preview images; browse archive files
+                                    _ if is_image(file.name.as_str()) => {
+                                        self.preview_open(file, true)
                                     }
+                                    _ => self.archive_open(file, true),
                                 }
                             }
                         }
                     }
                 }
+                KeyCode::F(5) => self.filetransfer_copy(true, true), // Copy to remote directory
                 KeyCode::Backspace => {
                     // Go to previous directory
                     if let Some(d) = self.local.popd() {
@@ -162,7 +242,7 @@ impl FileTransferActivity {
                 }
                 KeyCode::Delete => {
                     // Get file at index
-                    if let Some(entry) = self.local.files.get(self.local.index) {
+                    if let Some(entry) = self.local.get_selected() {
                         // Get file name
                         let file_name: String = match entry {
                             FsEntry::Directory(dir) => dir.name.clone(),
@@ -177,9 +257,16 @@ impl FileTransferActivity {
                     }
                 }
                 KeyCode::Char(ch) => match ch {
+                    'e' | 'E' if key.modifiers.intersects(KeyModifiers::CONTROL) => {
+                        // Create empty file
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Insert file name"),
+                            FileTransferActivity::callback_touch,
+                        ));
+                    }
                     'e' | 'E' => {
                         // Get file at index
-                        if let Some(entry) = self.local.files.get(self.local.index) {
+                        if let Some(entry) = self.local.get_selected() {
                             // Get file name
                             let file_name: String = match entry {
                                 FsEntry::Directory(dir) => dir.name.clone(),
@@ -193,13 +280,18 @@ impl FileTransferActivity {
                             ))
                         }
                     }
+                    'f' | 'F' => self.local.toggle_filter(), // Toggle view filter
+                    'b' | 'B' => self.queue_push(true), // Enqueue marked/selected entries for upload
+                    '/' => {
+                        // Filter entries by name; input is pre-filled with the active filter
+                        self.input_txt = self.local.name_filter.clone().unwrap_or_default();
+                        self.input_mode = InputMode::Popup(PopupType::Filter);
+                    }
                     'g' | 'G' => {
-                        // Goto
-                        // Show input popup
-                        self.input_mode = InputMode::Popup(PopupType::Input(
-                            String::from("Change working directory"),
-                            FileTransferActivity::callback_change_directory,
-                        ));
+                        // Goto; input is pre-filled with the working directory
+                        self.input_txt = self.local.wrkdir.to_string_lossy().to_string();
+                        self.goto_completion = None;
+                        self.input_mode = InputMode::Popup(PopupType::Goto);
                     }
                     'd' | 'D' => {
                         // Make directory
@@ -208,12 +300,34 @@ impl FileTransferActivity {
                             FileTransferActivity::callback_mkdir,
                         ));
                     }
+                    'n' | 'N' => {
+                        // Make directory and enter it
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Insert directory name (and enter it)"),
+                            FileTransferActivity::callback_mkdir_enter,
+                        ));
+                    }
                     'h' | 'H' => {
                         // Show help
                         self.input_mode = InputMode::Popup(PopupType::Help);
                     }
+                    'j' | 'J' => self.recent_open(), // Show recent transfers
                     'i' | 'I' => {
-                        // Show file info
+                        // Show file info; extended attributes and directory size are read once, when the popup is opened
+                        self.fileinfo_xattrs =
+                            match (self.context.as_ref(), self.local.get_selected()) {
+                                (Some(ctx), Some(entry)) => {
+                                    ctx.local.get_xattrs(entry.get_abs_path().as_path())
+                                }
+                                _ => None,
+                            };
+                        self.fileinfo_size =
+                            match (self.context.as_ref(), self.local.get_selected()) {
+                                (Some(ctx), Some(FsEntry::Directory(dir))) => {
+                                    ctx.local.du(dir.abs_path.as_path()).ok()
+                                }
+                                _ => None,
+                            };
                         self.input_mode = InputMode::Popup(PopupType::FileInfo);
                     }
                     'l' | 'L' => {
@@ -221,12 +335,30 @@ impl FileTransferActivity {
                         let pwd: PathBuf = self.local.wrkdir.clone();
                         self.local_scan(pwd.as_path());
                     }
+                    'a' | 'A' => {
+                        // Restore selection profile
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Restore selection profile"),
+                            FileTransferActivity::callback_restore_selection_profile,
+                        ));
+                    }
+                    'm' | 'M' => {
+                        // Mark selected entry and move to the next one
+                        self.local.toggle_mark();
+                        self.local.move_index_down(1);
+                    }
+                    '+' => {
+                        // Mark files matching a selection expression
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Select files matching"),
+                            FileTransferActivity::callback_select_expression,
+                        ));
+                    }
                     'o' | 'O' => {
                         // Edit local file
-                        if self.local.files.get(self.local.index).is_some() {
+                        if self.local.get_selected().is_some() {
                             // Clone entry due to mutable stuff...
-                            let fsentry: FsEntry =
-                                self.local.files.get(self.local.index).unwrap().clone();
+                            let fsentry: FsEntry = self.local.get_selected().unwrap().clone();
                             // Check if file
                             if fsentry.is_file() {
                                 self.log(
@@ -249,6 +381,13 @@ impl FileTransferActivity {
                             }
                         }
                     }
+                    'p' | 'P' => {
+                        // Save marked entries into a selection profile
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Save selection profile as..."),
+                            FileTransferActivity::callback_save_selection_profile,
+                        ));
+                    }
                     'q' | 'Q' => {
                         // Create quit prompt dialog
                         self.input_mode = self.create_quit_popup();
@@ -276,22 +415,18 @@ impl FileTransferActivity {
                             self.local_changedir(parent, true);
                         }
                     }
-                    ' ' => {
-                        // Get pwd
-                        let wrkdir: PathBuf = self.remote.wrkdir.clone();
-                        // Get file and clone (due to mutable / immutable stuff...)
-                        if self.local.files.get(self.local.index).is_some() {
-                            let file: FsEntry =
-                                self.local.files.get(self.local.index).unwrap().clone();
-                            let name: String = file.get_name();
-                            // Call upload; pass realfile, keep link name
-                            self.filetransfer_send(
-                                &file.get_realfile(),
-                                wrkdir.as_path(),
-                                Some(name),
-                            );
+                    'v' | 'V' => self.single_pane = !self.single_pane, // Show one/both explorers
+                    'w' | 'W' => self.toggle_log_panel(),              // Show/hide log panel
+                    'z' | 'Z' => self.toggle_relative_time(),          // Relative/absolute times
+                    'x' | 'X' => self.diff_selected(true),             // Diff with remote file
+                    'y' | 'Y' => {
+                        // Sync selected directory to remote
+                        if let Some(FsEntry::Directory(dir)) = self.local.get_selected().cloned() {
+                            let wrkdir: PathBuf = self.remote.wrkdir.clone();
+                            self.filetransfer_sync_request(dir, wrkdir.as_path(), true);
                         }
                     }
+                    ' ' => self.filetransfer_copy(true, false), // Upload marked/selected entries
                     _ => { /* Nothing to do */ }
                 },
                 _ => { /* Nothing to do */ }
@@ -305,6 +440,10 @@ impl FileTransferActivity {
     pub(super) fn handle_input_event_mode_explorer_tab_remote(&mut self, ev: &InputEvent) {
         // Match events
         if let InputEvent::Key(key) = ev {
+            if self.readonly && FileTransferActivity::is_write_key(key.code, false) {
+                self.log_readonly();
+                return;
+            }
             match key.code {
                 KeyCode::Esc => {
                     // Handle quit event
@@ -313,42 +452,14 @@ impl FileTransferActivity {
                 }
                 KeyCode::Tab => self.switch_input_field(), // <TAB> switch tab
                 KeyCode::Left => self.tab = FileExplorerTab::Local, // <LEFT> switch to local tab
-                KeyCode::Up => {
-                    // Move index up; or move to the last element if 0
-                    self.remote.index = match self.remote.index {
-                        0 => self.remote.files.len() - 1,
-                        _ => self.remote.index - 1,
-                    };
-                }
-                KeyCode::Down => {
-                    // Move index down
-                    if self.remote.index + 1 < self.remote.files.len() {
-                        self.remote.index += 1;
-                    } else {
-                        self.remote.index = 0; // Move at the beginning of the list
-                    }
-                }
-                KeyCode::PageUp => {
-                    // Move index up (fast)
-                    if self.remote.index > 8 {
-                        self.remote.index -= 8; // Decrease by `8` if possible
-                    } else {
-                        self.remote.index = 0; // Set to 0 otherwise
-                    }
-                }
-                KeyCode::PageDown => {
-                    // Move index down (fast)
-                    if self.remote.index + 8 >= self.remote.files.len() {
-                        // If overflows, set to size
-                        self.remote.index = self.remote.files.len() - 1;
-                    } else {
-                        self.remote.index += 8; // Increase by `8`
-                    }
-                }
+                KeyCode::Up => self.remote.move_index_up(1), // Move index up
+                KeyCode::Down => self.remote.move_index_down(1), // Move index down
+                KeyCode::PageUp => self.remote.move_index_up(8), // Move index up (fast)
+                KeyCode::PageDown => self.remote.move_index_down(8), // Move index down (fast)
                 KeyCode::Enter => {
                     // Match selected file
-                    let files: Vec<FsEntry> = self.remote.files.clone();
-                    if let Some(entry) = files.get(self.remote.index) {
+                    let entry: Option<FsEntry> = self.remote.get_selected().cloned();
+                    if let Some(entry) = &entry {
                         // If directory, enter directory; if file, check if is symlink
                         match entry {
                             FsEntry::Directory(dir) => {
@@ -356,11 +467,18 @@ impl FileTransferActivity {
                             }
                             FsEntry::File(file) => {
                                 // Check if symlink
-                                if let Some(symlink_entry) = &file.symlink {
+                                match &file.symlink {
                                     // If symlink entry is a directory, go to directory
-                                    if let FsEntry::Directory(dir) = &**symlink_entry {
-                                        self.remote_changedir(dir.abs_path.as_path(), true)
+                                    Some(symlink_entry) if symlink_entry.is_dir() => self
+                                        .remote_changedir(
+                                            symlink_entry.get_abs_path().as_path(),
+                                            true,
+                                        ),
+                                    // Preview images; browse archive files
+                                    _ if is_image(file.name.as_str()) => {
+                                        self.preview_open(file, false)
                                     }
+                                    _ => self.archive_open(file, false),
                                 }
                             }
                         }
@@ -372,9 +490,10 @@ impl FileTransferActivity {
                         self.remote_changedir(d.as_path(), false);
                     }
                 }
+                KeyCode::F(5) => self.filetransfer_copy(false, true), // Copy to local directory
                 KeyCode::Delete => {
                     // Get file at index
-                    if let Some(entry) = self.remote.files.get(self.remote.index) {
+                    if let Some(entry) = self.remote.get_selected() {
                         // Get file name
                         let file_name: String = match entry {
                             FsEntry::Directory(dir) => dir.name.clone(),
@@ -389,9 +508,16 @@ impl FileTransferActivity {
                     }
                 }
                 KeyCode::Char(ch) => match ch {
+                    'e' | 'E' if key.modifiers.intersects(KeyModifiers::CONTROL) => {
+                        // Create empty file
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Insert file name"),
+                            FileTransferActivity::callback_touch,
+                        ));
+                    }
                     'e' | 'E' => {
                         // Get file at index
-                        if let Some(entry) = self.remote.files.get(self.remote.index) {
+                        if let Some(entry) = self.remote.get_selected() {
                             // Get file name
                             let file_name: String = match entry {
                                 FsEntry::Directory(dir) => dir.name.clone(),
@@ -412,32 +538,85 @@ impl FileTransferActivity {
                             FileTransferActivity::callback_mkdir,
                         ));
                     }
-                    'g' | 'G' => {
-                        // Goto
-                        // Show input popup
+                    'n' | 'N' => {
+                        // Make directory and enter it
                         self.input_mode = InputMode::Popup(PopupType::Input(
-                            String::from("Change working directory"),
-                            FileTransferActivity::callback_change_directory,
+                            String::from("Insert directory name (and enter it)"),
+                            FileTransferActivity::callback_mkdir_enter,
                         ));
                     }
+                    'f' | 'F' => self.remote.toggle_filter(), // Toggle view filter
+                    'b' | 'B' => self.queue_push(false), // Enqueue marked/selected entries for download
+                    '/' => {
+                        // Filter entries by name; input is pre-filled with the active filter
+                        self.input_txt = self.remote.name_filter.clone().unwrap_or_default();
+                        self.input_mode = InputMode::Popup(PopupType::Filter);
+                    }
+                    'g' | 'G' => {
+                        // Goto; input is pre-filled with the working directory
+                        self.input_txt = self.remote.wrkdir.to_string_lossy().to_string();
+                        self.goto_completion = None;
+                        self.input_mode = InputMode::Popup(PopupType::Goto);
+                    }
                     'h' | 'H' => {
                         // Show help
                         self.input_mode = InputMode::Popup(PopupType::Help);
                     }
+                    'j' | 'J' => self.recent_open(), // Show recent transfers
                     'i' | 'I' => {
-                        // Show file info
+                        // Show file info (extended attributes aren't provided by the file transfer clients)
+                        self.fileinfo_xattrs = None;
+                        self.fileinfo_size = None;
                         self.input_mode = InputMode::Popup(PopupType::FileInfo);
                     }
+                    'c' | 'C' => {
+                        // Change mode of selected entry; input is pre-filled with the current mode
+                        if let Some(entry) = self.remote.get_selected() {
+                            self.input_txt = match entry.get_unix_pex() {
+                                Some((u, g, o)) => format!("{}{}{}", u, g, o),
+                                None => String::new(),
+                            };
+                            self.input_mode = InputMode::Popup(PopupType::Input(
+                                format!("Change mode of \"{}\"", entry.get_name()),
+                                FileTransferActivity::callback_chmod,
+                            ));
+                        }
+                    }
+                    'k' | 'K' => {
+                        // Make hard link to selected file
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Insert hard link name"),
+                            FileTransferActivity::callback_hardlink,
+                        ));
+                    }
                     'l' | 'L' => {
                         // Reload file entries
                         self.reload_remote_dir();
                     }
+                    'a' | 'A' => {
+                        // Restore selection profile
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Restore selection profile"),
+                            FileTransferActivity::callback_restore_selection_profile,
+                        ));
+                    }
+                    'm' | 'M' => {
+                        // Mark selected entry and move to the next one
+                        self.remote.toggle_mark();
+                        self.remote.move_index_down(1);
+                    }
+                    '+' => {
+                        // Mark files matching a selection expression
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Select files matching"),
+                            FileTransferActivity::callback_select_expression,
+                        ));
+                    }
                     'o' | 'O' => {
                         // Edit remote file
-                        if self.remote.files.get(self.remote.index).is_some() {
+                        if self.remote.get_selected().is_some() {
                             // Clone entry due to mutable stuff...
-                            let fsentry: FsEntry =
-                                self.remote.files.get(self.remote.index).unwrap().clone();
+                            let fsentry: FsEntry = self.remote.get_selected().unwrap().clone();
                             // Check if file
                             if let FsEntry::File(file) = fsentry {
                                 self.log(
@@ -459,6 +638,13 @@ impl FileTransferActivity {
                             }
                         }
                     }
+                    'p' | 'P' => {
+                        // Save marked entries into a selection profile
+                        self.input_mode = InputMode::Popup(PopupType::Input(
+                            String::from("Save selection profile as..."),
+                            FileTransferActivity::callback_save_selection_profile,
+                        ));
+                    }
                     'q' | 'Q' => {
                         // Create quit prompt dialog
                         self.input_mode = self.create_quit_popup();
@@ -486,21 +672,26 @@ impl FileTransferActivity {
                             self.remote_changedir(parent, true);
                         }
                     }
-                    ' ' => {
-                        // Get file and clone (due to mutable / immutable stuff...)
-                        if self.remote.files.get(self.remote.index).is_some() {
-                            let file: FsEntry =
-                                self.remote.files.get(self.remote.index).unwrap().clone();
-                            let name: String = file.get_name();
-                            // Call upload; pass realfile, keep link name
+                    't' | 'T' => {
+                        // Follow remote file
+                        if let Some(FsEntry::File(file)) = self.remote.get_selected() {
+                            // Clone entry due to mutable stuff...
+                            let file: FsFile = file.clone();
+                            self.follow_start(file);
+                        }
+                    }
+                    'v' | 'V' => self.single_pane = !self.single_pane, // Show one/both explorers
+                    'w' | 'W' => self.toggle_log_panel(),              // Show/hide log panel
+                    'z' | 'Z' => self.toggle_relative_time(),          // Relative/absolute times
+                    'x' | 'X' => self.diff_selected(false),            // Diff with local file
+                    'y' | 'Y' => {
+                        // Sync selected directory to localhost
+                        if let Some(FsEntry::Directory(dir)) = self.remote.get_selected().cloned() {
                             let wrkdir: PathBuf = self.local.wrkdir.clone();
-                            self.filetransfer_recv(
-                                &file.get_realfile(),
-                                wrkdir.as_path(),
-                                Some(name),
-                            );
+                            self.filetransfer_sync_request(dir, wrkdir.as_path(), false);
                         }
                     }
+                    ' ' => self.filetransfer_copy(false, false), // Download marked/selected entries
                     _ => { /* Nothing to do */ }
                 },
                 _ => { /* Nothing to do */ }
@@ -545,6 +736,8 @@ impl FileTransferActivity {
                         self.log_index = 0; // Set to 0 otherwise
                     }
                 }
+                KeyCode::End => self.log_index = 0, // Jump to latest record
+                KeyCode::Home => self.log_index = self.log_records.len().saturating_sub(1), // Jump to oldest record
                 KeyCode::PageUp => {
                     // NOTE: Twisted logic
                     // Fast increasing of log index
@@ -556,10 +749,14 @@ impl FileTransferActivity {
                     }
                 }
                 KeyCode::Char(ch) => match ch {
+                    'r' | 'R' => self.log_retry(), // Retry the selected failed transfer
                     'q' | 'Q' => {
                         // Create quit prompt dialog
                         self.input_mode = self.create_quit_popup();
                     }
+                    'v' | 'V' => self.single_pane = !self.single_pane, // Show one/both explorers
+                    'w' | 'W' => self.toggle_log_panel(),              // Show/hide log panel
+                    'z' | 'Z' => self.toggle_relative_time(),          // Relative/absolute times
                     _ => { /* Nothing to do */ }
                 },
                 _ => { /* Nothing to do */ }
@@ -573,11 +770,19 @@ impl FileTransferActivity {
     pub(super) fn handle_input_event_mode_popup(&mut self, ev: &InputEvent, popup: PopupType) {
         match popup {
             PopupType::Alert(_, _) => self.handle_input_event_mode_popup_alert(ev),
+            PopupType::Archive => self.handle_input_event_mode_popup_archive(ev),
+            PopupType::Diff => self.handle_input_event_mode_popup_diff(ev),
             PopupType::FileInfo => self.handle_input_event_mode_popup_fileinfo(ev),
+            PopupType::Filter => self.handle_input_event_mode_popup_filter(ev),
+            PopupType::Follow => self.handle_input_event_mode_popup_follow(ev),
+            PopupType::Goto => self.handle_input_event_mode_popup_goto(ev),
             PopupType::Help => self.handle_input_event_mode_popup_help(ev),
+            PopupType::Image => self.handle_input_event_mode_popup_image(ev),
             PopupType::Fatal(_) => self.handle_input_event_mode_popup_fatal(ev),
             PopupType::Input(_, cb) => self.handle_input_event_mode_popup_input(ev, cb),
             PopupType::Progress(_) => self.handle_input_event_mode_popup_progress(ev),
+            PopupType::Recent => self.handle_input_event_mode_popup_recent(ev),
+            PopupType::Secret(_, cb) => self.handle_input_event_mode_popup_input(ev, cb),
             PopupType::Wait(_) => self.handle_input_event_mode_popup_wait(ev),
             PopupType::YesNo(_, yes_cb, no_cb) => {
                 self.handle_input_event_mode_popup_yesno(ev, yes_cb, no_cb)
@@ -589,11 +794,95 @@ impl FileTransferActivity {
     ///
     /// Input event handler for popup alert
     pub(super) fn handle_input_event_mode_popup_alert(&mut self, ev: &InputEvent) {
-        // If enter, close popup
+        // If enter or esc, close popup
+        if let InputEvent::Key(key) = ev {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    // Set input mode back to explorer
+                    self.input_mode = InputMode::Explorer;
+                }
+                _ => { /* Nothing to do */ }
+            }
+        }
+    }
+
+    /// ### handle_input_event_mode_popup_archive
+    ///
+    /// Input event handler for popup archive
+    pub(super) fn handle_input_event_mode_popup_archive(&mut self, ev: &InputEvent) {
         if let InputEvent::Key(key) = ev {
-            if let KeyCode::Enter = key.code {
-                // Set input mode back to explorer
+            match key.code {
+                KeyCode::Esc => self.archive_close(),
+                KeyCode::Enter => self.archive_enter(),
+                KeyCode::Backspace | KeyCode::Char('u') | KeyCode::Char('U') => {
+                    self.archive_parent()
+                }
+                KeyCode::Char('l') | KeyCode::Char('L') => self.archive_extract(true),
+                KeyCode::Char('r') | KeyCode::Char('R') => self.archive_extract(false),
+                _ => {
+                    if let Some(state) = self.archive.as_mut() {
+                        match key.code {
+                            KeyCode::Up => state.explorer.move_index_up(1),
+                            KeyCode::Down => state.explorer.move_index_down(1),
+                            KeyCode::PageUp => state.explorer.move_index_up(8),
+                            KeyCode::PageDown => state.explorer.move_index_down(8),
+                            _ => { /* Nothing to do */ }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// ### handle_input_event_mode_popup_recent
+    ///
+    /// Input event handler for popup recent
+    pub(super) fn handle_input_event_mode_popup_recent(&mut self, ev: &InputEvent) {
+        if let InputEvent::Key(key) = ev {
+            match key.code {
+                KeyCode::Esc => self.input_mode = InputMode::Explorer,
+                KeyCode::Enter => self.recent_transfer(),
+                KeyCode::Up => self.recent_move_index(true),
+                KeyCode::Down => self.recent_move_index(false),
+                KeyCode::Char('l') | KeyCode::Char('L') => self.recent_goto(true),
+                KeyCode::Char('r') | KeyCode::Char('R') => self.recent_goto(false),
+                _ => { /* Nothing to do */ }
+            }
+        }
+    }
+
+    /// ### handle_input_event_mode_popup_image
+    ///
+    /// Input event handler for popup image
+    pub(super) fn handle_input_event_mode_popup_image(&mut self, ev: &InputEvent) {
+        if let InputEvent::Key(key) = ev {
+            if let KeyCode::Enter | KeyCode::Esc = key.code {
+                self.preview_close();
+            }
+        }
+    }
+
+    /// ### handle_input_event_mode_popup_diff
+    ///
+    /// Input event handler for popup diff
+    pub(super) fn handle_input_event_mode_popup_diff(&mut self, ev: &InputEvent) {
+        if let InputEvent::Key(key) = ev {
+            if let KeyCode::Enter | KeyCode::Esc = key.code {
+                // Close popup
+                self.diff = None;
                 self.input_mode = InputMode::Explorer;
+                return;
+            }
+            if let Some(diff) = self.diff.as_mut() {
+                match key.code {
+                    KeyCode::Up => diff.scroll_up(1),
+                    KeyCode::Down => diff.scroll_down(1),
+                    KeyCode::PageUp => diff.scroll_up(8),
+                    KeyCode::PageDown => diff.scroll_down(8),
+                    KeyCode::Home => diff.scroll = 0,
+                    KeyCode::End => diff.scroll_down(diff.lines.len()),
+                    _ => { /* Nothing to do */ }
+                }
             }
         }
     }
@@ -614,6 +903,19 @@ impl FileTransferActivity {
         }
     }
 
+    /// ### handle_input_event_mode_popup_follow
+    ///
+    /// Input event handler for popup follow
+    pub(super) fn handle_input_event_mode_popup_follow(&mut self, ev: &InputEvent) {
+        // If enter or esc, stop following
+        if let InputEvent::Key(key) = ev {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.follow_stop(),
+                _ => { /* Nothing to do */ }
+            }
+        }
+    }
+
     /// ### handle_input_event_mode_popup_help
     ///
     /// Input event handler for popup help
@@ -634,11 +936,14 @@ impl FileTransferActivity {
     ///
     /// Input event handler for popup alert
     pub(super) fn handle_input_event_mode_popup_fatal(&mut self, ev: &InputEvent) {
-        // If enter, close popup
+        // If enter or esc, close popup
         if let InputEvent::Key(key) = ev {
-            if let KeyCode::Enter = key.code {
-                // Set quit to true; since a fatal error happened
-                self.disconnect();
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    // Set quit to true; since a fatal error happened
+                    self.disconnect();
+                }
+                _ => { /* Nothing to do */ }
             }
         }
     }
@@ -660,6 +965,10 @@ impl FileTransferActivity {
                     self.input_txt.clear();
                     // Set mode back to explorer
                     self.input_mode = InputMode::Explorer;
+                    // If not connected, credentials were being entered: give up
+                    if !self.client.is_connected() {
+                        self.remote_tab_give_up(None);
+                    }
                 }
                 KeyCode::Enter => {
                     // Submit
@@ -680,17 +989,84 @@ impl FileTransferActivity {
         }
     }
 
+    /// ### handle_input_event_mode_popup_goto
+    ///
+    /// Input event handler for popup goto: as input popup, but <TAB> completes the path
+    pub(super) fn handle_input_event_mode_popup_goto(&mut self, ev: &InputEvent) {
+        match ev {
+            InputEvent::Key(key) if key.code == KeyCode::Tab => self.goto_complete(),
+            _ => {
+                // Editing the input ends the cycle through the candidates
+                self.goto_completion = None;
+                self.handle_input_event_mode_popup_input(
+                    ev,
+                    FileTransferActivity::callback_change_directory,
+                )
+            }
+        }
+    }
+
+    /// ### handle_input_event_mode_popup_filter
+    ///
+    /// Input event handler for popup filter: the explorer is filtered at each keystroke.
+    /// <ENTER> keeps the filter, while <ESC> clears it, displaying all the entries again
+    pub(super) fn handle_input_event_mode_popup_filter(&mut self, ev: &InputEvent) {
+        if let InputEvent::Key(key) = ev {
+            match key.code {
+                KeyCode::Esc => {
+                    self.input_txt.clear();
+                    self.input_mode = InputMode::Explorer;
+                }
+                KeyCode::Enter => {
+                    self.input_mode = InputMode::Explorer;
+                    self.input_txt.clear();
+                    return;
+                }
+                KeyCode::Char(ch) => self.input_txt.push(ch),
+                KeyCode::Backspace => {
+                    let _ = self.input_txt.pop();
+                }
+                _ => return,
+            }
+            let text: String = self.input_txt.clone();
+            match self.tab {
+                FileExplorerTab::Local => self.local.set_name_filter(text.as_str()),
+                FileExplorerTab::Remote => self.remote.set_name_filter(text.as_str()),
+            }
+        }
+    }
+
     /// ### handle_input_event_mode_popup_progress
     ///
     /// Input event handler for popup alert
     pub(super) fn handle_input_event_mode_popup_progress(&mut self, ev: &InputEvent) {
         if let InputEvent::Key(key) = ev {
-            if let KeyCode::Char(ch) = key.code {
-                // If is 'C' and CTRL
-                if matches!(ch, 'c' | 'C') && key.modifiers.intersects(KeyModifiers::CONTROL) {
-                    // Abort transfer
-                    self.transfer.aborted = true;
+            if matches!(key.code, KeyCode::Char('n') | KeyCode::Char('N'))
+                && key.modifiers.intersects(KeyModifiers::CONTROL)
+            {
+                // <CTRL+N> new connection: must be pressed twice to abort the transfer
+                match self.disconnect_request {
+                    DisconnectRequest::None => {
+                        self.disconnect_request = DisconnectRequest::Confirm;
+                        self.log(
+                            LogLevel::Warn,
+                            "Press <CTRL+N> again to abort the transfer and disconnect",
+                        );
+                    }
+                    _ => {
+                        self.disconnect_request = DisconnectRequest::Pending;
+                        self.transfer.aborted = true;
+                    }
                 }
+                return;
+            }
+            // Any other key cancels the disconnection, unless it's already pending
+            if self.disconnect_request == DisconnectRequest::Confirm {
+                self.disconnect_request = DisconnectRequest::None;
+            }
+            if self.is_abort_key(key) {
+                // Abort transfer
+                self.transfer.aborted = true;
             }
         }
     }
@@ -698,8 +1074,32 @@ impl FileTransferActivity {
     /// ### handle_input_event_mode_popup_wait
     ///
     /// Input event handler for popup alert
-    pub(super) fn handle_input_event_mode_popup_wait(&mut self, _ev: &InputEvent) {
-        // There's nothing you can do here I guess... maybe ctrl+c in the future idk
+    pub(super) fn handle_input_event_mode_popup_wait(&mut self, ev: &InputEvent) {
+        if let InputEvent::Key(key) = ev {
+            if self.is_abort_key(key) {
+                // Abort the scan (or transfer) in progress
+                self.transfer.aborted = true;
+            } else if let KeyCode::Esc = key.code {
+                // If esc, close popup; set input mode back to explorer
+                self.input_mode = InputMode::Explorer;
+            }
+        }
+    }
+
+    /// ### is_abort_key
+    ///
+    /// Returns whether `key` is the configured abort key
+    fn is_abort_key(&self, key: &KeyEvent) -> bool {
+        let code: bool = match (self.abort_key.key, key.code) {
+            (KeyName::Char(ch), KeyCode::Char(pressed)) => pressed.to_lowercase().eq(Some(ch)),
+            (KeyName::Delete, KeyCode::Delete)
+            | (KeyName::Esc, KeyCode::Esc)
+            | (KeyName::Tab, KeyCode::Tab) => true,
+            (KeyName::Function(n), KeyCode::F(pressed)) => n == pressed,
+            _ => false,
+        };
+        code && self.abort_key.ctrl == key.modifiers.intersects(KeyModifiers::CONTROL)
+            && self.abort_key.alt == key.modifiers.intersects(KeyModifiers::ALT)
     }
 
     /// ### handle_input_event_mode_popup_yesno
@@ -725,6 +1125,12 @@ impl FileTransferActivity {
                     // Reset choice option to yes
                     self.choice_opt = DialogYesNoOption::Yes;
                 }
+                KeyCode::Esc => {
+                    // Dismiss popup, answering no
+                    self.input_mode = InputMode::Explorer;
+                    self.choice_opt = DialogYesNoOption::Yes;
+                    no_cb(self);
+                }
                 KeyCode::Right => self.choice_opt = DialogYesNoOption::No, // Set to NO
                 KeyCode::Left => self.choice_opt = DialogYesNoOption::Yes, // Set to YES
                 _ => { /* Nothing to do */ }
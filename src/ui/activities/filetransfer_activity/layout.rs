@@ -25,13 +25,18 @@ extern crate hostname;
 extern crate users;
 
 use super::{
-    Context, DialogYesNoOption, FileExplorerTab, FileTransferActivity, FsEntry, InputField,
-    InputMode, LogLevel, LogRecord, PopupType,
+    Context, DialogYesNoOption, DisconnectRequest, FileExplorer, FileExplorerTab,
+    FileTransferActivity, FsEntry, InputField, InputMode, LogLevel, LogRecord, PopupType,
+    TransferResult, SINGLE_PANE_MAX_WIDTH,
+};
+use crate::fs::preview::Rgb;
+use crate::utils::fmt::{
+    align_text_center, elide_text_left, fmt_relative_time, fmt_time, fmt_xattr_value,
 };
-use crate::utils::fmt::{align_text_center, fmt_time};
 
 use bytesize::ByteSize;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tui::{
     layout::{Constraint, Corner, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -51,59 +56,94 @@ impl FileTransferActivity {
     pub(super) fn draw(&mut self) {
         let mut ctx: Context = self.context.take().unwrap();
         let _ = ctx.terminal.draw(|f| {
-            // Prepare chunks
+            // Prepare chunks; explorers get the entire height if log is hidden
+            let mut constraints: Vec<Constraint> = match self.log_visible {
+                true => vec![
+                    Constraint::Percentage(70), // Explorer
+                    Constraint::Percentage(30), // Log
+                ],
+                false => vec![Constraint::Percentage(100)],
+            };
+            if self.show_path_line {
+                constraints.push(Constraint::Length(1)); // Path line
+            }
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(1)
-                .constraints(
-                    [
-                        Constraint::Percentage(70), // Explorer
-                        Constraint::Percentage(30), // Log
-                    ]
-                    .as_ref(),
-                )
+                .constraints(constraints)
                 .split(f.size());
-            // Create explorer chunks
-            let tabs_chunks = Layout::default()
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-                .direction(Direction::Horizontal)
-                .split(chunks[0]);
-            // Set localhost state
-            let mut localhost_state: ListState = ListState::default();
-            localhost_state.select(Some(self.local.index));
-            // Set remote state
-            let mut remote_state: ListState = ListState::default();
-            remote_state.select(Some(self.remote.index));
+            // Create explorer chunks; in single pane mode (or if terminal is narrow) only the focused explorer is shown
+            let (local_area, remote_area): (Option<Rect>, Option<Rect>) = match (
+                self.single_pane || f.size().width < SINGLE_PANE_MAX_WIDTH,
+                &self.tab,
+            ) {
+                (true, FileExplorerTab::Local) => (Some(chunks[0]), None),
+                (true, FileExplorerTab::Remote) => (None, Some(chunks[0])),
+                (false, _) => {
+                    let tabs_chunks = Layout::default()
+                        .constraints(
+                            [Constraint::Percentage(50), Constraint::Percentage(50)].as_ref(),
+                        )
+                        .direction(Direction::Horizontal)
+                        .split(chunks[0]);
+                    (Some(tabs_chunks[0]), Some(tabs_chunks[1]))
+                }
+            };
             // Draw tabs
-            f.render_stateful_widget(
-                self.draw_local_explorer(tabs_chunks[0].width),
-                tabs_chunks[0],
-                &mut localhost_state,
-            );
-            f.render_stateful_widget(
-                self.draw_remote_explorer(tabs_chunks[1].width),
-                tabs_chunks[1],
-                &mut remote_state,
-            );
-            // Set log state
-            let mut log_state: ListState = ListState::default();
-            log_state.select(Some(self.log_index));
-            // Draw log
-            f.render_stateful_widget(
-                self.draw_log_list(chunks[1].width),
-                chunks[1],
-                &mut log_state,
-            );
+            if let Some(area) = local_area {
+                // Set localhost state
+                let mut localhost_state: ListState = ListState::default();
+                localhost_state.select(Some(self.local.index));
+                f.render_stateful_widget(
+                    self.draw_local_explorer(area.width),
+                    area,
+                    &mut localhost_state,
+                );
+            }
+            if let Some(area) = remote_area {
+                // Set remote state
+                let mut remote_state: ListState = ListState::default();
+                remote_state.select(Some(self.remote.index));
+                f.render_stateful_widget(
+                    self.draw_remote_explorer(area.width),
+                    area,
+                    &mut remote_state,
+                );
+            }
+            // Draw log (if visible)
+            if self.log_visible {
+                // Set log state
+                let mut log_state: ListState = ListState::default();
+                log_state.select(Some(self.log_index));
+                f.render_stateful_widget(
+                    self.draw_log_list(chunks[1].width),
+                    chunks[1],
+                    &mut log_state,
+                );
+            }
+            // Draw path line (if enabled)
+            if self.show_path_line {
+                let area: Rect = chunks[chunks.len() - 1];
+                f.render_widget(self.draw_path_line(area.width), area);
+            }
             // Draw popup
             if let InputMode::Popup(popup) = &self.input_mode {
                 // Calculate popup size
                 let (width, height): (u16, u16) = match popup {
                     PopupType::Alert(_, _) => (50, 10),
+                    PopupType::Archive => (90, 80),
+                    PopupType::Diff => (90, 80),
                     PopupType::Fatal(_) => (50, 10),
                     PopupType::FileInfo => (50, 50),
+                    PopupType::Filter => (40, 10),
+                    PopupType::Follow => (90, 80),
+                    PopupType::Goto => (60, 10),
                     PopupType::Help => (50, 70),
+                    PopupType::Image => (90, 80),
                     PopupType::Input(_, _) => (40, 10),
                     PopupType::Progress(_) => (40, 10),
+                    PopupType::Recent => (90, 50),
+                    PopupType::Secret(_, _) => (40, 10),
                     PopupType::Wait(_) => (50, 10),
                     PopupType::YesNo(_, _, _) => (30, 10),
                 };
@@ -118,8 +158,49 @@ impl FileTransferActivity {
                         self.draw_popup_fatal(txt.clone(), popup_area.width),
                         popup_area,
                     ),
+                    PopupType::Archive => {
+                        // Set archive explorer state
+                        let mut archive_state: ListState = ListState::default();
+                        if let Some(archive) = &self.archive {
+                            archive_state.select(Some(archive.explorer.index));
+                        }
+                        f.render_stateful_widget(
+                            self.draw_popup_archive(),
+                            popup_area,
+                            &mut archive_state,
+                        );
+                    }
+                    PopupType::Diff => {
+                        f.render_widget(self.draw_popup_diff(popup_area.height), popup_area)
+                    }
                     PopupType::FileInfo => f.render_widget(self.draw_popup_fileinfo(), popup_area),
+                    PopupType::Follow => {
+                        f.render_widget(self.draw_popup_follow(popup_area.height), popup_area)
+                    }
                     PopupType::Help => f.render_widget(self.draw_popup_help(), popup_area),
+                    PopupType::Image => f.render_widget(
+                        self.draw_popup_image(popup_area.width, popup_area.height),
+                        popup_area,
+                    ),
+                    PopupType::Goto => {
+                        f.render_widget(self.draw_popup_input(self.goto_title()), popup_area);
+                        // Set cursor
+                        f.set_cursor(
+                            popup_area.x + self.input_txt.width() as u16 + 1,
+                            popup_area.y + 1,
+                        )
+                    }
+                    PopupType::Filter => {
+                        f.render_widget(
+                            self.draw_popup_input(String::from("Filter by name (<ESC> to clear)")),
+                            popup_area,
+                        );
+                        // Set cursor
+                        f.set_cursor(
+                            popup_area.x + self.input_txt.width() as u16 + 1,
+                            popup_area.y + 1,
+                        )
+                    }
                     PopupType::Input(txt, _) => {
                         f.render_widget(self.draw_popup_input(txt.clone()), popup_area);
                         // Set cursor
@@ -131,6 +212,23 @@ impl FileTransferActivity {
                     PopupType::Progress(txt) => {
                         f.render_widget(self.draw_popup_progress(txt.clone()), popup_area)
                     }
+                    PopupType::Recent => {
+                        let mut recent_state: ListState = ListState::default();
+                        recent_state.select(Some(self.recent_index));
+                        f.render_stateful_widget(
+                            self.draw_popup_recent(),
+                            popup_area,
+                            &mut recent_state,
+                        );
+                    }
+                    PopupType::Secret(txt, _) => {
+                        f.render_widget(self.draw_popup_secret(txt.clone()), popup_area);
+                        // Set cursor
+                        f.set_cursor(
+                            popup_area.x + self.input_txt.chars().count() as u16 + 1,
+                            popup_area.y + 1,
+                        )
+                    }
                     PopupType::Wait(txt) => f.render_widget(
                         self.draw_popup_wait(txt.clone(), popup_area.width),
                         popup_area,
@@ -141,9 +239,36 @@ impl FileTransferActivity {
                 }
             }
         });
+        // Update terminal title
+        if let Some(title) = &self.terminal_title {
+            let new_title: String = self.get_terminal_title();
+            if *title != new_title {
+                ctx.set_title(new_title.as_str());
+                self.terminal_title = Some(new_title);
+            }
+        }
         self.context = Some(ctx);
     }
 
+    /// ### get_terminal_title
+    ///
+    /// Get the terminal title: transfer progress and speed while transferring, remote host otherwise
+    pub(super) fn get_terminal_title(&self) -> String {
+        let host: String = match &self.params.username {
+            Some(username) => format!("{}@{}", username, self.params.address),
+            None => self.params.address.clone(),
+        };
+        match &self.input_mode {
+            InputMode::Popup(PopupType::Progress(_)) => format!(
+                "Gateway - {:.0}% ({}/s) - {}",
+                self.transfer.progress,
+                ByteSize(self.transfer.bytes_per_second()),
+                host
+            ),
+            _ => format!("Gateway - {}", host),
+        }
+    }
+
     /// ### draw_local_explorer
     ///
     /// Draw local explorer list
@@ -158,9 +283,15 @@ impl FileTransferActivity {
         };
         let files: Vec<ListItem> = self
             .local
-            .files
-            .iter()
-            .map(|entry: &FsEntry| ListItem::new(Span::from(format!("{}", entry))))
+            .iter_files()
+            .map(|entry: &FsEntry| {
+                FileTransferActivity::draw_explorer_entry(
+                    &self.local,
+                    entry,
+                    self.relative_time,
+                    self.show_link_target,
+                )
+            })
             .collect();
         // Get colors to use; highlight element inverting fg/bg only when tab is active
         let (fg, bg): (Color, Color) = match self.tab {
@@ -179,14 +310,16 @@ impl FileTransferActivity {
                         _ => Style::default(),
                     })
                     .title(format!(
-                        "{}:{} ",
+                        "{}:{} {}{}",
                         hostname,
                         FileTransferActivity::elide_wrkdir_path(
                             self.local.wrkdir.as_path(),
                             hostname.as_str(),
                             width
                         )
-                        .display()
+                        .display(),
+                        self.local.filter_label(),
+                        self.local.sort_label()
                     )),
             )
             .start_corner(Corner::TopLeft)
@@ -199,9 +332,15 @@ impl FileTransferActivity {
     pub(super) fn draw_remote_explorer(&self, width: u16) -> List {
         let files: Vec<ListItem> = self
             .remote
-            .files
-            .iter()
-            .map(|entry: &FsEntry| ListItem::new(Span::from(format!("{}", entry))))
+            .iter_files()
+            .map(|entry: &FsEntry| {
+                FileTransferActivity::draw_explorer_entry(
+                    &self.remote,
+                    entry,
+                    self.relative_time,
+                    self.show_link_target,
+                )
+            })
             .collect();
         // Get colors to use; highlight element inverting fg/bg only when tab is active
         let (fg, bg): (Color, Color) = match self.tab {
@@ -220,20 +359,76 @@ impl FileTransferActivity {
                         _ => Style::default(),
                     })
                     .title(format!(
-                        "{}:{} ",
+                        "{}{}:{} {}{}",
+                        self.remote_tab_label(),
                         self.params.address,
                         FileTransferActivity::elide_wrkdir_path(
                             self.remote.wrkdir.as_path(),
                             self.params.address.as_str(),
                             width
                         )
-                        .display()
+                        .display(),
+                        self.remote.filter_label(),
+                        self.remote.sort_label()
                     )),
             )
             .start_corner(Corner::TopLeft)
             .highlight_style(Style::default().bg(bg).fg(fg).add_modifier(Modifier::BOLD))
     }
 
+    /// ### draw_explorer_entry
+    ///
+    /// Draw an explorer entry; marked entries are highlighted and entries recently transferred
+    /// are prefixed by their result (success, skipped, failed)
+    fn draw_explorer_entry(
+        explorer: &FileExplorer,
+        entry: &FsEntry,
+        relative_time: bool,
+        link_target: bool,
+    ) -> ListItem<'static> {
+        let mut spans: Vec<Span> = Vec::with_capacity(2);
+        if let Some(result) = explorer.get_result(entry) {
+            let (glyph, color): (&str, Color) = match result {
+                TransferResult::Success => ("✔ ", Color::LightGreen),
+                TransferResult::Skipped => ("- ", Color::Yellow),
+                TransferResult::Failed => ("✖ ", Color::LightRed),
+            };
+            spans.push(Span::styled(glyph, Style::default().fg(color)));
+        }
+        spans.push(match explorer.is_marked(entry) {
+            true => Span::styled(
+                entry.fmt_ls(relative_time, link_target),
+                Style::default()
+                    .fg(Color::LightGreen)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            false => Span::from(entry.fmt_ls(relative_time, link_target)),
+        });
+        ListItem::new(Spans::from(spans))
+    }
+
+    /// ### draw_path_line
+    ///
+    /// Draw the status line with the absolute path of the entry selected in the focused explorer.
+    /// Local paths are canonicalized; paths too long for the line are elided from the left
+    pub(super) fn draw_path_line(&self, width: u16) -> Paragraph {
+        let path: Option<PathBuf> = match self.tab {
+            FileExplorerTab::Local => self.local.get_selected().map(|entry| {
+                let abs_path: PathBuf = entry.get_abs_path();
+                std::fs::canonicalize(abs_path.as_path()).unwrap_or(abs_path)
+            }),
+            FileExplorerTab::Remote => self.remote.get_selected().map(|x| x.get_abs_path()),
+        };
+        let text: String = match path {
+            Some(path) => elide_text_left(path.display().to_string().as_str(), width as usize),
+            None => String::new(),
+        };
+        Paragraph::new(Span::styled(
+            text,
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+    }
+
     /// ### draw_log_list
     ///
     /// Draw log list
@@ -254,7 +449,10 @@ impl FileTransferActivity {
                 for (idx, row) in record_rows.iter().enumerate() {
                     let row: Spans = match idx {
                         0 => Spans::from(vec![
-                            Span::from(format!("{}", record.time.format("%Y-%m-%dT%H:%M:%S%Z"))),
+                            Span::from(match self.relative_time {
+                                true => fmt_relative_time(SystemTime::from(record.time)),
+                                false => format!("{}", record.time.format("%Y-%m-%dT%H:%M:%S%Z")),
+                            }),
                             Span::raw(" ["),
                             Span::styled(
                                 format!(
@@ -288,12 +486,27 @@ impl FileTransferActivity {
                         InputField::Logs => Style::default().fg(Color::LightGreen),
                         _ => Style::default(),
                     })
-                    .title("Log"),
+                    .title(self.log_title()),
             )
             .start_corner(Corner::BottomLeft)
             .highlight_style(Style::default().add_modifier(Modifier::BOLD))
     }
 
+    /// ### log_title
+    ///
+    /// Returns the title of the log panel; if the selected record is a failed transfer, how to retry it is shown
+    fn log_title(&self) -> &'static str {
+        let failed: bool = self
+            .log_records
+            .get(self.log_index)
+            .map(|x| x.transfer.is_some())
+            .unwrap_or(false);
+        match (&self.input_field, failed) {
+            (InputField::Logs, true) => "Log (<R> to retry the failed transfer)",
+            _ => "Log",
+        }
+    }
+
     /// ### draw_popup_area
     ///
     /// Draw popup area
@@ -379,6 +592,41 @@ impl FileTransferActivity {
             )
     }
 
+    /// ### goto_title
+    ///
+    /// Title of the goto popup, with the position of the completed candidate while cycling through them
+    fn goto_title(&self) -> String {
+        match self.goto_completion.as_ref() {
+            Some(completion) => match completion.index {
+                Some(index) => format!(
+                    "Change working directory ({}/{}; <TAB> for next)",
+                    index + 1,
+                    completion.candidates.len()
+                ),
+                None => format!(
+                    "Change working directory ({} matches; <TAB> to cycle)",
+                    completion.candidates.len()
+                ),
+            },
+            None => String::from("Change working directory (<TAB> to complete)"),
+        }
+    }
+
+    /// ### draw_popup_secret
+    ///
+    /// Draw input popup, masking the input text
+    pub(super) fn draw_popup_secret(&self, text: String) -> Paragraph {
+        let masked: String = self.input_txt.chars().map(|_| '*').collect();
+        Paragraph::new(masked)
+            .style(Style::default().fg(Color::White))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .title(text),
+            )
+    }
+
     /// ### draw_popup_progress
     ///
     /// Draw progress popup
@@ -400,8 +648,29 @@ impl FileTransferActivity {
             eta,
             ByteSize(self.transfer.bytes_per_second())
         );
+        // Ask to confirm the disconnection, instead of the transfer name
+        let text: String = match self.disconnect_request {
+            DisconnectRequest::Confirm => {
+                String::from("Press <CTRL+N> again to abort the transfer and disconnect")
+            }
+            _ => text,
+        };
+        // Position in the transfer queue
+        let text: String = match self.transfer_queue.position() {
+            Some((n, m)) => format!("{} (file {} of {})", text, n, m),
+            None => text,
+        };
+        // Overall progress of the transfer
+        let title: String = match &self.queue_progress {
+            Some(queue) => format!(
+                "{} (total: {})",
+                text,
+                queue.report(self.transfer.bytes_written)
+            ),
+            None => text,
+        };
         Gauge::default()
-            .block(Block::default().borders(Borders::ALL).title(text))
+            .block(Block::default().borders(Borders::ALL).title(title))
             .gauge_style(
                 Style::default()
                     .fg(Color::Green)
@@ -468,12 +737,12 @@ impl FileTransferActivity {
         let fsentry: Option<&FsEntry> = match self.tab {
             FileExplorerTab::Local => {
                 // Get selected file
-                match self.local.files.get(self.local.index) {
+                match self.local.get_selected() {
                     Some(entry) => Some(entry),
                     None => None,
                 }
             }
-            FileExplorerTab::Remote => match self.remote.files.get(self.remote.index) {
+            FileExplorerTab::Remote => match self.remote.get_selected() {
                 Some(entry) => Some(entry),
                 None => None,
             },
@@ -485,11 +754,14 @@ impl FileTransferActivity {
                 let abs_path: PathBuf = fsentry.get_abs_path();
                 let name: String = fsentry.get_name();
                 let ctime: String = fmt_time(fsentry.get_creation_time(), "%b %d %Y %H:%M:%S");
-                let atime: String =
-                fmt_time(fsentry.get_last_access_time(), "%b %d %Y %H:%M:%S");
+                let atime: String = fmt_time(fsentry.get_last_access_time(), "%b %d %Y %H:%M:%S");
                 let mtime: String = fmt_time(fsentry.get_creation_time(), "%b %d %Y %H:%M:%S");
-                let (bsize, size): (ByteSize, usize) =
-                    (ByteSize(fsentry.get_size() as u64), fsentry.get_size());
+                // Local directories: size of their content
+                let size: u64 = match (fsentry.is_dir(), self.fileinfo_size) {
+                    (true, Some(size)) => size,
+                    _ => fsentry.get_size() as u64,
+                };
+                let bsize: ByteSize = ByteSize(size);
                 let user: Option<u32> = fsentry.get_user();
                 let group: Option<u32> = fsentry.get_group();
                 let real_path: Option<PathBuf> = {
@@ -604,6 +876,46 @@ impl FileTransferActivity {
                             .add_modifier(Modifier::BOLD),
                     ),
                 ])));
+                // Links count and inode (UNIX only)
+                if let Some(nlink) = fsentry.get_nlink() {
+                    info.push(ListItem::new(Spans::from(vec![
+                        Span::styled("Links: ", Style::default()),
+                        Span::styled(
+                            nlink.to_string(),
+                            Style::default()
+                                .fg(Color::LightCyan)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ])));
+                }
+                if let Some(inode) = fsentry.get_inode() {
+                    info.push(ListItem::new(Spans::from(vec![
+                        Span::styled("Inode: ", Style::default()),
+                        Span::styled(
+                            inode.to_string(),
+                            Style::default()
+                                .fg(Color::LightYellow)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ])));
+                }
+                // Extended attributes (local only)
+                if let Some(attrs) = &self.fileinfo_xattrs {
+                    if !attrs.is_empty() {
+                        info.push(ListItem::new(Span::raw("Extended attributes:")));
+                    }
+                    for (name, value) in attrs.iter() {
+                        info.push(ListItem::new(Spans::from(vec![
+                            Span::raw(format!("  {}: ", name)),
+                            Span::styled(
+                                fmt_xattr_value(value.as_slice()),
+                                Style::default()
+                                    .fg(Color::LightGreen)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                        ])));
+                    }
+                }
                 // Finally return file name
                 name
             }
@@ -620,12 +932,197 @@ impl FileTransferActivity {
             .start_corner(Corner::TopLeft)
     }
 
+    /// ### draw_popup_follow
+    ///
+    /// Draw popup containing the last lines of the followed file
+    pub(super) fn draw_popup_follow(&self, height: u16) -> List {
+        let (title, lines): (String, Vec<String>) = match &self.follow {
+            Some(follow) => (
+                format!(
+                    "Following \"{}\" (<ESC> to stop)",
+                    follow.file.abs_path.display()
+                ),
+                follow.get_lines(height.saturating_sub(2) as usize),
+            ),
+            None => (String::new(), Vec::new()),
+        };
+        let lines: Vec<ListItem> = lines.into_iter().map(ListItem::new).collect();
+        List::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default())
+                    .border_type(BorderType::Rounded)
+                    .title(title),
+            )
+            .start_corner(Corner::TopLeft)
+    }
+
+    /// ### draw_popup_image
+    ///
+    /// Draw popup containing the preview of an image, made of half-block characters
+    /// (the top half colored as a pixel, the bottom half as the pixel below it)
+    pub(super) fn draw_popup_image(&self, width: u16, height: u16) -> Paragraph {
+        let (title, lines): (String, Vec<Spans>) = match &self.preview {
+            Some(preview) => {
+                let columns: u32 = width.saturating_sub(2) as u32;
+                let cells: Vec<Vec<(Rgb, Option<Rgb>)>> = preview
+                    .image
+                    .cells(columns, height.saturating_sub(2) as u32);
+                let lines: Vec<Spans> = cells
+                    .iter()
+                    .map(|row| {
+                        // Center image horizontally
+                        let margin: usize = (columns as usize).saturating_sub(row.len()) / 2;
+                        let mut spans: Vec<Span> = vec![Span::raw(" ".repeat(margin))];
+                        spans.extend(row.iter().map(|(top, bottom)| {
+                            let mut style: Style =
+                                Style::default().fg(Color::Rgb(top[0], top[1], top[2]));
+                            if let Some(bottom) = bottom {
+                                style = style.bg(Color::Rgb(bottom[0], bottom[1], bottom[2]));
+                            }
+                            Span::styled("▀", style)
+                        }));
+                        Spans::from(spans)
+                    })
+                    .collect();
+                (
+                    format!(
+                        "{} ({}x{}, <ESC> to close)",
+                        preview.name.display(),
+                        preview.image.width,
+                        preview.image.height
+                    ),
+                    lines,
+                )
+            }
+            None => (String::new(), Vec::new()),
+        };
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(title),
+        )
+    }
+
+    /// ### draw_popup_archive
+    ///
+    /// Draw popup containing the entries of the archive directory being browsed
+    pub(super) fn draw_popup_archive(&self) -> List {
+        let (title, files): (String, Vec<ListItem>) = match &self.archive {
+            Some(archive) => (
+                format!(
+                    "{}:{} (<L>/<R> to extract to local/remote, <ESC> to close)",
+                    archive.name.display(),
+                    archive.explorer.wrkdir.display()
+                ),
+                archive
+                    .explorer
+                    .iter_files()
+                    .map(|entry: &FsEntry| {
+                        FileTransferActivity::draw_explorer_entry(
+                            &archive.explorer,
+                            entry,
+                            self.relative_time,
+                            false,
+                        )
+                    })
+                    .collect(),
+            ),
+            None => (String::new(), Vec::new()),
+        };
+        List::new(files)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default())
+                    .border_type(BorderType::Rounded)
+                    .title(title),
+            )
+            .start_corner(Corner::TopLeft)
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+    }
+
+    /// ### draw_popup_recent
+    ///
+    /// Draw popup containing the files transferred during the session, latest first
+    pub(super) fn draw_popup_recent(&self) -> List {
+        let transfers: Vec<ListItem> = self
+            .recent
+            .iter()
+            .map(|transfer| {
+                let remote: String = format!("{}:{}", transfer.host, transfer.remote.display());
+                let local: String = transfer.local.display().to_string();
+                ListItem::new(match transfer.upload {
+                    true => format!("↑ {} -> {}", local, remote),
+                    false => format!("↓ {} -> {}", remote, local),
+                })
+            })
+            .collect();
+        List::new(transfers)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default())
+                    .border_type(BorderType::Rounded)
+                    .title("Recent transfers (<ENTER> to transfer again, <L>/<R> to go to the local/remote directory, <ESC> to close)"),
+            )
+            .start_corner(Corner::TopLeft)
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+    }
+
+    /// ### draw_popup_diff
+    ///
+    /// Draw popup containing the visible lines of the diff; added lines are green, removed lines are red
+    pub(super) fn draw_popup_diff(&self, height: u16) -> List {
+        let (title, lines): (String, Vec<ListItem>) = match &self.diff {
+            Some(diff) => (
+                format!("{} (<UP>/<DOWN> to scroll, <ESC> to close)", diff.title),
+                diff.lines
+                    .iter()
+                    .skip(diff.scroll)
+                    .take(height.saturating_sub(2) as usize)
+                    .map(|line| {
+                        let color: Color = match line.chars().next() {
+                            Some('+') => Color::Green,
+                            Some('-') => Color::Red,
+                            Some('@') => Color::Cyan,
+                            _ => Color::Reset,
+                        };
+                        ListItem::new(Span::styled(line.as_str(), Style::default().fg(color)))
+                    })
+                    .collect(),
+            ),
+            None => (String::new(), Vec::new()),
+        };
+        List::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default())
+                    .border_type(BorderType::Rounded)
+                    .title(title),
+            )
+            .start_corner(Corner::TopLeft)
+    }
+
     /// ### draw_footer
     ///
     /// Draw authentication page footer
     pub(super) fn draw_popup_help(&self) -> List {
         // Write header
-        let cmds: Vec<ListItem> = vec![
+        let mut cmds: Vec<ListItem> = vec![
             ListItem::new(Spans::from(vec![
                 Span::styled(
                     "<ESC>",
@@ -634,7 +1131,7 @@ impl FileTransferActivity {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("           "),
-                Span::raw("Disconnect"),
+                Span::raw("Disconnect; in popups, close popup (discarding input)"),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
@@ -666,6 +1163,26 @@ impl FileTransferActivity {
                 Span::raw("    "),
                 Span::raw("Change explorer tab"),
             ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<ALT+LEFT>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("      "),
+                Span::raw("Focus local explorer, also from log"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<ALT+RIGHT>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("     "),
+                Span::raw("Focus remote explorer, also from log"),
+            ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
                     "<UP/DOWN>",
@@ -686,6 +1203,26 @@ impl FileTransferActivity {
                 Span::raw("   "),
                 Span::raw("Scroll up/down in list quickly"),
             ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<HOME/END>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("      "),
+                Span::raw("Jump to oldest/latest log record"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<R>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Retry failed transfer (log panel)"),
+            ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
                     "<ENTER>",
@@ -694,7 +1231,14 @@ impl FileTransferActivity {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("         "),
-                Span::raw("Enter directory"),
+                Span::raw(
+                    match (cfg!(feature = "archives"), cfg!(feature = "images")) {
+                        (true, true) => "Enter directory, browse archive or preview image",
+                        (true, false) => "Enter directory or browse archive (.tar, .tar.gz, .zip)",
+                        (false, true) => "Enter directory or preview image",
+                        (false, false) => "Enter directory",
+                    },
+                ),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
@@ -704,37 +1248,98 @@ impl FileTransferActivity {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("         "),
-                Span::raw("Upload/download file"),
+                Span::raw(match self.readonly {
+                    true => "Download file (or marked entries); upload is disabled",
+                    false => "Upload/download file (or marked entries)",
+                }),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
-                    "<DEL>",
+                    "<F5>",
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ),
+                Span::raw("            "),
+                Span::raw("Same as <SPACE>, confirming overwrites"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<DEL>", self.help_key_style(true)),
                 Span::raw("           "),
-                Span::raw("Delete file"),
+                Span::styled("Delete file", self.help_text_style(true)),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
-                    "<D>",
+                    "<+>",
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("             "),
-                Span::raw("Make directory"),
+                Span::raw("Mark files matching an expression (e.g. size>1M && age>30d)"),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
-                    "<E>",
+                    "</>",
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("             "),
-                Span::raw("Same as <DEL>"),
+                Span::raw("Filter entries by name while typing; <ESC> clears the filter"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<[/]>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("           "),
+                Span::raw("Switch to the previous/next remote tab"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<A>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Mark entries saved in a selection profile"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<B>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled(
+                    "Enqueue marked/selected files for transfer",
+                    self.help_text_style(true),
+                ),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<C>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled("Change mode of remote file", self.help_text_style(true)),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<D>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled("Make directory", self.help_text_style(true)),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<E>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled("Same as <DEL>", self.help_text_style(true)),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<F>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Show all entries/directories only/files only"),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
@@ -766,6 +1371,21 @@ impl FileTransferActivity {
                 Span::raw("             "),
                 Span::raw("Show info about the selected file or directory"),
             ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<J>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Show the files transferred recently"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<K>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled("Make hard link to remote file", self.help_text_style(true)),
+            ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
                     "<L>",
@@ -776,6 +1396,31 @@ impl FileTransferActivity {
                 Span::raw("             "),
                 Span::raw("Reload directory content"),
             ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<M>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Mark/unmark selected entry"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<N>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled("Make directory and enter it", self.help_text_style(true)),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<P>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Save marked entries into a selection profile"),
+            ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
                     "<Q>",
@@ -786,15 +1431,20 @@ impl FileTransferActivity {
                 Span::raw("             "),
                 Span::raw("Quit Gateway"),
             ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<R>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled("Rename file", self.help_text_style(true)),
+            ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
-                    "<R>",
+                    "<T>",
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("             "),
-                Span::raw("Rename file"),
+                Span::raw("Follow remote file (as tail -f)"),
             ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
@@ -806,17 +1456,191 @@ impl FileTransferActivity {
                 Span::raw("             "),
                 Span::raw("Go to parent directory"),
             ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<Y>", self.help_key_style(true)),
+                Span::raw("             "),
+                Span::styled(
+                    "Sync directory to the other side",
+                    self.help_text_style(true),
+                ),
+            ])),
             ListItem::new(Spans::from(vec![
                 Span::styled(
-                    "<CTRL+C>",
+                    "<V>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Show only the focused explorer / both explorers"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<W>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Show/hide log panel"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<X>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Diff file with the one with the same name on the other side"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<Z>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("             "),
+                Span::raw("Show times relative to now / as dates"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{:<16}", format!("<{}>", self.abort_key)),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("Abort current file transfer or scan"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+B>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Start the transfer queue"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+D>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Enable/disable dry run (transfers are only logged)"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled("<CTRL+E>", self.help_key_style(true)),
+                Span::raw("        "),
+                Span::styled("Create empty file", self.help_text_style(true)),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+F>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Mark entries matching a glob"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+N>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Disconnect and connect to another host"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+R>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Reverse sort order (ascending/descending)"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+S>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Sort by name, size or modified time"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+T>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Open a new remote tab"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+W>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Close the active remote tab"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+Y>",
                     Style::default()
                         .fg(Color::Cyan)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("        "),
-                Span::raw("Abort current file transfer"),
+                Span::raw("Enable/disable synchronized browsing"),
+            ])),
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    "<CTRL+Z>",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("        "),
+                Span::raw("Undo last rename (or delete/overwrite, in trash mode)"),
             ])),
         ];
+        if self.dry_run {
+            cmds.insert(
+                0,
+                ListItem::new(Span::styled(
+                    "Dry run: transfers are only logged, nothing is written",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+            );
+        }
+        if self.readonly {
+            cmds.insert(
+                0,
+                ListItem::new(Span::styled(
+                    "Readonly mode: grayed out commands are disabled",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )),
+            );
+        }
         List::new(cmds)
             .block(
                 Block::default()
@@ -828,6 +1652,28 @@ impl FileTransferActivity {
             .start_corner(Corner::TopLeft)
     }
 
+    /// ### help_key_style
+    ///
+    /// Get the style of a key in the help popup; keys of operations which modify files are grayed out in readonly mode
+    fn help_key_style(&self, write: bool) -> Style {
+        match write && self.readonly {
+            true => Style::default().fg(Color::DarkGray),
+            false => Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// ### help_text_style
+    ///
+    /// Get the style of a command description in the help popup; descriptions of operations which modify files are grayed out in readonly mode
+    fn help_text_style(&self, write: bool) -> Style {
+        match write && self.readonly {
+            true => Style::default().fg(Color::DarkGray),
+            false => Style::default(),
+        }
+    }
+
     /// ### elide_wrkdir_path
     ///
     /// Elide working directory path if longer than width + host.len
@@ -20,6 +20,11 @@
 */
 
 use super::{Color, FileTransferActivity, InputField, InputMode, LogLevel, LogRecord, PopupType};
+use crate::system::hook;
+
+use crossterm::event::KeyCode;
+use std::path::Path;
+use std::process::Child;
 
 impl FileTransferActivity {
     /// ### log
@@ -28,14 +33,114 @@ impl FileTransferActivity {
     pub(super) fn log(&mut self, level: LogLevel, msg: &str) {
         // Create log record
         let record: LogRecord = LogRecord::new(level, msg);
+        // Append it to the log file, if any and if the level isn't filtered out; if that fails, the log file is given up
+        let log_file_level: LogLevel = self.log_file_level;
+        if let Some(log_file) = self
+            .log_file
+            .as_mut()
+            .filter(|_| record.level <= log_file_level)
+        {
+            let line: String = format!(
+                "{} [{:5}]: {}",
+                record.time.format("%Y-%m-%dT%H:%M:%S%Z"),
+                match record.level {
+                    LogLevel::Error => "ERROR",
+                    LogLevel::Warn => "WARN",
+                    LogLevel::Info => "INFO",
+                },
+                record.msg
+            );
+            if let Err(err) = log_file.write_line(line.as_str()) {
+                self.log_file = None;
+                self.log(
+                    LogLevel::Error,
+                    format!("Could not write log file: {}; log file disabled", err).as_str(),
+                );
+            }
+        }
         //Check if history overflows the size
         if self.log_records.len() + 1 > self.log_size {
             self.log_records.pop_back(); // Start cleaning events from back
         }
         // Eventually push front the new record
         self.log_records.push_front(record);
-        // Set log index
-        self.log_index = 0;
+        // Keep the record the user scrolled to selected; index 0 follows the latest record
+        if self.log_index > 0 {
+            self.log_index = (self.log_index + 1).min(self.log_records.len() - 1);
+        }
+    }
+
+    /// ### log_client_warnings
+    ///
+    /// Log the warnings reported by the file transfer client
+    pub(super) fn log_client_warnings(&mut self) {
+        for warning in self.client.take_warnings().iter() {
+            self.log(LogLevel::Warn, warning.as_str());
+        }
+    }
+
+    /// ### run_post_download_command
+    ///
+    /// Run the post download command configured by the user (if any) for the downloaded file.
+    /// The command is not waited for; its exit status is logged by `poll_hooks`
+    pub(super) fn run_post_download_command(&mut self, path: &Path, size: usize) {
+        let template: String = match self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref())
+            .and_then(|config_client| config_client.get_post_download_command())
+        {
+            Some(template) => template,
+            None => return,
+        };
+        let command: String = hook::render_command(template.as_str(), path, size);
+        match hook::spawn_command(command.as_str()) {
+            Ok(child) => self.hooks.push((command, child)),
+            Err(err) => self.log(
+                LogLevel::Error,
+                format!(
+                    "Could not run post download command \"{}\": {}",
+                    command, err
+                )
+                .as_str(),
+            ),
+        }
+    }
+
+    /// ### poll_hooks
+    ///
+    /// Log the exit status of the post transfer commands which have terminated.
+    /// Returns whether something has been logged
+    pub(super) fn poll_hooks(&mut self) -> bool {
+        let mut logged: bool = false;
+        let hooks: Vec<(String, Child)> = std::mem::take(&mut self.hooks);
+        let mut running: Vec<(String, Child)> = Vec::with_capacity(hooks.len());
+        for (command, mut child) in hooks.into_iter() {
+            match child.try_wait() {
+                Ok(None) => running.push((command, child)),
+                Ok(Some(status)) => {
+                    let level: LogLevel = match status.success() {
+                        true => LogLevel::Info,
+                        false => LogLevel::Warn,
+                    };
+                    self.log(
+                        level,
+                        format!("Command \"{}\" terminated with {}", command, status).as_str(),
+                    );
+                    logged = true;
+                }
+                Err(err) => {
+                    self.log(
+                        LogLevel::Error,
+                        format!("Could not get status of command \"{}\": {}", command, err)
+                            .as_str(),
+                    );
+                    logged = true;
+                }
+            }
+        }
+        self.hooks = running;
+        logged
     }
 
     /// ### log_and_alert
@@ -52,6 +157,58 @@ impl FileTransferActivity {
         self.input_mode = InputMode::Popup(PopupType::Alert(color, msg));
     }
 
+    /// ### is_write_key
+    ///
+    /// Returns whether the key runs an operation which modifies files (delete, mkdir, rename, link, edit, sync and upload),
+    /// when pressed in the local (`local` is true) or in the remote explorer
+    pub(super) fn is_write_key(key: KeyCode, local: bool) -> bool {
+        match key {
+            KeyCode::Delete => true,
+            KeyCode::F(5) => local, // Upload
+            KeyCode::Char(ch) => match ch.to_ascii_lowercase() {
+                'd' | 'e' | 'n' | 'o' | 'r' => true,
+                'y' => true,        // Sync (may delete destination entries)
+                'k' => !local,      // Hard link (remote only)
+                'c' => !local,      // Change mode (remote only)
+                's' | ' ' => local, // Upload; downloads are allowed
+                'b' => local,       // Enqueue upload
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// ### log_readonly
+    ///
+    /// Report an operation refused in readonly mode
+    pub(super) fn log_readonly(&mut self) {
+        self.log(
+            LogLevel::Warn,
+            "Operation not allowed: Gateway is in readonly mode",
+        );
+    }
+
+    /// ### new_connection
+    ///
+    /// Disconnect and go back to the authentication form, to connect to another host.
+    /// If post transfer commands are still running, the user is asked to confirm first,
+    /// since their exit status won't be reported anymore
+    pub(super) fn new_connection(&mut self) {
+        match self.hooks.is_empty() {
+            true => self.disconnect(),
+            false => {
+                self.input_mode = InputMode::Popup(PopupType::YesNo(
+                    format!(
+                        "{} post transfer command(s) still running; start a new connection?",
+                        self.hooks.len()
+                    ),
+                    FileTransferActivity::disconnect,
+                    FileTransferActivity::callback_nothing_to_do,
+                ))
+            }
+        }
+    }
+
     /// ### create_quit_popup
     ///
     /// Create quit popup input mode (since must be shared between different input handlers)
@@ -79,8 +236,64 @@ impl FileTransferActivity {
     /// Switch input field based on current input field
     pub(super) fn switch_input_field(&mut self) {
         self.input_field = match self.input_field {
-            InputField::Explorer => InputField::Logs,
-            InputField::Logs => InputField::Explorer,
+            InputField::Explorer if self.log_visible => InputField::Logs,
+            _ => InputField::Explorer, // Log panel can't be selected when hidden
+        }
+    }
+
+    /// ### toggle_log_panel
+    ///
+    /// Show/hide the log panel and save the preference in the user configuration.
+    /// If the log panel gets hidden while selected, the focus is moved to the explorer
+    pub(super) fn toggle_log_panel(&mut self) {
+        self.log_visible = !self.log_visible;
+        if !self.log_visible {
+            self.input_field = InputField::Explorer;
+        }
+        // Persist preference
+        let show_log: bool = self.log_visible;
+        let result = match self
+            .context
+            .as_mut()
+            .and_then(|ctx| ctx.config_client.as_mut())
+        {
+            Some(config_client) => {
+                config_client.set_show_log(show_log);
+                config_client.write_config()
+            }
+            None => Ok(()),
+        };
+        if let Err(err) = result {
+            self.log(
+                LogLevel::Error,
+                format!("Could not save configuration: {}", err).as_str(),
+            );
+        }
+    }
+
+    /// ### toggle_relative_time
+    ///
+    /// Show times in explorers and log relative to now / as dates and save the preference in the user configuration
+    pub(super) fn toggle_relative_time(&mut self) {
+        self.relative_time = !self.relative_time;
+        // Persist preference
+        let relative_time: bool = self.relative_time;
+        let result = match self
+            .context
+            .as_mut()
+            .and_then(|ctx| ctx.config_client.as_mut())
+        {
+            Some(config_client) => {
+                config_client.set_relative_time(relative_time);
+                config_client.write_config()
+            }
+            None => Ok(()),
+        };
+        if let Err(err) = result {
+            self.log(
+                LogLevel::Error,
+                format!("Could not save configuration: {}", err).as_str(),
+            );
         }
     }
 }
@@ -24,13 +24,24 @@
 */
 
 // This module is split into files, cause it's just too big
+mod archive;
 mod callbacks;
+mod diff;
+mod dry_run;
+mod goto;
 mod input;
 mod layout;
 mod misc;
+mod preview;
+mod queue;
+mod recent;
 mod session;
+mod sync;
+mod tabs;
+mod undo;
 
 // Dependencies
+extern crate bytesize;
 extern crate chrono;
 extern crate crossterm;
 extern crate textwrap;
@@ -39,28 +50,66 @@ extern crate unicode_width;
 
 // locals
 use super::{Activity, Context};
+use crate::config::{GroupDirs, KeyBinding, KeyName, LineEnding, RedrawInterval, UiConfig};
 use crate::filetransfer::FileTransferProtocol;
 
 // File transfer
-use crate::filetransfer::ftp_transfer::FtpFileTransfer;
-use crate::filetransfer::scp_transfer::ScpFileTransfer;
-use crate::filetransfer::sftp_transfer::SftpFileTransfer;
-use crate::filetransfer::FileTransfer;
-use crate::fs::FsEntry;
+use crate::filetransfer::proxy_jump::JumpHost;
+use crate::filetransfer::{new_client, FileTransfer, SshIdentity};
+use crate::fs::{FsEntry, FsFile};
+use crate::system::config_client::ConfigClient;
+use crate::system::environment;
+use crate::system::log_file::LogFile;
+use crate::utils::fmt::fmt_millis;
+use archive::ArchiveState;
+use diff::DiffState;
+use goto::GotoCompletion;
+use preview::PreviewState;
+use queue::TransferQueue;
+use recent::RecentTransfer;
+use tabs::RemoteTab;
+use undo::UndoAction;
 
 // Includes
+use bytesize::ByteSize;
 use chrono::{DateTime, Local};
 use crossterm::event::Event as InputEvent;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::process::Child;
+use std::time::{Duration, Instant};
 use tui::style::Color;
 
 // Types
 type DialogCallback = fn(&mut FileTransferActivity);
 type OnInputSubmitCallback = fn(&mut FileTransferActivity, String);
 
+// Amount of times the user can re-enter credentials after an authentication failure
+const MAX_AUTH_RETRIES: u8 = 3;
+// Terminal width below which only the focused explorer is displayed
+const SINGLE_PANE_MAX_WIDTH: u16 = 80;
+// Amount of bytes displayed from the end of a file when starting to follow it
+const FOLLOW_INITIAL_BYTES: usize = 8192;
+// Maximum amount of lines kept while following a file
+const FOLLOW_MAX_LINES: usize = 1024;
+// Interval between checks for new data while following a file
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Time the transfer result markers are displayed in the explorer
+const TRANSFER_RESULT_TIMEOUT: Duration = Duration::from_secs(30);
+// Time window of the samples used to calculate the throughput of a transfer queue
+const QUEUE_THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+// Size of the transfer buffer; initial size when auto-tuning
+const BUFFER_SIZE: usize = 65536;
+// Size the auto-tuned transfer buffer never drops below
+const BUFFER_MIN_SIZE: usize = 8192;
+// Time the throughput is measured for, before adjusting the transfer buffer size
+const BUFFER_TUNE_INTERVAL: Duration = Duration::from_secs(1);
+// Throughput increase required to keep growing the transfer buffer (10%)
+const BUFFER_TUNE_GAIN: f64 = 1.1;
+// Interval between checks for input events while a remote directory is listed
+const SCAN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// ### FileTransferParams
 ///
 /// Holds connection parameters for file transfers
@@ -70,6 +119,9 @@ pub struct FileTransferParams {
     pub protocol: FileTransferProtocol,
     pub username: Option<String>,
     pub password: Option<String>,
+    pub jump_hosts: Vec<JumpHost>, // SSH jump hosts to go through (SFTP/SCP only)
+    pub identity: Option<SshIdentity>, // Private key to authenticate with (SFTP/SCP only)
+    pub buffer_size: Option<usize>, // Fixed size (bytes) of the transfer buffer; if None, the configured one is used
 }
 
 /// ### InputField
@@ -96,12 +148,20 @@ enum DialogYesNoOption {
 #[derive(Clone)]
 enum PopupType {
     Alert(Color, String),                          // Block color; Block text
-    Fatal(String),                                 // Must quit after being hidden
-    FileInfo,                                      // Show info about current file
-    Help,                                          // Show Help
-    Input(String, OnInputSubmitCallback),          // Input description; Callback for submit
-    Progress(String),                              // Progress block text
-    Wait(String),                                  // Wait block text
+    Archive,                                       // Browse the content of an archive file
+    Diff,                                  // Show the diff between a local and a remote file
+    Fatal(String),                         // Must quit after being hidden
+    Follow,                                // Show the remote file being followed
+    Goto,     // Input the working directory to change to, with completion
+    FileInfo, // Show info about current file
+    Filter, // Input the text the displayed entries' names must contain, filtering them while typing
+    Help,   // Show Help
+    Image,  // Show the preview of an image file
+    Input(String, OnInputSubmitCallback), // Input description; Callback for submit
+    Progress(String), // Progress block text
+    Recent, // Show the files transferred during the session
+    Secret(String, OnInputSubmitCallback), // As `Input`, but input text is masked
+    Wait(String), // Wait block text
     YesNo(String, DialogCallback, DialogCallback), // Yes, no callback
 }
 
@@ -115,27 +175,407 @@ enum InputMode {
     Popup(PopupType),
 }
 
+/// ## ExplorerFilter
+///
+/// Describes which kind of entries are displayed in a file explorer
+#[derive(Clone, Copy, PartialEq, std::fmt::Debug)]
+enum ExplorerFilter {
+    AllEntries,
+    DirsOnly,
+    FilesOnly,
+}
+
+impl ExplorerFilter {
+    /// ### next
+    ///
+    /// Get the filter which follows the current one
+    pub fn next(self) -> ExplorerFilter {
+        match self {
+            ExplorerFilter::AllEntries => ExplorerFilter::DirsOnly,
+            ExplorerFilter::DirsOnly => ExplorerFilter::FilesOnly,
+            ExplorerFilter::FilesOnly => ExplorerFilter::AllEntries,
+        }
+    }
+
+    /// ### matches
+    ///
+    /// Returns whether the entry must be displayed with this filter.
+    /// Symlinks are matched against the entry they point to
+    pub fn matches(self, entry: &FsEntry) -> bool {
+        match self {
+            ExplorerFilter::AllEntries => true,
+            ExplorerFilter::DirsOnly => ExplorerFilter::is_dir(entry),
+            ExplorerFilter::FilesOnly => !ExplorerFilter::is_dir(entry),
+        }
+    }
+
+    /// ### is_dir
+    ///
+    /// Returns whether entry is a directory or a symlink pointing to a directory
+    fn is_dir(entry: &FsEntry) -> bool {
+        let symlink: &Option<Box<FsEntry>> = match entry {
+            FsEntry::Directory(dir) => &dir.symlink,
+            FsEntry::File(file) => &file.symlink,
+        };
+        match symlink {
+            Some(target) => ExplorerFilter::is_dir(target),
+            None => entry.is_dir(),
+        }
+    }
+}
+
+/// ## SortMode
+///
+/// Key the explorer entries are sorted by; directories are then grouped according to `GroupDirs`
+#[derive(Clone, Copy, std::fmt::Debug, PartialEq)]
+enum SortMode {
+    Name,     // Case insensitive name
+    Size,     // Size, smallest first
+    Modified, // Last change time, oldest first
+}
+
+impl SortMode {
+    /// ### next
+    ///
+    /// Get the sort mode which follows the current one
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Name => SortMode::Size,
+            SortMode::Size => SortMode::Modified,
+            SortMode::Modified => SortMode::Name,
+        }
+    }
+}
+
 /// ## FileExplorer
 ///
 /// File explorer states
 struct FileExplorer {
-    pub wrkdir: PathBuf,         // Current directory
-    pub index: usize,            // Selected file
-    pub files: Vec<FsEntry>,     // Files in directory
-    dirstack: VecDeque<PathBuf>, // Stack of visited directory (max 16)
+    pub wrkdir: PathBuf,                       // Current directory
+    pub index: usize,                          // Selected file
+    pub files: Vec<FsEntry>,                   // Files in directory
+    dirstack: VecDeque<PathBuf>,               // Stack of visited directory (max 16)
+    wrap: bool, // Does selection wrap around when reaching the list boundaries?
+    filter: ExplorerFilter, // Kind of entries displayed
+    name_filter: Option<String>, // Text the names of the displayed entries contain (case insensitive)
+    marked: HashMap<PathBuf, FsEntry>, // Marked entries, by absolute path
+    results: HashMap<PathBuf, TransferResult>, // Result of the entries recently transferred
+    results_time: Instant,       // Instant the transfer results were set
+    sort_by: SortMode,           // Key the entries are sorted by
+    sort_desc: bool,             // Whether the sort key order is reversed
+    group_dirs: GroupDirs,       // Where directories are listed, whatever the sort key
 }
 
 impl FileExplorer {
     /// ### new
     ///
     /// Instantiates a new FileExplorer
-    pub fn new() -> FileExplorer {
+    pub fn new(wrap: bool, group_dirs: GroupDirs) -> FileExplorer {
         FileExplorer {
             wrkdir: PathBuf::from("/"),
             index: 0,
             files: Vec::new(),
             dirstack: VecDeque::with_capacity(16),
+            wrap,
+            filter: ExplorerFilter::AllEntries,
+            name_filter: None,
+            marked: HashMap::new(),
+            results: HashMap::new(),
+            results_time: Instant::now(),
+            sort_by: SortMode::Name,
+            sort_desc: false,
+            group_dirs,
+        }
+    }
+
+    /// ### iter_files
+    ///
+    /// Iterate over the entries which are displayed (i.e. which pass the filters)
+    pub fn iter_files(&self) -> impl Iterator<Item = &FsEntry> {
+        let filter: ExplorerFilter = self.filter;
+        let name_filter: Option<String> = self.name_filter.as_ref().map(|x| x.to_lowercase());
+        self.files.iter().filter(move |x| {
+            filter.matches(x)
+                && match name_filter.as_ref() {
+                    Some(name_filter) => x.get_name().to_lowercase().contains(name_filter.as_str()),
+                    None => true,
+                }
+        })
+    }
+
+    /// ### count
+    ///
+    /// Returns the amount of entries displayed
+    pub fn count(&self) -> usize {
+        self.iter_files().count()
+    }
+
+    /// ### get_selected
+    ///
+    /// Get the selected entry among those displayed
+    pub fn get_selected(&self) -> Option<&FsEntry> {
+        self.iter_files().nth(self.index)
+    }
+
+    /// ### clamp_index
+    ///
+    /// Keep index if possible, otherwise set it to the last displayed entry
+    pub fn clamp_index(&mut self) {
+        let count: usize = self.count();
+        if self.index >= count {
+            self.index = count.saturating_sub(1);
+        }
+    }
+
+    /// ### toggle_filter
+    ///
+    /// Switch to the next filter and clamp index against the displayed entries
+    pub fn toggle_filter(&mut self) {
+        self.filter = self.filter.next();
+        self.clamp_index();
+    }
+
+    /// ### set_name_filter
+    ///
+    /// Display only the entries whose name contains `text`, ignoring case; an empty text displays all the entries.
+    /// Index is clamped against the displayed entries
+    pub fn set_name_filter(&mut self, text: &str) {
+        self.name_filter = match text.is_empty() {
+            true => None,
+            false => Some(String::from(text)),
+        };
+        self.clamp_index();
+    }
+
+    /// ### filter_label
+    ///
+    /// Get the label describing the active filters, to be displayed in the explorer title
+    pub fn filter_label(&self) -> String {
+        let kind: &str = match self.filter {
+            ExplorerFilter::AllEntries => "",
+            ExplorerFilter::DirsOnly => "[dirs only] ",
+            ExplorerFilter::FilesOnly => "[files only] ",
+        };
+        match self.name_filter.as_ref() {
+            Some(name_filter) => format!("{}[/{}] ", kind, name_filter),
+            None => String::from(kind),
+        }
+    }
+
+    /// ### sort_label
+    ///
+    /// Get the label describing the sort mode, to be displayed in the explorer title; empty when sorting by name, ascending
+    pub fn sort_label(&self) -> String {
+        let key: &str = match self.sort_by {
+            SortMode::Name => "name",
+            SortMode::Size => "size",
+            SortMode::Modified => "modified",
+        };
+        match (self.sort_by, self.sort_desc) {
+            (SortMode::Name, false) => String::new(),
+            (_, false) => format!("[by {}] ", key),
+            (_, true) => format!("[by {}, desc] ", key),
+        }
+    }
+
+    /// ### cycle_sort
+    ///
+    /// Sort entries by the next sort mode (name, size, modified), keeping the selected entry selected
+    pub fn cycle_sort(&mut self) {
+        self.sort_by = self.sort_by.next();
+        self.resort();
+    }
+
+    /// ### toggle_sort_order
+    ///
+    /// Reverse the order of the sort key (ascending/descending), keeping the selected entry selected
+    pub fn toggle_sort_order(&mut self) {
+        self.sort_desc = !self.sort_desc;
+        self.resort();
+    }
+
+    /// ### resort
+    ///
+    /// Sort entries again, then select the entry which was selected before
+    fn resort(&mut self) {
+        let selected: Option<PathBuf> = self.get_selected().map(|x| x.get_abs_path());
+        self.sort_files();
+        if let Some(index) = selected
+            .and_then(|selected| self.iter_files().position(|x| x.get_abs_path() == selected))
+        {
+            self.index = index;
+        }
+    }
+
+    /// ### toggle_mark
+    ///
+    /// Mark the selected entry, or unmark it if already marked
+    pub fn toggle_mark(&mut self) {
+        if let Some(entry) = self.get_selected().cloned() {
+            let path: PathBuf = entry.get_abs_path();
+            if self.marked.remove(&path).is_none() {
+                self.marked.insert(path, entry);
+            }
+        }
+    }
+
+    /// ### is_marked
+    ///
+    /// Returns whether provided entry is marked
+    pub fn is_marked(&self, entry: &FsEntry) -> bool {
+        self.marked.contains_key(&entry.get_abs_path())
+    }
+
+    /// ### clear_marks
+    ///
+    /// Unmark all entries
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+    }
+
+    /// ### get_marked
+    ///
+    /// Get the marked entries in the current directory
+    pub fn get_marked(&self) -> Vec<&FsEntry> {
+        self.files.iter().filter(|x| self.is_marked(x)).collect()
+    }
+
+    /// ### get_marked_or_selected
+    ///
+    /// Get a copy of the marked entries; if no entry is marked, get the selected entry
+    pub fn get_marked_or_selected(&self) -> Vec<FsEntry> {
+        match self.get_marked() {
+            marked if marked.is_empty() => self.get_selected().cloned().into_iter().collect(),
+            marked => marked.into_iter().cloned().collect(),
+        }
+    }
+
+    /// ### get_all_marked_or_selected
+    ///
+    /// As `get_marked_or_selected`, but the entries marked in other directories are returned too, sorted by path
+    pub fn get_all_marked_or_selected(&self) -> Vec<FsEntry> {
+        let mut marked: Vec<FsEntry> = self.marked.values().cloned().collect();
+        match marked.is_empty() {
+            true => self.get_selected().cloned().into_iter().collect(),
+            false => {
+                marked.sort_by_key(|x| x.get_abs_path());
+                marked
+            }
+        }
+    }
+
+    /// ### mark_matching
+    ///
+    /// Mark the entries in the current directory satisfying `predicate`.
+    /// Returns the amount of matching entries
+    pub fn mark_matching<F: Fn(&FsEntry) -> bool>(&mut self, predicate: F) -> usize {
+        let matching: Vec<(PathBuf, FsEntry)> = self
+            .files
+            .iter()
+            .filter(|x| predicate(x))
+            .map(|x| (x.get_abs_path(), x.clone()))
+            .collect();
+        let count: usize = matching.len();
+        self.marked.extend(matching);
+        count
+    }
+
+    /// ### restore_marks
+    ///
+    /// Mark the entries matching the provided paths.
+    /// A path matches an entry if it is the entry absolute path, otherwise if it is the name of an entry in the current directory.
+    /// Returns the paths which didn't match any entry
+    pub fn restore_marks(&mut self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let mut missing: Vec<PathBuf> = Vec::new();
+        for path in paths.iter() {
+            let matched: Option<FsEntry> = self
+                .files
+                .iter()
+                .find(|x| x.get_abs_path() == *path)
+                .or_else(|| {
+                    self.files.iter().find(|x| {
+                        path.file_name()
+                            .map(|name| name.to_string_lossy() == x.get_name())
+                            .unwrap_or(false)
+                    })
+                })
+                .cloned();
+            match matched {
+                Some(entry) => {
+                    self.marked.insert(entry.get_abs_path(), entry);
+                }
+                None => missing.push(path.clone()),
+            }
+        }
+        missing
+    }
+
+    /// ### set_results
+    ///
+    /// Set the result of the entries transferred (by absolute path); results are displayed until `TRANSFER_RESULT_TIMEOUT`
+    pub fn set_results(&mut self, results: Vec<(PathBuf, TransferResult)>) {
+        self.results.extend(results);
+        self.results_time = Instant::now();
+    }
+
+    /// ### get_result
+    ///
+    /// Get the result of the last transfer of provided entry, if recent
+    pub fn get_result(&self, entry: &FsEntry) -> Option<TransferResult> {
+        self.results.get(&entry.get_abs_path()).copied()
+    }
+
+    /// ### clear_results
+    ///
+    /// Clear transfer results
+    pub fn clear_results(&mut self) {
+        self.results.clear();
+    }
+
+    /// ### expire_results
+    ///
+    /// Clear transfer results if they've been displayed for `TRANSFER_RESULT_TIMEOUT`.
+    /// Returns whether results have been cleared
+    pub fn expire_results(&mut self) -> bool {
+        if self.results.is_empty() || self.results_time.elapsed() < TRANSFER_RESULT_TIMEOUT {
+            return false;
+        }
+        self.clear_results();
+        true
+    }
+
+    /// ### move_index_up
+    ///
+    /// Move selected index up by `step`; index stops at the first entry.
+    /// If wrap is enabled and the first entry is already selected, the last entry gets selected
+    pub fn move_index_up(&mut self, step: usize) {
+        let count: usize = self.count();
+        if count == 0 {
+            self.index = 0;
+            return;
         }
+        self.index = match self.index {
+            0 if self.wrap => count - 1,
+            _ => self.index.saturating_sub(step),
+        };
+    }
+
+    /// ### move_index_down
+    ///
+    /// Move selected index down by `step`; index stops at the last entry.
+    /// If wrap is enabled and the last entry is already selected, the first entry gets selected
+    pub fn move_index_down(&mut self, step: usize) {
+        let count: usize = self.count();
+        if count == 0 {
+            self.index = 0;
+            return;
+        }
+        let last: usize = count - 1;
+        self.index = match self.index >= last {
+            true if self.wrap => 0,
+            true => last,
+            false => std::cmp::min(self.index + step, last),
+        };
     }
 
     /// ### pushd
@@ -157,14 +597,34 @@ impl FileExplorer {
         self.dirstack.pop_front()
     }
 
-    /// ### sort_files_by_name
+    /// ### sort_files
     ///
-    /// Sort explorer files by their name
-    pub fn sort_files_by_name(&mut self) {
-        self.files.sort_by_key(|x: &FsEntry| match x {
-            FsEntry::Directory(dir) => dir.name.as_str().to_lowercase(),
-            FsEntry::File(file) => file.name.as_str().to_lowercase(),
-        });
+    /// Sort explorer files by `sort_by` (reversed if `sort_desc`), then group directories (and symlinks to directories)
+    /// as `group_dirs` says; grouping keeps the order of the sort key
+    pub fn sort_files(&mut self) {
+        match self.sort_by {
+            // Lowercase names are computed once per entry, rather than for each comparison
+            SortMode::Name => self.files.sort_by_cached_key(|x: &FsEntry| match x {
+                FsEntry::Directory(dir) => dir.name.as_str().to_lowercase(),
+                FsEntry::File(file) => file.name.as_str().to_lowercase(),
+            }),
+            SortMode::Size => self.files.sort_by_key(|x: &FsEntry| x.get_size()),
+            SortMode::Modified => self
+                .files
+                .sort_by_key(|x: &FsEntry| x.get_last_change_time()),
+        }
+        if self.sort_desc {
+            self.files.reverse();
+        }
+        match self.group_dirs {
+            GroupDirs::First => self
+                .files
+                .sort_by_key(|x: &FsEntry| !ExplorerFilter::is_dir(x)),
+            GroupDirs::Last => self
+                .files
+                .sort_by_key(|x: &FsEntry| ExplorerFilter::is_dir(x)),
+            GroupDirs::Mixed => {}
+        }
     }
 }
 
@@ -176,10 +636,21 @@ enum FileExplorerTab {
     Remote,
 }
 
+/// ## DisconnectRequest
+///
+/// State of a request to disconnect made while a transfer is in progress
+#[derive(Debug, PartialEq)]
+enum DisconnectRequest {
+    None,
+    Confirm, // Disconnect key pressed once; pressing it again aborts the transfer
+    Pending, // Transfer aborted; disconnect once it has terminated
+}
+
 /// ## LogLevel
 ///
-/// Log level type
-enum LogLevel {
+/// Log level type; levels are ordered from the most to the least severe
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum LogLevel {
     Error,
     Warn,
     Info,
@@ -192,6 +663,7 @@ struct LogRecord {
     pub time: DateTime<Local>,
     pub level: LogLevel,
     pub msg: String,
+    pub transfer: Option<RecentTransfer>, // Failed file transfer, which can be retried from the log panel
 }
 
 impl LogRecord {
@@ -203,6 +675,7 @@ impl LogRecord {
             time: Local::now(),
             level,
             msg: String::from(msg),
+            transfer: None,
         }
     }
 }
@@ -210,12 +683,13 @@ impl LogRecord {
 /// ### TransferStates
 ///
 /// TransferStates contains the states related to the transfer process
-struct TransferStates {
+pub struct TransferStates {
     pub progress: f64,        // Current read/write progress (percentage)
     pub started: Instant,     // Instant the transfer process started
     pub aborted: bool,        // Describes whether the transfer process has been aborted
     pub bytes_written: usize, // Bytes written during transfer
     pub bytes_total: usize,   // Total bytes to write
+    pub bytes_resumed: usize, // Bytes already transferred before the transfer was resumed
 }
 
 impl TransferStates {
@@ -229,6 +703,7 @@ impl TransferStates {
             aborted: false,
             bytes_written: 0,
             bytes_total: 0,
+            bytes_resumed: 0,
         }
     }
 
@@ -241,14 +716,29 @@ impl TransferStates {
         self.aborted = false;
         self.bytes_written = 0;
         self.bytes_total = 0;
+        self.bytes_resumed = 0;
+    }
+
+    /// ### resume
+    ///
+    /// Start the transfer from `offset` of `sz` bytes; the bytes before it don't count for the speed
+    pub fn resume(&mut self, offset: usize, sz: usize) {
+        self.bytes_resumed = offset;
+        self.set_progress(offset, sz);
     }
 
     /// ### set_progress
     ///
-    /// Calculate progress percentage based on current progress
+    /// Calculate progress percentage based on current progress.
+    /// If total size is 0, progress is always 100%
     pub fn set_progress(&mut self, w: usize, sz: usize) {
         self.bytes_written = w;
         self.bytes_total = sz;
+        if sz == 0 {
+            // NOTE: would divide by 0
+            self.progress = 100.0;
+            return;
+        }
         let mut prog: f64 = ((self.bytes_written as f64) * 100.0) / (self.bytes_total as f64);
         // Check value
         if prog > 100.0 {
@@ -267,9 +757,22 @@ impl TransferStates {
         let elapsed_secs: u64 = self.started.elapsed().as_secs();
         match elapsed_secs {
             0 => 0, // NOTE: would divide by 0 :D
-            _ => self.bytes_written as u64 / elapsed_secs,
+            _ => self.bytes_written.saturating_sub(self.bytes_resumed) as u64 / elapsed_secs,
         }
     }
+
+    /// ### to_json
+    ///
+    /// Format transfer states as a single line JSON object (`{"bytes_done":..,"bytes_total":..,"rate":..}`),
+    /// where rate is in bytes per second. Used to report progress to scripts
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"bytes_done\":{},\"bytes_total\":{},\"rate\":{}}}",
+            self.bytes_written,
+            self.bytes_total,
+            self.bytes_per_second()
+        )
+    }
 }
 
 impl Default for TransferStates {
@@ -278,57 +781,730 @@ impl Default for TransferStates {
     }
 }
 
+/// ## TransferResult
+///
+/// Result of the transfer of an entry
+#[derive(Clone, Copy, std::fmt::Debug, PartialEq)]
+enum TransferResult {
+    Success,
+    Skipped,
+    Failed,
+}
+
+/// ### TransferSummary
+///
+/// TransferSummary accumulates the result of each file transferred during a batch (recursive) transfer
+struct TransferSummary {
+    pub started: Instant,                        // Instant the batch started
+    pub succeeded: usize,                        // Amount of files transferred successfully
+    pub skipped: usize,                          // Amount of entries skipped (e.g. after abort)
+    pub failed: Vec<PathBuf>,                    // Files which couldn't be transferred
+    pub bytes_total: usize,                      // Total bytes transferred
+    pub results: Vec<(PathBuf, TransferResult)>, // Result of each entry (by absolute path)
+    pub depth: usize, // Depth of the directory being walked, relative to the outermost entry
+    visited: HashSet<PathBuf>, // Directories already walked (by their real path), to break symlink loops
+}
+
+impl TransferSummary {
+    /// ### new
+    ///
+    /// Instantiates a new transfer summary
+    pub fn new() -> TransferSummary {
+        TransferSummary {
+            started: Instant::now(),
+            succeeded: 0,
+            skipped: 0,
+            failed: Vec::new(),
+            bytes_total: 0,
+            results: Vec::new(),
+            depth: 0,
+            visited: HashSet::new(),
+        }
+    }
+
+    /// ### visit
+    ///
+    /// Register the directory at `path` (the real path, symlinks resolved) as walked.
+    /// Returns false if it had already been walked during this batch
+    pub fn visit(&mut self, path: &Path) -> bool {
+        self.visited.insert(PathBuf::from(path))
+    }
+
+    /// ### add_success
+    ///
+    /// Register a file transferred successfully
+    pub fn add_success(&mut self, bytes: usize) {
+        self.succeeded += 1;
+        self.bytes_total += bytes;
+    }
+
+    /// ### add_skipped
+    ///
+    /// Register a skipped entry
+    pub fn add_skipped(&mut self) {
+        self.skipped += 1;
+    }
+
+    /// ### add_failure
+    ///
+    /// Register a file which couldn't be transferred
+    pub fn add_failure(&mut self, path: &Path) {
+        self.failed.push(PathBuf::from(path));
+    }
+
+    /// ### set_result
+    ///
+    /// Set the result of the entry at provided path
+    pub fn set_result(&mut self, path: &Path, result: TransferResult) {
+        self.results.push((PathBuf::from(path), result));
+    }
+
+    /// ### result
+    ///
+    /// Get the overall result of the batch: failed if any file failed, skipped if nothing was transferred
+    pub fn result(&self) -> TransferResult {
+        match (self.failed.is_empty(), self.succeeded) {
+            (false, _) => TransferResult::Failed,
+            (true, 0) if self.skipped > 0 => TransferResult::Skipped,
+            (true, _) => TransferResult::Success,
+        }
+    }
+
+    /// ### result_since
+    ///
+    /// Get the overall result of the entries registered since the `first` result:
+    /// failed if any of them failed, skipped if none succeeded and some were skipped
+    pub fn result_since(&self, first: usize) -> TransferResult {
+        let results: &[(PathBuf, TransferResult)] = self.results.get(first..).unwrap_or(&[]);
+        let has = |x: TransferResult| results.iter().any(|(_, result)| *result == x);
+        match (has(TransferResult::Failed), has(TransferResult::Success)) {
+            (true, _) => TransferResult::Failed,
+            (false, false) if has(TransferResult::Skipped) => TransferResult::Skipped,
+            (false, _) => TransferResult::Success,
+        }
+    }
+
+    /// ### report
+    ///
+    /// Make the summary message for the batch; at most 3 failed files are listed
+    pub fn report(&self, operation: &str) -> String {
+        let mut report: String = format!(
+            "{} completed: {} succeeded, {} skipped, {} failed ({} in {} seconds)",
+            operation,
+            self.succeeded,
+            self.skipped,
+            self.failed.len(),
+            ByteSize(self.bytes_total as u64),
+            fmt_millis(self.started.elapsed())
+        );
+        if !self.failed.is_empty() {
+            let failed: Vec<String> = self
+                .failed
+                .iter()
+                .take(3)
+                .map(|x| format!("\"{}\"", x.display()))
+                .collect();
+            report.push_str(format!(". Failed: {}", failed.join(", ")).as_str());
+            if self.failed.len() > 3 {
+                report.push_str(format!(" and {} more (see log)", self.failed.len() - 3).as_str());
+            }
+        }
+        report
+    }
+}
+
+/// ### PendingTransfer
+///
+/// PendingTransfer contains the entries to transfer once the user has confirmed the transfer
+struct PendingTransfer {
+    pub entries: Vec<(FsEntry, Option<String>)>, // Entries to transfer and their destination name
+    pub dst: PathBuf,                            // Destination directory
+    pub upload: bool,                            // Transfer from local to remote?
+    pub size: Option<usize>,                     // Total bytes to transfer, if known
+}
+
+/// ### QueueProgress
+///
+/// QueueProgress tracks the progress of all the entries of a transfer request, to estimate when it will complete
+struct QueueProgress {
+    pub bytes_total: Option<usize>, // Total bytes to transfer; None if unknown
+    pub bytes_done: usize,          // Bytes of the files already transferred
+    samples: VecDeque<(Instant, usize)>, // Recent samples of the bytes transferred
+}
+
+impl QueueProgress {
+    /// ### new
+    ///
+    /// Instantiates a new QueueProgress
+    pub fn new(bytes_total: Option<usize>) -> QueueProgress {
+        let mut samples: VecDeque<(Instant, usize)> = VecDeque::new();
+        samples.push_back((Instant::now(), 0));
+        QueueProgress {
+            bytes_total,
+            bytes_done: 0,
+            samples,
+        }
+    }
+
+    /// ### file_done
+    ///
+    /// Register a file which has been transferred
+    pub fn file_done(&mut self, bytes: usize) {
+        self.bytes_done += bytes;
+    }
+
+    /// ### sample
+    ///
+    /// Sample the bytes transferred, given the bytes written of the file in progress.
+    /// Samples older than `QUEUE_THROUGHPUT_WINDOW` are discarded, but the most recent of them
+    pub fn sample(&mut self, bytes_written: usize) {
+        self.sample_at(Instant::now(), bytes_written);
+    }
+
+    /// ### throughput
+    ///
+    /// Get the average throughput (bytes/second) of the recent samples
+    pub fn throughput(&self) -> Option<f64> {
+        let (first, last) = (self.samples.front()?, self.samples.back()?);
+        let elapsed: f64 = last.0.duration_since(first.0).as_secs_f64();
+        match elapsed > 0.0 {
+            true => Some(last.1.saturating_sub(first.1) as f64 / elapsed),
+            false => None,
+        }
+    }
+
+    /// ### eta
+    ///
+    /// Get the estimated time to transfer the remaining bytes; None if total bytes or throughput are unknown
+    pub fn eta(&self, bytes_written: usize) -> Option<Duration> {
+        let remaining: usize = self
+            .bytes_total?
+            .saturating_sub(self.bytes_done + bytes_written);
+        match self.throughput() {
+            Some(throughput) if throughput > 0.0 => {
+                Some(Duration::from_secs_f64(remaining as f64 / throughput))
+            }
+            _ => None,
+        }
+    }
+
+    /// ### report
+    ///
+    /// Make the progress report (bytes transferred, total bytes and ETA) to display.
+    /// ETA is `--` if it can't be estimated
+    pub fn report(&self, bytes_written: usize) -> String {
+        let done: ByteSize = ByteSize((self.bytes_done + bytes_written) as u64);
+        let eta: String = match self.eta(bytes_written) {
+            Some(eta) => format!(
+                "{:0width$}:{:0width$}",
+                eta.as_secs() / 60,
+                eta.as_secs() % 60,
+                width = 2
+            ),
+            None => String::from("--"),
+        };
+        match self.bytes_total {
+            Some(total) => format!("{} / {} - ETA {}", done, ByteSize(total as u64), eta),
+            None => format!("{} - ETA {}", done, eta),
+        }
+    }
+
+    fn sample_at(&mut self, instant: Instant, bytes_written: usize) {
+        self.samples
+            .push_back((instant, self.bytes_done + bytes_written));
+        while self.samples.len() > 2
+            && instant.duration_since(self.samples[1].0) >= QUEUE_THROUGHPUT_WINDOW
+        {
+            self.samples.pop_front();
+        }
+    }
+}
+
+/// ### RedrawThrottle
+///
+/// RedrawThrottle decides when the progress of a file transfer must be redrawn, according to the configured interval
+struct RedrawThrottle {
+    interval: RedrawInterval,
+    last_progress: f64, // Progress at the last redraw
+    last_draw: Instant, // Time of the last redraw
+}
+
+impl RedrawThrottle {
+    /// ### new
+    ///
+    /// Instantiates a new RedrawThrottle, for a transfer just started
+    pub fn new(interval: RedrawInterval) -> RedrawThrottle {
+        RedrawThrottle {
+            interval,
+            last_progress: 0.0,
+            last_draw: Instant::now(),
+        }
+    }
+
+    /// ### should_draw
+    ///
+    /// Returns whether the transfer must be redrawn, given its current progress (percentage)
+    pub fn should_draw(&mut self, progress: f64) -> bool {
+        self.should_draw_at(Instant::now(), progress)
+    }
+
+    fn should_draw_at(&mut self, instant: Instant, progress: f64) -> bool {
+        let draw: bool = match self.interval {
+            RedrawInterval::Percent(percent) => self.last_progress < progress - percent,
+            RedrawInterval::Millis(millis) => {
+                instant.duration_since(self.last_draw) >= Duration::from_millis(millis)
+            }
+        };
+        if draw {
+            self.last_progress = progress;
+            self.last_draw = instant;
+        }
+        draw
+    }
+}
+
+/// ### BufferTuner
+///
+/// BufferTuner chooses the size of the transfer buffer. When auto-tuning, the size is doubled each `BUFFER_TUNE_INTERVAL`
+/// while the throughput keeps increasing; once it plateaus, the previous size is restored. Otherwise the size is fixed
+struct BufferTuner {
+    pub size: usize,              // Current buffer size
+    auto_tune: bool,              // Whether the size is adjusted
+    max_size: usize,              // Size the buffer never exceeds
+    growing: bool,                // Whether a bigger size is still being tried
+    last_throughput: Option<f64>, // Throughput measured with the previous size
+    window: (Instant, usize),     // Start of the measurement and bytes written at that time
+}
+
+impl BufferTuner {
+    /// ### new
+    ///
+    /// Instantiates a new BufferTuner; `max_size` is raised to `BUFFER_MIN_SIZE` if smaller
+    pub fn new(auto_tune: bool, max_size: usize) -> BufferTuner {
+        let max_size: usize = max_size.max(BUFFER_MIN_SIZE);
+        BufferTuner {
+            size: match auto_tune {
+                true => BUFFER_SIZE.min(max_size),
+                false => BUFFER_SIZE,
+            },
+            auto_tune,
+            max_size,
+            growing: auto_tune,
+            last_throughput: None,
+            window: (Instant::now(), 0),
+        }
+    }
+
+    /// ### fixed
+    ///
+    /// Instantiates a new BufferTuner which is never adjusted, with the provided `size`
+    pub fn fixed(size: usize) -> BufferTuner {
+        BufferTuner {
+            size,
+            auto_tune: false,
+            max_size: size,
+            growing: false,
+            last_throughput: None,
+            window: (Instant::now(), 0),
+        }
+    }
+
+    /// ### is_auto_tune
+    ///
+    /// Returns whether the size is adjusted according to the throughput
+    pub fn is_auto_tune(&self) -> bool {
+        self.auto_tune
+    }
+
+    /// ### start
+    ///
+    /// Start measuring a new file transfer; the size found so far is kept, but bigger sizes are tried again
+    pub fn start(&mut self) {
+        self.growing = self.auto_tune;
+        self.last_throughput = None;
+        self.window = (Instant::now(), 0);
+    }
+
+    /// ### sample
+    ///
+    /// Sample the bytes written of the file in progress; eventually adjust the buffer size
+    pub fn sample(&mut self, bytes_written: usize) {
+        self.sample_at(Instant::now(), bytes_written);
+    }
+
+    fn sample_at(&mut self, instant: Instant, bytes_written: usize) {
+        let elapsed: Duration = instant.duration_since(self.window.0);
+        if !self.growing || elapsed < BUFFER_TUNE_INTERVAL {
+            return;
+        }
+        let throughput: f64 =
+            bytes_written.saturating_sub(self.window.1) as f64 / elapsed.as_secs_f64();
+        self.window = (instant, bytes_written);
+        match self.last_throughput {
+            // Plateau: the bigger buffer didn't help; back off
+            Some(last) if throughput < last * BUFFER_TUNE_GAIN => {
+                self.size = (self.size / 2).max(BUFFER_MIN_SIZE);
+                self.growing = false;
+            }
+            _ => {
+                self.last_throughput = Some(throughput);
+                let size: usize = (self.size * 2).min(self.max_size);
+                self.growing = size > self.size;
+                self.size = size;
+            }
+        }
+    }
+}
+
+/// ### FollowState
+///
+/// FollowState contains the states of a remote file being followed (as `tail -f`)
+struct FollowState {
+    pub file: FsFile,            // Followed file
+    pub offset: usize,           // Amount of bytes already read
+    pub lines: VecDeque<String>, // Lines read (most recent at back)
+    pub partial: Vec<u8>,        // Data of the last line, not terminated yet
+    pub last_poll: Instant,      // Instant the file was last polled
+}
+
+impl FollowState {
+    /// ### new
+    ///
+    /// Instantiates a new FollowState, which starts reading the file from `offset`
+    pub fn new(file: FsFile, offset: usize) -> FollowState {
+        FollowState {
+            file,
+            offset,
+            lines: VecDeque::with_capacity(FOLLOW_MAX_LINES),
+            partial: Vec::new(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// ### push
+    ///
+    /// Push data read from file; data is split into lines.
+    /// Only the most recent `FOLLOW_MAX_LINES` lines are kept
+    pub fn push(&mut self, data: &[u8]) {
+        self.offset += data.len();
+        self.partial.extend_from_slice(data);
+        while let Some(pos) = self.partial.iter().position(|x| *x == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=pos).collect();
+            self.push_line(String::from_utf8_lossy(&line[..pos]).trim_end_matches('\r'));
+        }
+    }
+
+    /// ### restart
+    ///
+    /// Restart reading file from the beginning (file has been truncated or rotated)
+    pub fn restart(&mut self) {
+        self.offset = 0;
+        self.partial.clear();
+        self.push_line("--- file truncated; following from start ---");
+    }
+
+    /// ### get_lines
+    ///
+    /// Get the last `amount` lines, including the line not terminated yet
+    pub fn get_lines(&self, amount: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().cloned().collect();
+        if !self.partial.is_empty() {
+            lines.push(String::from_utf8_lossy(self.partial.as_slice()).to_string());
+        }
+        let skip: usize = lines.len().saturating_sub(amount);
+        lines.into_iter().skip(skip).collect()
+    }
+
+    fn push_line(&mut self, line: &str) {
+        if self.lines.len() >= FOLLOW_MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(String::from(line));
+    }
+}
+
 /// ## FileTransferActivity
 ///
 /// FileTransferActivity is the data holder for the file transfer activity
 pub struct FileTransferActivity {
-    pub disconnected: bool,           // Has disconnected from remote?
-    pub quit: bool,                   // Has quit term scp?
-    context: Option<Context>,         // Context holder
-    params: FileTransferParams,       // FT connection params
-    client: Box<dyn FileTransfer>,    // File transfer client
-    local: FileExplorer,              // Local File explorer state
-    remote: FileExplorer,             // Remote File explorer state
-    tab: FileExplorerTab,             // Current selected tab
-    log_index: usize,                 // Current log index entry selected
-    log_records: VecDeque<LogRecord>, // Log records
-    log_size: usize,                  // Log records size (max)
-    input_mode: InputMode,            // Current input mode
-    input_field: InputField,          // Current selected input mode
-    input_txt: String,                // Input text
-    choice_opt: DialogYesNoOption,    // Dialog popup selected option
-    transfer: TransferStates,         // Transfer states
+    pub disconnected: bool,                    // Has disconnected from remote?
+    pub quit: bool,                            // Has quit term scp?
+    context: Option<Context>,                  // Context holder
+    params: FileTransferParams,                // FT connection params
+    client: Box<dyn FileTransfer>,             // File transfer client
+    local: FileExplorer,                       // Local File explorer state
+    remote: FileExplorer,                      // Remote File explorer state
+    tab: FileExplorerTab,                      // Current selected tab
+    log_index: usize,                          // Current log index entry selected
+    log_records: VecDeque<LogRecord>,          // Log records
+    log_size: usize,                           // Log records size (max)
+    log_file: Option<LogFile>,                 // File log records are appended to
+    log_file_path: Option<PathBuf>,            // Log file path, overriding the configured one
+    log_file_level: LogLevel, // Least severe level of the records written to the log file
+    input_mode: InputMode,    // Current input mode
+    input_field: InputField,  // Current selected input mode
+    input_txt: String,        // Input text
+    choice_opt: DialogYesNoOption, // Dialog popup selected option
+    transfer: TransferStates, // Transfer states
+    batch: Option<TransferSummary>, // Summary of the batch transfer in progress
+    confirm_size: u64,        // Transfers bigger than this size (bytes) must be confirmed; 0 never
+    max_depth: usize, // Directory levels walked by recursive transfers and deletes; 0 is unlimited
+    remote_line_ending: Option<LineEnding>, // Line endings of the remote text files; if set, they're converted on transfer
+    preserve_paths: bool, // Keep marks across directories and transfer them with their path relative to the working directory
+    follow_symlinks: bool, // Walk the directories symlinks point to during recursive downloads, instead of recreating the links
+    resume_uploads: bool, // Append to a smaller, newer remote file instead of uploading the whole file again
+    max_connections: usize, // Connections (tabs) which can be open to the same host at once; 0 is unlimited
+    clock_skew_threshold: u64, // Remote clock skew (seconds) warned about at connect; 0 disables the check
+    clock_skew_compensate: bool, // Compensate the remote clock skew when syncing
+    clock_skew: Option<i64>,   // Seconds the remote clock is ahead of the local one, if measured
+    reconnect_wrkdir: Option<PathBuf>, // Remote directory to go back to once reconnected, after a cancelled scan
+    flush_interval: u64, // Bytes to write between flushes of the upload stream; 0 flushes only at the end
+    redraw_interval: RedrawInterval, // How often the progress of a file transfer is redrawn
+    buffer: BufferTuner, // Size of the transfer buffer
+    abort_key: KeyBinding, // Key which aborts the running transfer or scan
+    pending_transfer: Option<PendingTransfer>, // Transfer waiting for confirmation
+    pending_sync: Option<PendingTransfer>, // Directory sync waiting for confirmation
+    queue_progress: Option<QueueProgress>, // Progress of the entries of the transfer in progress
+    transfer_queue: TransferQueue, // Entries enqueued for transfer, started with <CTRL+B>
+    log_visible: bool,   // Is log panel visible?
+    single_pane: bool,   // Show only the focused explorer, full-width
+    sync_browsing: bool, // Repeat the changes of directory on the other explorer
+    dry_run: bool,       // Transfers are only logged, without writing anything
+    relative_time: bool, // Show times relative to now (e.g. `2 hours ago`)
+    show_link_target: bool, // Show symlinks as `name -> target`
+    show_path_line: bool, // Show the absolute path of the selected entry in a status line
+    show_banner: bool,   // Write the server banner to the log at connect
+    readonly: bool,      // Are operations which modify files disabled?
+    fileinfo_xattrs: Option<Vec<(String, Vec<u8>)>>, // Extended attributes of the entry in the file info popup
+    fileinfo_size: Option<u64>, // Size of the local directory in the file info popup, including its subdirectories
+    terminal_title: Option<String>, // Last title set on terminal; None if terminal title is disabled
+    auth_retries: u8, // Times credentials have been re-entered after an authentication failure
+    hooks: Vec<(String, Child)>, // Post transfer commands still running (command, process)
+    follow: Option<FollowState>, // Remote file being followed
+    diff: Option<DiffState>, // Diff displayed in the diff popup
+    archive: Option<ArchiveState>, // Archive browsed in the archive popup
+    preview: Option<PreviewState>, // Image displayed in the image popup
+    remote_tabs: Vec<RemoteTab>, // Remote tabs which are not displayed
+    remote_tab_index: usize, // Position of the active remote tab among all the remote tabs
+    undo_stack: Vec<UndoAction>, // Actions which can be undone, latest last
+    trash_dir: Option<PathBuf>, // Local deletes are moved here, if set (trash mode)
+    disconnect_request: DisconnectRequest, // Disconnect requested during a transfer
+    goto_completion: Option<GotoCompletion>, // Candidates of the last completion in the goto popup
+    recent: Vec<RecentTransfer>, // Files transferred during the session, latest first
+    recent_index: usize, // Transfer selected in the recent transfers popup
 }
 
 impl FileTransferActivity {
     /// ### new
     ///
-    /// Instantiates a new FileTransferActivity
-    pub fn new(params: FileTransferParams) -> FileTransferActivity {
+    /// Instantiates a new FileTransferActivity.
+    /// The file transfer client is configured using the user configuration, if available
+    pub fn new(
+        params: FileTransferParams,
+        config_client: Option<&ConfigClient>,
+    ) -> FileTransferActivity {
         let protocol: FileTransferProtocol = params.protocol;
+        // A buffer size set on the command line is fixed, even if auto-tuning is configured
+        let buffer: BufferTuner = match (params.buffer_size, config_client) {
+            (Some(size), _) => BufferTuner::fixed(size),
+            (None, Some(config_client)) => BufferTuner::new(
+                config_client.get_buffer_auto_tune(),
+                config_client.get_buffer_max_size(),
+            ),
+            (None, None) => BufferTuner::new(false, 0),
+        };
+        let group_dirs: GroupDirs = match config_client {
+            Some(config_client) => config_client.get_group_dirs(),
+            None => GroupDirs::First,
+        };
+        let wrap_selection: bool = match config_client {
+            Some(config_client) => config_client.get_wrap_selection(),
+            None => false,
+        };
+        let log_size: usize = match config_client {
+            Some(config_client) => config_client.get_log_size().max(1),
+            None => 256,
+        };
         FileTransferActivity {
             disconnected: false,
             quit: false,
             context: None,
-            client: match protocol {
-                FileTransferProtocol::Sftp => Box::new(SftpFileTransfer::new()),
-                FileTransferProtocol::Ftp(ftps) => Box::new(FtpFileTransfer::new(ftps)),
-                FileTransferProtocol::Scp => Box::new(ScpFileTransfer::new()),
-            },
+            client: FileTransferActivity::make_client(&params, config_client),
             params,
-            local: FileExplorer::new(),
-            remote: FileExplorer::new(),
+            local: FileExplorer::new(wrap_selection, group_dirs),
+            remote: FileExplorer::new(wrap_selection, group_dirs),
             tab: FileExplorerTab::Local,
             log_index: 0,
-            log_records: VecDeque::with_capacity(256), // 256 events is enough I guess
-            log_size: 256,                             // Must match with capacity
+            log_records: VecDeque::with_capacity(log_size),
+            log_size,
+            log_file: None,
+            log_file_path: None,
+            log_file_level: LogLevel::Info,
             input_mode: InputMode::Explorer,
             input_field: InputField::Explorer,
             input_txt: String::new(),
             choice_opt: DialogYesNoOption::Yes,
             transfer: TransferStates::default(),
+            batch: None,
+            max_depth: match config_client {
+                Some(config_client) => config_client.get_max_depth(),
+                None => 0,
+            },
+            remote_line_ending: config_client.and_then(|cli| cli.get_remote_line_ending()),
+            preserve_paths: match config_client {
+                Some(config_client) => config_client.get_preserve_paths(),
+                None => false,
+            },
+            follow_symlinks: match config_client {
+                Some(config_client) => config_client.get_follow_symlinks(),
+                None => false,
+            },
+            resume_uploads: match config_client {
+                Some(config_client) => config_client.get_resume_uploads(),
+                None => false,
+            },
+            max_connections: match config_client {
+                Some(config_client) => config_client.get_max_connections(),
+                None => 4,
+            },
+            clock_skew_threshold: match config_client {
+                Some(config_client) => config_client.get_clock_skew_threshold(),
+                None => 0,
+            },
+            clock_skew_compensate: match config_client {
+                Some(config_client) => config_client.get_clock_skew_compensate(),
+                None => false,
+            },
+            clock_skew: None,
+            reconnect_wrkdir: None,
+            confirm_size: match config_client {
+                Some(config_client) => config_client.get_confirm_size(),
+                None => 0,
+            },
+            flush_interval: match (config_client, protocol) {
+                (Some(config_client), FileTransferProtocol::Ftp(_)) => {
+                    config_client.get_ftp_flush_interval()
+                }
+                _ => 0,
+            },
+            redraw_interval: match config_client {
+                Some(config_client) => config_client.get_redraw_interval(),
+                None => RedrawInterval::Percent(1.0),
+            },
+            buffer,
+            abort_key: match config_client {
+                Some(config_client) => config_client.get_abort_key(),
+                None => KeyBinding {
+                    ctrl: true,
+                    alt: false,
+                    key: KeyName::Char('c'),
+                },
+            },
+            pending_transfer: None,
+            pending_sync: None,
+            queue_progress: None,
+            transfer_queue: TransferQueue::new(),
+            log_visible: match config_client {
+                Some(config_client) => config_client.get_show_log(),
+                None => true,
+            },
+            single_pane: false,
+            sync_browsing: false,
+            dry_run: false,
+            relative_time: match config_client {
+                Some(config_client) => config_client.get_relative_time(),
+                None => false,
+            },
+            show_link_target: match config_client {
+                Some(config_client) => config_client.get_show_link_target(),
+                None => true,
+            },
+            show_path_line: match config_client {
+                Some(config_client) => config_client.get_show_path_line(),
+                None => false,
+            },
+            show_banner: match config_client {
+                Some(config_client) => config_client.get_show_banner(),
+                None => true,
+            },
+            readonly: false,
+            fileinfo_xattrs: None,
+            fileinfo_size: None,
+            terminal_title: match config_client {
+                Some(config_client) if !config_client.get_terminal_title() => None,
+                _ => Some(String::new()),
+            },
+            auth_retries: 0,
+            hooks: Vec::new(),
+            follow: None,
+            diff: None,
+            archive: None,
+            preview: None,
+            remote_tabs: Vec::new(),
+            remote_tab_index: 0,
+            undo_stack: Vec::new(),
+            trash_dir: None,
+            disconnect_request: DisconnectRequest::None,
+            goto_completion: None,
+            recent: Vec::new(),
+            recent_index: 0,
         }
     }
+
+    /// ### make_client
+    ///
+    /// Instantiates a (not connected) client for the host described by `params`, configured as in `config_client`
+    fn make_client(
+        params: &FileTransferParams,
+        config_client: Option<&ConfigClient>,
+    ) -> Box<dyn FileTransfer> {
+        new_client(
+            params.protocol,
+            params.jump_hosts.as_slice(),
+            params.identity.as_ref(),
+            config_client.and_then(|cli| cli.get_ftp_list_args()),
+            Duration::from_secs(match config_client {
+                Some(config_client) => config_client.get_ssh_read_timeout(),
+                None => 60,
+            }),
+            config_client
+                .map(|cli| cli.get_sftp_concurrency())
+                .unwrap_or(1),
+        )
+    }
+
+    /// ### set_readonly
+    ///
+    /// Enable/disable readonly mode; in readonly mode, operations which modify files (except downloads) are disabled
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    /// ### set_trash_dir
+    ///
+    /// Set the directory where local deletes are moved to, so they can be undone (trash mode).
+    /// In trash mode, remote files overwritten by uploads are backed up too
+    pub fn set_trash_dir(&mut self, trash_dir: Option<PathBuf>) {
+        self.trash_dir = trash_dir;
+    }
+
+    /// ### set_log_file
+    ///
+    /// Set the file log records are appended to, instead of the configured one (or `gateway.log` in the configuration directory).
+    /// If None, the configured file is kept
+    pub fn set_log_file(&mut self, path: Option<PathBuf>) {
+        if path.is_some() {
+            self.log_file_path = path;
+        }
+    }
+
+    /// ### set_log_level
+    ///
+    /// Set the least severe level of the records written to the log file (e.g. `Warn` leaves out info records);
+    /// the log panel still shows all the records
+    pub fn set_log_level(&mut self, level: LogLevel) {
+        self.log_file_level = level;
+    }
 }
 
 /**
@@ -345,6 +1521,29 @@ impl Activity for FileTransferActivity {
     fn on_create(&mut self, context: Context) {
         // Set context
         self.context = Some(context);
+        // Open log file: the one provided from CLI, the configured one or `gateway.log` in the configuration directory.
+        // Writing is best-effort: if the file can't be opened, the error is only logged
+        let config_client: Option<&ConfigClient> = self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref());
+        let max_size: u64 = config_client
+            .map(|cli| cli.get_log_file_max_size())
+            .unwrap_or_else(|| UiConfig::default().log_file_max_size);
+        if let Some(path) = self
+            .log_file_path
+            .clone()
+            .or_else(|| config_client.and_then(|cli| cli.get_log_file()))
+            .or_else(environment::get_log_file_path)
+        {
+            match LogFile::open(path.as_path(), max_size) {
+                Ok(log_file) => self.log_file = Some(log_file),
+                Err(err) => self.log(
+                    LogLevel::Error,
+                    format!("Could not open log file \"{}\": {}", path.display(), err).as_str(),
+                ),
+            }
+        }
         // Clear terminal
         self.context.as_mut().unwrap().clear_screen();
         // Put raw mode on enabled
@@ -367,6 +1566,11 @@ impl Activity for FileTransferActivity {
         if self.context.is_none() {
             return;
         }
+        // Disconnect, if requested during the transfer which has just terminated
+        if self.disconnect_request == DisconnectRequest::Pending {
+            self.disconnect_request = DisconnectRequest::None;
+            self.disconnect();
+        }
         let is_explorer_mode: bool = matches!(self.input_mode, InputMode::Explorer);
         // Check if connected
         if !self.client.is_connected() && is_explorer_mode {
@@ -382,6 +1586,15 @@ impl Activity for FileTransferActivity {
             // Redraw
             redraw = true;
         }
+        // Log terminated post transfer commands
+        redraw |= self.poll_hooks();
+        // Read new data of the followed file
+        if matches!(self.input_mode, InputMode::Popup(PopupType::Follow)) {
+            redraw |= self.follow_poll();
+        }
+        // Remove expired transfer results
+        redraw |= self.local.expire_results();
+        redraw |= self.remote.expire_results();
         // Handle input events (if false, becomes true; otherwise remains true)
         redraw |= self.read_input_event();
         // @! draw interface
@@ -401,13 +1614,1842 @@ impl Activity for FileTransferActivity {
         if self.client.is_connected() {
             let _ = self.client.disconnect();
         }
+        self.remote_tabs_disconnect();
         // Clear terminal and return
         match self.context.take() {
             Some(mut ctx) => {
                 ctx.clear_screen();
+                ctx.reset_title();
                 Some(ctx)
             }
             None => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::host::Localhost;
+
+    #[test]
+    fn test_ui_activities_filetransfer_explorer_move_index() {
+        let mut explorer: FileExplorer = FileExplorer::new(false, GroupDirs::First);
+        // Empty explorer
+        explorer.move_index_up(1);
+        assert_eq!(explorer.index, 0);
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 0);
+        // Fill with 10 entries
+        explorer.files = (0..10)
+            .map(|i| make_fs_entry(format!("dir{}", i).as_str(), true))
+            .collect();
+        // No wrap: stop at boundaries
+        explorer.move_index_up(1);
+        assert_eq!(explorer.index, 0);
+        explorer.move_index_down(1);
+        assert_eq!(explorer.index, 1);
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 9);
+        explorer.move_index_down(1);
+        assert_eq!(explorer.index, 9);
+        explorer.move_index_up(8);
+        assert_eq!(explorer.index, 1);
+        explorer.move_index_up(8);
+        assert_eq!(explorer.index, 0);
+        // Wrap
+        explorer.wrap = true;
+        explorer.move_index_up(1);
+        assert_eq!(explorer.index, 9);
+        explorer.move_index_down(1);
+        assert_eq!(explorer.index, 0);
+        explorer.move_index_up(8);
+        assert_eq!(explorer.index, 9);
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 0);
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 8);
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 9);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_explorer_filter() {
+        let mut explorer: FileExplorer = FileExplorer::new(false, GroupDirs::First);
+        explorer.files = vec![
+            make_fs_entry("bin", true),
+            make_fs_entry("a.txt", false),
+            make_fs_entry("etc", true),
+            make_fs_entry("b.txt", false),
+            make_fs_entry("c.txt", false),
+        ];
+        assert_eq!(explorer.filter, ExplorerFilter::AllEntries);
+        assert_eq!(explorer.count(), 5);
+        assert_eq!(explorer.filter_label(), "");
+        explorer.index = 4;
+        assert_eq!(explorer.get_selected().unwrap().get_name(), "c.txt");
+        // Dirs only; index is clamped
+        explorer.toggle_filter();
+        assert_eq!(explorer.filter, ExplorerFilter::DirsOnly);
+        assert_eq!(explorer.count(), 2);
+        assert_eq!(explorer.index, 1);
+        assert_eq!(explorer.get_selected().unwrap().get_name(), "etc");
+        assert_eq!(explorer.filter_label(), "[dirs only] ");
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 1);
+        // Files only
+        explorer.toggle_filter();
+        assert_eq!(explorer.filter, ExplorerFilter::FilesOnly);
+        assert_eq!(explorer.count(), 3);
+        assert_eq!(explorer.index, 1);
+        assert_eq!(explorer.get_selected().unwrap().get_name(), "b.txt");
+        explorer.move_index_down(1);
+        assert_eq!(explorer.get_selected().unwrap().get_name(), "c.txt");
+        // Back to all
+        explorer.toggle_filter();
+        assert_eq!(explorer.filter, ExplorerFilter::AllEntries);
+        assert_eq!(explorer.count(), 5);
+        // Symlink to directory is treated as a directory
+        let mut link: FsEntry = make_fs_entry("link", false);
+        if let FsEntry::File(file) = &mut link {
+            file.symlink = Some(Box::new(make_fs_entry("target", true)));
+        }
+        assert!(ExplorerFilter::DirsOnly.matches(&link));
+        assert!(!ExplorerFilter::FilesOnly.matches(&link));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_explorer_name_filter() {
+        let mut explorer: FileExplorer = FileExplorer::new(false, GroupDirs::First);
+        explorer.files = vec![
+            make_fs_entry("bin", true),
+            make_fs_entry("a.TXT", false),
+            make_fs_entry("etc", true),
+            make_fs_entry("b.txt", false),
+            make_fs_entry("c.log", false),
+        ];
+        explorer.index = 4;
+        // Case insensitive; index is clamped
+        explorer.set_name_filter("TxT");
+        assert_eq!(explorer.count(), 2);
+        assert_eq!(explorer.index, 1);
+        assert_eq!(explorer.get_selected().unwrap().get_name(), "b.txt");
+        assert_eq!(explorer.filter_label(), "[/TxT] ");
+        explorer.move_index_down(8);
+        assert_eq!(explorer.index, 1);
+        // Combined with the view filter
+        explorer.toggle_filter();
+        assert_eq!(explorer.count(), 0);
+        assert_eq!(explorer.index, 0);
+        assert!(explorer.get_selected().is_none());
+        assert_eq!(explorer.filter_label(), "[dirs only] [/TxT] ");
+        explorer.toggle_filter();
+        explorer.toggle_filter();
+        // Empty text clears the filter
+        explorer.set_name_filter("");
+        assert_eq!(explorer.count(), 5);
+        assert_eq!(explorer.filter_label(), "");
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_explorer_sort() {
+        let make_file = |name: &str, size: usize, symlink: Option<FsEntry>| -> FsEntry {
+            match make_fs_entry(name, false) {
+                FsEntry::File(mut file) => {
+                    file.size = size;
+                    file.symlink = symlink.map(Box::new);
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        let files: Vec<FsEntry> = vec![
+            make_file("c.txt", 10, None),
+            make_fs_entry("D", true),
+            make_file("a.txt", 10000, None),
+            make_file("link", 0, Some(make_fs_entry("target", true))), // Symlink to directory
+            make_fs_entry("b", true),
+        ];
+        let sorted = |sort_by: SortMode, group_dirs: GroupDirs| -> Vec<String> {
+            let mut explorer: FileExplorer = FileExplorer::new(false, group_dirs);
+            explorer.sort_by = sort_by;
+            explorer.files = files.clone();
+            explorer.sort_files();
+            explorer.files.iter().map(|x| x.get_name()).collect()
+        };
+        // By name
+        assert_eq!(
+            sorted(SortMode::Name, GroupDirs::First),
+            vec!["b", "D", "link", "a.txt", "c.txt"]
+        );
+        assert_eq!(
+            sorted(SortMode::Name, GroupDirs::Last),
+            vec!["a.txt", "c.txt", "b", "D", "link"]
+        );
+        assert_eq!(
+            sorted(SortMode::Name, GroupDirs::Mixed),
+            vec!["a.txt", "b", "c.txt", "D", "link"]
+        );
+        // By size; directories are 4096 bytes
+        assert_eq!(
+            sorted(SortMode::Size, GroupDirs::First),
+            vec!["link", "D", "b", "c.txt", "a.txt"]
+        );
+        assert_eq!(
+            sorted(SortMode::Size, GroupDirs::Last),
+            vec!["c.txt", "a.txt", "link", "D", "b"]
+        );
+        assert_eq!(
+            sorted(SortMode::Size, GroupDirs::Mixed),
+            vec!["link", "c.txt", "D", "b", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_explorer_sort_mode() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let make_file = |name: &str, size: usize, mtime: u64| -> FsEntry {
+            match make_fs_entry(name, false) {
+                FsEntry::File(mut file) => {
+                    file.size = size;
+                    file.last_change_time =
+                        std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(mtime);
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        let mut activity: FileTransferActivity = test_activity();
+        activity.local.group_dirs = GroupDirs::First;
+        activity.local.files = vec![
+            make_file("a.txt", 300, 20),
+            make_file("b.txt", 100, 30),
+            make_fs_entry("z", true),
+            make_file("c.txt", 200, 10),
+        ];
+        activity.local.sort_files();
+        activity.local.index = 1; // a.txt
+        let names = |a: &FileTransferActivity| -> Vec<String> {
+            a.local.files.iter().map(|x| x.get_name()).collect()
+        };
+        let ctrl =
+            |ch: char| InputEvent::Key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL));
+        assert_eq!(activity.local.sort_label().as_str(), "");
+        // By size; directories are grouped first whatever the mode
+        activity.handle_input_event_mode_explorer(&ctrl('s'));
+        assert_eq!(names(&activity), vec!["z", "b.txt", "c.txt", "a.txt"]);
+        assert_eq!(activity.local.sort_label().as_str(), "[by size] ");
+        // Selected entry is kept
+        assert_eq!(activity.local.index, 3);
+        // Descending
+        activity.handle_input_event_mode_explorer(&ctrl('r'));
+        assert_eq!(names(&activity), vec!["z", "a.txt", "c.txt", "b.txt"]);
+        assert_eq!(activity.local.sort_label().as_str(), "[by size, desc] ");
+        assert_eq!(activity.local.index, 1);
+        // By modified, descending
+        activity.handle_input_event_mode_explorer(&ctrl('s'));
+        assert_eq!(names(&activity), vec!["z", "b.txt", "a.txt", "c.txt"]);
+        assert_eq!(activity.local.sort_label().as_str(), "[by modified, desc] ");
+        // Back to name, ascending
+        activity.handle_input_event_mode_explorer(&ctrl('s'));
+        activity.handle_input_event_mode_explorer(&ctrl('r'));
+        assert_eq!(names(&activity), vec!["z", "a.txt", "b.txt", "c.txt"]);
+        assert_eq!(activity.local.sort_label().as_str(), "");
+        // Remote explorer is unchanged
+        assert_eq!(activity.remote.sort_by, SortMode::Name);
+        assert!(!activity.remote.sort_desc);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_explorer_marks() {
+        let mut explorer: FileExplorer = FileExplorer::new(false, GroupDirs::First);
+        explorer.files = vec![
+            make_fs_entry("bin", true),
+            make_fs_entry("a.txt", false),
+            make_fs_entry("b.txt", false),
+        ];
+        // Without marks, selected entry is returned
+        explorer.index = 1;
+        assert!(explorer.get_marked().is_empty());
+        assert_eq!(explorer.get_marked_or_selected().len(), 1);
+        assert_eq!(explorer.get_marked_or_selected()[0].get_name(), "a.txt");
+        // Mark entries
+        explorer.toggle_mark();
+        explorer.index = 2;
+        explorer.toggle_mark();
+        assert!(explorer.is_marked(&explorer.files[1]));
+        assert!(!explorer.is_marked(&explorer.files[0]));
+        assert_eq!(explorer.get_marked_or_selected().len(), 2);
+        // Unmark
+        explorer.toggle_mark();
+        assert_eq!(explorer.get_marked().len(), 1);
+        explorer.clear_marks();
+        assert!(explorer.get_marked().is_empty());
+        // Restore marks by absolute path and by name; report missing paths
+        let missing: Vec<PathBuf> = explorer.restore_marks(&[
+            PathBuf::from("/home/omar/bin"),
+            PathBuf::from("/srv/b.txt"),
+            PathBuf::from("c.txt"),
+        ]);
+        assert_eq!(missing, vec![PathBuf::from("c.txt")]);
+        assert!(explorer.is_marked(&explorer.files[0]));
+        assert!(!explorer.is_marked(&explorer.files[1]));
+        assert!(explorer.is_marked(&explorer.files[2]));
+        // Mark entries matching a predicate; marks are kept
+        assert_eq!(explorer.mark_matching(|x| x.get_name() == "a.txt"), 1);
+        assert_eq!(explorer.get_marked().len(), 3);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_terminal_title() {
+        let mut activity: FileTransferActivity = test_activity();
+        activity.params.username = Some(String::from("omar"));
+        assert!(activity.terminal_title.is_some());
+        assert_eq!(
+            activity.get_terminal_title().as_str(),
+            "Gateway - omar@192.168.1.31"
+        );
+        // While transferring, progress is reported
+        activity.input_mode = InputMode::Popup(PopupType::Progress(String::from("Uploading")));
+        activity.transfer.set_progress(512, 1024);
+        assert!(activity.get_terminal_title().starts_with("Gateway - 50% ("));
+        assert!(activity
+            .get_terminal_title()
+            .ends_with("/s) - omar@192.168.1.31"));
+        // Without username
+        activity.params.username = None;
+        activity.input_mode = InputMode::Explorer;
+        assert_eq!(
+            activity.get_terminal_title().as_str(),
+            "Gateway - 192.168.1.31"
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_focus_explorer() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        // From log panel to remote explorer
+        activity.input_field = InputField::Logs;
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Right,
+            KeyModifiers::ALT,
+        )));
+        assert!(matches!(activity.input_field, InputField::Explorer));
+        assert!(matches!(activity.tab, FileExplorerTab::Remote));
+        // From log panel to local explorer
+        activity.input_field = InputField::Logs;
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Left,
+            KeyModifiers::ALT,
+        )));
+        assert!(matches!(activity.input_field, InputField::Explorer));
+        assert!(matches!(activity.tab, FileExplorerTab::Local));
+        // Already on local explorer
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Left,
+            KeyModifiers::ALT,
+        )));
+        assert!(matches!(activity.tab, FileExplorerTab::Local));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_single_pane() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.local.files = vec![
+            make_fs_entry("a.txt", false),
+            make_fs_entry("b.txt", false),
+            make_fs_entry("c.txt", false),
+        ];
+        activity.remote.files = vec![make_fs_entry("d.txt", false), make_fs_entry("e.txt", false)];
+        assert!(!activity.single_pane);
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        // Enable single pane
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('v')));
+        assert!(activity.single_pane);
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Down));
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Down));
+        // Switch side; selection is kept
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Right));
+        assert!(matches!(activity.tab, FileExplorerTab::Remote));
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Down));
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Left));
+        assert!(matches!(activity.tab, FileExplorerTab::Local));
+        assert_eq!(activity.local.index, 2);
+        assert_eq!(activity.remote.index, 1);
+        // Disable from remote side
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Right));
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('V')));
+        assert!(!activity.single_pane);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_mkdir_enter() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.tab = FileExplorerTab::Remote;
+        activity.remote.wrkdir = PathBuf::from("/home/omar");
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('n'),
+            KeyModifiers::NONE,
+        )));
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Input(txt, _)) if txt.as_str() == "Insert directory name (and enter it)"
+        ));
+        // Directory can't be created (not connected): working directory doesn't change
+        activity.callback_mkdir_enter(String::from("docs"));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        assert_eq!(activity.remote.wrkdir, PathBuf::from("/home/omar"));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_touch() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.tab = FileExplorerTab::Remote;
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('e'),
+            KeyModifiers::CONTROL,
+        )));
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Input(txt, _)) if txt.as_str() == "Insert file name"
+        ));
+        // File can't be created (not connected)
+        activity.callback_touch(String::from("notes.txt"));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        // Readonly: the popup is not opened
+        activity.input_mode = InputMode::Explorer;
+        activity.readonly = true;
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('e'),
+            KeyModifiers::CONTROL,
+        )));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_hardlink() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.tab = FileExplorerTab::Remote;
+        activity.remote.files = vec![make_fs_entry("docs", true), make_fs_entry("a.txt", false)];
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('k'),
+            KeyModifiers::NONE,
+        )));
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Input(txt, _)) if txt.as_str() == "Insert hard link name"
+        ));
+        // Directories can't be linked
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_hardlink(String::from("docs.link"));
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, txt)) if txt.contains("directories")
+        ));
+        // Link can't be created (not connected)
+        activity.input_mode = InputMode::Explorer;
+        activity.remote.index = 1;
+        activity.callback_hardlink(String::from("a.link"));
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, txt)) if txt.starts_with("Could not create hard link \"a.link\"")
+        ));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    fn test_ui_activities_filetransfer_same_file() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = tmpdir.path().join("a.txt");
+        std::fs::write(path.as_path(), b"hello").unwrap();
+        let entry: FsEntry = Localhost::new(PathBuf::from(tmpdir.path()))
+            .ok()
+            .unwrap()
+            .stat(path.as_path())
+            .ok()
+            .unwrap();
+        let file: FsFile = match &entry {
+            FsEntry::File(file) => file.clone(),
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        let mut activity: FileTransferActivity = test_activity();
+        activity.params.address = String::from("127.0.0.1");
+        // Remote is this host: copies onto the same path are refused before opening anything
+        assert!(activity
+            .filetransfer_recv_file(path.as_path(), &file)
+            .err()
+            .unwrap()
+            .contains("onto itself"));
+        assert!(activity
+            .filetransfer_send_file(&file, path.as_path())
+            .err()
+            .unwrap()
+            .contains("onto itself"));
+        assert_eq!(std::fs::read(path.as_path()).unwrap(), b"hello");
+        // Different file
+        assert!(
+            !activity.remote_is_same_file(path.as_path(), tmpdir.path().join("b.txt").as_path())
+        );
+        // Remote is another host
+        activity.params.address = String::from("10.0.0.1");
+        assert!(!activity.remote_is_same_file(path.as_path(), path.as_path()));
+        // Moving a file onto a hard link of itself is refused too
+        let link: PathBuf = tmpdir.path().join("link.txt");
+        std::fs::hard_link(path.as_path(), link.as_path()).unwrap();
+        activity.local.files = vec![entry];
+        activity.local.index = 0;
+        activity.callback_rename(link.display().to_string());
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, txt)) if txt.ends_with("is the same file")
+        ));
+        assert_eq!(std::fs::read(path.as_path()).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_log_file() {
+        let mut activity: FileTransferActivity = test_activity();
+        activity.log_size = 1;
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = tmp_dir.path().join("gateway.log");
+        activity.log_file = Some(LogFile::open(path.as_path(), 0).unwrap());
+        activity.log(LogLevel::Info, "record 0");
+        activity.log(LogLevel::Error, "record 1");
+        // Records discarded from memory are kept in the file, as soon as they're written
+        assert_eq!(activity.log_records.len(), 1);
+        let lines: Vec<String> = std::fs::read_to_string(path.as_path())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" [INFO ]: record 0"));
+        assert!(lines[1].ends_with(" [ERROR]: record 1"));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_log_file_level() {
+        let mut activity: FileTransferActivity = test_activity();
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = tmp_dir.path().join("gateway.log");
+        activity.set_log_file(Some(path.clone()));
+        activity.set_log_file(None); // Provided file is kept
+        assert_eq!(activity.log_file_path.as_deref(), Some(path.as_path()));
+        activity.set_log_level(LogLevel::Warn);
+        activity.log_file = Some(LogFile::open(path.as_path(), 0).unwrap());
+        activity.log(LogLevel::Info, "record 0");
+        activity.log(LogLevel::Warn, "record 1");
+        activity.log(LogLevel::Error, "record 2");
+        // Info records are shown, but not written to the file
+        assert_eq!(activity.log_records.len(), 3);
+        let lines: Vec<String> = std::fs::read_to_string(path.as_path())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" [WARN ]: record 1"));
+        assert!(lines[1].ends_with(" [ERROR]: record 2"));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_log_scroll() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.log_size = 4;
+        for i in 0..3 {
+            activity.log(LogLevel::Info, format!("record {}", i).as_str());
+        }
+        // Follows latest record
+        assert_eq!(activity.log_index, 0);
+        activity.log(LogLevel::Info, "record 3");
+        assert_eq!(activity.log_index, 0);
+        // Scroll up to "record 2": inserting records keeps it selected
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.handle_input_event_mode_explorer_log(&key(KeyCode::Up));
+        let selected = |a: &FileTransferActivity| a.log_records[a.log_index].msg.clone();
+        assert_eq!(selected(&activity).as_str(), "record 2");
+        activity.log(LogLevel::Info, "record 4");
+        assert_eq!(selected(&activity).as_str(), "record 2");
+        assert_eq!(activity.log_records.len(), 4);
+        // Oldest record is discarded while selected: selection stays on the oldest
+        activity.handle_input_event_mode_explorer_log(&key(KeyCode::Home));
+        assert_eq!(activity.log_index, 3);
+        activity.log(LogLevel::Info, "record 5");
+        assert_eq!(activity.log_index, 3);
+        assert_eq!(activity.log_records[3].msg.as_str(), "record 2");
+        // Jump to latest
+        activity.handle_input_event_mode_explorer_log(&key(KeyCode::End));
+        assert_eq!(activity.log_index, 0);
+        assert_eq!(activity.log_records[0].msg.as_str(), "record 5");
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_relative_time() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        // Absolute by default
+        assert!(!activity.relative_time);
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.handle_input_event_mode_explorer_log(&key(KeyCode::Char('z')));
+        assert!(activity.relative_time);
+        activity.handle_input_event_mode_explorer_log(&key(KeyCode::Char('Z')));
+        assert!(!activity.relative_time);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_abort_key() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let ctrl_c: InputEvent =
+            InputEvent::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        let f2: InputEvent = InputEvent::Key(KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE));
+        // Nothing is running: no-op
+        activity.handle_input_event(&ctrl_c);
+        assert!(!activity.transfer.aborted);
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        // Transfers are aborted
+        activity.input_mode = InputMode::Popup(PopupType::Progress(String::from("file.txt")));
+        activity.handle_input_event(&ctrl_c);
+        assert!(activity.transfer.aborted);
+        // Scans are aborted too
+        activity.transfer.aborted = false;
+        activity.input_mode = InputMode::Popup(PopupType::Wait(String::from("Scanning...")));
+        activity.handle_input_event(&ctrl_c);
+        assert!(activity.transfer.aborted);
+        // Configured key
+        activity.transfer.aborted = false;
+        activity.abort_key = KeyBinding {
+            ctrl: false,
+            alt: false,
+            key: KeyName::Function(2),
+        };
+        activity.handle_input_event(&ctrl_c);
+        assert!(!activity.transfer.aborted);
+        activity.handle_input_event(&f2);
+        assert!(activity.transfer.aborted);
+        // Modifiers must match
+        activity.transfer.aborted = false;
+        activity.handle_input_event(&InputEvent::Key(KeyEvent::new(
+            KeyCode::F(2),
+            KeyModifiers::CONTROL,
+        )));
+        assert!(!activity.transfer.aborted);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_new_connection() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let ctrl_n: InputEvent =
+            InputEvent::Key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL));
+        // Running post transfer commands must be confirmed
+        #[cfg(unix)]
+        {
+            let child: Child = std::process::Command::new("sleep")
+                .arg("5")
+                .spawn()
+                .unwrap();
+            activity.hooks.push((String::from("sleep 5"), child));
+            activity.handle_input_event_mode_explorer(&ctrl_n);
+            assert!(!activity.disconnected);
+            assert!(matches!(
+                activity.input_mode,
+                InputMode::Popup(PopupType::YesNo(_, _, _))
+            ));
+            let (_, mut child) = activity.hooks.pop().unwrap();
+            let _ = child.kill();
+            let _ = child.wait();
+            activity.input_mode = InputMode::Explorer;
+        }
+        // During a transfer, the key must be pressed twice; any other key cancels
+        activity.input_mode = InputMode::Popup(PopupType::Progress(String::from("file.txt")));
+        activity.handle_input_event(&ctrl_n);
+        assert_eq!(activity.disconnect_request, DisconnectRequest::Confirm);
+        assert!(!activity.transfer.aborted);
+        activity.handle_input_event(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('a'),
+            KeyModifiers::NONE,
+        )));
+        assert_eq!(activity.disconnect_request, DisconnectRequest::None);
+        activity.handle_input_event(&ctrl_n);
+        activity.handle_input_event(&ctrl_n);
+        assert_eq!(activity.disconnect_request, DisconnectRequest::Pending);
+        assert!(activity.transfer.aborted);
+        assert!(!activity.disconnected);
+        activity.transfer.aborted = false;
+        activity.disconnect_request = DisconnectRequest::None;
+        activity.input_mode = InputMode::Explorer;
+        // Disconnect straight away, from any field
+        activity.input_field = InputField::Logs;
+        activity.handle_input_event_mode_explorer(&ctrl_n);
+        assert!(activity.disconnected);
+        assert!(!activity.quit);
+        assert_eq!(
+            activity.log_records[0].msg.as_str(),
+            "Disconnected from \"192.168.1.31\""
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_readonly() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        // Write keys
+        let write = |code: KeyCode, local: bool| FileTransferActivity::is_write_key(code, local);
+        assert!(write(KeyCode::Delete, false));
+        assert!(write(KeyCode::Char('D'), false));
+        assert!(write(KeyCode::Char('r'), true));
+        assert!(write(KeyCode::Char(' '), true));
+        assert!(!write(KeyCode::Char(' '), false));
+        assert!(!write(KeyCode::Char('s'), false));
+        assert!(write(KeyCode::Char('C'), false));
+        assert!(!write(KeyCode::Char('c'), true));
+        assert!(write(KeyCode::F(5), true));
+        assert!(!write(KeyCode::F(5), false));
+        assert!(!write(KeyCode::Char('g'), true));
+        assert!(!write(KeyCode::Enter, true));
+        let mut activity: FileTransferActivity = test_activity();
+        assert!(!activity.readonly);
+        activity.set_readonly(true);
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        // Mkdir is refused and logged
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('d')));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        assert_eq!(activity.log_records.len(), 1);
+        activity.tab = FileExplorerTab::Remote;
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('n')));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        assert_eq!(activity.log_records.len(), 2);
+        // Browsing is still allowed
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('g')));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Goto)
+        ));
+        assert_eq!(activity.log_records.len(), 2);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_missing_parents() {
+        // Destination in base directory
+        assert!(FileTransferActivity::missing_parents(
+            Path::new("/home/omar"),
+            Path::new("/home/omar/a.txt")
+        )
+        .is_empty());
+        // Relative destination with nested directories
+        assert_eq!(
+            FileTransferActivity::missing_parents(
+                Path::new("/home/omar"),
+                Path::new("/home/omar/a/b/c.txt")
+            ),
+            vec![
+                PathBuf::from("/home/omar/a"),
+                PathBuf::from("/home/omar/a/b")
+            ]
+        );
+        // Absolute destination elsewhere
+        assert_eq!(
+            FileTransferActivity::missing_parents(
+                Path::new("/home/omar"),
+                Path::new("/srv/ftp/c.txt")
+            ),
+            vec![PathBuf::from("/srv"), PathBuf::from("/srv/ftp")]
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_retry_credentials() {
+        let mut activity: FileTransferActivity = test_activity();
+        activity.params.username = Some(String::from("omar"));
+        activity.params.password = Some(String::from("wrong"));
+        assert_eq!(activity.auth_retries, 0);
+        // Username is submitted, then password is asked (masked)
+        activity.callback_retry_username(String::from(" root "));
+        assert_eq!(activity.params.username.as_deref().unwrap(), "root");
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Secret(_, _))
+        ));
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_retry_password(String::from("s3cret"));
+        assert_eq!(activity.params.password.as_deref().unwrap(), "s3cret");
+        // Empty values mean none
+        activity.callback_retry_username(String::new());
+        assert!(activity.params.username.is_none());
+        activity.callback_retry_password(String::new());
+        assert!(activity.params.password.is_none());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_states() {
+        let mut states: TransferStates = TransferStates::default();
+        states.set_progress(512, 1024);
+        assert_eq!(states.progress, 50.0);
+        assert_eq!(states.bytes_written, 512);
+        assert_eq!(states.bytes_total, 1024);
+        states.reset();
+        assert_eq!(states.progress, 0.0);
+        assert_eq!(states.bytes_written, 0);
+        assert_eq!(states.bytes_total, 0);
+        // Resumed transfer: progress starts from offset, but the speed doesn't count it
+        states.resume(768, 1024);
+        assert_eq!(states.progress, 75.0);
+        states.set_progress(1024, 1024);
+        states.started -= Duration::from_secs(2);
+        assert_eq!(states.bytes_per_second(), 128);
+        states.reset();
+        assert_eq!(states.bytes_resumed, 0);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_states_zero_bytes() {
+        let mut states: TransferStates = TransferStates::default();
+        // Zero-byte transfer must be complete and not NaN
+        states.set_progress(0, 0);
+        assert_eq!(states.progress, 100.0);
+        assert_eq!(states.bytes_per_second(), 0);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_states_json() {
+        let mut states: TransferStates = TransferStates::default();
+        states.set_progress(512, 1024);
+        assert_eq!(
+            states.to_json().as_str(),
+            "{\"bytes_done\":512,\"bytes_total\":1024,\"rate\":0}"
+        );
+        // Rate is computed on elapsed seconds
+        states.started -= Duration::from_secs(2);
+        assert_eq!(
+            states.to_json().as_str(),
+            "{\"bytes_done\":512,\"bytes_total\":1024,\"rate\":256}"
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_summary() {
+        let mut summary: TransferSummary = TransferSummary::new();
+        summary.add_success(1024);
+        summary.add_success(1024);
+        summary.add_skipped();
+        assert_eq!(summary.succeeded, 2);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.bytes_total, 2048);
+        assert!(summary
+            .report("Upload")
+            .starts_with("Upload completed: 2 succeeded, 1 skipped, 0 failed (2.0 KB in "));
+        // Failures
+        for i in 0..5 {
+            summary.add_failure(PathBuf::from(format!("/tmp/{}.txt", i)).as_path());
+        }
+        let report: String = summary.report("Download");
+        assert!(report.contains("5 failed"));
+        assert!(
+            report.contains("Failed: \"/tmp/0.txt\", \"/tmp/1.txt\", \"/tmp/2.txt\" and 2 more")
+        );
+        // Result of the entries registered since a given result
+        let mut summary: TransferSummary = TransferSummary::new();
+        summary.set_result(Path::new("/tmp/a.txt"), TransferResult::Failed);
+        assert_eq!(summary.result_since(1), TransferResult::Success);
+        summary.set_result(Path::new("/tmp/b/c.txt"), TransferResult::Skipped);
+        assert_eq!(summary.result_since(1), TransferResult::Skipped);
+        summary.set_result(Path::new("/tmp/b/d.txt"), TransferResult::Success);
+        assert_eq!(summary.result_since(1), TransferResult::Success);
+        assert_eq!(summary.result_since(0), TransferResult::Failed);
+        assert_eq!(summary.result_since(8), TransferResult::Success);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_run_summary() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let mut activity: FileTransferActivity = test_activity();
+        activity.context = Some(Context::new(
+            Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap(),
+            None,
+        ));
+        let make_remote_file = |path: &str| -> FsEntry {
+            match make_fs_entry("", false) {
+                FsEntry::File(mut file) => {
+                    file.abs_path = PathBuf::from(path);
+                    file.name = file
+                        .abs_path
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string();
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        let files: Vec<FsEntry> = vec![
+            make_remote_file("/srv/a.txt"),
+            make_remote_file("/srv/b.txt"),
+        ];
+        // Many marked files are summarized once (downloads fail, since the client is not connected)
+        activity.filetransfer_run(PendingTransfer {
+            entries: files.iter().map(|x| (x.clone(), None)).collect(),
+            dst: PathBuf::from(tmpdir.path()),
+            upload: false,
+            size: None,
+        });
+        assert!(activity.batch.is_none());
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, txt))
+                if txt.starts_with("Download completed: 0 succeeded, 0 skipped, 2 failed")
+                    && txt.contains("\"/srv/a.txt\", \"/srv/b.txt\"")
+        ));
+        assert_eq!(
+            activity
+                .log_records
+                .iter()
+                .filter(|x| x.msg.starts_with("Download completed"))
+                .count(),
+            1
+        );
+        for file in files.iter() {
+            assert_eq!(
+                activity.remote.get_result(file),
+                Some(TransferResult::Failed)
+            );
+        }
+        // A single file isn't summarized
+        activity.input_mode = InputMode::Explorer;
+        activity.filetransfer_run(PendingTransfer {
+            entries: vec![(files[0].clone(), None)],
+            dst: PathBuf::from(tmpdir.path()),
+            upload: false,
+            size: None,
+        });
+        assert!(!matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, txt)) if txt.starts_with("Download completed")
+        ));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_summary_visit() {
+        let mut summary: TransferSummary = TransferSummary::new();
+        assert!(summary.visit(Path::new("/srv/data")));
+        assert!(summary.visit(Path::new("/srv/data/logs")));
+        // A symlink pointing back to a parent is walked only once
+        assert!(!summary.visit(Path::new("/srv/data")));
+        assert!(!summary.visit(Path::new("/srv/data/logs")));
+        // A new batch starts from scratch
+        let mut summary: TransferSummary = TransferSummary::new();
+        assert!(summary.visit(Path::new("/srv/data")));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    fn test_ui_activities_filetransfer_recv_symlink() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let mut activity: FileTransferActivity = test_activity();
+        activity.context = Some(Context::new(
+            Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap(),
+            None,
+        ));
+        // Remote symlink `/var/www/current -> releases/42`
+        let link: FsEntry = match make_fs_entry("current", false) {
+            FsEntry::File(mut file) => {
+                file.abs_path = PathBuf::from("/var/www/current");
+                file.symlink = Some(Box::new(make_fs_entry("42", true)));
+                file.link_target = Some(PathBuf::from("releases/42"));
+                FsEntry::File(file)
+            }
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        // Inside a downloaded directory, the link is recreated with its relative target
+        activity.batch = Some(TransferSummary::new());
+        activity.transfer_depth_step(true);
+        activity.filetransfer_recv(&link, tmpdir.path(), None);
+        assert_eq!(
+            std::fs::read_link(tmpdir.path().join("current")).unwrap(),
+            PathBuf::from("releases/42")
+        );
+        // A selected symlink is downloaded as a file, not recreated (the download fails, since the client is not connected)
+        activity.batch = None;
+        activity.filetransfer_recv(&link, tmpdir.path(), Some(String::from("selected")));
+        assert!(!matches!(
+            std::fs::symlink_metadata(tmpdir.path().join("selected")),
+            Ok(metadata) if metadata.file_type().is_symlink()
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "ftp")]
+    fn test_ui_activities_filetransfer_remote_scan_hung() {
+        use crate::filetransfer::ftp_transfer::FtpFileTransfer;
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+        // Fake FTP server, which never replies once asked to list a directory
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut control, _) = listener.accept().unwrap();
+            control.write_all(b"220 ready\r\n").unwrap();
+            let reader = BufReader::new(control.try_clone().unwrap());
+            for line in reader.lines().map_while(Result::ok) {
+                let reply: &str = match line.as_str() {
+                    "USER anonymous" => "331 Password required\r\n",
+                    "PASS " | "PASS" => "230 Logged in\r\n",
+                    "PASV" => continue,
+                    _ => break,
+                };
+                if control.write_all(reply.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let mut activity: FileTransferActivity = test_activity();
+        activity.params.address = String::from("127.0.0.1");
+        activity.params.port = port;
+        activity.params.protocol = FileTransferProtocol::Ftp(false);
+        activity.context = Some(Context::new(
+            Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap(),
+            None,
+        ));
+        let mut client: FtpFileTransfer = FtpFileTransfer::new(false);
+        client.set_timeout(Duration::from_millis(500));
+        activity.client = Box::new(client);
+        assert!(activity
+            .client
+            .connect(String::from("127.0.0.1"), port, None, None)
+            .is_ok());
+        activity.remote.wrkdir = PathBuf::from("/");
+        activity.remote.files = vec![make_fs_entry("a.txt", false)];
+        // The list is run by a worker and fails once the reply times out; the client is given back
+        assert!(!activity.remote_scan(Path::new("/hung")));
+        assert!(activity.client.is_connected());
+        assert!(activity.reconnect_wrkdir.is_none());
+        assert_eq!(activity.remote.wrkdir, PathBuf::from("/"));
+        assert_eq!(activity.remote.files.len(), 1);
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, txt)) if txt.starts_with("Could not scan current directory")
+        ));
+        activity.client = FileTransferActivity::make_client(&activity.params, None);
+        server.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "ftp")]
+    fn test_ui_activities_filetransfer_empty_file() {
+        use crate::filetransfer::ftp_transfer::FtpFileTransfer;
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+        // Fake FTP server, which reports the size of the stored files and serves empty files
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port: u16 = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel::<(String, usize)>();
+        let server = std::thread::spawn(move || {
+            let (mut control, _) = listener.accept().unwrap();
+            control.write_all(b"220 ready\r\n").unwrap();
+            let reader = BufReader::new(control.try_clone().unwrap());
+            let mut data: Option<TcpListener> = None;
+            for line in reader.lines().map_while(Result::ok) {
+                let reply: String = match line.as_str() {
+                    "USER anonymous" => String::from("331 Password required\r\n"),
+                    "PASS " | "PASS" => String::from("230 Logged in\r\n"),
+                    "PASV" => {
+                        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+                        let port: u16 = listener.local_addr().unwrap().port();
+                        data = Some(listener);
+                        format!(
+                            "227 Entering Passive Mode (127,0,0,1,{},{})\r\n",
+                            port >> 8,
+                            port & 0xff
+                        )
+                    }
+                    cmd if cmd.starts_with("STOR ") || cmd.starts_with("RETR ") => {
+                        control
+                            .write_all(b"150 Opening data connection\r\n")
+                            .unwrap();
+                        let (mut stream, _) = data.take().unwrap().accept().unwrap();
+                        // Store: read uploaded data; retrieve: send no data
+                        if cmd.starts_with("STOR ") {
+                            let mut buf: Vec<u8> = Vec::new();
+                            stream.read_to_end(&mut buf).unwrap();
+                            tx.send((cmd[5..].to_string(), buf.len())).unwrap();
+                        }
+                        drop(stream);
+                        String::from("226 Transfer complete\r\n")
+                    }
+                    "QUIT" => {
+                        let _ = control.write_all(b"221 Goodbye\r\n");
+                        break;
+                    }
+                    cmd if cmd.starts_with("TYPE ") => String::from("200 Type set\r\n"),
+                    _ => String::from("502 Not implemented\r\n"),
+                };
+                if control.write_all(reply.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let mut activity: FileTransferActivity = test_activity();
+        activity.context = Some(Context::new(
+            Localhost::new(PathBuf::from(tmpdir.path())).ok().unwrap(),
+            None,
+        ));
+        activity.client = Box::new(FtpFileTransfer::new(false));
+        assert!(activity
+            .client
+            .connect(String::from("127.0.0.1"), port, None, None)
+            .is_ok());
+        // Upload an empty file: remote file is created with no data
+        let path: PathBuf = tmpdir.path().join("empty.txt");
+        std::fs::write(path.as_path(), b"").unwrap();
+        let local: FsFile = match activity
+            .context
+            .as_ref()
+            .unwrap()
+            .local
+            .stat(path.as_path())
+            .ok()
+            .unwrap()
+        {
+            FsEntry::File(file) => file,
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        assert_eq!(
+            activity
+                .filetransfer_send_file(&local, Path::new("/upload/empty.txt"))
+                .ok()
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            (String::from("/upload/empty.txt"), 0)
+        );
+        // Download an empty file: local file is created (or truncated) and left empty
+        let remote: FsFile = match make_fs_entry("empty.txt", false) {
+            FsEntry::File(file) => file,
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        let dest: PathBuf = tmpdir.path().join("downloaded.txt");
+        std::fs::write(dest.as_path(), b"stale").unwrap();
+        assert!(activity
+            .filetransfer_recv_file(dest.as_path(), &remote)
+            .is_ok());
+        assert_eq!(std::fs::metadata(dest.as_path()).unwrap().len(), 0);
+        assert!(activity.client.disconnect().is_ok());
+        server.join().unwrap();
+    }
+
+    #[test]
+    #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+    fn test_ui_activities_filetransfer_local_changedir_scan_error() {
+        let tmpdir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let root: PathBuf = PathBuf::from(tmpdir.path());
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        // Directory with a dangling symlink, which can't be stat
+        std::fs::create_dir(root.join("broken")).unwrap();
+        std::os::unix::fs::symlink("/tmp/krgiogoiegj/kwrgnoerig", root.join("broken/link"))
+            .unwrap();
+        let mut activity: FileTransferActivity = test_activity();
+        activity.context = Some(Context::new(
+            Localhost::new(root.clone()).ok().unwrap(),
+            None,
+        ));
+        activity.local.wrkdir = root.clone();
+        assert!(activity.local_scan(root.as_path()));
+        // Changing directory fails: working directory and entries are kept
+        activity.local_changedir(root.join("broken").as_path(), true);
+        assert_eq!(activity.local.wrkdir, root);
+        assert_eq!(activity.context.as_ref().unwrap().local.pwd(), root);
+        let names: Vec<String> = activity.local.files.iter().map(|x| x.get_name()).collect();
+        assert_eq!(names, vec!["broken", "a.txt"]);
+        assert!(activity.local.popd().is_none());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_results() {
+        let mut summary: TransferSummary = TransferSummary::new();
+        assert_eq!(summary.result(), TransferResult::Success);
+        summary.add_skipped();
+        assert_eq!(summary.result(), TransferResult::Skipped);
+        summary.add_success(1024);
+        summary.set_result(Path::new("/home/omar/a.txt"), TransferResult::Success);
+        assert_eq!(summary.result(), TransferResult::Success);
+        summary.add_failure(Path::new("/home/omar/b.txt"));
+        summary.set_result(Path::new("/home/omar/b.txt"), TransferResult::Failed);
+        assert_eq!(summary.result(), TransferResult::Failed);
+        // Results in explorer
+        let mut explorer: FileExplorer = FileExplorer::new(true, GroupDirs::First);
+        let entry: FsEntry = make_fs_entry("a.txt", false);
+        assert!(explorer.get_result(&entry).is_none());
+        explorer.set_results(summary.results);
+        assert_eq!(explorer.get_result(&entry), Some(TransferResult::Success));
+        assert_eq!(
+            explorer.get_result(&make_fs_entry("b.txt", false)),
+            Some(TransferResult::Failed)
+        );
+        assert!(explorer
+            .get_result(&make_fs_entry("c.txt", false))
+            .is_none());
+        // Results are kept until timeout
+        assert!(!explorer.expire_results());
+        assert!(explorer.get_result(&entry).is_some());
+        explorer.results_time = Instant::now() - TRANSFER_RESULT_TIMEOUT;
+        assert!(explorer.expire_results());
+        assert!(explorer.get_result(&entry).is_none());
+        assert!(!explorer.expire_results());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_listing_contains() {
+        // Directory listing used by the recursion
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"].iter() {
+            std::fs::File::create(tmp_dir.path().join(name)).unwrap();
+        }
+        let host: Localhost = Localhost::new(PathBuf::from(tmp_dir.path())).ok().unwrap();
+        let cached: Vec<FsEntry> = host.scan_dir(tmp_dir.path()).ok().unwrap();
+        assert_eq!(cached.len(), 3);
+        // An entry is removed while the directory is being transferred
+        std::fs::remove_file(tmp_dir.path().join("b.txt")).unwrap();
+        let entries: Vec<FsEntry> = host.scan_dir(tmp_dir.path()).ok().unwrap();
+        let missing: Vec<String> = cached
+            .iter()
+            .filter(|x| {
+                !FileTransferActivity::listing_contains(&entries, x.get_abs_path().as_path())
+            })
+            .map(|x| x.get_name())
+            .collect();
+        assert_eq!(missing, vec![String::from("b.txt")]);
+        assert!(FileTransferActivity::listing_contains(
+            &entries,
+            Path::new("/")
+        ));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_max_depth() {
+        use session::tree_exceeds_depth;
+        // Tree is 3 levels deep: a/b/c/d.txt
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let root: PathBuf = tmp_dir.path().join("a");
+        std::fs::create_dir_all(root.join("b/c")).unwrap();
+        std::fs::File::create(root.join("b/c/d.txt")).unwrap();
+        std::fs::File::create(root.join("e.txt")).unwrap();
+        let host: Localhost = Localhost::new(PathBuf::from(tmp_dir.path())).ok().unwrap();
+        let mut list_dir =
+            |path: &Path| -> Vec<FsEntry> { host.scan_dir(path).unwrap_or_default() };
+        assert!(tree_exceeds_depth(root.as_path(), 1, &mut list_dir));
+        assert!(tree_exceeds_depth(root.as_path(), 2, &mut list_dir));
+        assert!(!tree_exceeds_depth(root.as_path(), 3, &mut list_dir));
+        assert!(!tree_exceeds_depth(root.as_path(), 16, &mut list_dir));
+        // Transfers stop walking directories at the depth limit
+        let mut activity: FileTransferActivity = test_activity();
+        assert_eq!(activity.max_depth, 0);
+        activity.batch = Some(TransferSummary::new());
+        assert!(!activity.transfer_depth_reached(root.as_path()));
+        activity.max_depth = 1;
+        assert!(!activity.transfer_depth_reached(root.as_path()));
+        activity.transfer_depth_step(true);
+        assert!(activity.transfer_depth_reached(root.join("b").as_path()));
+        assert_eq!(activity.batch.as_ref().unwrap().skipped, 1);
+        activity.transfer_depth_step(false);
+        assert_eq!(activity.batch.as_ref().unwrap().depth, 0);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_confirm_size() {
+        let mut activity: FileTransferActivity = test_activity();
+        assert_eq!(activity.confirm_size, 0);
+        activity.confirm_size = 1000;
+        let make_file = |name: &str, size: usize| -> FsEntry {
+            match make_fs_entry(name, false) {
+                FsEntry::File(mut file) => {
+                    file.size = size;
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        // Aggregate size exceeds threshold: confirm once
+        activity.filetransfer_request(
+            vec![
+                (make_file("a.iso", 600), None),
+                (make_file("b.iso", 600), None),
+            ],
+            Path::new("/tmp"),
+            false,
+        );
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Download 2 entries (1.2 KB)?"
+        ));
+        assert_eq!(activity.pending_transfer.as_ref().unwrap().entries.len(), 2);
+        assert!(!activity.pending_transfer.as_ref().unwrap().upload);
+        // Cancel
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_transfer_cancel();
+        assert!(activity.pending_transfer.is_none());
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        // Single entry
+        activity.filetransfer_request(
+            vec![(make_file("c.iso", 4096), Some(String::from("d.iso")))],
+            Path::new("/tmp"),
+            true,
+        );
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Upload \"c.iso\" (4.1 KB)?"
+        ));
+        assert!(activity.pending_transfer.as_ref().unwrap().upload);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_copy_overwrite() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.confirm_size = 1;
+        let make_file = |name: &str| -> FsEntry {
+            match make_fs_entry(name, false) {
+                FsEntry::File(mut file) => {
+                    file.size = 1024;
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        activity.local.files = vec![make_file("a.txt"), make_file("b.txt")];
+        activity.remote.files = vec![make_file("b.txt")];
+        activity.remote.wrkdir = PathBuf::from("/srv");
+        activity.local.index = 1;
+        activity.local.toggle_mark();
+        activity.local.index = 0;
+        activity.local.toggle_mark();
+        // <F5>: marked entries are copied into the remote directory; b.txt exists there
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::F(5),
+            KeyModifiers::NONE,
+        )));
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Overwrite \"b.txt\" in \"/srv\"?"
+        ));
+        assert!(activity.local.get_marked().is_empty());
+        let transfer: &PendingTransfer = activity.pending_transfer.as_ref().unwrap();
+        assert_eq!(transfer.entries.len(), 2);
+        assert_eq!(transfer.dst, PathBuf::from("/srv"));
+        assert!(transfer.upload);
+        // Confirmed: the transfer size must be confirmed then
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_copy_overwrite_confirm();
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Upload 2 entries (2.0 KB)?"
+        ));
+        assert!(activity.pending_transfer.is_some());
+        // Nothing to overwrite: no overwrite confirmation
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_transfer_cancel();
+        activity.local.index = 0;
+        activity.filetransfer_copy(true, true);
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Upload \"a.txt\" (1.0 KB)?"
+        ));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_redraw_throttle() {
+        // Percent: redraw once progress advanced by more than 1%
+        let mut throttle: RedrawThrottle = RedrawThrottle::new(RedrawInterval::Percent(1.0));
+        let start: Instant = throttle.last_draw;
+        assert!(!throttle.should_draw_at(start, 0.5));
+        assert!(!throttle.should_draw_at(start, 1.0));
+        assert!(throttle.should_draw_at(start, 1.5));
+        assert!(!throttle.should_draw_at(start, 2.0));
+        assert!(throttle.should_draw_at(start, 2.6));
+        // 0%: redraw whenever progress advances
+        let mut throttle: RedrawThrottle = RedrawThrottle::new(RedrawInterval::Percent(0.0));
+        assert!(throttle.should_draw_at(start, 0.1));
+        assert!(!throttle.should_draw_at(start, 0.1));
+        // Time: redraw each 100ms, whatever the progress
+        let mut throttle: RedrawThrottle = RedrawThrottle::new(RedrawInterval::Millis(100));
+        let start: Instant = throttle.last_draw;
+        assert!(!throttle.should_draw_at(start + Duration::from_millis(50), 50.0));
+        assert!(throttle.should_draw_at(start + Duration::from_millis(100), 50.0));
+        assert!(!throttle.should_draw_at(start + Duration::from_millis(150), 60.0));
+        assert!(throttle.should_draw_at(start + Duration::from_millis(200), 60.0));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_buffer_tuner() {
+        let second = |n: u64| -> Duration { Duration::from_secs(n) };
+        // Fixed size
+        let mut tuner: BufferTuner = BufferTuner::new(false, 0);
+        let start: Instant = tuner.window.0;
+        tuner.sample_at(start + second(1), 1048576);
+        assert_eq!(tuner.size, BUFFER_SIZE);
+        assert!(!tuner.is_auto_tune());
+        // Size set on the command line
+        let mut tuner: BufferTuner = BufferTuner::fixed(4096);
+        tuner.start();
+        let start: Instant = tuner.window.0;
+        tuner.sample_at(start + second(1), 1048576);
+        assert_eq!(tuner.size, 4096);
+        assert!(!tuner.is_auto_tune());
+        // Grow while throughput increases, back off once it plateaus
+        let mut tuner: BufferTuner = BufferTuner::new(true, 1048576);
+        let start: Instant = tuner.window.0;
+        tuner.sample_at(start + Duration::from_millis(500), 100000);
+        assert_eq!(tuner.size, 65536);
+        tuner.sample_at(start + second(1), 1000000);
+        assert_eq!(tuner.size, 131072);
+        tuner.sample_at(start + second(2), 3000000);
+        assert_eq!(tuner.size, 262144);
+        tuner.sample_at(start + second(3), 5050000);
+        assert_eq!(tuner.size, 131072);
+        // Settled
+        tuner.sample_at(start + second(4), 9000000);
+        assert_eq!(tuner.size, 131072);
+        // Never exceeds maximum
+        let mut tuner: BufferTuner = BufferTuner::new(true, 100000);
+        let start: Instant = tuner.window.0;
+        for n in 1..8 {
+            tuner.sample_at(start + second(n), (n * n * 1000000) as usize);
+        }
+        assert_eq!(tuner.size, 100000);
+        // Never drops below minimum
+        let mut tuner: BufferTuner = BufferTuner::new(true, 1);
+        assert_eq!(tuner.size, BUFFER_MIN_SIZE);
+        let start: Instant = tuner.window.0;
+        tuner.sample_at(start + second(1), 1000);
+        tuner.sample_at(start + second(2), 1000);
+        assert_eq!(tuner.size, BUFFER_MIN_SIZE);
+        // A new file tries bigger sizes again
+        tuner.start();
+        assert!(tuner.growing);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_queue_progress() {
+        let start: Instant = Instant::now();
+        let mut queue: QueueProgress = QueueProgress::new(Some(100000));
+        queue.samples.clear();
+        assert!(queue.throughput().is_none());
+        assert!(queue.eta(0).is_none());
+        assert_eq!(queue.report(0).as_str(), "0 B / 100.0 KB - ETA --");
+        // 1000 bytes/s
+        queue.sample_at(start, 0);
+        queue.sample_at(start + Duration::from_secs(1), 1000);
+        queue.sample_at(start + Duration::from_secs(2), 2000);
+        assert_eq!(queue.throughput(), Some(1000.0));
+        assert_eq!(queue.eta(2000), Some(Duration::from_secs(98)));
+        // First file completed
+        queue.file_done(2000);
+        assert_eq!(queue.eta(0), Some(Duration::from_secs(98)));
+        assert_eq!(queue.report(0).as_str(), "2.0 KB / 100.0 KB - ETA 01:38");
+        queue.sample_at(start + Duration::from_secs(3), 1000);
+        queue.sample_at(start + Duration::from_secs(4), 2000);
+        assert_eq!(queue.samples.len(), 5);
+        assert_eq!(queue.throughput(), Some(1000.0));
+        // Old samples are discarded: throughput is calculated on the recent ones
+        queue.sample_at(start + Duration::from_secs(10), 14000);
+        assert_eq!(queue.samples.len(), 2);
+        assert_eq!(queue.throughput(), Some(2000.0));
+        // Unknown total
+        let queue: QueueProgress = QueueProgress::new(None);
+        assert!(queue.eta(1000).is_none());
+        assert_eq!(queue.report(1000).as_str(), "1.0 KB - ETA --");
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_diff() {
+        let (lines, truncated): (Vec<String>, bool) =
+            diff::diff_lines("a\nb\nc\n", "a\nB\nc\nd\n", 100);
+        assert!(!truncated);
+        assert_eq!(
+            lines,
+            vec![
+                String::from("--- local"),
+                String::from("+++ remote"),
+                String::from("@@ -1,3 +1,4 @@"),
+                String::from(" a"),
+                String::from("-b"),
+                String::from("+B"),
+                String::from(" c"),
+                String::from("+d"),
+            ]
+        );
+        // Identical files have no diff
+        assert!(diff::diff_lines("a\nb\n", "a\nb\n", 100).0.is_empty());
+        // Truncated diff
+        let (lines, truncated): (Vec<String>, bool) =
+            diff::diff_lines("a\nb\nc\n", "a\nB\nc\nd\n", 4);
+        assert!(truncated);
+        assert_eq!(lines.len(), 4);
+        // Scroll
+        let mut state: DiffState = DiffState {
+            title: String::new(),
+            lines,
+            scroll: 0,
+        };
+        state.scroll_down(2);
+        assert_eq!(state.scroll, 2);
+        state.scroll_down(8);
+        assert_eq!(state.scroll, 3); // Last line stays visible
+        state.scroll_up(8);
+        assert_eq!(state.scroll, 0);
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_follow_state() {
+        let file: FsFile = match make_fs_entry("syslog", false) {
+            FsEntry::File(file) => file,
+            FsEntry::Directory(_) => panic!("Expected file"),
+        };
+        let mut follow: FollowState = FollowState::new(file, 64);
+        assert_eq!(follow.offset, 64);
+        assert!(follow.get_lines(10).is_empty());
+        // Lines and partial line
+        follow.push(b"first\r\nsecond\nthi");
+        assert_eq!(follow.offset, 81);
+        assert_eq!(follow.lines.len(), 2);
+        assert_eq!(
+            follow.get_lines(10),
+            vec![
+                String::from("first"),
+                String::from("second"),
+                String::from("thi")
+            ]
+        );
+        assert_eq!(follow.get_lines(1), vec![String::from("thi")]);
+        // Complete partial line
+        follow.push(b"rd\n");
+        assert_eq!(follow.get_lines(1), vec![String::from("third")]);
+        assert!(follow.partial.is_empty());
+        // Restart
+        follow.push(b"partial");
+        follow.restart();
+        assert_eq!(follow.offset, 0);
+        assert!(follow.partial.is_empty());
+        assert_eq!(follow.lines.len(), 4);
+        // Max lines
+        for i in 0..FOLLOW_MAX_LINES {
+            follow.push(format!("{}\n", i).as_bytes());
+        }
+        assert_eq!(follow.lines.len(), FOLLOW_MAX_LINES);
+        assert_eq!(follow.lines.front().unwrap().as_str(), "0");
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_preserve_paths() {
+        let mut activity: FileTransferActivity = test_activity();
+        activity.confirm_size = 1;
+        activity.preserve_paths = true;
+        activity.remote.wrkdir = PathBuf::from("/srv");
+        let make_file = |dir: &str, name: &str| -> FsEntry {
+            match make_fs_entry(name, false) {
+                FsEntry::File(mut file) => {
+                    file.abs_path = PathBuf::from("/home/omar").join(dir).join(name);
+                    file.size = 1024;
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        // Mark a file in two subdirectories
+        activity.local.wrkdir = PathBuf::from("/home/omar/a");
+        activity.local.files = vec![make_file("a", "x.txt")];
+        activity.local.toggle_mark();
+        activity.local.wrkdir = PathBuf::from("/home/omar/b");
+        activity.local.files = vec![make_file("b", "y.txt"), make_file("b", "z.txt")];
+        activity.local.index = 1;
+        activity.local.toggle_mark();
+        // Transferred from their common parent: paths are recreated under the destination
+        activity.local.wrkdir = PathBuf::from("/home/omar");
+        activity.local.files = vec![make_fs_entry("a", true), make_fs_entry("b", true)];
+        activity.filetransfer_copy(true, true);
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Upload 2 entries (2.0 KB)?"
+        ));
+        let transfer: &PendingTransfer = activity.pending_transfer.as_ref().unwrap();
+        assert_eq!(transfer.dst, PathBuf::from("/srv"));
+        let names: Vec<Option<String>> = transfer
+            .entries
+            .iter()
+            .map(|(_, name)| name.clone())
+            .collect();
+        assert_eq!(
+            names,
+            vec![Some(String::from("a/x.txt")), Some(String::from("b/z.txt"))]
+        );
+        assert!(activity.local.marked.is_empty());
+        // Entries outside of the working directory keep their name
+        assert_eq!(
+            FileTransferActivity::relative_name(
+                &make_file("a", "x.txt"),
+                Path::new("/home/omar/b")
+            )
+            .as_str(),
+            "x.txt"
+        );
+        // Disabled (default): only the marks of the working directory are transferred, flattened
+        activity.callback_transfer_cancel();
+        activity.input_mode = InputMode::Explorer;
+        activity.preserve_paths = false;
+        activity.local.wrkdir = PathBuf::from("/home/omar/a");
+        activity.local.files = vec![make_file("a", "x.txt")];
+        activity.local.index = 0;
+        activity.local.toggle_mark();
+        activity.local.wrkdir = PathBuf::from("/home/omar/b");
+        activity.local.files = vec![make_file("b", "y.txt")];
+        activity.local.toggle_mark();
+        activity.filetransfer_copy(true, true);
+        let transfer: &PendingTransfer = activity.pending_transfer.as_ref().unwrap();
+        assert_eq!(transfer.entries.len(), 1);
+        assert_eq!(transfer.entries[0].1.as_deref(), Some("y.txt"));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_select_glob() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        activity.remote.files = vec![
+            make_fs_entry("access.log", false),
+            make_fs_entry("error.LOG", false),
+            make_fs_entry("logs", true),
+            make_fs_entry("old.log", true),
+            make_fs_entry("readme.txt", false),
+        ];
+        activity.tab = FileExplorerTab::Remote;
+        // <CTRL+F> asks for the glob
+        activity.handle_input_event_mode_explorer(&InputEvent::Key(KeyEvent::new(
+            KeyCode::Char('f'),
+            KeyModifiers::CONTROL,
+        )));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Input(..))
+        ));
+        // Names only, case-sensitive; directories are marked too
+        activity.callback_select_glob(String::from("*.log"));
+        let mut marked: Vec<String> = activity
+            .remote
+            .marked
+            .values()
+            .map(|x| x.get_name())
+            .collect();
+        marked.sort();
+        assert_eq!(marked, vec!["access.log", "old.log"]);
+        // Marks are added
+        activity.callback_select_glob(String::from("readme.???"));
+        assert_eq!(activity.remote.marked.len(), 3);
+        assert!(activity.local.marked.is_empty());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_buffer_size_param() {
+        // Not set: configured buffer is used
+        let activity: FileTransferActivity = test_activity();
+        assert_eq!(activity.buffer.size, BUFFER_SIZE);
+        let mut params: FileTransferParams = activity.params;
+        params.buffer_size = Some(1048576);
+        let activity: FileTransferActivity = FileTransferActivity::new(params, None);
+        assert_eq!(activity.buffer.size, 1048576);
+        assert!(!activity.buffer.is_auto_tune());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_popup_filter() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.remote.files = vec![
+            make_fs_entry("Documents", true),
+            make_fs_entry("docker-compose.yml", false),
+            make_fs_entry("notes.txt", false),
+        ];
+        activity.remote.index = 2;
+        activity.tab = FileExplorerTab::Remote;
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('/')));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Filter)
+        ));
+        // Listing is filtered at each keystroke
+        activity.handle_input_event(&key(KeyCode::Char('D')));
+        assert_eq!(activity.remote.count(), 2);
+        assert_eq!(activity.remote.index, 1);
+        activity.handle_input_event(&key(KeyCode::Char('o')));
+        activity.handle_input_event(&key(KeyCode::Char('c')));
+        activity.handle_input_event(&key(KeyCode::Char('u')));
+        assert_eq!(activity.remote.count(), 1);
+        assert_eq!(activity.remote.index, 0);
+        activity.handle_input_event(&key(KeyCode::Backspace));
+        assert_eq!(activity.remote.count(), 2);
+        // Enter keeps the filter
+        activity.handle_input_event(&key(KeyCode::Enter));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        assert!(activity.input_txt.is_empty());
+        assert_eq!(activity.remote.count(), 2);
+        // Input is pre-filled with the active filter; esc clears it
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('/')));
+        assert_eq!(activity.input_txt.as_str(), "Doc");
+        activity.handle_input_event(&key(KeyCode::Esc));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        assert_eq!(activity.remote.count(), 3);
+        // Local explorer isn't filtered
+        assert!(activity.local.name_filter.is_none());
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_chmod() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.tab = FileExplorerTab::Remote;
+        activity.remote.files = vec![make_fs_entry("omar.txt", false)];
+        if let FsEntry::File(file) = &mut activity.remote.files[0] {
+            file.unix_pex = Some((6, 4, 0));
+        }
+        // Input is pre-filled with the current mode
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('c')));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Input(_, _))
+        ));
+        assert_eq!(activity.input_txt.as_str(), "640");
+        // Bad mode is reported
+        activity.handle_input_event(&key(KeyCode::Char('9')));
+        activity.handle_input_event(&key(KeyCode::Enter));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        // Not connected: error is reported
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_chmod(String::from("644"));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        // Readonly mode
+        activity.input_mode = InputMode::Explorer;
+        activity.set_readonly(true);
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('c')));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_resume_offset() {
+        let t: std::time::SystemTime = std::time::SystemTime::UNIX_EPOCH;
+        let make_file = |size: usize, secs: u64| -> FsFile {
+            match make_fs_entry("a.bin", false) {
+                FsEntry::File(mut file) => {
+                    file.size = size;
+                    file.last_change_time = t + Duration::from_secs(secs);
+                    file
+                }
+                FsEntry::Directory(_) => unreachable!(),
+            }
+        };
+        let local: FsFile = make_file(1024, 100);
+        // Interrupted upload
+        assert_eq!(
+            FileTransferActivity::resume_offset(&local, &make_file(512, 200)),
+            512
+        );
+        assert_eq!(
+            FileTransferActivity::resume_offset(&local, &make_file(512, 100)),
+            512
+        );
+        // Older file: it's replaced
+        assert_eq!(
+            FileTransferActivity::resume_offset(&local, &make_file(512, 50)),
+            0
+        );
+        // Empty, complete or bigger file
+        assert_eq!(
+            FileTransferActivity::resume_offset(&local, &make_file(0, 200)),
+            0
+        );
+        assert_eq!(
+            FileTransferActivity::resume_offset(&local, &make_file(1024, 200)),
+            0
+        );
+        assert_eq!(
+            FileTransferActivity::resume_offset(&local, &make_file(2048, 200)),
+            0
+        );
+    }
+
+    pub(super) fn test_activity() -> FileTransferActivity {
+        FileTransferActivity::new(
+            FileTransferParams {
+                address: String::from("192.168.1.31"),
+                port: 22,
+                protocol: FileTransferProtocol::default(),
+                username: None,
+                password: None,
+                jump_hosts: Vec::new(),
+                identity: None,
+                buffer_size: None,
+            },
+            None,
+        )
+    }
+
+    pub(super) fn make_fs_entry(name: &str, is_dir: bool) -> FsEntry {
+        let t: std::time::SystemTime = std::time::SystemTime::UNIX_EPOCH;
+        match is_dir {
+            true => FsEntry::Directory(crate::fs::FsDirectory {
+                name: String::from(name),
+                abs_path: PathBuf::from("/home/omar").join(name),
+                last_change_time: t,
+                last_access_time: t,
+                creation_time: t,
+                readonly: false,
+                symlink: None,
+                link_target: None,
+                user: None,
+                group: None,
+                unix_pex: None,
+                nlink: None,
+                inode: None,
+            }),
+            false => FsEntry::File(crate::fs::FsFile {
+                name: String::from(name),
+                abs_path: PathBuf::from("/home/omar").join(name),
+                last_change_time: t,
+                last_access_time: t,
+                creation_time: t,
+                size: 0,
+                ftype: None,
+                readonly: false,
+                symlink: None,
+                link_target: None,
+                user: None,
+                group: None,
+                unix_pex: None,
+                nlink: None,
+                inode: None,
+            }),
+        }
+    }
+}
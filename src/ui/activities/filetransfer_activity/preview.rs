@@ -0,0 +1,87 @@
+//! ## Preview
+//!
+//! `preview` is the module which shows a preview of image files in a popup
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{FileTransferActivity, InputMode, LogLevel, PopupType};
+use crate::fs::preview::ImagePreview;
+use crate::fs::FsFile;
+
+// Ext
+use std::path::PathBuf;
+
+/// ## PreviewState
+///
+/// PreviewState contains the image displayed in the image popup
+pub(super) struct PreviewState {
+    pub name: PathBuf,       // Path of the image file (local or remote)
+    pub image: ImagePreview, // Decoded image
+}
+
+impl FileTransferActivity {
+    /// ### preview_open
+    ///
+    /// Show a preview of the image `file` in the image popup.
+    /// Remote images are downloaded to a temporary file first, which is removed once decoded
+    pub(super) fn preview_open(&mut self, file: &FsFile, local: bool) {
+        let tmpfile: Option<tempfile::NamedTempFile> = match local {
+            true => None,
+            false => match self.download_tmpfile(file) {
+                Ok(tmpfile) => Some(tmpfile),
+                Err(err) => {
+                    self.log_and_alert(
+                        LogLevel::Error,
+                        format!("Could not preview image: {}", err),
+                    );
+                    return;
+                }
+            },
+        };
+        let path: PathBuf = match &tmpfile {
+            Some(tmpfile) => PathBuf::from(tmpfile.path()),
+            None => file.abs_path.clone(),
+        };
+        match ImagePreview::open(path.as_path()) {
+            Ok(image) => {
+                self.preview = Some(PreviewState {
+                    name: file.abs_path.clone(),
+                    image,
+                });
+                self.input_mode = InputMode::Popup(PopupType::Image);
+            }
+            Err(err) => self.log_and_alert(
+                LogLevel::Error,
+                format!("\"{}\": {}", file.abs_path.display(), err),
+            ),
+        }
+    }
+
+    /// ### preview_close
+    ///
+    /// Close the image popup
+    pub(super) fn preview_close(&mut self) {
+        self.preview = None;
+        self.input_mode = InputMode::Explorer;
+    }
+}
@@ -0,0 +1,337 @@
+//! ## Queue
+//!
+//! `queue` is the module which keeps the entries enqueued for transfer,
+//! which are then transferred one after another once the queue is started
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{FileTransferActivity, InputMode, LogLevel, PopupType, QueueProgress};
+use crate::fs::FsEntry;
+
+// Ext
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// ## QueuedTransfer
+///
+/// QueuedTransfer describes an entry waiting in the transfer queue
+pub(super) struct QueuedTransfer {
+    pub entry: FsEntry,       // Entry to transfer
+    pub name: Option<String>, // Destination name
+    pub dst: PathBuf,         // Destination directory
+    pub upload: bool,         // Transfer from local to remote?
+}
+
+/// ## TransferQueue
+///
+/// TransferQueue contains the entries enqueued for transfer, in order
+pub(super) struct TransferQueue {
+    items: VecDeque<QueuedTransfer>,
+    done: usize,       // Items taken from the queue since it was started
+    pub running: bool, // Is the queue being transferred?
+}
+
+impl TransferQueue {
+    /// ### new
+    ///
+    /// Instantiates a new empty TransferQueue
+    pub fn new() -> TransferQueue {
+        TransferQueue {
+            items: VecDeque::new(),
+            done: 0,
+            running: false,
+        }
+    }
+
+    /// ### push
+    ///
+    /// Enqueue `entry`, to be transferred with `name` into `dst`
+    pub fn push(&mut self, entry: FsEntry, name: Option<String>, dst: &Path, upload: bool) {
+        self.items.push_back(QueuedTransfer {
+            entry,
+            name,
+            dst: PathBuf::from(dst),
+            upload,
+        });
+    }
+
+    /// ### pop
+    ///
+    /// Take the next entry to transfer
+    pub fn pop(&mut self) -> Option<QueuedTransfer> {
+        let item: QueuedTransfer = self.items.pop_front()?;
+        self.done += 1;
+        Some(item)
+    }
+
+    /// ### len
+    ///
+    /// Returns the amount of entries waiting in the queue
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// ### is_empty
+    ///
+    /// Returns whether there are no entries waiting in the queue
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// ### clear
+    ///
+    /// Remove all the entries from the queue; returns the amount of entries removed
+    pub fn clear(&mut self) -> usize {
+        let items: usize = self.items.len();
+        self.items.clear();
+        self.done = 0;
+        items
+    }
+
+    /// ### size
+    ///
+    /// Returns the total size of the entries waiting in the queue; None if directories are enqueued, since their size is unknown
+    pub fn size(&self) -> Option<usize> {
+        self.items
+            .iter()
+            .map(|x| match &x.entry {
+                FsEntry::File(file) => Some(file.size),
+                FsEntry::Directory(_) => None,
+            })
+            .sum()
+    }
+
+    /// ### position
+    ///
+    /// Returns the position of the entry being transferred and the amount of entries of the queue (e.g. file 2 of 5),
+    /// if the queue is running
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self.running {
+            true => Some((self.done, self.done + self.items.len())),
+            false => None,
+        }
+    }
+}
+
+impl Default for TransferQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileTransferActivity {
+    /// ### queue_push
+    ///
+    /// Enqueue the marked entries (or the selected one) of the focused explorer, to be transferred into the working directory
+    /// of the other explorer; entries are uploaded if `upload` is true, downloaded otherwise
+    pub(super) fn queue_push(&mut self, upload: bool) {
+        let entries: Vec<(FsEntry, Option<String>)> = self.take_copy_entries(upload);
+        if entries.is_empty() {
+            return;
+        }
+        let dst: PathBuf = match upload {
+            true => self.remote.wrkdir.clone(),
+            false => self.local.wrkdir.clone(),
+        };
+        let count: usize = entries.len();
+        for (entry, name) in entries.into_iter() {
+            self.transfer_queue.push(entry, name, dst.as_path(), upload);
+        }
+        self.log(
+            LogLevel::Info,
+            format!(
+                "Enqueued {} entries for {} into \"{}\" ({} in queue); press <CTRL+B> to start the queue",
+                count,
+                match upload {
+                    true => "upload",
+                    false => "download",
+                },
+                dst.display(),
+                self.transfer_queue.len()
+            )
+            .as_str(),
+        );
+    }
+
+    /// ### process_queue
+    ///
+    /// Transfer the enqueued entries one after another, tracking the overall progress.
+    /// If the transfer gets aborted, the entry in progress is skipped and the user is asked whether to go on
+    /// with the remaining entries or to cancel the whole queue
+    pub(super) fn process_queue(&mut self) {
+        if self.transfer_queue.is_empty() {
+            self.log(
+                LogLevel::Warn,
+                "Transfer queue is empty; enqueue entries with <B>",
+            );
+            return;
+        }
+        self.queue_progress = Some(QueueProgress::new(self.transfer_queue.size()));
+        self.transfer.aborted = false;
+        self.transfer_queue.running = true;
+        while let Some(item) = self.transfer_queue.pop() {
+            match item.upload {
+                true => self.filetransfer_send(&item.entry, item.dst.as_path(), item.name),
+                false => self.filetransfer_recv(&item.entry, item.dst.as_path(), item.name),
+            }
+            if self.transfer.aborted {
+                break;
+            }
+        }
+        self.transfer_queue.running = false;
+        self.queue_progress = None;
+        let aborted: bool = std::mem::replace(&mut self.transfer.aborted, false);
+        match (aborted, self.transfer_queue.len()) {
+            (true, remaining) if remaining > 0 => {
+                self.input_mode = InputMode::Popup(PopupType::YesNo(
+                    format!(
+                        "Transfer aborted: go on with the {} remaining queued entries?",
+                        remaining
+                    ),
+                    FileTransferActivity::callback_queue_resume,
+                    FileTransferActivity::callback_queue_cancel,
+                ));
+            }
+            (true, _) => {
+                self.transfer_queue.clear();
+            }
+            (false, _) => {
+                let done: usize = self.transfer_queue.done;
+                self.transfer_queue.clear();
+                self.log(
+                    LogLevel::Info,
+                    format!("Transfer queue completed ({} entries)", done).as_str(),
+                );
+            }
+        }
+    }
+
+    /// ### callback_queue_resume
+    ///
+    /// After an abort, go on with the remaining entries of the queue
+    pub(super) fn callback_queue_resume(&mut self) {
+        self.process_queue();
+    }
+
+    /// ### callback_queue_cancel
+    ///
+    /// After an abort, discard the remaining entries of the queue
+    pub(super) fn callback_queue_cancel(&mut self) {
+        let removed: usize = self.transfer_queue.clear();
+        self.log(
+            LogLevel::Info,
+            format!("Transfer queue cancelled ({} entries removed)", removed).as_str(),
+        );
+    }
+
+    /// ### forget_queue
+    ///
+    /// Discard the enqueued entries, since their remote paths refer to the remote which is no longer active
+    pub(super) fn forget_queue(&mut self) {
+        let removed: usize = self.transfer_queue.clear();
+        if removed > 0 {
+            self.log(
+                LogLevel::Warn,
+                format!(
+                    "{} entries removed from the transfer queue of \"{}\"",
+                    removed, self.params.address
+                )
+                .as_str(),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ui::activities::filetransfer_activity::tests::{make_fs_entry, test_activity};
+    use crate::ui::activities::filetransfer_activity::{FileExplorerTab, InputEvent};
+
+    #[test]
+    fn test_ui_activities_filetransfer_transfer_queue() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut queue: TransferQueue = TransferQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.size(), Some(0));
+        let mut file: FsEntry = make_fs_entry("a.txt", false);
+        if let FsEntry::File(file) = &mut file {
+            file.size = 1024;
+        }
+        queue.push(file, None, Path::new("/tmp"), true);
+        queue.push(make_fs_entry("b.txt", false), None, Path::new("/tmp"), true);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.size(), Some(1024));
+        // Position is reported only while running
+        assert!(queue.position().is_none());
+        queue.running = true;
+        assert_eq!(queue.pop().unwrap().entry.get_name(), "a.txt");
+        assert_eq!(queue.position(), Some((1, 2)));
+        // Directories size is unknown
+        queue.push(make_fs_entry("docs", true), None, Path::new("/tmp"), true);
+        assert!(queue.size().is_none());
+        assert_eq!(queue.position(), Some((1, 3)));
+        assert_eq!(queue.clear(), 2);
+        assert!(queue.is_empty());
+        // Enqueue from explorers
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.local.wrkdir = PathBuf::from("/home/omar");
+        activity.remote.wrkdir = PathBuf::from("/srv");
+        activity.remote.files = vec![
+            make_fs_entry("a.txt", false),
+            make_fs_entry("b.txt", false),
+            make_fs_entry("c.txt", false),
+        ];
+        activity.tab = FileExplorerTab::Remote;
+        // Marked entries are enqueued and unmarked
+        activity.remote.index = 0;
+        activity.remote.toggle_mark();
+        activity.remote.index = 2;
+        activity.remote.toggle_mark();
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('b')));
+        assert_eq!(activity.transfer_queue.len(), 2);
+        assert_eq!(activity.remote.marked.len(), 0);
+        // Selected entry, if none is marked
+        activity.remote.index = 1;
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('B')));
+        assert_eq!(activity.transfer_queue.len(), 3);
+        let item: QueuedTransfer = activity.transfer_queue.pop().unwrap();
+        assert_eq!(item.entry.get_name(), "a.txt");
+        assert_eq!(item.dst.as_path(), Path::new("/home/omar"));
+        assert!(!item.upload);
+        // Uploads can't be enqueued in readonly mode
+        activity.set_readonly(true);
+        activity.local.files = vec![make_fs_entry("d.txt", false)];
+        activity.tab = FileExplorerTab::Local;
+        activity.handle_input_event_mode_explorer(&key(KeyCode::Char('b')));
+        assert_eq!(activity.transfer_queue.len(), 2);
+        // Cancel queue
+        activity.callback_queue_cancel();
+        assert!(activity.transfer_queue.is_empty());
+        // Empty queue isn't started
+        activity.process_queue();
+        assert!(activity.queue_progress.is_none());
+    }
+}
@@ -0,0 +1,410 @@
+//! ## Recent
+//!
+//! `recent` is the module which keeps the list of the files transferred during the session,
+//! so they can be transferred again or their directories reopened
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{
+    FileExplorer, FileExplorerTab, FileTransferActivity, InputMode, LogLevel, PendingTransfer,
+    PopupType,
+};
+use crate::fs::FsEntry;
+
+// Ext
+use std::path::{Path, PathBuf};
+
+// Maximum amount of files in the recent transfers list; older transfers are forgotten
+pub(super) const RECENT_TRANSFERS_SIZE: usize = 32;
+
+/// ## RecentTransfer
+///
+/// RecentTransfer describes a file transferred during the session
+#[derive(Clone, std::fmt::Debug, PartialEq)]
+pub(super) struct RecentTransfer {
+    pub local: PathBuf,  // Path of the local file
+    pub remote: PathBuf, // Path of the remote file
+    pub host: String,    // Address of the remote host
+    pub upload: bool,    // Was the file uploaded (or downloaded)?
+}
+
+/// ### push_recent
+///
+/// Put `transfer` on top of `recent`. A previous transfer of the same file (local and remote path on the same host,
+/// in either direction) is removed, and the oldest transfer is forgotten if the list is full
+pub(super) fn push_recent(recent: &mut Vec<RecentTransfer>, transfer: RecentTransfer) {
+    recent.retain(|x| {
+        x.local != transfer.local || x.remote != transfer.remote || x.host != transfer.host
+    });
+    recent.insert(0, transfer);
+    recent.truncate(RECENT_TRANSFERS_SIZE);
+}
+
+impl FileTransferActivity {
+    /// ### recent_add
+    ///
+    /// Record a file transferred between `local` and `remote` on the current host
+    pub(super) fn recent_add(&mut self, local: &Path, remote: &Path, upload: bool) {
+        let transfer: RecentTransfer = RecentTransfer {
+            local: PathBuf::from(local),
+            remote: PathBuf::from(remote),
+            host: self.params.address.clone(),
+            upload,
+        };
+        push_recent(&mut self.recent, transfer);
+    }
+
+    /// ### recent_open
+    ///
+    /// Show the recent transfers popup, with the latest transfer selected
+    pub(super) fn recent_open(&mut self) {
+        if self.recent.is_empty() {
+            self.log(LogLevel::Info, "No file has been transferred yet");
+            return;
+        }
+        self.recent_index = 0;
+        self.input_mode = InputMode::Popup(PopupType::Recent);
+    }
+
+    /// ### recent_move_index
+    ///
+    /// Move the selection of the recent transfers popup up (or down)
+    pub(super) fn recent_move_index(&mut self, up: bool) {
+        self.recent_index = match up {
+            true => self.recent_index.saturating_sub(1),
+            false => (self.recent_index + 1).min(self.recent.len().saturating_sub(1)),
+        };
+    }
+
+    /// ### recent_transfer
+    ///
+    /// Transfer the selected file again, in the same direction and to the same destination
+    pub(super) fn recent_transfer(&mut self) {
+        let transfer: RecentTransfer = match self.recent_selected(true) {
+            Some(transfer) => transfer,
+            None => return,
+        };
+        if transfer.upload && self.readonly {
+            self.log_readonly();
+            return;
+        }
+        if let Some(pending) = self.recent_pending(&transfer) {
+            self.filetransfer_request(pending.entries, pending.dst.as_path(), pending.upload);
+        }
+    }
+
+    /// ### log_transfer_failure
+    ///
+    /// Log and display the error of a failed file transfer between `local` and `remote`.
+    /// The log record keeps the transfer, so it can be retried from the log panel
+    pub(super) fn log_transfer_failure(
+        &mut self,
+        err: String,
+        local: &Path,
+        remote: &Path,
+        upload: bool,
+    ) {
+        self.log_and_alert(LogLevel::Error, err);
+        if let Some(record) = self.log_records.front_mut() {
+            record.transfer = Some(RecentTransfer {
+                local: PathBuf::from(local),
+                remote: PathBuf::from(remote),
+                host: self.params.address.clone(),
+                upload,
+            });
+        }
+    }
+
+    /// ### log_retry
+    ///
+    /// If the selected log record is a failed file transfer, ask to queue it again.
+    /// Other records are ignored
+    pub(super) fn log_retry(&mut self) {
+        let transfer: RecentTransfer = match self
+            .log_records
+            .get(self.log_index)
+            .and_then(|x| x.transfer.clone())
+        {
+            Some(transfer) => transfer,
+            None => return,
+        };
+        if !self.recent_check_host(&transfer) {
+            return;
+        }
+        if transfer.upload && self.readonly {
+            self.log_readonly();
+            return;
+        }
+        if let Some(pending) = self.recent_pending(&transfer) {
+            let (operation, src): (&str, &Path) = match transfer.upload {
+                true => ("upload", transfer.local.as_path()),
+                false => ("download", transfer.remote.as_path()),
+            };
+            self.input_mode = InputMode::Popup(PopupType::YesNo(
+                format!("Retry {} of \"{}\"?", operation, src.display()),
+                FileTransferActivity::callback_retry_confirm,
+                FileTransferActivity::callback_transfer_cancel,
+            ));
+            self.pending_transfer = Some(pending);
+        }
+    }
+
+    /// ### recent_pending
+    ///
+    /// Get the transfer of the source file of `transfer` to its destination.
+    /// If the source can't be stat, None is returned and the error displayed
+    fn recent_pending(&mut self, transfer: &RecentTransfer) -> Option<PendingTransfer> {
+        let (src, dst): (&Path, &Path) = match transfer.upload {
+            true => (transfer.local.as_path(), transfer.remote.as_path()),
+            false => (transfer.remote.as_path(), transfer.local.as_path()),
+        };
+        let entry: Result<FsEntry, String> = match transfer.upload {
+            true => self
+                .context
+                .as_ref()
+                .unwrap()
+                .local
+                .stat(src)
+                .map_err(|err| err.to_string()),
+            false => self.client.stat(src).map_err(|err| err.to_string()),
+        };
+        match entry {
+            Ok(entry) => Some(PendingTransfer {
+                entries: vec![(
+                    entry.get_realfile(),
+                    dst.file_name().map(|x| x.to_string_lossy().to_string()),
+                )],
+                dst: dst.parent().map(PathBuf::from).unwrap_or_default(),
+                upload: transfer.upload,
+                size: None,
+            }),
+            Err(err) => {
+                self.log_and_alert(
+                    LogLevel::Error,
+                    format!("Could not transfer \"{}\" again: {}", src.display(), err),
+                );
+                None
+            }
+        }
+    }
+
+    /// ### recent_goto
+    ///
+    /// Change the working directory of the local (or remote) explorer to the directory of the selected file,
+    /// and select the file
+    pub(super) fn recent_goto(&mut self, local: bool) {
+        let transfer: RecentTransfer = match self.recent_selected(!local) {
+            Some(transfer) => transfer,
+            None => return,
+        };
+        let path: &Path = match local {
+            true => transfer.local.as_path(),
+            false => transfer.remote.as_path(),
+        };
+        let dir: PathBuf = match path.parent() {
+            Some(dir) => PathBuf::from(dir),
+            None => return,
+        };
+        match local {
+            true => {
+                self.local_changedir(dir.as_path(), true);
+                FileTransferActivity::recent_select(&mut self.local, path);
+                self.tab = FileExplorerTab::Local;
+            }
+            false => {
+                self.remote_changedir(dir.as_path(), true);
+                FileTransferActivity::recent_select(&mut self.remote, path);
+                self.tab = FileExplorerTab::Remote;
+            }
+        }
+    }
+
+    /// ### recent_selected
+    ///
+    /// Close the recent transfers popup and return the selected transfer.
+    /// If the `remote` file is needed, None is returned (and an error displayed) when it's on another host than the active remote
+    fn recent_selected(&mut self, remote: bool) -> Option<RecentTransfer> {
+        self.input_mode = InputMode::Explorer;
+        let transfer: RecentTransfer = self.recent.get(self.recent_index).cloned()?;
+        match !remote || self.recent_check_host(&transfer) {
+            true => Some(transfer),
+            false => None,
+        }
+    }
+
+    /// ### recent_check_host
+    ///
+    /// Returns whether the remote file of `transfer` is on the active remote; otherwise an error is displayed
+    fn recent_check_host(&mut self, transfer: &RecentTransfer) -> bool {
+        if transfer.host == self.params.address {
+            return true;
+        }
+        self.log_and_alert(
+            LogLevel::Error,
+            format!(
+                "\"{}\" was transferred with \"{}\"; switch to a remote tab connected to it first",
+                transfer.remote.display(),
+                transfer.host
+            ),
+        );
+        false
+    }
+
+    /// ### recent_select
+    ///
+    /// Select the entry at `path` in `explorer`, if displayed
+    fn recent_select(explorer: &mut FileExplorer, path: &Path) {
+        let index: Option<usize> = explorer
+            .iter_files()
+            .position(|x| x.get_abs_path().as_path() == path);
+        if let Some(index) = index {
+            explorer.index = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ui::activities::filetransfer_activity::tests::test_activity;
+    use crate::ui::activities::filetransfer_activity::{InputEvent, InputField};
+
+    #[test]
+    fn test_ui_activities_filetransfer_push_recent() {
+        let transfer = |local: &str, remote: &str, upload: bool| RecentTransfer {
+            local: PathBuf::from(local),
+            remote: PathBuf::from(remote),
+            host: String::from("192.168.1.31"),
+            upload,
+        };
+        let mut recent: Vec<RecentTransfer> = Vec::new();
+        push_recent(&mut recent, transfer("/tmp/a.txt", "/home/a.txt", true));
+        push_recent(&mut recent, transfer("/tmp/b.txt", "/home/b.txt", false));
+        // Same file again: moved on top, with the latest direction
+        push_recent(&mut recent, transfer("/tmp/a.txt", "/home/a.txt", false));
+        assert_eq!(
+            recent,
+            vec![
+                transfer("/tmp/a.txt", "/home/a.txt", false),
+                transfer("/tmp/b.txt", "/home/b.txt", false)
+            ]
+        );
+        // Same paths on another host are another file
+        let mut other: RecentTransfer = transfer("/tmp/a.txt", "/home/a.txt", true);
+        other.host = String::from("10.0.0.1");
+        push_recent(&mut recent, other.clone());
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0], other);
+        // Oldest transfers are forgotten
+        for i in 0..RECENT_TRANSFERS_SIZE {
+            push_recent(
+                &mut recent,
+                transfer(format!("/tmp/{}", i).as_str(), "/home/x", true),
+            );
+        }
+        assert_eq!(recent.len(), RECENT_TRANSFERS_SIZE);
+        assert_eq!(
+            recent[0].local,
+            PathBuf::from(format!("/tmp/{}", RECENT_TRANSFERS_SIZE - 1))
+        );
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_recent_popup() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        // Nothing transferred yet: no popup
+        activity.handle_input_event(&key(KeyCode::Char('j')));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        activity.recent_add(Path::new("/tmp/a.txt"), Path::new("/home/a.txt"), true);
+        activity.recent_add(Path::new("/tmp/b.txt"), Path::new("/home/b.txt"), false);
+        activity.handle_input_event(&key(KeyCode::Char('j')));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Recent)
+        ));
+        assert_eq!(activity.recent_index, 0);
+        // Selection stops at the boundaries
+        activity.handle_input_event(&key(KeyCode::Down));
+        activity.handle_input_event(&key(KeyCode::Down));
+        assert_eq!(activity.recent_index, 1);
+        activity.handle_input_event(&key(KeyCode::Up));
+        assert_eq!(activity.recent_index, 0);
+        activity.handle_input_event(&key(KeyCode::Esc));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        // Remote files transferred with another host can't be reached from this one
+        activity.params.address = String::from("10.0.0.1");
+        activity.handle_input_event(&key(KeyCode::Char('j')));
+        activity.handle_input_event(&key(KeyCode::Char('r')));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_log_retry() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut activity: FileTransferActivity = test_activity();
+        let key = |code: KeyCode| InputEvent::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        activity.input_field = InputField::Logs;
+        // Failed transfers are kept in their record
+        activity.log_transfer_failure(
+            String::from("Connection reset"),
+            Path::new("/tmp/a.txt"),
+            Path::new("/home/a.txt"),
+            false,
+        );
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        assert_eq!(
+            activity.log_records.front().unwrap().transfer,
+            Some(RecentTransfer {
+                local: PathBuf::from("/tmp/a.txt"),
+                remote: PathBuf::from("/home/a.txt"),
+                host: String::from("192.168.1.31"),
+                upload: false,
+            })
+        );
+        // Other records are not actionable
+        activity.input_mode = InputMode::Explorer;
+        activity.log(LogLevel::Error, "Could not rename file");
+        activity.handle_input_event(&key(KeyCode::Char('r')));
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+        // Remote file on another host
+        activity.log_index = 1;
+        activity.params.address = String::from("10.0.0.1");
+        activity.handle_input_event(&key(KeyCode::Char('r')));
+        match &activity.input_mode {
+            InputMode::Popup(PopupType::Alert(_, msg)) => {
+                assert!(msg.contains("switch to a remote tab connected to it"))
+            }
+            _ => panic!("Expected alert"),
+        }
+        assert!(activity.pending_transfer.is_none());
+    }
+}
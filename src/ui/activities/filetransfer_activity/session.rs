@@ -26,17 +26,30 @@ extern crate crossterm;
 extern crate tempfile;
 
 // Locals
-use super::{FileTransferActivity, InputMode, LogLevel, PopupType};
-use crate::fs::{FsEntry, FsFile};
+use super::{
+    ExplorerFilter, FileExplorer, FileExplorerTab, FileTransferActivity, FollowState, InputMode,
+    LogLevel, PendingTransfer, PopupType, QueueProgress, RedrawThrottle, TransferResult,
+    TransferSummary, FOLLOW_INITIAL_BYTES, FOLLOW_POLL_INTERVAL, MAX_AUTH_RETRIES,
+    SCAN_POLL_INTERVAL,
+};
+use crate::config::LineEnding;
+use crate::filetransfer::{FileTransfer, FileTransferError, FileTransferErrorType, StreamFlusher};
+use crate::fs::{FsDirectory, FsEntry, FsFile};
+use crate::host::{DirIter, HostError, Localhost};
+use crate::system::config_client::ConfigClient;
+use crate::utils::eol::{is_convertible, EolConverter};
 use crate::utils::fmt::fmt_millis;
 use crate::utils::hash::hash_sha256_file;
 
 // Ext
 use bytesize::ByteSize;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use std::borrow::Cow;
 use std::fs::OpenOptions;
 use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
 use std::time::Instant;
 use tui::style::Color;
 
@@ -53,25 +66,94 @@ impl FileTransferActivity {
             self.params.password.clone(),
         ) {
             Ok(welcome) => {
-                if let Some(banner) = welcome {
-                    // Log welcome
+                if let Some(method) = self.client.get_auth_method() {
                     self.log(
+                        LogLevel::Info,
+                        format!("Authenticated on '{}' with {}", self.params.address, method)
+                            .as_str(),
+                    );
+                }
+                // Log welcome (unless disabled); multi-line banners are logged line by line
+                match welcome.filter(|_| self.show_banner) {
+                    Some(banner) if banner.trim().contains('\n') => {
+                        self.log(
+                            LogLevel::Info,
+                            format!("Established connection with '{}':", self.params.address)
+                                .as_ref(),
+                        );
+                        for line in banner.lines().map(|x| x.trim_end()) {
+                            if !line.is_empty() {
+                                self.log(LogLevel::Info, line);
+                            }
+                        }
+                    }
+                    Some(banner) => self.log(
                         LogLevel::Info,
                         format!(
                             "Established connection with '{}': \"{}\"",
-                            self.params.address, banner
+                            self.params.address,
+                            banner.trim()
                         )
                         .as_ref(),
-                    );
+                    ),
+                    None => {}
+                }
+                // Get remote working directory; it is then tracked by `remote.wrkdir`
+                match self.client.pwd() {
+                    Ok(pwd) => self.remote.wrkdir = pwd,
+                    Err(err) => {
+                        let msg: String = format!(
+                            "Could not get remote working directory: {}; using {}",
+                            err,
+                            self.remote.wrkdir.display()
+                        );
+                        self.log(LogLevel::Warn, msg.as_str());
+                    }
                 }
+                // Reconnected after a cancelled scan: go back to the directory which was displayed
+                if let Some(wrkdir) = self.reconnect_wrkdir.take() {
+                    match self.client.change_dir(wrkdir.as_path()) {
+                        Ok(wrkdir) => self.remote.wrkdir = wrkdir,
+                        Err(err) => {
+                            let msg: String =
+                                format!("Could not go back to \"{}\": {}", wrkdir.display(), err);
+                            self.log(LogLevel::Warn, msg.as_str());
+                        }
+                    }
+                }
+                self.check_clock_skew();
                 // Set state to explorer
                 self.input_mode = InputMode::Explorer;
                 self.reload_remote_dir();
             }
-            Err(err) => {
-                // Set popup fatal error
-                self.input_mode = InputMode::Popup(PopupType::Fatal(format!("{}", err)));
-            }
+            Err(err) => match err.kind() {
+                FileTransferErrorType::AuthenticationFailed
+                    if self.auth_retries < MAX_AUTH_RETRIES =>
+                {
+                    // Let the user correct credentials; username is pre-filled
+                    self.auth_retries += 1;
+                    self.log(LogLevel::Error, format!("{}", err).as_str());
+                    self.input_txt = self.params.username.clone().unwrap_or_default();
+                    self.input_mode = InputMode::Popup(PopupType::Input(
+                        format!(
+                            "Authentication failed; username ({}/{})",
+                            self.auth_retries, MAX_AUTH_RETRIES
+                        ),
+                        FileTransferActivity::callback_retry_username,
+                    ));
+                }
+                FileTransferErrorType::AuthenticationFailed => {
+                    self.remote_tab_give_up(Some(format!(
+                        "{} (giving up after {} attempts)",
+                        err,
+                        MAX_AUTH_RETRIES + 1
+                    )));
+                }
+                _ => {
+                    // Set popup fatal error (or close the remote tab, if other ones are open)
+                    self.remote_tab_give_up(Some(format!("{}", err)));
+                }
+            },
         }
     }
 
@@ -86,6 +168,11 @@ impl FileTransferActivity {
         ));
         // Disconnect
         let _ = self.client.disconnect();
+        self.remote_tabs_disconnect();
+        self.log(
+            LogLevel::Info,
+            format!("Disconnected from \"{}\"", self.params.address).as_str(),
+        );
         // Quit
         self.disconnected = true;
     }
@@ -100,14 +187,273 @@ impl FileTransferActivity {
 
     /// ### reload_remote_dir
     ///
-    /// Reload remote directory entries
+    /// Reload remote directory entries.
+    /// The cached working directory is used, so the server isn't asked for it on each reload
     pub(super) fn reload_remote_dir(&mut self) {
-        // Get current entries
-        if let Ok(pwd) = self.client.pwd() {
-            self.remote_scan(pwd.as_path());
-            // Set wrkdir
-            self.remote.wrkdir = pwd;
+        let wrkdir: PathBuf = self.remote.wrkdir.clone();
+        self.remote_scan(wrkdir.as_path());
+    }
+
+    /// ### filetransfer_request
+    ///
+    /// Transfer entries (with their destination name) to `dst`; entries are uploaded if `upload` is true, downloaded otherwise.
+    /// If their total size exceeds the confirmation size, the user is asked to confirm the transfer first
+    pub(super) fn filetransfer_request(
+        &mut self,
+        entries: Vec<(FsEntry, Option<String>)>,
+        dst: &Path,
+        upload: bool,
+    ) {
+        let mut transfer: PendingTransfer = PendingTransfer {
+            entries,
+            dst: PathBuf::from(dst),
+            upload,
+            size: None,
+        };
+        // Files size is known; directories are scanned only if size must be confirmed
+        if !transfer.entries.iter().any(|(entry, _)| entry.is_dir()) {
+            transfer.size = Some(
+                transfer
+                    .entries
+                    .iter()
+                    .map(|(entry, _)| match entry {
+                        FsEntry::File(file) => file.size,
+                        FsEntry::Directory(_) => 0,
+                    })
+                    .sum(),
+            );
+        }
+        if self.confirm_size > 0 {
+            // Directories must be scanned to get their size; the scan can be aborted
+            self.transfer.aborted = false;
+            if transfer.entries.iter().any(|(entry, _)| entry.is_dir()) {
+                self.input_mode = InputMode::Popup(PopupType::Wait(format!(
+                    "Calculating transfer size... (<{}> to abort)",
+                    self.abort_key
+                )));
+                self.draw();
+            }
+            let mut size: u64 = 0;
+            for (entry, _) in transfer.entries.iter() {
+                size += self.transfer_size(entry, upload);
+            }
+            self.input_mode = InputMode::Explorer;
+            if self.transfer.aborted {
+                self.transfer.aborted = false;
+                self.log(
+                    LogLevel::Warn,
+                    "Transfer aborted while calculating its size",
+                );
+                return;
+            }
+            transfer.size = Some(size as usize);
+            if size > self.confirm_size {
+                let what: String = match transfer.entries.as_slice() {
+                    [(entry, _)] => format!("\"{}\"", entry.get_name()),
+                    entries => format!("{} entries", entries.len()),
+                };
+                self.input_mode = InputMode::Popup(PopupType::YesNo(
+                    format!(
+                        "{} {} ({})?",
+                        match upload {
+                            true => "Upload",
+                            false => "Download",
+                        },
+                        what,
+                        ByteSize(size)
+                    ),
+                    FileTransferActivity::callback_transfer_confirm,
+                    FileTransferActivity::callback_transfer_cancel,
+                ));
+                self.pending_transfer = Some(transfer);
+                return;
+            }
+        }
+        self.filetransfer_run(transfer);
+    }
+
+    /// ### filetransfer_copy
+    ///
+    /// Transfer the marked entries (or the selected one) of the focused explorer into the working directory
+    /// of the other explorer; entries are uploaded if `upload` is true, downloaded otherwise.
+    /// If `confirm_overwrite` is true and entries with the same name are listed in the destination,
+    /// the user is asked to confirm the overwrite first.
+    /// If relative paths are preserved, the entries marked in the subdirectories of the working directory are transferred too,
+    /// recreating their path relative to the working directory in the destination
+    pub(super) fn filetransfer_copy(&mut self, upload: bool, confirm_overwrite: bool) {
+        let entries: Vec<(FsEntry, Option<String>)> = self.take_copy_entries(upload);
+        let dst: &FileExplorer = match upload {
+            true => &self.remote,
+            false => &self.local,
+        };
+        // Entries in subdirectories are never listed in the destination working directory
+        let existing: Vec<String> = match confirm_overwrite {
+            true => entries
+                .iter()
+                .filter_map(|(_, name)| name.clone())
+                .filter(|name| dst.files.iter().any(|x| x.get_name() == *name))
+                .collect(),
+            false => Vec::new(),
+        };
+        let wrkdir: PathBuf = dst.wrkdir.clone();
+        if existing.is_empty() {
+            self.filetransfer_request(entries, wrkdir.as_path(), upload);
+            return;
+        }
+        let what: String = match existing.as_slice() {
+            [name] => format!("\"{}\"", name),
+            names => format!("{} entries", names.len()),
+        };
+        self.input_mode = InputMode::Popup(PopupType::YesNo(
+            format!("Overwrite {} in \"{}\"?", what, wrkdir.display()),
+            FileTransferActivity::callback_copy_overwrite_confirm,
+            FileTransferActivity::callback_transfer_cancel,
+        ));
+        self.pending_transfer = Some(PendingTransfer {
+            entries,
+            dst: wrkdir,
+            upload,
+            size: None,
+        });
+    }
+
+    /// ### take_copy_entries
+    ///
+    /// Get the marked entries (or the selected one) of the local explorer if `upload` is true, of the remote one otherwise,
+    /// with the name they're transferred with, and unmark them
+    pub(super) fn take_copy_entries(&mut self, upload: bool) -> Vec<(FsEntry, Option<String>)> {
+        let preserve_paths: bool = self.preserve_paths;
+        let src: &mut FileExplorer = match upload {
+            true => &mut self.local,
+            false => &mut self.remote,
+        };
+        // Get marked files, or selected file if none is marked, and clone (due to mutable / immutable stuff...)
+        let files: Vec<FsEntry> = match preserve_paths {
+            true => src.get_all_marked_or_selected(),
+            false => src.get_marked_or_selected(),
+        };
+        src.clear_marks();
+        // Pass realfile, keep link name (or its path relative to the working directory)
+        files
+            .iter()
+            .map(|file| {
+                let name: String = match preserve_paths {
+                    true => FileTransferActivity::relative_name(file, src.wrkdir.as_path()),
+                    false => file.get_name(),
+                };
+                (file.get_realfile(), Some(name))
+            })
+            .collect()
+    }
+
+    /// ### relative_name
+    ///
+    /// Returns the path of `entry` relative to `base`, which is the name it's transferred with when relative paths are preserved.
+    /// Components are separated by `/`, which both local and remote hosts accept. Entries outside of `base` keep their name
+    pub(super) fn relative_name(entry: &FsEntry, base: &Path) -> String {
+        match entry.get_abs_path().strip_prefix(base) {
+            Ok(path) if path.components().count() > 0 => path
+                .components()
+                .map(|x| x.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+                .join("/"),
+            _ => entry.get_name(),
+        }
+    }
+
+    /// ### filetransfer_run
+    ///
+    /// Transfer all the entries of the transfer, tracking the overall progress.
+    /// The results of all the entries are accumulated in one batch summary, reported once the transfer has stopped
+    pub(super) fn filetransfer_run(&mut self, transfer: PendingTransfer) {
+        self.queue_progress = Some(QueueProgress::new(transfer.size));
+        self.transfer.aborted = false;
+        // Nothing is transferred in dry run, so there's nothing to summarize
+        if !self.dry_run {
+            self.batch = Some(TransferSummary::new());
+        }
+        for (entry, name) in transfer.entries.iter() {
+            // If aborted; skip remaining entries
+            if self.transfer.aborted {
+                self.batch_add_skipped(entry.get_abs_path().as_path());
+                continue;
+            }
+            match transfer.upload {
+                true => self.filetransfer_send(entry, transfer.dst.as_path(), name.clone()),
+                false => self.filetransfer_recv(entry, transfer.dst.as_path(), name.clone()),
+            }
+        }
+        // A single file doesn't need a summary, its transfer is already logged
+        let summarize: bool =
+            transfer.entries.len() > 1 || transfer.entries.iter().any(|(x, _)| x.is_dir());
+        self.batch_report(transfer.upload, summarize);
+        self.transfer.aborted = false;
+        self.queue_progress = None;
+    }
+
+    /// ### log_buffer_size
+    ///
+    /// Log the transfer buffer size chosen by the auto-tuner, if enabled
+    fn log_buffer_size(&mut self) {
+        if self.buffer.is_auto_tune() {
+            let size: usize = self.buffer.size;
+            self.log(
+                LogLevel::Info,
+                format!("Transfer buffer auto-tuned to {}", ByteSize(size as u64)).as_str(),
+            );
+        }
+    }
+
+    /// ### queue_sample
+    ///
+    /// Sample the overall progress of the transfer in progress
+    fn queue_sample(&mut self) {
+        let bytes_written: usize = self.transfer.bytes_written;
+        if let Some(queue) = self.queue_progress.as_mut() {
+            queue.sample(bytes_written);
+        }
+    }
+
+    /// ### transfer_size
+    ///
+    /// Get the size of the entry; directories are scanned recursively (on localhost if `local` is true, on remote otherwise).
+    /// Symlinks to directories are not followed. The scan stops as soon as the transfer gets aborted
+    fn transfer_size(&mut self, entry: &FsEntry, local: bool) -> u64 {
+        let dir: &FsDirectory = match entry {
+            FsEntry::File(file) => return file.size as u64,
+            FsEntry::Directory(dir) if dir.symlink.is_some() => return 0,
+            FsEntry::Directory(dir) => dir,
+        };
+        // Handle the abort key; closing the wait popup with <ESC> aborts the scan too
+        while self.read_input_event() {}
+        if !matches!(self.input_mode, InputMode::Popup(PopupType::Wait(_))) {
+            self.transfer.aborted = true;
+        }
+        if self.transfer.aborted {
+            return 0;
+        }
+        let entries: Vec<FsEntry> = match local {
+            true => self
+                .context
+                .as_ref()
+                .unwrap()
+                .local
+                .scan_dir(dir.abs_path.as_path())
+                .unwrap_or_default(),
+            false => {
+                let entries: Vec<FsEntry> = self
+                    .client
+                    .list_dir(dir.abs_path.as_path())
+                    .unwrap_or_default();
+                self.log_client_warnings();
+                entries
+            }
+        };
+        let mut size: u64 = 0;
+        for entry in entries.iter() {
+            size += self.transfer_size(entry, local);
         }
+        size
     }
 
     /// ### filetransfer_send
@@ -121,6 +467,11 @@ impl FileTransferActivity {
         curr_remote_path: &Path,
         dst_name: Option<String>,
     ) {
+        // In dry run, operations are only logged
+        if self.dry_run {
+            self.filetransfer_dry_run(entry, curr_remote_path, dst_name, true);
+            return;
+        }
         // Write popup
         let file_name: String = match entry {
             FsEntry::Directory(dir) => dir.name.clone(),
@@ -129,6 +480,14 @@ impl FileTransferActivity {
         self.input_mode = InputMode::Popup(PopupType::Wait(format!("Uploading \"{}\"", file_name)));
         // Draw
         self.draw();
+        // If this is the outermost call and no batch has been started for the whole transfer, start a new batch summary
+        let batch_root: bool = self.batch.is_none();
+        if batch_root {
+            self.batch = Some(TransferSummary::new());
+        }
+        // Entries found while walking a directory (not the ones selected by the user)
+        let nested: bool = matches!(self.batch.as_ref(), Some(batch) if batch.depth > 0);
+        let first_result: usize = self.batch_results_len();
         // Get remote path
         let mut remote_path: PathBuf = PathBuf::from(curr_remote_path);
         let remote_file_name: PathBuf = match dst_name {
@@ -136,10 +495,76 @@ impl FileTransferActivity {
             None => PathBuf::from(file_name.as_str()),
         };
         remote_path.push(remote_file_name);
+        // Create missing parent directories (e.g. destination name is `a/b/file`)
+        if self.create_parents_enabled() {
+            self.remote_create_parents(curr_remote_path, remote_path.as_path());
+        }
         // Match entry
         match entry {
+            FsEntry::File(file)
+                if self
+                    .context
+                    .as_ref()
+                    .unwrap()
+                    .local
+                    .is_special_file(file.abs_path.as_path()) =>
+            {
+                // FIFOs, sockets and devices can't be read as files (opening a FIFO would block forever)
+                self.log(
+                    LogLevel::Warn,
+                    format!(
+                        "Skipped \"{}\": not a regular file (FIFO, socket or device)",
+                        file.abs_path.display()
+                    )
+                    .as_ref(),
+                );
+                self.batch_add_skipped(file.abs_path.as_path());
+            }
             FsEntry::File(file) => {
-                let _ = self.filetransfer_send_file(file, remote_path.as_path());
+                let mut result: Result<usize, String> =
+                    self.filetransfer_send_file(file, remote_path.as_path());
+                // Verify upload; eventually retry
+                if let Some(mut retries) = self.upload_verify_retries() {
+                    while !self.transfer.aborted {
+                        let size: usize = match &result {
+                            Ok(size) => *size,
+                            Err(_) => break,
+                        };
+                        match self.filetransfer_verify_upload(remote_path.as_path(), size) {
+                            Ok(_) => break,
+                            Err(err) if retries > 0 => {
+                                self.log(
+                                    LogLevel::Warn,
+                                    format!("{}; retrying upload...", err).as_str(),
+                                );
+                                retries -= 1;
+                                result = self.filetransfer_send_file(file, remote_path.as_path());
+                            }
+                            Err(err) => result = Err(err),
+                        }
+                    }
+                }
+                match &result {
+                    // Entry removed after the parent directory was scanned
+                    Err(_) if nested && !self.local_entry_exists(file.abs_path.as_path()) => {
+                        self.batch_skip_missing(file.abs_path.as_path())
+                    }
+                    Err(err) => {
+                        self.log_transfer_failure(
+                            err.clone(),
+                            file.abs_path.as_path(),
+                            remote_path.as_path(),
+                            true,
+                        );
+                        self.batch_add_result(file, false);
+                    }
+                    Ok(_) => {
+                        if !nested && !self.transfer.aborted {
+                            self.recent_add(file.abs_path.as_path(), remote_path.as_path(), true);
+                        }
+                        self.batch_add_result(file, true)
+                    }
+                }
             }
             FsEntry::Directory(dir) => {
                 // Create directory on remote
@@ -149,26 +574,40 @@ impl FileTransferActivity {
                             LogLevel::Info,
                             format!("Created directory \"{}\"", remote_path.display()).as_ref(),
                         );
-                        // Get files in dir
-                        match self
-                            .context
-                            .as_ref()
-                            .unwrap()
-                            .local
-                            .scan_dir(dir.abs_path.as_path())
-                        {
-                            Ok(entries) => {
+                        // Get files in dir, unless the depth limit has been reached
+                        let entries: Option<Result<Vec<FsEntry>, HostError>> =
+                            match self.transfer_depth_reached(dir.abs_path.as_path()) {
+                                true => None,
+                                false => Some(
+                                    self.context
+                                        .as_ref()
+                                        .unwrap()
+                                        .local
+                                        .scan_dir(dir.abs_path.as_path()),
+                                ),
+                            };
+                        match entries {
+                            None => {}
+                            Some(Ok(entries)) => {
+                                self.transfer_depth_step(true);
                                 // Iterate over files
                                 for entry in entries.iter() {
-                                    // If aborted; break
+                                    // If aborted; skip remaining entries
                                     if self.transfer.aborted {
-                                        break;
+                                        self.batch_add_skipped(entry.get_abs_path().as_path());
+                                        continue;
                                     }
                                     // Send entry; name is always None after first call
                                     self.filetransfer_send(&entry, remote_path.as_path(), None);
                                 }
+                                self.transfer_depth_step(false);
+                            }
+                            Some(Err(_))
+                                if nested && !self.local_entry_exists(dir.abs_path.as_path()) =>
+                            {
+                                self.batch_skip_missing(dir.abs_path.as_path())
                             }
-                            Err(err) => {
+                            Some(Err(err)) => {
                                 self.log_and_alert(
                                     LogLevel::Error,
                                     format!(
@@ -189,6 +628,7 @@ impl FileTransferActivity {
                                 err
                             ),
                         );
+                        self.batch_add_failure(dir.abs_path.as_path());
                     }
                 }
             }
@@ -196,15 +636,15 @@ impl FileTransferActivity {
         // Scan dir on remote
         let path: PathBuf = self.remote.wrkdir.clone();
         self.remote_scan(path.as_path());
-        // If aborted; show popup
+        // If aborted; show popup (once, for the outermost entry); the flag is reset once the whole transfer has stopped
         if self.transfer.aborted {
-            // Log abort
-            self.log_and_alert(
-                LogLevel::Warn,
-                format!("Upload aborted for \"{}\"!", entry.get_abs_path().display()),
-            );
-            // Set aborted to false
-            self.transfer.aborted = false;
+            if !nested {
+                // Log abort
+                self.log_and_alert(
+                    LogLevel::Warn,
+                    format!("Upload aborted for \"{}\"!", entry.get_abs_path().display()),
+                );
+            }
         } else {
             // @! Successful
             // Eventually, Reset input mode to explorer (if input mode is wait or progress)
@@ -214,6 +654,14 @@ impl FileTransferActivity {
                 }
             }
         }
+        // Selected directories get the overall result of their content
+        if !nested && entry.is_dir() {
+            self.batch_set_result_since(entry.get_abs_path().as_path(), first_result);
+        }
+        // Report summary if batch has completed
+        if batch_root {
+            self.batch_report(true, entry.is_dir());
+        }
     }
 
     /// ### filetransfer_recv
@@ -227,6 +675,11 @@ impl FileTransferActivity {
         local_path: &Path,
         dst_name: Option<String>,
     ) {
+        // In dry run, operations are only logged
+        if self.dry_run {
+            self.filetransfer_dry_run(entry, local_path, dst_name, false);
+            return;
+        }
         // Write popup
         let file_name: String = match entry {
             FsEntry::Directory(dir) => dir.name.clone(),
@@ -236,8 +689,42 @@ impl FileTransferActivity {
             InputMode::Popup(PopupType::Wait(format!("Downloading \"{}\"...", file_name)));
         // Draw
         self.draw();
+        // If this is the outermost call and no batch has been started for the whole transfer, start a new batch summary
+        let batch_root: bool = self.batch.is_none();
+        if batch_root {
+            self.batch = Some(TransferSummary::new());
+        }
+        // Entries found while walking a directory (not the ones selected by the user)
+        let nested: bool = matches!(self.batch.as_ref(), Some(batch) if batch.depth > 0);
+        let first_result: usize = self.batch_results_len();
+        if !nested {
+            self.local_create_download_dir(local_path);
+        }
         // Match entry
         match entry {
+            // Symlinks inside downloaded directories are recreated on localhost, with their original target, unless they're followed
+            FsEntry::File(FsFile {
+                symlink: Some(_),
+                link_target: Some(target),
+                ..
+            })
+            | FsEntry::Directory(FsDirectory {
+                symlink: Some(_),
+                link_target: Some(target),
+                ..
+            }) if nested && !self.follow_symlinks => {
+                let local_link_path: PathBuf =
+                    local_path.join(dst_name.unwrap_or_else(|| entry.get_name()));
+                // Create missing parent directories
+                if self.create_parents_enabled() {
+                    self.local_create_parents(local_path, local_link_path.as_path());
+                }
+                self.local_create_symlink(
+                    local_link_path.as_path(),
+                    entry.get_abs_path().as_path(),
+                    target.as_path(),
+                );
+            }
             FsEntry::File(file) => {
                 // Get local file
                 let mut local_file_path: PathBuf = PathBuf::from(local_path);
@@ -246,9 +733,40 @@ impl FileTransferActivity {
                     None => file.name.clone(),
                 };
                 local_file_path.push(local_file_name.as_str());
+                // Create missing parent directories
+                if self.create_parents_enabled() {
+                    self.local_create_parents(local_path, local_file_path.as_path());
+                }
                 // Download file
-                if let Err(err) = self.filetransfer_recv_file(local_file_path.as_path(), file) {
-                    self.log_and_alert(LogLevel::Error, err);
+                let result: Result<(), String> =
+                    self.filetransfer_recv_file(local_file_path.as_path(), file);
+                match &result {
+                    // Entry removed after the parent directory was listed
+                    Err(_) if nested && !self.remote_entry_exists(file.abs_path.as_path()) => {
+                        self.batch_skip_missing(file.abs_path.as_path())
+                    }
+                    Err(err) => {
+                        self.log_transfer_failure(
+                            err.clone(),
+                            local_file_path.as_path(),
+                            file.abs_path.as_path(),
+                            false,
+                        );
+                        self.batch_add_result(file, false);
+                    }
+                    Ok(_) => {
+                        if !self.transfer.aborted {
+                            self.run_post_download_command(local_file_path.as_path(), file.size);
+                            if !nested {
+                                self.recent_add(
+                                    local_file_path.as_path(),
+                                    file.abs_path.as_path(),
+                                    false,
+                                );
+                            }
+                        }
+                        self.batch_add_result(file, true);
+                    }
                 }
             }
             FsEntry::Directory(dir) => {
@@ -258,6 +776,10 @@ impl FileTransferActivity {
                     Some(name) => local_dir_path.push(name),
                     None => local_dir_path.push(dir.name.as_str()),
                 }
+                // Create missing parent directories
+                if self.create_parents_enabled() {
+                    self.local_create_parents(local_path, local_dir_path.as_path());
+                }
                 // Create directory on local
                 match self
                     .context
@@ -293,21 +815,38 @@ impl FileTransferActivity {
                             LogLevel::Info,
                             format!("Created directory \"{}\"", local_dir_path.display()).as_ref(),
                         );
-                        // Get files in dir
-                        match self.client.list_dir(dir.abs_path.as_path()) {
-                            Ok(entries) => {
+                        // Get files in dir, unless it has already been walked or the depth limit has been reached
+                        let entries: Option<Result<Vec<FsEntry>, FileTransferError>> = match self
+                            .transfer_loop_detected(dir)
+                            || self.transfer_depth_reached(dir.abs_path.as_path())
+                        {
+                            true => None,
+                            false => Some(self.client.list_dir(dir.abs_path.as_path())),
+                        };
+                        self.log_client_warnings();
+                        match entries {
+                            None => {}
+                            Some(Ok(entries)) => {
+                                self.transfer_depth_step(true);
                                 // Iterate over files
                                 for entry in entries.iter() {
-                                    // If transfer has been aborted; break
+                                    // If transfer has been aborted; skip remaining entries
                                     if self.transfer.aborted {
-                                        break;
+                                        self.batch_add_skipped(entry.get_abs_path().as_path());
+                                        continue;
                                     }
                                     // Receive entry; name is always None after first call
                                     // Local path becomes local_dir_path
                                     self.filetransfer_recv(&entry, local_dir_path.as_path(), None);
                                 }
+                                self.transfer_depth_step(false);
+                            }
+                            Some(Err(_))
+                                if nested && !self.remote_entry_exists(dir.abs_path.as_path()) =>
+                            {
+                                self.batch_skip_missing(dir.abs_path.as_path())
                             }
-                            Err(err) => {
+                            Some(Err(err)) => {
                                 self.log_and_alert(
                                     LogLevel::Error,
                                     format!(
@@ -329,168 +868,812 @@ impl FileTransferActivity {
                             )
                             .as_ref(),
                         );
+                        self.batch_add_failure(dir.abs_path.as_path());
+                    }
+                }
+            }
+        }
+        // Reload directory on local
+        self.local_scan(local_path);
+        // if aborted; show alert (once, for the outermost entry); the flag is reset once the whole transfer has stopped
+        if self.transfer.aborted {
+            if !nested {
+                // Log abort
+                self.log_and_alert(
+                    LogLevel::Warn,
+                    format!(
+                        "Download aborted for \"{}\"!",
+                        entry.get_abs_path().display()
+                    ),
+                );
+            }
+        } else {
+            // Eventually, Reset input mode to explorer
+            self.input_mode = InputMode::Explorer;
+        }
+        // Selected directories get the overall result of their content
+        if !nested && entry.is_dir() {
+            self.batch_set_result_since(entry.get_abs_path().as_path(), first_result);
+        }
+        // Report summary if batch has completed
+        if batch_root {
+            self.batch_report(false, entry.is_dir());
+        }
+    }
+
+    /// ### batch_add_result
+    ///
+    /// Register the result of a file transfer in the current batch summary.
+    /// If the transfer has been aborted, the file is considered as skipped
+    pub(super) fn batch_add_result(&mut self, file: &FsFile, success: bool) {
+        let aborted: bool = self.transfer.aborted;
+        if let Some(queue) = self.queue_progress.as_mut() {
+            queue.file_done(file.size);
+        }
+        if let Some(batch) = self.batch.as_mut() {
+            let result: TransferResult = match (success, aborted) {
+                (false, _) => {
+                    batch.add_failure(file.abs_path.as_path());
+                    TransferResult::Failed
+                }
+                (true, true) => {
+                    batch.add_skipped();
+                    TransferResult::Skipped
+                }
+                (true, false) => {
+                    batch.add_success(file.size);
+                    TransferResult::Success
+                }
+            };
+            batch.set_result(file.abs_path.as_path(), result);
+        }
+    }
+
+    /// ### batch_add_skipped
+    ///
+    /// Register a skipped entry in the current batch summary
+    pub(super) fn batch_add_skipped(&mut self, path: &Path) {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.add_skipped();
+            batch.set_result(path, TransferResult::Skipped);
+        }
+    }
+
+    /// ### transfer_depth_reached
+    ///
+    /// Returns whether the directory being transferred is as deep as the depth limit, relative to the outermost entry;
+    /// if so, its content won't be transferred and the directory is reported as skipped
+    pub(super) fn transfer_depth_reached(&mut self, dir: &Path) -> bool {
+        let depth: usize = self.batch.as_ref().map(|x| x.depth).unwrap_or(0);
+        if self.max_depth == 0 || depth < self.max_depth {
+            return false;
+        }
+        self.log(
+            LogLevel::Warn,
+            format!(
+                "Depth limit ({}) reached: content of \"{}\" skipped",
+                self.max_depth,
+                dir.display()
+            )
+            .as_str(),
+        );
+        self.batch_add_skipped(dir);
+        true
+    }
+
+    /// ### transfer_loop_detected
+    ///
+    /// Returns whether the directory being downloaded has already been walked during the batch, which happens when
+    /// symlinks are followed and one of them points to a parent directory; if so, the directory is reported as skipped
+    pub(super) fn transfer_loop_detected(&mut self, dir: &FsDirectory) -> bool {
+        let real_path: PathBuf = match dir.symlink.as_ref() {
+            Some(target) => target.get_realfile().get_abs_path(),
+            None => dir.abs_path.clone(),
+        };
+        let first_visit: bool = match self.batch.as_mut() {
+            Some(batch) => batch.visit(real_path.as_path()),
+            None => true,
+        };
+        if first_visit {
+            return false;
+        }
+        self.log(
+            LogLevel::Warn,
+            format!(
+                "Skipped \"{}\": symlink loop (\"{}\" has already been downloaded)",
+                dir.abs_path.display(),
+                real_path.display()
+            )
+            .as_str(),
+        );
+        self.batch_add_skipped(dir.abs_path.as_path());
+        true
+    }
+
+    /// ### local_create_symlink
+    ///
+    /// Recreate as `local` the remote symlink at `remote`, which points to `target`;
+    /// the symlink is skipped where symlinks can't be created
+    fn local_create_symlink(&mut self, local: &Path, remote: &Path, target: &Path) {
+        #[cfg(any(target_os = "unix", target_os = "macos", target_os = "linux"))]
+        match self.context.as_ref().unwrap().local.symlink(local, target) {
+            Ok(_) => {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "Created symlink \"{}\" pointing to \"{}\"",
+                        local.display(),
+                        target.display()
+                    )
+                    .as_str(),
+                );
+                if let Some(batch) = self.batch.as_mut() {
+                    batch.add_success(0);
+                    batch.set_result(remote, TransferResult::Success);
+                }
+            }
+            Err(err) => {
+                self.log(
+                    LogLevel::Error,
+                    format!(
+                        "Could not create symlink \"{}\" pointing to \"{}\": {}",
+                        local.display(),
+                        target.display(),
+                        err
+                    )
+                    .as_str(),
+                );
+                self.batch_add_failure(remote);
+            }
+        }
+        #[cfg(target_os = "windows")]
+        {
+            self.log(
+                LogLevel::Warn,
+                format!(
+                    "Skipped symlink \"{}\" pointing to \"{}\": \"{}\" can't be created as a symlink on this platform",
+                    remote.display(),
+                    target.display(),
+                    local.display()
+                )
+                .as_str(),
+            );
+            self.batch_add_skipped(remote);
+        }
+    }
+
+    /// ### transfer_depth_step
+    ///
+    /// Go one directory level down (or back up) in the batch being transferred
+    pub(super) fn transfer_depth_step(&mut self, down: bool) {
+        if let Some(batch) = self.batch.as_mut() {
+            match down {
+                true => batch.depth += 1,
+                false => batch.depth -= 1,
+            }
+        }
+    }
+
+    /// ### remove_depth_exceeded
+    ///
+    /// Returns whether `entry` is a directory deeper than the depth limit, which mustn't be removed;
+    /// the tree is walked on localhost if `local` is true, on remote otherwise
+    pub(super) fn remove_depth_exceeded(&mut self, entry: &FsEntry, local: bool) -> bool {
+        let dir: &FsDirectory = match entry {
+            FsEntry::Directory(dir) if dir.symlink.is_none() && self.max_depth > 0 => dir,
+            _ => return false,
+        };
+        let exceeded: bool = match local {
+            true => {
+                let host: &Localhost = &self.context.as_ref().unwrap().local;
+                tree_exceeds_depth(dir.abs_path.as_path(), self.max_depth, &mut |path| {
+                    host.scan_dir(path).unwrap_or_default()
+                })
+            }
+            false => {
+                let client: &mut Box<dyn FileTransfer> = &mut self.client;
+                tree_exceeds_depth(dir.abs_path.as_path(), self.max_depth, &mut |path| {
+                    client.list_dir(path).unwrap_or_default()
+                })
+            }
+        };
+        if !local {
+            self.log_client_warnings();
+        }
+        if exceeded {
+            self.log_and_alert(
+                LogLevel::Warn,
+                format!(
+                    "Depth limit ({}) reached: \"{}\" not removed",
+                    self.max_depth,
+                    dir.abs_path.display()
+                ),
+            );
+        }
+        exceeded
+    }
+
+    /// ### batch_skip_missing
+    ///
+    /// Register an entry which has been removed while transferring its parent directory as skipped
+    fn batch_skip_missing(&mut self, path: &Path) {
+        self.log(
+            LogLevel::Warn,
+            format!(
+                "Skipped \"{}\": no longer exists (directory changed during transfer)",
+                path.display()
+            )
+            .as_str(),
+        );
+        self.batch_add_skipped(path);
+    }
+
+    /// ### local_entry_exists
+    ///
+    /// Returns whether the entry at `path` still exists on localhost
+    fn local_entry_exists(&self, path: &Path) -> bool {
+        self.context.as_ref().unwrap().local.file_exists(path)
+    }
+
+    /// ### remote_entry_exists
+    ///
+    /// Returns whether the entry at `path` still exists on remote.
+    /// The entry is looked up in its parent directory if the protocol doesn't support stat;
+    /// if it can't be told, the entry is considered existing
+    fn remote_entry_exists(&mut self, path: &Path) -> bool {
+        let err: FileTransferError = match self.client.stat(path) {
+            Ok(_) => return true,
+            Err(err) => err,
+        };
+        match (err.kind(), path.parent()) {
+            (FileTransferErrorType::NoSuchFileOrDirectory, _) => false,
+            (FileTransferErrorType::UnsupportedFeature, Some(parent)) => {
+                let entries: Result<Vec<FsEntry>, FileTransferError> = self.client.list_dir(parent);
+                let _ = self.client.take_warnings();
+                match entries {
+                    Ok(entries) => FileTransferActivity::listing_contains(&entries, path),
+                    Err(_) => true,
+                }
+            }
+            _ => true,
+        }
+    }
+
+    /// ### listing_contains
+    ///
+    /// Returns whether a directory listing contains the entry at `path`
+    pub(super) fn listing_contains(entries: &[FsEntry], path: &Path) -> bool {
+        match path.file_name() {
+            Some(name) => entries
+                .iter()
+                .any(|x| name.to_string_lossy() == x.get_name()),
+            None => true,
+        }
+    }
+
+    /// ### batch_add_failure
+    ///
+    /// Register a failed entry in the current batch summary
+    pub(super) fn batch_add_failure(&mut self, path: &Path) {
+        if let Some(batch) = self.batch.as_mut() {
+            batch.add_failure(path);
+            batch.set_result(path, TransferResult::Failed);
+        }
+    }
+
+    /// ### batch_results_len
+    ///
+    /// Get the amount of results registered in the current batch summary so far
+    fn batch_results_len(&self) -> usize {
+        self.batch.as_ref().map(|x| x.results.len()).unwrap_or(0)
+    }
+
+    /// ### batch_set_result_since
+    ///
+    /// Set the result of the entry at `path` as the overall result of the entries registered since `first` in the current batch summary
+    fn batch_set_result_since(&mut self, path: &Path, first: usize) {
+        if let Some(batch) = self.batch.as_mut() {
+            let result: TransferResult = batch.result_since(first);
+            batch.set_result(path, result);
+        }
+    }
+
+    /// ### batch_report
+    ///
+    /// Terminate the current batch and set the result of each transferred entry on the explorer they were transferred from.
+    /// If `summarize` is true (many entries or directories), a summary is logged and displayed
+    fn batch_report(&mut self, upload: bool, summarize: bool) {
+        if let Some(batch) = self.batch.take() {
+            if summarize {
+                let level: LogLevel = match batch.failed.is_empty() {
+                    true => LogLevel::Info,
+                    false => LogLevel::Error,
+                };
+                let operation: &str = match upload {
+                    true => "Upload",
+                    false => "Download",
+                };
+                self.log_and_alert(level, batch.report(operation));
+            }
+            match upload {
+                true => self.local.set_results(batch.results),
+                false => self.remote.set_results(batch.results),
+            }
+        }
+    }
+
+    /// ### filetransfer_send_file
+    ///
+    /// Send local file and write it to remote path.
+    /// Returns the amount of bytes written to the remote file, which differs from the local size if line endings have been converted
+    pub(super) fn filetransfer_send_file(
+        &mut self,
+        local: &FsFile,
+        remote: &Path,
+    ) -> Result<usize, String> {
+        // Uploading a file onto itself would truncate it before it's read
+        if self.remote_is_same_file(local.abs_path.as_path(), remote) {
+            return Err(format!(
+                "Refused to upload \"{}\" onto itself",
+                local.abs_path.display()
+            ));
+        }
+        let mut bytes_sent: usize = 0;
+        // Upload file
+        // Try to open local file
+        match self
+            .context
+            .as_ref()
+            .unwrap()
+            .local
+            .open_file_read(local.abs_path.as_path())
+        {
+            Ok(mut fhnd) => {
+                let resume_offset: usize = self.upload_resume_offset(local, remote);
+                // In trash mode, back up the remote file which is going to be overwritten
+                if self.trash_dir.is_some() {
+                    self.backup_remote_file(remote);
+                }
+                match self.client.send_file_at(local, remote, resume_offset) {
+                    Ok((mut rhnd, offset)) => {
+                        self.log_client_warnings();
+                        // Write file
+                        let file_size: usize =
+                            fhnd.seek(std::io::SeekFrom::End(0)).unwrap_or(0) as usize;
+                        // rewind (or seek to the data the remote file is missing)
+                        if let Err(err) = fhnd.seek(std::io::SeekFrom::Start(offset as u64)) {
+                            return Err(format!("Could not rewind local file: {}", err));
+                        }
+                        if offset > 0 {
+                            self.log(
+                                LogLevel::Info,
+                                format!(
+                                    "Resuming upload of \"{}\" to \"{}\" from {}",
+                                    local.abs_path.display(),
+                                    remote.display(),
+                                    ByteSize(offset as u64)
+                                )
+                                .as_str(),
+                            );
+                        }
+                        // Write remote file
+                        let mut total_bytes_written: usize = offset;
+                        bytes_sent += offset;
+                        let mut eol: Option<EolConverter> =
+                            self.remote_line_ending.map(EolConverter::new);
+                        // Set input state to popup progress
+                        self.input_mode = InputMode::Popup(PopupType::Progress(format!(
+                            "Uploading \"{}\"",
+                            local.name
+                        )));
+                        // Reset transfer states
+                        self.transfer.reset();
+                        self.transfer.resume(offset, file_size);
+                        let mut throttle: RedrawThrottle =
+                            RedrawThrottle::new(self.redraw_interval);
+                        let mut last_input_event_fetch: Instant = Instant::now();
+                        let mut flusher: StreamFlusher = StreamFlusher::new(self.flush_interval);
+                        self.buffer.start();
+                        let mut buffer: Vec<u8> = vec![0; self.buffer.size];
+                        // Zero-length file: there's nothing to write, remote file is created by finalizing the stream
+                        if file_size == 0 {
+                            self.transfer.set_progress(0, 0);
+                            self.draw();
+                        }
+                        // While the entire file hasn't been completely written,
+                        // Or filetransfer has been aborted
+                        while total_bytes_written < file_size && !self.transfer.aborted {
+                            // Handle input events (each 500ms)
+                            if last_input_event_fetch.elapsed().as_millis() >= 500 {
+                                // Read events
+                                self.read_input_event();
+                                // Reset instant
+                                last_input_event_fetch = Instant::now();
+                            }
+                            // Read till you can
+                            match fhnd.read(&mut buffer) {
+                                Ok(bytes_read) => {
+                                    total_bytes_written += bytes_read;
+                                    if bytes_read == 0 {
+                                        continue;
+                                    } else {
+                                        // Line endings are converted only in text files, detected by the first chunk
+                                        if total_bytes_written == bytes_read
+                                            && !is_convertible(&buffer[0..bytes_read])
+                                        {
+                                            eol = None;
+                                        }
+                                        let data: Cow<[u8]> = match eol.as_mut() {
+                                            Some(eol) => {
+                                                Cow::Owned(eol.convert(&buffer[0..bytes_read]))
+                                            }
+                                            None => Cow::Borrowed(&buffer[0..bytes_read]),
+                                        };
+                                        let mut buf_start: usize = 0;
+                                        while buf_start < data.len() {
+                                            // Write bytes
+                                            match rhnd.write(&data[buf_start..]) {
+                                                Ok(bytes) => {
+                                                    buf_start += bytes;
+                                                }
+                                                Err(err) => {
+                                                    return Err(format!(
+                                                        "Could not write remote file: {}",
+                                                        err
+                                                    ))
+                                                }
+                                            }
+                                        }
+                                        bytes_sent += data.len();
+                                        // Flush periodically, if configured
+                                        if let Err(err) = flusher.written(&mut rhnd, data.len()) {
+                                            return Err(format!(
+                                                "Could not flush remote file: {}",
+                                                err
+                                            ));
+                                        }
+                                    }
+                                }
+                                Err(err) => {
+                                    return Err(format!("Could not read local file: {}", err))
+                                }
+                            }
+                            // Increase progress
+                            self.transfer.set_progress(total_bytes_written, file_size);
+                            self.queue_sample();
+                            self.buffer.sample(total_bytes_written - offset);
+                            buffer.resize(self.buffer.size, 0);
+                            // Draw only if the configured redraw interval has passed (performance improvement)
+                            if throttle.should_draw(self.transfer.progress) {
+                                self.draw();
+                            }
+                        }
+                        // Write the bytes held back by the line ending conversion
+                        if let Some(eol) = eol.as_mut() {
+                            let tail: Vec<u8> = eol.finish();
+                            if let Err(err) = rhnd.write_all(tail.as_slice()) {
+                                return Err(format!("Could not write remote file: {}", err));
+                            }
+                            bytes_sent += tail.len();
+                        }
+                        // Finalize stream
+                        if let Err(err) = self.client.on_sent(rhnd) {
+                            self.log(
+                                LogLevel::Warn,
+                                format!("Could not finalize remote stream: \"{}\"", err).as_str(),
+                            );
+                        }
+                        self.log(
+                            LogLevel::Info,
+                            format!(
+                                "Saved file \"{}\" to \"{}\" (took {} seconds; at {}/s)",
+                                local.abs_path.display(),
+                                remote.display(),
+                                fmt_millis(self.transfer.started.elapsed()),
+                                ByteSize(self.transfer.bytes_per_second()),
+                            )
+                            .as_ref(),
+                        );
+                        self.log_eol_conversion(eol, remote);
+                        self.log_buffer_size();
+                    }
+                    Err(err) => {
+                        self.log_client_warnings();
+                        return Err(format!(
+                            "Failed to upload file \"{}\": {}",
+                            local.abs_path.display(),
+                            err
+                        ));
                     }
                 }
             }
+            Err(err) => {
+                return Err(format!(
+                    "Failed to open file \"{}\": {}",
+                    local.abs_path.display(),
+                    err
+                ))
+            }
         }
-        // Reload directory on local
-        self.local_scan(local_path);
-        // if aborted; show alert
-        if self.transfer.aborted {
-            // Log abort
-            self.log_and_alert(
-                LogLevel::Warn,
-                format!(
-                    "Download aborted for \"{}\"!",
-                    entry.get_abs_path().display()
-                ),
-            );
-            // Reset aborted to false
-            self.transfer.aborted = false;
-        } else {
-            // Eventually, Reset input mode to explorer
-            self.input_mode = InputMode::Explorer;
+        Ok(bytes_sent)
+    }
+
+    /// ### upload_resume_offset
+    ///
+    /// Returns the offset an upload to `remote` can be resumed from, if the remote file looks like an
+    /// interrupted upload of `local`; 0 otherwise.
+    /// Uploads are resumed only if enabled in configuration, since the remote file content isn't compared;
+    /// never if line endings are converted, since sizes don't match, or in trash mode,
+    /// since the remote file is moved to the trash
+    fn upload_resume_offset(&mut self, local: &FsFile, remote: &Path) -> usize {
+        if !self.resume_uploads
+            || !self.client.can_resume_upload()
+            || self.remote_line_ending.is_some()
+            || self.trash_dir.is_some()
+        {
+            return 0;
+        }
+        match self.client.stat(remote) {
+            Ok(FsEntry::File(partial)) => FileTransferActivity::resume_offset(local, &partial),
+            _ => 0,
         }
     }
 
-    /// ### filetransfer_send_file
+    /// ### resume_offset
     ///
-    /// Send local file and write it to remote path
-    fn filetransfer_send_file(&mut self, local: &FsFile, remote: &Path) -> Result<(), String> {
-        // Upload file
-        // Try to open local file
+    /// Returns the size of `partial` if it can be the beginning of `local`: it's smaller and it's been
+    /// written after `local` was last changed (otherwise it's an older version of the file); 0 otherwise
+    pub(super) fn resume_offset(local: &FsFile, partial: &FsFile) -> usize {
+        match partial.size > 0
+            && partial.size < local.size
+            && partial.last_change_time >= local.last_change_time
+        {
+            true => partial.size,
+            false => 0,
+        }
+    }
+
+    /// ### upload_verify_retries
+    ///
+    /// Returns the amount of retries allowed for uploads if upload verification is enabled.
+    /// If configuration is not available, verification is enabled without retries
+    fn upload_verify_retries(&self) -> Option<u8> {
         match self
             .context
             .as_ref()
-            .unwrap()
-            .local
-            .open_file_read(local.abs_path.as_path())
+            .and_then(|ctx| ctx.config_client.as_ref())
         {
-            Ok(mut fhnd) => match self.client.send_file(local, remote) {
-                Ok(mut rhnd) => {
-                    // Write file
-                    let file_size: usize =
-                        fhnd.seek(std::io::SeekFrom::End(0)).unwrap_or(0) as usize;
-                    // rewind
-                    if let Err(err) = fhnd.seek(std::io::SeekFrom::Start(0)) {
-                        return Err(format!("Could not rewind local file: {}", err));
-                    }
-                    // Write remote file
-                    let mut total_bytes_written: usize = 0;
-                    // Set input state to popup progress
-                    self.input_mode = InputMode::Popup(PopupType::Progress(format!(
-                        "Uploading \"{}\"",
-                        local.name
-                    )));
-                    // Reset transfer states
-                    self.transfer.reset();
-                    let mut last_progress_val: f64 = 0.0;
-                    let mut last_input_event_fetch: Instant = Instant::now();
-                    // While the entire file hasn't been completely written,
-                    // Or filetransfer has been aborted
-                    while total_bytes_written < file_size && !self.transfer.aborted {
-                        // Handle input events (each 500ms)
-                        if last_input_event_fetch.elapsed().as_millis() >= 500 {
-                            // Read events
-                            self.read_input_event();
-                            // Reset instant
-                            last_input_event_fetch = Instant::now();
-                        }
-                        // Read till you can
-                        let mut buffer: [u8; 65536] = [0; 65536];
-                        match fhnd.read(&mut buffer) {
-                            Ok(bytes_read) => {
-                                total_bytes_written += bytes_read;
-                                if bytes_read == 0 {
-                                    continue;
-                                } else {
-                                    let mut buf_start: usize = 0;
-                                    while buf_start < bytes_read {
-                                        // Write bytes
-                                        match rhnd.write(&buffer[buf_start..bytes_read]) {
-                                            Ok(bytes) => {
-                                                buf_start += bytes;
-                                            }
-                                            Err(err) => {
-                                                return Err(format!(
-                                                    "Could not write remote file: {}",
-                                                    err
-                                                ))
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            Err(err) => return Err(format!("Could not read local file: {}", err)),
-                        }
-                        // Increase progress
-                        self.transfer.set_progress(total_bytes_written, file_size);
-                        // Draw only if a significant progress has been made (performance improvement)
-                        if last_progress_val < self.transfer.progress - 1.0 {
-                            // Draw
-                            self.draw();
-                            last_progress_val = self.transfer.progress;
-                        }
-                    }
-                    // Finalize stream
-                    if let Err(err) = self.client.on_sent(rhnd) {
-                        self.log(
-                            LogLevel::Warn,
-                            format!("Could not finalize remote stream: \"{}\"", err).as_str(),
-                        );
-                    }
+            Some(config_client) => match config_client.get_verify_uploads() {
+                true => Some(config_client.get_verify_retries()),
+                false => None,
+            },
+            None => Some(0),
+        }
+    }
+
+    /// ### create_parents_enabled
+    ///
+    /// Returns whether missing parent directories of the transfer destination must be created;
+    /// they always are when relative paths are preserved
+    fn create_parents_enabled(&self) -> bool {
+        self.preserve_paths
+            || self
+                .context
+                .as_ref()
+                .and_then(|ctx| ctx.config_client.as_ref())
+                .map(|config_client| config_client.get_create_parents())
+                .unwrap_or(false)
+    }
+
+    /// ### missing_parents
+    ///
+    /// Returns the parent directories of `path` which are not `base` or one of its ancestors,
+    /// sorted from the outermost; these are the directories to create before writing `path`
+    pub(super) fn missing_parents(base: &Path, path: &Path) -> Vec<PathBuf> {
+        let mut parents: Vec<PathBuf> = match path.parent() {
+            Some(parent) => parent
+                .ancestors()
+                .filter(|dir| !dir.as_os_str().is_empty() && !base.starts_with(dir))
+                .map(PathBuf::from)
+                .collect(),
+            None => Vec::new(),
+        };
+        parents.reverse();
+        parents
+    }
+
+    /// ### remote_create_parents
+    ///
+    /// Create the missing parent directories of `path` on remote, as `mkdir -p` does
+    fn remote_create_parents(&mut self, base: &Path, path: &Path) {
+        for dir in FileTransferActivity::missing_parents(base, path).iter() {
+            // NOTE: mkdir fails if directory already exists; if it couldn't be created, the transfer will report it
+            if self.client.mkdir(dir.as_path()).is_ok() {
+                self.log(
+                    LogLevel::Info,
+                    format!("Created directory \"{}\"", dir.display()).as_str(),
+                );
+            }
+        }
+    }
+
+    /// ### local_create_parents
+    ///
+    /// Create the missing parent directories of `path` on localhost, as `mkdir -p` does
+    fn local_create_parents(&mut self, base: &Path, path: &Path) {
+        for dir in FileTransferActivity::missing_parents(base, path).iter() {
+            if dir.exists() {
+                continue;
+            }
+            match self
+                .context
+                .as_mut()
+                .unwrap()
+                .local
+                .mkdir_ex(dir.as_path(), true)
+            {
+                Ok(_) => self.log(
+                    LogLevel::Info,
+                    format!("Created directory \"{}\"", dir.display()).as_str(),
+                ),
+                Err(err) => self.log(
+                    LogLevel::Error,
+                    format!("Could not create directory \"{}\": {}", dir.display(), err).as_str(),
+                ),
+            }
+        }
+    }
+
+    /// ### local_create_download_dir
+    ///
+    /// If enabled in configuration, create the local download directory `dir` and its missing parents, if it doesn't exist
+    fn local_create_download_dir(&mut self, dir: &Path) {
+        let enabled: bool = self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref())
+            .map(|config_client| config_client.get_create_download_dir())
+            .unwrap_or(false);
+        if !enabled || dir.exists() {
+            return;
+        }
+        match self.context.as_mut().unwrap().local.mkdir_all(dir) {
+            Ok(created) => {
+                for dir in created.iter() {
                     self.log(
                         LogLevel::Info,
-                        format!(
-                            "Saved file \"{}\" to \"{}\" (took {} seconds; at {}/s)",
-                            local.abs_path.display(),
-                            remote.display(),
-                            fmt_millis(self.transfer.started.elapsed()),
-                            ByteSize(self.transfer.bytes_per_second()),
-                        )
-                        .as_ref(),
+                        format!("Created directory \"{}\"", dir.display()).as_str(),
                     );
                 }
-                Err(err) => {
-                    return Err(format!(
-                        "Failed to upload file \"{}\": {}",
-                        local.abs_path.display(),
-                        err
-                    ))
-                }
+            }
+            Err(err) => self.log(
+                LogLevel::Error,
+                format!(
+                    "Could not create download directory \"{}\": {}",
+                    dir.display(),
+                    err
+                )
+                .as_str(),
+            ),
+        }
+    }
+
+    /// ### filetransfer_verify_upload
+    ///
+    /// Stat the uploaded file on remote and compare its size with the amount of bytes uploaded (`size`),
+    /// which is the local file size unless line endings have been converted.
+    /// If the remote file can't be stat (e.g. not supported by protocol), verification is skipped
+    fn filetransfer_verify_upload(&mut self, remote: &Path, size: usize) -> Result<(), String> {
+        match self.client.stat(remote) {
+            Ok(FsEntry::File(remote_file)) => match remote_file.size == size {
+                true => Ok(()),
+                false => Err(format!(
+                    "Upload verification failed for \"{}\": remote size is {} bytes, but {} bytes have been uploaded",
+                    remote.display(),
+                    remote_file.size,
+                    size
+                )),
             },
+            Ok(FsEntry::Directory(_)) => Err(format!(
+                "Upload verification failed for \"{}\": remote entry is a directory",
+                remote.display()
+            )),
             Err(err) => {
-                return Err(format!(
-                    "Failed to open file \"{}\": {}",
-                    local.abs_path.display(),
-                    err
-                ))
+                self.log(
+                    LogLevel::Warn,
+                    format!("Could not verify upload of \"{}\": {}", remote.display(), err)
+                        .as_str(),
+                );
+                Ok(())
             }
         }
-        Ok(())
+    }
+
+    /// ### log_eol_conversion
+    ///
+    /// Log the line endings converted while transferring the file to `dst`, if any
+    fn log_eol_conversion(&mut self, eol: Option<EolConverter>, dst: &Path) {
+        if let Some(eol) = eol {
+            if eol.lines_changed() > 0 {
+                self.log(
+                    LogLevel::Info,
+                    format!(
+                        "Converted {} line endings of \"{}\" to {}",
+                        eol.lines_changed(),
+                        dst.display(),
+                        eol.target()
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+
+    /// ### remote_is_same_file
+    ///
+    /// Returns whether the local path and the remote path are the same file; this happens only if the remote
+    /// is this host (`localhost` or a loopback address), in which case remote paths are local paths too
+    pub(super) fn remote_is_same_file(&self, local: &Path, remote: &Path) -> bool {
+        let address: &str = self.params.address.as_str();
+        let loopback: bool = address.eq_ignore_ascii_case("localhost")
+            || address
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<std::net::IpAddr>()
+                .map(|x| x.is_loopback())
+                .unwrap_or(false);
+        if !loopback {
+            return false;
+        }
+        let remote: PathBuf = self.remote.wrkdir.join(remote);
+        Localhost::is_same_file(local, remote.as_path())
     }
 
     /// ### filetransfer_recv_file
     ///
     /// Receive file from remote and write it to local path
-    fn filetransfer_recv_file(&mut self, local: &Path, remote: &FsFile) -> Result<(), String> {
+    pub(super) fn filetransfer_recv_file(
+        &mut self,
+        local: &Path,
+        remote: &FsFile,
+    ) -> Result<(), String> {
+        // Downloading a file onto itself would truncate it before it's read
+        if self.remote_is_same_file(local, remote.abs_path.as_path()) {
+            return Err(format!(
+                "Refused to download \"{}\" onto itself",
+                remote.abs_path.display()
+            ));
+        }
         // Try to open local file
         match self.context.as_ref().unwrap().local.open_file_write(local) {
             Ok(mut local_file) => {
                 // Download file from remote
                 match self.client.recv_file(remote) {
                     Ok(mut rhnd) => {
+                        self.log_client_warnings();
                         // Set popup progress
                         self.input_mode = InputMode::Popup(PopupType::Progress(format!(
                             "Downloading \"{}\"...",
                             remote.name,
                         )));
                         let mut total_bytes_written: usize = 0;
+                        // Remote text files are converted to the line endings of this host
+                        let mut eol: Option<EolConverter> = self
+                            .remote_line_ending
+                            .map(|_| EolConverter::new(LineEnding::local()));
                         // Reset transfer states
                         self.transfer.reset();
                         // Write local file
-                        let mut last_progress_val: f64 = 0.0;
+                        let mut throttle: RedrawThrottle =
+                            RedrawThrottle::new(self.redraw_interval);
                         let mut last_input_event_fetch: Instant = Instant::now();
+                        self.buffer.start();
+                        let mut buffer: Vec<u8> = vec![0; self.buffer.size];
+                        // Zero-length file: there's nothing to read, local file has already been created empty
+                        if remote.size == 0 {
+                            self.transfer.set_progress(0, 0);
+                            self.draw();
+                        }
                         // While the entire file hasn't been completely read,
                         // Or filetransfer has been aborted
                         while total_bytes_written < remote.size && !self.transfer.aborted {
@@ -502,17 +1685,28 @@ impl FileTransferActivity {
                                 last_input_event_fetch = Instant::now();
                             }
                             // Read till you can
-                            let mut buffer: [u8; 65536] = [0; 65536];
                             match rhnd.read(&mut buffer) {
                                 Ok(bytes_read) => {
                                     total_bytes_written += bytes_read;
                                     if bytes_read == 0 {
                                         continue;
                                     } else {
+                                        // Line endings are converted only in text files, detected by the first chunk
+                                        if total_bytes_written == bytes_read
+                                            && !is_convertible(&buffer[0..bytes_read])
+                                        {
+                                            eol = None;
+                                        }
+                                        let data: Cow<[u8]> = match eol.as_mut() {
+                                            Some(eol) => {
+                                                Cow::Owned(eol.convert(&buffer[0..bytes_read]))
+                                            }
+                                            None => Cow::Borrowed(&buffer[0..bytes_read]),
+                                        };
                                         let mut buf_start: usize = 0;
-                                        while buf_start < bytes_read {
+                                        while buf_start < data.len() {
                                             // Write bytes
-                                            match local_file.write(&buffer[buf_start..bytes_read]) {
+                                            match local_file.write(&data[buf_start..]) {
                                                 Ok(bytes) => buf_start += bytes,
                                                 Err(err) => {
                                                     return Err(format!(
@@ -530,11 +1724,18 @@ impl FileTransferActivity {
                             }
                             // Set progress
                             self.transfer.set_progress(total_bytes_written, remote.size);
-                            // Draw only if a significant progress has been made (performance improvement)
-                            if last_progress_val < self.transfer.progress - 1.0 {
-                                // Draw
+                            self.queue_sample();
+                            self.buffer.sample(self.transfer.bytes_written);
+                            buffer.resize(self.buffer.size, 0);
+                            // Draw only if the configured redraw interval has passed (performance improvement)
+                            if throttle.should_draw(self.transfer.progress) {
                                 self.draw();
-                                last_progress_val = self.transfer.progress;
+                            }
+                        }
+                        // Write the bytes held back by the line ending conversion
+                        if let Some(eol) = eol.as_mut() {
+                            if let Err(err) = local_file.write_all(eol.finish().as_slice()) {
+                                return Err(format!("Could not write local file: {}", err));
                             }
                         }
                         // Finalize stream
@@ -573,13 +1774,16 @@ impl FileTransferActivity {
                             )
                             .as_ref(),
                         );
+                        self.log_eol_conversion(eol, local);
+                        self.log_buffer_size();
                     }
                     Err(err) => {
+                        self.log_client_warnings();
                         return Err(format!(
                             "Failed to download file \"{}\": {}",
                             remote.abs_path.display(),
                             err
-                        ))
+                        ));
                     }
                 }
             }
@@ -596,54 +1800,147 @@ impl FileTransferActivity {
 
     /// ### local_scan
     ///
-    /// Scan current local directory
-    pub(super) fn local_scan(&mut self, path: &Path) {
-        match self.context.as_ref().unwrap().local.scan_dir(path) {
-            Ok(files) => {
-                self.local.files = files;
-                // Set index; keep if possible, otherwise set to last item
-                self.local.index = match self.local.files.get(self.local.index) {
-                    Some(_) => self.local.index,
-                    None => match self.local.files.len() {
-                        0 => 0,
-                        _ => self.local.files.len() - 1,
-                    },
-                };
-                // Sort files
-                self.local.sort_files_by_name();
-            }
+    /// Scan current local directory.
+    /// Returns whether the directory has been scanned; if not, the previous entries are kept
+    pub(super) fn local_scan(&mut self, path: &Path) -> bool {
+        let entries: DirIter = match self.context.as_ref().unwrap().local.iter_dir(path) {
+            Ok(entries) => entries,
             Err(err) => {
                 self.log_and_alert(
                     LogLevel::Error,
                     format!("Could not scan current directory: {}", err),
                 );
+                return false;
+            }
+        };
+        // Read entries progressively; explorer is redrawn periodically,
+        // so that the first entries of large directories are displayed without waiting for the entire scan.
+        // While reading, entries are displayed in the order they are read; they're sorted once at the end
+        let prev_files: Vec<FsEntry> = std::mem::take(&mut self.local.files);
+        let mut last_draw: Instant = Instant::now();
+        for entry in entries {
+            match entry {
+                Ok(entry) => self.local.files.push(entry),
+                Err(err) => {
+                    // Restore previous entries
+                    self.local.files = prev_files;
+                    self.log_and_alert(
+                        LogLevel::Error,
+                        format!("Could not scan current directory: {}", err),
+                    );
+                    return false;
+                }
+            }
+            // Draw once the first screenful has been read, then each 100ms
+            if self.local.files.len() == 64 || last_draw.elapsed().as_millis() >= 100 {
+                self.local.clamp_index();
+                self.draw();
+                last_draw = Instant::now();
             }
         }
+        // Set index; keep if possible, otherwise set to last item
+        self.local.clamp_index();
+        // Sort files
+        self.local.sort_files();
+        true
     }
 
     /// ### remote_scan
     ///
-    /// Scan current remote directory
-    pub(super) fn remote_scan(&mut self, path: &Path) {
-        match self.client.list_dir(path) {
+    /// Scan current remote directory.
+    /// The directory is listed on a worker thread, while a wait popup is displayed; if it gets closed with `<ESC>`
+    /// (or the abort key is pressed) the scan is cancelled, even if the server doesn't answer.
+    /// The client of a cancelled scan is still busy listing, so it's left to the worker and a new client
+    /// reconnects to the remote, going back to the working directory.
+    /// Returns whether remote entries have been updated
+    pub(super) fn remote_scan(&mut self, path: &Path) -> bool {
+        let prev_input_mode: InputMode = std::mem::replace(
+            &mut self.input_mode,
+            InputMode::Popup(PopupType::Wait(format!(
+                "Scanning \"{}\"... (<ESC> to cancel)",
+                path.display()
+            ))),
+        );
+        self.draw();
+        // The scan may follow an aborted transfer, which must still be reported as aborted
+        let transfer_aborted: bool = std::mem::replace(&mut self.transfer.aborted, false);
+        let files: Option<Result<Vec<FsEntry>, FileTransferError>> = self.remote_list_dir(path);
+        self.transfer.aborted = transfer_aborted;
+        self.input_mode = prev_input_mode;
+        self.log_client_warnings();
+        let files: Result<Vec<FsEntry>, FileTransferError> = match files {
+            Some(files) => files,
+            None => {
+                self.log(
+                    LogLevel::Warn,
+                    format!(
+                        "Scan of \"{}\" cancelled; reconnecting to {}",
+                        path.display(),
+                        self.params.address
+                    )
+                    .as_str(),
+                );
+                self.reconnect_wrkdir = Some(self.remote.wrkdir.clone());
+                return false;
+            }
+        };
+        match files {
             Ok(files) => {
                 self.remote.files = files;
                 // Set index; keep if possible, otherwise set to last item
-                self.remote.index = match self.remote.files.get(self.remote.index) {
-                    Some(_) => self.remote.index,
-                    None => match self.remote.files.len() {
-                        0 => 0,
-                        _ => self.remote.files.len() - 1,
-                    },
-                };
+                self.remote.clamp_index();
                 // Sort files
-                self.remote.sort_files_by_name();
+                self.remote.sort_files();
+                true
             }
             Err(err) => {
                 self.log_and_alert(
                     LogLevel::Error,
                     format!("Could not scan current directory: {}", err),
                 );
+                false
+            }
+        }
+    }
+
+    /// ### remote_list_dir
+    ///
+    /// List `path` on the remote on a worker thread, handling input events meanwhile.
+    /// Returns None if the list has been cancelled, closing the wait popup or pressing the abort key;
+    /// in this case the client is replaced with a new one, which isn't connected yet
+    fn remote_list_dir(&mut self, path: &Path) -> Option<Result<Vec<FsEntry>, FileTransferError>> {
+        let config_client: Option<&ConfigClient> = self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref());
+        let mut client: Box<dyn FileTransfer> = std::mem::replace(
+            &mut self.client,
+            FileTransferActivity::make_client(&self.params, config_client),
+        );
+        let (tx, rx) = mpsc::channel();
+        let path: PathBuf = PathBuf::from(path);
+        thread::spawn(move || {
+            let files: Result<Vec<FsEntry>, FileTransferError> = client.list_dir(path.as_path());
+            // If the scan has been cancelled, nobody is waiting: the client is dropped
+            let _ = tx.send((client, files));
+        });
+        loop {
+            match rx.recv_timeout(SCAN_POLL_INTERVAL) {
+                Ok((client, files)) => {
+                    self.client = client;
+                    return Some(files);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    // <ESC> closes the wait popup, the abort key aborts the scan
+                    while self.read_input_event() {}
+                    if self.transfer.aborted
+                        || !matches!(self.input_mode, InputMode::Popup(PopupType::Wait(_)))
+                    {
+                        return None;
+                    }
+                }
+                // Worker panicked: the client is lost
+                Err(mpsc::RecvTimeoutError::Disconnected) => return None,
             }
         }
     }
@@ -663,20 +1960,31 @@ impl FileTransferActivity {
             .change_wrkdir(PathBuf::from(path))
         {
             Ok(_) => {
+                // Reload files; if the directory can't be scanned, go back to the previous one
+                if !self.local_scan(path) {
+                    let _ = self.context.as_mut().unwrap().local.change_wrkdir(prev_dir);
+                    return;
+                }
                 self.log(
                     LogLevel::Info,
                     format!("Changed directory on local: {}", path.display()).as_str(),
                 );
-                // Reload files
-                self.local_scan(path);
                 // Reset index
                 self.local.index = 0;
+                // Marks refer to the previous directory, unless they're transferred with their relative path
+                if !self.preserve_paths {
+                    self.local.clear_marks();
+                }
+                self.local.clear_results();
+                // The name filter narrows the listing of the previous directory
+                self.local.set_name_filter("");
                 // Set wrkdir
                 self.local.wrkdir = PathBuf::from(path);
                 // Push prev_dir to stack
                 if push {
                     self.local.pushd(prev_dir.as_path())
                 }
+                self.sync_browsing_changedir(FileExplorerTab::Local, prev_dir.as_path(), path);
             }
             Err(err) => {
                 // Report err
@@ -693,21 +2001,52 @@ impl FileTransferActivity {
         let prev_dir: PathBuf = self.remote.wrkdir.clone();
         // Change directory
         match self.client.as_mut().change_dir(path) {
-            Ok(_) => {
+            Ok(wrkdir) => {
+                // NOTE: use the directory returned by the client, which may be resolved by the server
                 self.log(
                     LogLevel::Info,
-                    format!("Changed directory on remote: {}", path.display()).as_str(),
+                    format!("Changed directory on remote: {}", wrkdir.display()).as_str(),
                 );
-                // Update files
-                self.remote_scan(path);
+                // Update files; if the scan fails or is cancelled, go back to the previous directory,
+                // so the working directory is the one of the displayed entries
+                if !self.remote_scan(wrkdir.as_path()) {
+                    // After a cancelled scan, the new client goes back to the previous directory once connected
+                    if !self.client.is_connected() {
+                        return;
+                    }
+                    if let Err(err) = self.client.as_mut().change_dir(prev_dir.as_path()) {
+                        self.log(
+                            LogLevel::Error,
+                            format!(
+                                "Could not go back to previous directory \"{}\": {}",
+                                prev_dir.display(),
+                                err
+                            )
+                            .as_str(),
+                        );
+                    }
+                    return;
+                }
                 // Reset index
                 self.remote.index = 0;
+                // Marks refer to the previous directory, unless they're transferred with their relative path
+                if !self.preserve_paths {
+                    self.remote.clear_marks();
+                }
+                self.remote.clear_results();
+                // The name filter narrows the listing of the previous directory
+                self.remote.set_name_filter("");
                 // Set wrkdir
-                self.remote.wrkdir = PathBuf::from(path);
+                self.remote.wrkdir = wrkdir.clone();
                 // Push prev_dir to stack
                 if push {
                     self.remote.pushd(prev_dir.as_path())
                 }
+                self.sync_browsing_changedir(
+                    FileExplorerTab::Remote,
+                    prev_dir.as_path(),
+                    wrkdir.as_path(),
+                );
             }
             Err(err) => {
                 // Report err
@@ -719,6 +2058,91 @@ impl FileTransferActivity {
         }
     }
 
+    /// ### toggle_sync_browsing
+    ///
+    /// Enable/disable synchronized browsing
+    pub(super) fn toggle_sync_browsing(&mut self) {
+        self.sync_browsing = !self.sync_browsing;
+        let state: &str = match self.sync_browsing {
+            true => "enabled",
+            false => "disabled",
+        };
+        self.log(
+            LogLevel::Info,
+            format!("Synchronized browsing {}", state).as_str(),
+        );
+    }
+
+    /// ### sync_browsing_changedir
+    ///
+    /// If synchronized browsing is enabled, repeat on the other explorer the change of directory from `prev_dir`
+    /// to `wrkdir` made on `tab`. If the other directory doesn't exist, a warning is logged and the other explorer is left unchanged
+    fn sync_browsing_changedir(&mut self, tab: FileExplorerTab, prev_dir: &Path, wrkdir: &Path) {
+        if !self.sync_browsing {
+            return;
+        }
+        let other: &FileExplorer = match tab {
+            FileExplorerTab::Local => &self.remote,
+            FileExplorerTab::Remote => &self.local,
+        };
+        match FileTransferActivity::sync_browsing_target(other, prev_dir, wrkdir) {
+            Ok(Some(target)) => {
+                // Don't repeat the change back
+                self.sync_browsing = false;
+                match tab {
+                    FileExplorerTab::Local => self.remote_changedir(target.as_path(), true),
+                    FileExplorerTab::Remote => self.local_changedir(target.as_path(), true),
+                }
+                self.sync_browsing = true;
+            }
+            Ok(None) => {}
+            Err(name) => {
+                let side: &str = match tab {
+                    FileExplorerTab::Local => "remote",
+                    FileExplorerTab::Remote => "local",
+                };
+                self.log(
+                    LogLevel::Warn,
+                    format!(
+                        "Synchronized browsing: directory \"{}\" doesn't exist on {}",
+                        name, side
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+
+    /// ### sync_browsing_target
+    ///
+    /// Returns the directory `other` must change to, to repeat the change of directory from `prev_dir` to `wrkdir`:
+    /// entering a subdirectory enters the one with the same name in `other`, going to the parent goes to the parent.
+    /// Other changes (e.g. goto) aren't repeated. Returns the name of the subdirectory if it's not listed in `other`
+    pub(super) fn sync_browsing_target(
+        other: &FileExplorer,
+        prev_dir: &Path,
+        wrkdir: &Path,
+    ) -> Result<Option<PathBuf>, String> {
+        if wrkdir.parent() == Some(prev_dir) {
+            let name: String = match wrkdir.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => return Ok(None),
+            };
+            match other
+                .files
+                .iter()
+                .find(|x| ExplorerFilter::is_dir(x) && x.get_name() == name)
+            {
+                Some(entry) => Ok(Some(entry.get_abs_path())),
+                None => Err(name),
+            }
+        } else if prev_dir.parent() == Some(wrkdir) {
+            Ok(other.wrkdir.parent().map(PathBuf::from))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// ### edit_local_file
     ///
     /// Edit a file on localhost
@@ -857,4 +2281,201 @@ impl FileTransferActivity {
         }
         Ok(())
     }
+
+    /// ### follow_start
+    ///
+    /// Start following remote file (as `tail -f`); the last bytes of the file are displayed first
+    pub(super) fn follow_start(&mut self, file: FsFile) {
+        let offset: usize = file.size.saturating_sub(FOLLOW_INITIAL_BYTES);
+        self.log(
+            LogLevel::Info,
+            format!("Following \"{}\"", file.abs_path.display()).as_ref(),
+        );
+        self.follow = Some(FollowState::new(file, offset));
+        self.input_mode = InputMode::Popup(PopupType::Follow);
+        // Read initial data immediately
+        self.follow_update();
+    }
+
+    /// ### follow_stop
+    ///
+    /// Stop following remote file
+    pub(super) fn follow_stop(&mut self) {
+        if let Some(follow) = self.follow.take() {
+            self.log(
+                LogLevel::Info,
+                format!("Stopped following \"{}\"", follow.file.abs_path.display()).as_ref(),
+            );
+        }
+        self.input_mode = InputMode::Explorer;
+    }
+
+    /// ### follow_poll
+    ///
+    /// Read data appended to the followed file, if the poll interval has elapsed since the last read.
+    /// Returns whether the view must be redrawn
+    pub(super) fn follow_poll(&mut self) -> bool {
+        match self.follow.as_ref() {
+            Some(follow) if follow.last_poll.elapsed() >= FOLLOW_POLL_INTERVAL => {
+                self.follow_update()
+            }
+            _ => false,
+        }
+    }
+
+    /// ### follow_update
+    ///
+    /// Read data appended to the followed file since the last read.
+    /// If the file got smaller (truncated or rotated), it is read again from the beginning.
+    /// Returns whether the view must be redrawn
+    fn follow_update(&mut self) -> bool {
+        let (mut file, offset): (FsFile, usize) = match self.follow.as_ref() {
+            Some(follow) => (follow.file.clone(), follow.offset),
+            None => return false,
+        };
+        let result: Result<Vec<u8>, String> = self.follow_read(&mut file, offset);
+        match result {
+            Ok(data) => match self.follow.as_mut() {
+                Some(follow) => {
+                    let truncated: bool = file.size < follow.offset;
+                    if truncated {
+                        follow.restart();
+                    }
+                    follow.file = file;
+                    follow.last_poll = Instant::now();
+                    if !data.is_empty() {
+                        follow.push(data.as_slice());
+                    }
+                    truncated || !data.is_empty()
+                }
+                None => false,
+            },
+            Err(err) => {
+                self.log_and_alert(
+                    LogLevel::Error,
+                    format!("Could not follow \"{}\": {}", file.abs_path.display(), err),
+                );
+                self.follow = None;
+                true
+            }
+        }
+    }
+
+    /// ### follow_read
+    ///
+    /// Update `file` size and read its data after `offset` (or from the beginning, if the file got smaller)
+    fn follow_read(&mut self, file: &mut FsFile, offset: usize) -> Result<Vec<u8>, String> {
+        // Get current size
+        file.size = match self.client.stat(file.abs_path.as_path()) {
+            Ok(FsEntry::File(f)) => f.size,
+            Ok(FsEntry::Directory(_)) => return Err(String::from("file is a directory")),
+            Err(_) => self.follow_stat_from_parent(file)?,
+        };
+        let offset: usize = match file.size < offset {
+            true => 0,
+            false => offset,
+        };
+        if file.size == offset {
+            return Ok(Vec::new());
+        }
+        // Read new data
+        let mut reader: Box<dyn Read> = match self.client.recv_file_at(file, offset) {
+            Ok(reader) => reader,
+            Err(err) => return Err(err.to_string()),
+        };
+        let mut data: Vec<u8> = Vec::with_capacity(file.size - offset);
+        let result: std::io::Result<usize> = (&mut reader)
+            .take((file.size - offset) as u64)
+            .read_to_end(&mut data);
+        if let Err(err) = self.client.on_recv(reader) {
+            return Err(err.to_string());
+        }
+        match result {
+            Ok(_) => Ok(data),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// ### follow_stat_from_parent
+    ///
+    /// Get file size from its parent directory listing, for protocols which can't stat files
+    fn follow_stat_from_parent(&mut self, file: &FsFile) -> Result<usize, String> {
+        let parent: &Path = file.abs_path.parent().unwrap_or_else(|| Path::new("/"));
+        match self.client.list_dir(parent) {
+            Ok(entries) => entries
+                .iter()
+                .find_map(|x| match x {
+                    FsEntry::File(f) if f.name == file.name => Some(f.size),
+                    _ => None,
+                })
+                .ok_or_else(|| String::from("file doesn't exist anymore")),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+/// ### tree_exceeds_depth
+///
+/// Returns whether the tree rooted in `dir` contains entries more than `max_depth` levels below it;
+/// `list_dir` provides the content of a directory. Symlinks to directories are not followed
+pub(super) fn tree_exceeds_depth<F>(dir: &Path, max_depth: usize, list_dir: &mut F) -> bool
+where
+    F: FnMut(&Path) -> Vec<FsEntry>,
+{
+    let entries: Vec<FsEntry> = list_dir(dir);
+    if max_depth == 0 {
+        return !entries.is_empty();
+    }
+    entries.iter().any(|entry| match entry {
+        FsEntry::Directory(sub) if sub.symlink.is_none() => {
+            tree_exceeds_depth(sub.abs_path.as_path(), max_depth - 1, list_dir)
+        }
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GroupDirs;
+    use crate::ui::activities::filetransfer_activity::tests::{make_fs_entry, test_activity};
+    use crate::ui::activities::filetransfer_activity::InputEvent;
+
+    #[test]
+    fn test_ui_activities_filetransfer_sync_browsing() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut other: FileExplorer = FileExplorer::new(false, GroupDirs::First);
+        other.wrkdir = PathBuf::from("/home/omar");
+        other.files = vec![make_fs_entry("src", true), make_fs_entry("docs", false)];
+        let target = |prev: &str, wrkdir: &str| -> Result<Option<PathBuf>, String> {
+            FileTransferActivity::sync_browsing_target(&other, Path::new(prev), Path::new(wrkdir))
+        };
+        // Subdirectory: the one with the same name
+        assert_eq!(
+            target("/tmp/project", "/tmp/project/src"),
+            Ok(Some(PathBuf::from("/home/omar/src")))
+        );
+        // Missing on the other side (files don't match)
+        assert_eq!(
+            target("/tmp/project", "/tmp/project/docs"),
+            Err(String::from("docs"))
+        );
+        // Parent
+        assert_eq!(
+            target("/tmp/project/src", "/tmp/project"),
+            Ok(Some(PathBuf::from("/home")))
+        );
+        // Other changes aren't repeated
+        assert_eq!(target("/tmp/project", "/etc"), Ok(None));
+        // <CTRL+Y> toggles it
+        let mut activity: FileTransferActivity = test_activity();
+        assert!(!activity.sync_browsing);
+        let ctrl_y: InputEvent =
+            InputEvent::Key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL));
+        activity.handle_input_event_mode_explorer(&ctrl_y);
+        assert!(activity.sync_browsing);
+        activity.handle_input_event_mode_explorer(&ctrl_y);
+        assert!(!activity.sync_browsing);
+    }
 }
@@ -0,0 +1,697 @@
+//! ## Sync
+//!
+//! `sync` is the module which mirrors a directory between localhost and remote, transferring only new and changed files
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{
+    FileTransferActivity, InputMode, LogLevel, PendingTransfer, PopupType, TransferResult,
+    TransferSummary,
+};
+use crate::filetransfer::FileTransferError;
+use crate::fs::{FsDirectory, FsEntry, FsFile};
+
+// Ext
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// ## SyncAction
+///
+/// SyncAction describes what must be done to bring a destination entry in sync with the source
+#[derive(std::fmt::Debug)]
+pub(super) enum SyncAction<'a> {
+    Transfer(&'a FsFile),                      // File is new or changed
+    Keep,                                      // File is unchanged
+    Create(&'a FsDirectory),                   // Directory doesn't exist on destination
+    Descend(&'a FsDirectory, &'a FsDirectory), // Directory exists on both sides (source, destination)
+    Conflict(&'a FsEntry), // Source is a file and destination a directory or vice versa
+    Delete(&'a FsEntry),   // Destination entry doesn't exist on source
+}
+
+/// ## SyncStats
+///
+/// SyncStats counts the entries which didn't need to be transferred during a sync
+#[derive(Default)]
+struct SyncStats {
+    unchanged: usize, // Files already up to date
+    deleted: usize,   // Destination entries removed
+}
+
+/// ### sync_plan
+///
+/// Compare the source directory entries with the destination ones.
+/// A file is changed if its size differs or if it's newer than the destination one;
+/// `skew` is the amount of seconds the source clock is ahead of the destination one, subtracted from source times.
+/// Extraneous destination entries are deleted only if `delete` is true; deletions come last,
+/// so that nothing is removed if the sync gets aborted
+pub(super) fn sync_plan<'a>(
+    src: &'a [FsEntry],
+    dst: &'a [FsEntry],
+    delete: bool,
+    skew: i64,
+) -> Vec<SyncAction<'a>> {
+    let dst_names: HashMap<String, &FsEntry> = dst.iter().map(|x| (x.get_name(), x)).collect();
+    let mut actions: Vec<SyncAction> = Vec::with_capacity(src.len());
+    for entry in src.iter() {
+        actions.push(match (entry, dst_names.get(&entry.get_name())) {
+            (FsEntry::File(file), None) => SyncAction::Transfer(file),
+            (FsEntry::Directory(dir), None) => SyncAction::Create(dir),
+            (FsEntry::File(file), Some(FsEntry::File(other))) => {
+                match file.size != other.size
+                    || shift_time(file.last_change_time, -skew) > other.last_change_time
+                {
+                    true => SyncAction::Transfer(file),
+                    false => SyncAction::Keep,
+                }
+            }
+            (FsEntry::Directory(dir), Some(FsEntry::Directory(other))) => {
+                SyncAction::Descend(dir, other)
+            }
+            (_, Some(_)) => SyncAction::Conflict(entry),
+        });
+    }
+    if delete {
+        let src_names: HashMap<String, &FsEntry> = src.iter().map(|x| (x.get_name(), x)).collect();
+        for entry in dst.iter() {
+            if !src_names.contains_key(&entry.get_name()) {
+                actions.push(SyncAction::Delete(entry));
+            }
+        }
+    }
+    actions
+}
+
+/// ### shift_time
+///
+/// Move `time` forward (or backward, if negative) by `secs` seconds
+fn shift_time(time: SystemTime, secs: i64) -> SystemTime {
+    match secs >= 0 {
+        true => time + Duration::from_secs(secs as u64),
+        false => time
+            .checked_sub(Duration::from_secs(secs.unsigned_abs()))
+            .unwrap_or(SystemTime::UNIX_EPOCH),
+    }
+}
+
+/// ### time_diff
+///
+/// Get the amount of seconds `time` is ahead of `other` (negative if it's behind)
+pub(super) fn time_diff(time: SystemTime, other: SystemTime) -> i64 {
+    match time.duration_since(other) {
+        Ok(diff) => diff.as_secs_f64().round() as i64,
+        Err(err) => -(err.duration().as_secs_f64().round() as i64),
+    }
+}
+
+impl FileTransferActivity {
+    /// ### check_clock_skew
+    ///
+    /// Measure how many seconds the remote clock is ahead of the local one (negative if behind):
+    /// an empty temporary file is created in the remote working directory, its modification time is compared
+    /// with the local time and the file is removed. The skew is logged, and displayed as a warning if it exceeds
+    /// `clock_skew_threshold`. Nothing is measured if the threshold is 0 or in readonly mode
+    pub(super) fn check_clock_skew(&mut self) {
+        self.clock_skew = None;
+        if self.clock_skew_threshold == 0 || self.readonly {
+            return;
+        }
+        let name: String = format!(".gateway-clock-{}", std::process::id());
+        let path: PathBuf = self.remote.wrkdir.join(name.as_str());
+        let started: SystemTime = SystemTime::now();
+        let file: FsFile = FsFile {
+            name,
+            abs_path: path.clone(),
+            last_change_time: started,
+            last_access_time: started,
+            creation_time: started,
+            size: 0,
+            ftype: None,
+            readonly: false,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex: Some((6, 0, 0)),
+            nlink: None,
+            inode: None,
+        };
+        let written: Result<SystemTime, FileTransferError> = self
+            .client
+            .send_file(&file, path.as_path())
+            .and_then(|writer| self.client.on_sent(writer))
+            .map(|_| SystemTime::now());
+        // The remote time is compared with the local time halfway through the upload
+        let result: Result<i64, FileTransferError> = written.and_then(|ended| {
+            let local: SystemTime = started + ended.duration_since(started).unwrap_or_default() / 2;
+            self.client
+                .stat(path.as_path())
+                .map(|entry| time_diff(entry.get_last_change_time(), local))
+        });
+        // Remove temporary file; if the upload failed, it may not exist at all
+        let _ = self.client.remove(&FsEntry::File(file));
+        let skew: i64 = match result {
+            Ok(skew) => skew,
+            Err(err) => {
+                self.log(
+                    LogLevel::Warn,
+                    format!("Could not measure remote clock skew: {}", err).as_str(),
+                );
+                return;
+            }
+        };
+        self.clock_skew = Some(skew);
+        let msg: String = format!(
+            "Remote clock is {} seconds {} the local one",
+            skew.abs(),
+            match skew >= 0 {
+                true => "ahead of",
+                false => "behind",
+            }
+        );
+        match skew.unsigned_abs() > self.clock_skew_threshold {
+            true if self.clock_skew_compensate => self.log_and_alert(
+                LogLevel::Warn,
+                format!("{}; it is compensated when syncing", msg),
+            ),
+            true => self.log_and_alert(
+                LogLevel::Warn,
+                format!(
+                    "{}; syncs may transfer unchanged files or skip changed ones",
+                    msg
+                ),
+            ),
+            false => self.log(LogLevel::Info, msg.as_str()),
+        }
+    }
+
+    /// ### sync_skew
+    ///
+    /// Get the amount of seconds the source clock is ahead of the destination one when syncing (see `sync_plan`);
+    /// 0 unless the skew has been measured and must be compensated
+    pub(super) fn sync_skew(&self, upload: bool) -> i64 {
+        match (self.clock_skew, self.clock_skew_compensate, upload) {
+            (Some(skew), true, true) => -skew,
+            (Some(skew), true, false) => skew,
+            _ => 0,
+        }
+    }
+
+    /// ### filetransfer_sync_request
+    ///
+    /// Ask to confirm the sync of the directory into `dst` (on remote if `upload` is true, on localhost otherwise)
+    pub(super) fn filetransfer_sync_request(&mut self, dir: FsDirectory, dst: &Path, upload: bool) {
+        // Sync may delete destination entries, which the dry run doesn't simulate
+        if self.dry_run {
+            self.log(
+                LogLevel::Warn,
+                "Sync is not available in dry run mode; disable it with <CTRL+D>",
+            );
+            return;
+        }
+        self.input_mode = InputMode::Popup(PopupType::YesNo(
+            format!(
+                "Synchronize \"{}\" to {}?",
+                dir.name,
+                match upload {
+                    true => "remote",
+                    false => "local",
+                }
+            ),
+            FileTransferActivity::callback_sync_confirm,
+            FileTransferActivity::callback_sync_cancel,
+        ));
+        self.pending_sync = Some(PendingTransfer {
+            entries: vec![(FsEntry::Directory(dir), None)],
+            dst: PathBuf::from(dst),
+            upload,
+            size: None,
+        });
+    }
+
+    /// ### filetransfer_sync
+    ///
+    /// Mirror the directory `dir` into `dst` (on remote if `upload` is true, on localhost otherwise),
+    /// transferring only new and changed files. If `delete` is true, destination entries which don't exist
+    /// on source are removed. A summary is logged and displayed once completed
+    pub(super) fn filetransfer_sync(
+        &mut self,
+        dir: &FsDirectory,
+        dst: &Path,
+        upload: bool,
+        delete: bool,
+    ) {
+        self.input_mode = InputMode::Popup(PopupType::Wait(format!(
+            "Synchronizing \"{}\"...",
+            dir.name
+        )));
+        self.draw();
+        let mut dst_path: PathBuf = PathBuf::from(dst);
+        dst_path.push(dir.name.as_str());
+        self.batch = Some(TransferSummary::new());
+        self.transfer.aborted = false;
+        let mut stats: SyncStats = SyncStats::default();
+        match upload {
+            true => {
+                // Destination may not exist yet; mkdir fails if it does
+                let _ = self.client.mkdir(dst_path.as_path());
+                self.sync_send_dir(
+                    dir.abs_path.as_path(),
+                    dst_path.as_path(),
+                    delete,
+                    &mut stats,
+                );
+            }
+            false => {
+                if let Err(err) = self
+                    .context
+                    .as_mut()
+                    .unwrap()
+                    .local
+                    .mkdir_ex(dst_path.as_path(), true)
+                {
+                    self.log(
+                        LogLevel::Error,
+                        format!(
+                            "Failed to create directory \"{}\": {}",
+                            dst_path.display(),
+                            err
+                        )
+                        .as_str(),
+                    );
+                }
+                self.sync_recv_dir(
+                    dir.abs_path.as_path(),
+                    dst_path.as_path(),
+                    delete,
+                    &mut stats,
+                );
+            }
+        }
+        let aborted: bool = self.transfer.aborted;
+        self.transfer.aborted = false;
+        self.input_mode = InputMode::Explorer;
+        // Reload destination explorer
+        match upload {
+            true => {
+                let path: PathBuf = self.remote.wrkdir.clone();
+                self.remote_scan(path.as_path());
+            }
+            false => {
+                let path: PathBuf = self.local.wrkdir.clone();
+                self.local_scan(path.as_path());
+            }
+        }
+        // Report summary
+        let mut batch: TransferSummary = self.batch.take().unwrap_or_else(TransferSummary::new);
+        let mut report: String = batch.report("Sync");
+        report.push_str(
+            format!("; {} unchanged, {} deleted", stats.unchanged, stats.deleted).as_str(),
+        );
+        let level: LogLevel = match (batch.failed.is_empty(), aborted) {
+            (false, _) => LogLevel::Error,
+            (true, true) => LogLevel::Warn,
+            (true, false) => LogLevel::Info,
+        };
+        if aborted {
+            report.push_str(". Aborted by user");
+        }
+        self.log_and_alert(level, report);
+        let result: TransferResult = batch.result();
+        batch.set_result(dir.abs_path.as_path(), result);
+        match upload {
+            true => self.local.set_results(batch.results),
+            false => self.remote.set_results(batch.results),
+        }
+    }
+
+    /// ### sync_send_dir
+    ///
+    /// Mirror the local directory `src` into the remote directory `dst`
+    fn sync_send_dir(&mut self, src: &Path, dst: &Path, delete: bool, stats: &mut SyncStats) {
+        let src_entries: Vec<FsEntry> = match self.context.as_ref().unwrap().local.scan_dir(src) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.sync_scan_failed(src, err.to_string());
+                return;
+            }
+        };
+        let dst_entries: Result<Vec<FsEntry>, FileTransferError> = self.client.list_dir(dst);
+        self.log_client_warnings();
+        let dst_entries: Vec<FsEntry> = match dst_entries {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.sync_scan_failed(dst, err.to_string());
+                return;
+            }
+        };
+        let skew: i64 = self.sync_skew(true);
+        for action in sync_plan(&src_entries, &dst_entries, delete, skew) {
+            if self.transfer.aborted {
+                break;
+            }
+            match action {
+                SyncAction::Keep => stats.unchanged += 1,
+                SyncAction::Transfer(file) => {
+                    let mut remote_path: PathBuf = PathBuf::from(dst);
+                    remote_path.push(file.name.as_str());
+                    let result: Result<usize, String> =
+                        self.filetransfer_send_file(file, remote_path.as_path());
+                    if let Err(err) = &result {
+                        self.log(LogLevel::Error, err.as_str());
+                    }
+                    self.batch_add_result(file, result.is_ok());
+                }
+                SyncAction::Create(dir) => {
+                    let mut remote_path: PathBuf = PathBuf::from(dst);
+                    remote_path.push(dir.name.as_str());
+                    match self.client.mkdir(remote_path.as_path()) {
+                        Ok(_) => {
+                            self.log(
+                                LogLevel::Info,
+                                format!("Created directory \"{}\"", remote_path.display()).as_str(),
+                            );
+                            self.sync_send_dir(
+                                dir.abs_path.as_path(),
+                                remote_path.as_path(),
+                                delete,
+                                stats,
+                            );
+                        }
+                        Err(err) => {
+                            self.log(
+                                LogLevel::Error,
+                                format!(
+                                    "Failed to create directory \"{}\": {}",
+                                    remote_path.display(),
+                                    err
+                                )
+                                .as_str(),
+                            );
+                            self.batch_add_failure(dir.abs_path.as_path());
+                        }
+                    }
+                }
+                SyncAction::Descend(dir, other) => self.sync_send_dir(
+                    dir.abs_path.as_path(),
+                    other.abs_path.as_path(),
+                    delete,
+                    stats,
+                ),
+                SyncAction::Conflict(entry) => self.sync_conflict(entry),
+                SyncAction::Delete(entry) => {
+                    let result: Result<(), String> =
+                        self.client.remove(entry).map_err(|x| x.to_string());
+                    self.sync_deleted(entry, result, stats);
+                }
+            }
+        }
+    }
+
+    /// ### sync_recv_dir
+    ///
+    /// Mirror the remote directory `src` into the local directory `dst`
+    fn sync_recv_dir(&mut self, src: &Path, dst: &Path, delete: bool, stats: &mut SyncStats) {
+        let src_entries: Result<Vec<FsEntry>, FileTransferError> = self.client.list_dir(src);
+        self.log_client_warnings();
+        let src_entries: Vec<FsEntry> = match src_entries {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.sync_scan_failed(src, err.to_string());
+                return;
+            }
+        };
+        let dst_entries: Vec<FsEntry> = match self.context.as_ref().unwrap().local.scan_dir(dst) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.sync_scan_failed(dst, err.to_string());
+                return;
+            }
+        };
+        let skew: i64 = self.sync_skew(false);
+        for action in sync_plan(&src_entries, &dst_entries, delete, skew) {
+            if self.transfer.aborted {
+                break;
+            }
+            match action {
+                SyncAction::Keep => stats.unchanged += 1,
+                SyncAction::Transfer(file) => {
+                    let mut local_path: PathBuf = PathBuf::from(dst);
+                    local_path.push(file.name.as_str());
+                    let result: Result<(), String> =
+                        self.filetransfer_recv_file(local_path.as_path(), file);
+                    match &result {
+                        Ok(_) if !self.transfer.aborted => {
+                            self.run_post_download_command(local_path.as_path(), file.size)
+                        }
+                        Ok(_) => {}
+                        Err(err) => self.log(LogLevel::Error, err.as_str()),
+                    }
+                    self.batch_add_result(file, result.is_ok());
+                }
+                SyncAction::Create(dir) => {
+                    let mut local_path: PathBuf = PathBuf::from(dst);
+                    local_path.push(dir.name.as_str());
+                    match self
+                        .context
+                        .as_mut()
+                        .unwrap()
+                        .local
+                        .mkdir(local_path.as_path())
+                    {
+                        Ok(_) => {
+                            self.log(
+                                LogLevel::Info,
+                                format!("Created directory \"{}\"", local_path.display()).as_str(),
+                            );
+                            self.sync_recv_dir(
+                                dir.abs_path.as_path(),
+                                local_path.as_path(),
+                                delete,
+                                stats,
+                            );
+                        }
+                        Err(err) => {
+                            self.log(
+                                LogLevel::Error,
+                                format!(
+                                    "Failed to create directory \"{}\": {}",
+                                    local_path.display(),
+                                    err
+                                )
+                                .as_str(),
+                            );
+                            self.batch_add_failure(dir.abs_path.as_path());
+                        }
+                    }
+                }
+                SyncAction::Descend(dir, other) => self.sync_recv_dir(
+                    dir.abs_path.as_path(),
+                    other.abs_path.as_path(),
+                    delete,
+                    stats,
+                ),
+                SyncAction::Conflict(entry) => self.sync_conflict(entry),
+                SyncAction::Delete(entry) => {
+                    let result: Result<(), String> = self
+                        .context
+                        .as_mut()
+                        .unwrap()
+                        .local
+                        .remove(entry)
+                        .map_err(|x| x.to_string());
+                    self.sync_deleted(entry, result, stats);
+                }
+            }
+        }
+    }
+
+    /// ### sync_scan_failed
+    ///
+    /// Report a directory which couldn't be read during a sync
+    fn sync_scan_failed(&mut self, path: &Path, err: String) {
+        self.log(
+            LogLevel::Error,
+            format!("Could not scan directory \"{}\": {}", path.display(), err).as_str(),
+        );
+        self.batch_add_failure(path);
+    }
+
+    /// ### sync_conflict
+    ///
+    /// Report a source entry which has a different type on destination; the entry is skipped
+    fn sync_conflict(&mut self, entry: &FsEntry) {
+        self.log(
+            LogLevel::Warn,
+            format!(
+                "Skipped \"{}\": destination has a {} with the same name",
+                entry.get_abs_path().display(),
+                match entry.is_dir() {
+                    true => "file",
+                    false => "directory",
+                }
+            )
+            .as_str(),
+        );
+        self.batch_add_skipped(entry.get_abs_path().as_path());
+    }
+
+    /// ### sync_deleted
+    ///
+    /// Report the removal of an extraneous destination entry
+    fn sync_deleted(&mut self, entry: &FsEntry, result: Result<(), String>, stats: &mut SyncStats) {
+        match result {
+            Ok(_) => {
+                stats.deleted += 1;
+                self.log(
+                    LogLevel::Info,
+                    format!("Removed \"{}\"", entry.get_abs_path().display()).as_str(),
+                );
+            }
+            Err(err) => self.log(
+                LogLevel::Error,
+                format!(
+                    "Could not remove \"{}\": {}",
+                    entry.get_abs_path().display(),
+                    err
+                )
+                .as_str(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ui::activities::filetransfer_activity::tests::{make_fs_entry, test_activity};
+
+    #[test]
+    fn test_ui_activities_filetransfer_sync_plan() {
+        use {sync_plan, SyncAction};
+        let make_file = |name: &str, size: usize, mtime: u64| -> FsEntry {
+            match make_fs_entry(name, false) {
+                FsEntry::File(mut file) => {
+                    file.size = size;
+                    file.last_change_time =
+                        std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(mtime);
+                    FsEntry::File(file)
+                }
+                FsEntry::Directory(_) => panic!("Expected file"),
+            }
+        };
+        let src: Vec<FsEntry> = vec![
+            make_file("new.txt", 8, 100),
+            make_file("same.txt", 8, 100),
+            make_file("resized.txt", 16, 100),
+            make_file("newer.txt", 8, 200),
+            make_file("older.txt", 8, 50),
+            make_fs_entry("docs", true),
+            make_fs_entry("pics", true),
+            make_fs_entry("conflict", true),
+        ];
+        let dst: Vec<FsEntry> = vec![
+            make_file("same.txt", 8, 100),
+            make_file("resized.txt", 8, 100),
+            make_file("newer.txt", 8, 100),
+            make_file("older.txt", 8, 100),
+            make_fs_entry("docs", true),
+            make_file("conflict", 0, 0),
+            make_file("extraneous.txt", 8, 100),
+        ];
+        let actions: Vec<SyncAction> = sync_plan(&src, &dst, false, 0);
+        assert_eq!(actions.len(), 8);
+        assert!(matches!(actions[0], SyncAction::Transfer(file) if file.name == "new.txt"));
+        assert!(matches!(actions[1], SyncAction::Keep));
+        assert!(matches!(actions[2], SyncAction::Transfer(file) if file.name == "resized.txt"));
+        assert!(matches!(actions[3], SyncAction::Transfer(file) if file.name == "newer.txt"));
+        assert!(matches!(actions[4], SyncAction::Keep));
+        assert!(matches!(actions[5], SyncAction::Descend(dir, _) if dir.name == "docs"));
+        assert!(matches!(actions[6], SyncAction::Create(dir) if dir.name == "pics"));
+        assert!(matches!(actions[7], SyncAction::Conflict(_)));
+        // Delete extraneous entries, after everything else
+        let actions: Vec<SyncAction> = sync_plan(&src, &dst, true, 0);
+        assert_eq!(actions.len(), 9);
+        assert!(
+            matches!(actions[8], SyncAction::Delete(entry) if entry.get_name() == "extraneous.txt")
+        );
+        // Source clock ahead of destination: newer file is actually unchanged
+        let actions: Vec<SyncAction> = sync_plan(&src, &dst, false, 150);
+        assert!(matches!(actions[3], SyncAction::Keep));
+        assert!(matches!(actions[4], SyncAction::Keep));
+        // Source clock behind destination: older file is actually newer
+        let actions: Vec<SyncAction> = sync_plan(&src, &dst, false, -60);
+        assert!(matches!(actions[3], SyncAction::Transfer(file) if file.name == "newer.txt"));
+        assert!(matches!(actions[4], SyncAction::Transfer(file) if file.name == "older.txt"));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_sync_request() {
+        let mut activity: FileTransferActivity = test_activity();
+        let dir: crate::fs::FsDirectory = match make_fs_entry("docs", true) {
+            FsEntry::Directory(dir) => dir,
+            FsEntry::File(_) => panic!("Expected directory"),
+        };
+        activity.filetransfer_sync_request(dir, Path::new("/tmp"), true);
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Synchronize \"docs\" to remote?"
+        ));
+        // Confirm: ask whether to delete extraneous entries
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_sync_confirm();
+        assert!(matches!(
+            &activity.input_mode,
+            InputMode::Popup(PopupType::YesNo(txt, _, _)) if txt.as_str() == "Delete remote entries which don't exist on local?"
+        ));
+        assert!(activity.pending_sync.as_ref().unwrap().upload);
+        // Cancel
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_sync_cancel();
+        assert!(activity.pending_sync.is_none());
+        assert!(matches!(activity.input_mode, InputMode::Explorer));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_clock_skew() {
+        use std::time::SystemTime;
+        let now: SystemTime = SystemTime::now();
+        assert_eq!(time_diff(now + Duration::from_secs(30), now), 30);
+        assert_eq!(time_diff(now, now + Duration::from_millis(29600)), -30);
+        let mut activity: FileTransferActivity = test_activity();
+        assert_eq!(activity.clock_skew_threshold, 0);
+        assert!(!activity.clock_skew_compensate);
+        // Check disabled: nothing is measured
+        activity.clock_skew = Some(30);
+        activity.check_clock_skew();
+        assert!(activity.clock_skew.is_none());
+        // Not compensated
+        activity.clock_skew = Some(30);
+        assert_eq!(activity.sync_skew(true), 0);
+        // Remote is ahead: local source is behind on upload, remote source is ahead on download
+        activity.clock_skew_compensate = true;
+        assert_eq!(activity.sync_skew(true), -30);
+        assert_eq!(activity.sync_skew(false), 30);
+    }
+}
@@ -0,0 +1,353 @@
+//! ## Tabs
+//!
+//! `tabs` is the module which manages the remote tabs: additional connections,
+//! each one with its own client and explorer, which share the local explorer
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{
+    FileExplorer, FileTransferActivity, FileTransferParams, InputMode, LogLevel, PopupType,
+};
+use crate::filetransfer::{FileTransfer, FileTransferProtocol};
+use crate::system::config_client::ConfigClient;
+
+// Ext
+
+/// ## RemoteTab
+///
+/// RemoteTab contains the states of a remote connection which is not displayed
+pub(super) struct RemoteTab {
+    params: FileTransferParams,
+    client: Box<dyn FileTransfer>,
+    explorer: FileExplorer,
+    flush_interval: u64,
+    auth_retries: u8,
+    clock_skew: Option<i64>,
+}
+
+impl FileTransferActivity {
+    /// ### remote_tab_open
+    ///
+    /// Open a new remote tab connecting to the host described by `params`, and make it active.
+    /// The connection is established once back in explorer mode, as for the first remote.
+    /// If as many tabs as `max_connections` are already open on the same host, the tab is refused and false is returned
+    pub(super) fn remote_tab_open(&mut self, params: FileTransferParams) -> bool {
+        let connections: usize = self.remote_host_connections(params.address.as_str());
+        if self.max_connections > 0 && connections >= self.max_connections {
+            self.log_and_alert(
+                LogLevel::Warn,
+                format!(
+                    "Connection to \"{}\" refused: {} connections to this host are already open (limit is {}); close a tab first",
+                    params.address, connections, self.max_connections
+                ),
+            );
+            return false;
+        }
+        let config_client: Option<&ConfigClient> = self
+            .context
+            .as_ref()
+            .and_then(|ctx| ctx.config_client.as_ref());
+        let tab: RemoteTab = RemoteTab {
+            client: FileTransferActivity::make_client(&params, config_client),
+            flush_interval: match (config_client, params.protocol) {
+                (Some(config_client), FileTransferProtocol::Ftp(_)) => {
+                    config_client.get_ftp_flush_interval()
+                }
+                _ => 0,
+            },
+            params,
+            explorer: FileExplorer::new(self.remote.wrap, self.remote.group_dirs),
+            auth_retries: 0,
+            clock_skew: None,
+        };
+        self.forget_remote_undo();
+        self.forget_queue();
+        let previous: RemoteTab = self.remote_tab_swap(tab);
+        self.remote_tabs.insert(self.remote_tab_index, previous);
+        self.remote_tab_index = self.remote_tabs.len();
+        true
+    }
+
+    /// ### remote_tab_switch
+    ///
+    /// Make the next (or previous) remote tab active
+    pub(super) fn remote_tab_switch(&mut self, forward: bool) {
+        if self.remote_tabs.is_empty() {
+            self.log(
+                LogLevel::Warn,
+                "There is only one remote tab; open another one with <CTRL+T>",
+            );
+            return;
+        }
+        let tabs: usize = self.remote_tabs.len() + 1;
+        let index: usize = match forward {
+            true => (self.remote_tab_index + 1) % tabs,
+            false => (self.remote_tab_index + tabs - 1) % tabs,
+        };
+        self.forget_remote_undo();
+        self.forget_queue();
+        // Inactive tabs are sorted as all tabs, without the active one
+        let tab: RemoteTab = self
+            .remote_tabs
+            .remove(match index < self.remote_tab_index {
+                true => index,
+                false => index - 1,
+            });
+        let previous: RemoteTab = self.remote_tab_swap(tab);
+        self.remote_tabs.insert(
+            match self.remote_tab_index < index {
+                true => self.remote_tab_index,
+                false => self.remote_tab_index - 1,
+            },
+            previous,
+        );
+        self.remote_tab_index = index;
+    }
+
+    /// ### remote_tab_close
+    ///
+    /// Disconnect the active remote tab and make the previous one active.
+    /// The last remote tab can't be closed: disconnect instead
+    pub(super) fn remote_tab_close(&mut self) {
+        if self.remote_tabs.is_empty() {
+            self.log(
+                LogLevel::Warn,
+                "The last remote tab can't be closed; press <ESC> to disconnect",
+            );
+            return;
+        }
+        if self.client.is_connected() {
+            let _ = self.client.disconnect();
+        }
+        self.forget_remote_undo();
+        self.forget_queue();
+        let address: String = self.params.address.clone();
+        self.remote_tab_discard();
+        self.log(
+            LogLevel::Info,
+            format!("Closed remote tab \"{}\"", address).as_str(),
+        );
+    }
+
+    /// ### remote_tab_give_up
+    ///
+    /// Connecting to the remote of the active tab failed (or it was aborted): if other tabs are open,
+    /// close the tab and report the error, instead of disconnecting from all the remotes
+    pub(super) fn remote_tab_give_up(&mut self, err: Option<String>) {
+        match (self.remote_tabs.is_empty(), err) {
+            (true, Some(err)) => self.input_mode = InputMode::Popup(PopupType::Fatal(err)),
+            (true, None) => self.disconnect(),
+            (false, err) => {
+                let address: String = self.params.address.clone();
+                self.remote_tab_discard();
+                match err {
+                    Some(err) => self.log_and_alert(
+                        LogLevel::Error,
+                        format!("Could not connect to \"{}\": {}", address, err),
+                    ),
+                    None => {
+                        self.input_mode = InputMode::Explorer;
+                        self.log(
+                            LogLevel::Info,
+                            format!("Connection to \"{}\" aborted", address).as_str(),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// ### remote_tabs_disconnect
+    ///
+    /// Disconnect the remotes of the inactive tabs
+    pub(super) fn remote_tabs_disconnect(&mut self) {
+        for tab in self.remote_tabs.iter_mut() {
+            if tab.client.is_connected() {
+                let _ = tab.client.disconnect();
+            }
+        }
+    }
+
+    /// ### remote_tab_label
+    ///
+    /// Get the label with the position of the active remote tab, to be displayed in the explorer title.
+    /// Empty if there's only one remote
+    pub(super) fn remote_tab_label(&self) -> String {
+        match self.remote_tabs.is_empty() {
+            true => String::new(),
+            false => format!(
+                "[{}/{}] ",
+                self.remote_tab_index + 1,
+                self.remote_tabs.len() + 1
+            ),
+        }
+    }
+
+    /// ### remote_host_connections
+    ///
+    /// Get the amount of tabs (active or not) connecting to `address`
+    fn remote_host_connections(&self, address: &str) -> usize {
+        std::iter::once(&self.params)
+            .chain(self.remote_tabs.iter().map(|tab| &tab.params))
+            .filter(|params| params.address.eq_ignore_ascii_case(address))
+            .count()
+    }
+
+    /// ### remote_tab_discard
+    ///
+    /// Drop the active remote tab, making the previous one active
+    fn remote_tab_discard(&mut self) {
+        let index: usize = self.remote_tab_index.saturating_sub(1);
+        let tab: RemoteTab = self.remote_tabs.remove(index);
+        let _ = self.remote_tab_swap(tab);
+        self.remote_tab_index = index;
+    }
+
+    /// ### remote_tab_swap
+    ///
+    /// Make `tab` the active remote; returns the remote which was active
+    fn remote_tab_swap(&mut self, mut tab: RemoteTab) -> RemoteTab {
+        std::mem::swap(&mut self.params, &mut tab.params);
+        std::mem::swap(&mut self.client, &mut tab.client);
+        std::mem::swap(&mut self.remote, &mut tab.explorer);
+        std::mem::swap(&mut self.flush_interval, &mut tab.flush_interval);
+        std::mem::swap(&mut self.auth_retries, &mut tab.auth_retries);
+        std::mem::swap(&mut self.clock_skew, &mut tab.clock_skew);
+        tab
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ui::activities::filetransfer_activity::tests::test_activity;
+    use crate::ui::activities::filetransfer_activity::UndoAction;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_ui_activities_filetransfer_remote_tabs() {
+        let make_params = |address: &str| FileTransferParams {
+            address: String::from(address),
+            port: 22,
+            protocol: FileTransferProtocol::default(),
+            username: Some(String::from("omar")),
+            password: None,
+            jump_hosts: Vec::new(),
+            identity: None,
+            buffer_size: None,
+        };
+        let mut activity: FileTransferActivity = test_activity();
+        activity.params = make_params("10.0.0.1");
+        assert!(activity.remote_tab_label().is_empty());
+        // Switching or closing the only tab does nothing
+        activity.remote_tab_switch(true);
+        activity.remote_tab_close();
+        assert_eq!(activity.params.address.as_str(), "10.0.0.1");
+        // Open tabs; the new tab becomes active
+        activity.remote.wrkdir = PathBuf::from("/home/omar");
+        activity.remote_tab_open(make_params("10.0.0.2"));
+        assert_eq!(activity.params.address.as_str(), "10.0.0.2");
+        assert_eq!(activity.remote_tab_label().as_str(), "[2/2] ");
+        activity.remote_tab_open(make_params("10.0.0.3"));
+        assert_eq!(activity.remote_tab_label().as_str(), "[3/3] ");
+        // Switch; explorer follows the tab
+        activity.remote_tab_switch(true);
+        assert_eq!(activity.params.address.as_str(), "10.0.0.1");
+        assert_eq!(activity.remote.wrkdir, PathBuf::from("/home/omar"));
+        assert_eq!(activity.remote_tab_label().as_str(), "[1/3] ");
+        activity.remote_tab_switch(false);
+        assert_eq!(activity.params.address.as_str(), "10.0.0.3");
+        activity.remote_tab_switch(false);
+        assert_eq!(activity.params.address.as_str(), "10.0.0.2");
+        // Remote actions can't be undone on another remote
+        activity.push_undo(UndoAction::RemoteRename(
+            PathBuf::from("/a"),
+            PathBuf::from("/b"),
+        ));
+        activity.push_undo(UndoAction::LocalRename(
+            PathBuf::from("/c"),
+            PathBuf::from("/d"),
+        ));
+        activity.remote_tab_switch(true);
+        assert_eq!(activity.params.address.as_str(), "10.0.0.3");
+        assert_eq!(activity.undo_stack.len(), 1);
+        // Close the active tab: the previous one becomes active
+        activity.remote_tab_close();
+        assert_eq!(activity.params.address.as_str(), "10.0.0.2");
+        assert_eq!(activity.remote_tab_label().as_str(), "[2/2] ");
+        // Failed connection closes the tab
+        activity.remote_tab_give_up(Some(String::from("connection refused")));
+        assert_eq!(activity.params.address.as_str(), "10.0.0.1");
+        assert!(activity.remote_tab_label().is_empty());
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        assert!(!activity.disconnected);
+        // Bad address
+        activity.input_mode = InputMode::Explorer;
+        activity.callback_remote_tab_address(String::from("sftp://10.0.0.4:port"));
+        assert!(activity.remote_tab_label().is_empty());
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_ui_activities_filetransfer_max_connections() {
+        let make_params = |address: &str| FileTransferParams {
+            address: String::from(address),
+            port: 22,
+            protocol: FileTransferProtocol::default(),
+            username: Some(String::from("omar")),
+            password: None,
+            jump_hosts: Vec::new(),
+            identity: None,
+            buffer_size: None,
+        };
+        let mut activity: FileTransferActivity = test_activity();
+        activity.params = make_params("10.0.0.1");
+        assert_eq!(activity.max_connections, 4);
+        activity.max_connections = 2;
+        assert!(activity.remote_tab_open(make_params("10.0.0.1")));
+        assert!(activity.remote_tab_open(make_params("10.0.0.2")));
+        // Limit reached for 10.0.0.1: tab is refused
+        activity.input_mode = InputMode::Explorer;
+        assert!(!activity.remote_tab_open(make_params("10.0.0.1")));
+        assert!(matches!(
+            activity.input_mode,
+            InputMode::Popup(PopupType::Alert(_, _))
+        ));
+        assert_eq!(activity.remote_tab_label().as_str(), "[3/3] ");
+        assert_eq!(activity.params.address.as_str(), "10.0.0.2");
+        // Closing a tab makes room
+        activity.remote_tab_switch(false);
+        activity.remote_tab_close();
+        assert!(activity.remote_tab_open(make_params("10.0.0.1")));
+        // Unlimited
+        activity.max_connections = 0;
+        assert!(activity.remote_tab_open(make_params("10.0.0.1")));
+    }
+}
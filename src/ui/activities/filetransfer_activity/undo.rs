@@ -0,0 +1,313 @@
+//! ## Undo
+//!
+//! `undo` is the module which records the destructive actions which can be reverted and reverts them
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+// Locals
+use super::{FileTransferActivity, LogLevel};
+use crate::fs::FsEntry;
+
+// Ext
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Maximum amount of actions which can be undone; older actions are forgotten
+const UNDO_STACK_SIZE: usize = 64;
+// Suffix appended to the name of remote files overwritten by an upload, in trash mode
+const BACKUP_SUFFIX: &str = ".gateway-backup";
+
+/// ## UndoAction
+///
+/// UndoAction describes a destructive action which can be reverted
+#[derive(Clone, std::fmt::Debug, PartialEq)]
+pub(super) enum UndoAction {
+    LocalRename(PathBuf, PathBuf),  // Local entry renamed (from, to)
+    RemoteRename(PathBuf, PathBuf), // Remote entry renamed (from, to)
+    LocalTrash(PathBuf, PathBuf), // Local entry moved to trash instead of deleted (original path, path in trash)
+    RemoteBackup(PathBuf, PathBuf), // Remote file overwritten by an upload (path, backup path)
+}
+
+/// ### trash_path
+///
+/// Get the path in the trash directory for an entry named `name`, deleted at `time`.
+/// Names are prefixed with the deletion time, so entries with the same name don't collide
+pub(super) fn trash_path(trash_dir: &Path, name: &str, time: SystemTime) -> PathBuf {
+    let millis: u128 = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|x| x.as_millis())
+        .unwrap_or(0);
+    trash_dir.join(format!("{}-{}", millis, name))
+}
+
+/// ### backup_path
+///
+/// Get the path of the backup of a remote file overwritten by an upload
+pub(super) fn backup_path(path: &Path) -> PathBuf {
+    let mut backup: std::ffi::OsString = path.as_os_str().to_os_string();
+    backup.push(BACKUP_SUFFIX);
+    PathBuf::from(backup)
+}
+
+impl FileTransferActivity {
+    /// ### push_undo
+    ///
+    /// Record an action which can be undone; the oldest action is forgotten if the stack is full.
+    /// A remote backup replaces the previous one of the same file, whose action can't be undone anymore
+    pub(super) fn push_undo(&mut self, action: UndoAction) {
+        if let UndoAction::RemoteBackup(_, backup) = &action {
+            self.undo_stack
+                .retain(|x| !matches!(x, UndoAction::RemoteBackup(_, old) if old == backup));
+        }
+        if self.undo_stack.len() >= UNDO_STACK_SIZE {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(action);
+    }
+
+    /// ### forget_remote_undo
+    ///
+    /// Forget the remote actions, which can't be undone once the active remote changed
+    pub(super) fn forget_remote_undo(&mut self) {
+        let actions: usize = self.undo_stack.len();
+        self.undo_stack.retain(|x| {
+            matches!(
+                x,
+                UndoAction::LocalRename(_, _) | UndoAction::LocalTrash(_, _)
+            )
+        });
+        if self.undo_stack.len() < actions {
+            self.log(
+                LogLevel::Warn,
+                format!(
+                    "{} remote action(s) on \"{}\" can no longer be undone",
+                    actions - self.undo_stack.len(),
+                    self.params.address
+                )
+                .as_str(),
+            );
+        }
+    }
+
+    /// ### trash_local_entry
+    ///
+    /// Move local entry to the trash directory, instead of deleting it, so it can be restored with undo.
+    /// Returns the path of the entry in the trash
+    pub(super) fn trash_local_entry(
+        &mut self,
+        entry: &FsEntry,
+        trash_dir: &Path,
+    ) -> Result<PathBuf, String> {
+        let dst: PathBuf = trash_path(trash_dir, entry.get_name().as_str(), SystemTime::now());
+        match self
+            .context
+            .as_mut()
+            .unwrap()
+            .local
+            .rename(entry, dst.as_path())
+        {
+            Ok(_) => {
+                self.push_undo(UndoAction::LocalTrash(entry.get_abs_path(), dst.clone()));
+                Ok(dst)
+            }
+            Err(err) => Err(format!("Could not move to trash: {}", err)),
+        }
+    }
+
+    /// ### backup_remote_file
+    ///
+    /// If a remote file exists at `path`, rename it to its backup path, so the upload which is going to overwrite it can be undone.
+    /// A previous backup of the same file is replaced, and can no longer be undone. Nothing is done if the protocol doesn't support stat
+    pub(super) fn backup_remote_file(&mut self, path: &Path) {
+        let entry: FsEntry = match self.client.stat(path) {
+            Ok(FsEntry::File(file)) => FsEntry::File(file),
+            _ => return,
+        };
+        let backup: PathBuf = backup_path(path);
+        if let Ok(old_backup) = self.client.stat(backup.as_path()) {
+            let _ = self.client.remove(&old_backup);
+        }
+        match self.client.rename(&entry, backup.as_path()) {
+            Ok(_) => self.push_undo(UndoAction::RemoteBackup(PathBuf::from(path), backup)),
+            Err(err) => self.log(
+                LogLevel::Warn,
+                format!(
+                    "Could not back up \"{}\" before overwriting it: {}",
+                    path.display(),
+                    err
+                )
+                .as_str(),
+            ),
+        }
+    }
+
+    /// ### undo
+    ///
+    /// Revert the last recorded action and reload the explorer it affected
+    pub(super) fn undo(&mut self) {
+        let action: UndoAction = match self.undo_stack.pop() {
+            Some(action) => action,
+            None => {
+                self.log(LogLevel::Warn, "Nothing to undo");
+                return;
+            }
+        };
+        let (result, local): (Result<String, String>, bool) = match &action {
+            UndoAction::LocalRename(from, to) => (self.undo_local_move(to, from), true),
+            UndoAction::LocalTrash(original, trashed) => {
+                (self.undo_local_move(trashed, original), true)
+            }
+            UndoAction::RemoteRename(from, to) => (self.undo_remote_rename(to, from), false),
+            UndoAction::RemoteBackup(path, backup) => {
+                (self.undo_remote_backup(path, backup), false)
+            }
+        };
+        match result {
+            Ok(msg) => self.log(LogLevel::Info, format!("Undo: {}", msg).as_str()),
+            Err(err) => self.log_and_alert(LogLevel::Error, format!("Could not undo: {}", err)),
+        }
+        match local {
+            true => {
+                let wrkdir: PathBuf = self.local.wrkdir.clone();
+                self.local_scan(wrkdir.as_path());
+            }
+            false => self.reload_remote_dir(),
+        }
+    }
+
+    /// ### undo_local_move
+    ///
+    /// Move local entry at `src` back to `dst`, which must not exist
+    fn undo_local_move(&mut self, src: &Path, dst: &Path) -> Result<String, String> {
+        let local = &mut self.context.as_mut().unwrap().local;
+        if local.file_exists(dst) {
+            return Err(format!("\"{}\" already exists", dst.display()));
+        }
+        let entry: FsEntry = local
+            .stat(src)
+            .map_err(|err| format!("\"{}\": {}", src.display(), err))?;
+        local
+            .rename(&entry, dst)
+            .map_err(|err| format!("\"{}\": {}", src.display(), err))?;
+        Ok(format!("restored \"{}\"", dst.display()))
+    }
+
+    /// ### undo_remote_rename
+    ///
+    /// Rename remote entry at `src` back to `dst`
+    fn undo_remote_rename(&mut self, src: &Path, dst: &Path) -> Result<String, String> {
+        if self.client.stat(dst).is_ok() {
+            return Err(format!("\"{}\" already exists", dst.display()));
+        }
+        let entry: FsEntry = self
+            .client
+            .stat(src)
+            .map_err(|err| format!("\"{}\": {}", src.display(), err))?;
+        self.client
+            .rename(&entry, dst)
+            .map_err(|err| format!("\"{}\": {}", src.display(), err))?;
+        Ok(format!(
+            "renamed \"{}\" back to \"{}\"",
+            src.display(),
+            dst.display()
+        ))
+    }
+
+    /// ### undo_remote_backup
+    ///
+    /// Replace the uploaded remote file at `path` with its backup
+    fn undo_remote_backup(&mut self, path: &Path, backup: &Path) -> Result<String, String> {
+        let backup_entry: FsEntry = self
+            .client
+            .stat(backup)
+            .map_err(|err| format!("backup \"{}\": {}", backup.display(), err))?;
+        if let Ok(uploaded) = self.client.stat(path) {
+            self.client
+                .remove(&uploaded)
+                .map_err(|err| format!("\"{}\": {}", path.display(), err))?;
+        }
+        self.client
+            .rename(&backup_entry, path)
+            .map_err(|err| format!("backup \"{}\": {}", backup.display(), err))?;
+        Ok(format!("restored \"{}\" from its backup", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::ui::activities::filetransfer_activity::tests::test_activity;
+    use std::time::Duration;
+
+    #[test]
+    fn test_ui_activities_filetransfer_undo() {
+        let mut activity: FileTransferActivity = test_activity();
+        assert!(activity.trash_dir.is_none());
+        // Nothing to undo
+        activity.undo();
+        assert_eq!(activity.log_records[0].msg.as_str(), "Nothing to undo");
+        // Oldest actions are forgotten
+        for i in 0..70 {
+            activity.push_undo(UndoAction::LocalRename(
+                PathBuf::from(format!("/tmp/{}", i)),
+                PathBuf::from(format!("/tmp/{}.bak", i)),
+            ));
+        }
+        assert_eq!(activity.undo_stack.len(), 64);
+        assert_eq!(
+            activity.undo_stack[0],
+            UndoAction::LocalRename(PathBuf::from("/tmp/6"), PathBuf::from("/tmp/6.bak"))
+        );
+        // Trash and backup paths
+        let time: std::time::SystemTime =
+            std::time::SystemTime::UNIX_EPOCH + Duration::from_millis(1612345678901);
+        assert_eq!(
+            trash_path(Path::new("/home/omar/.config/gateway/trash"), "a.txt", time),
+            PathBuf::from("/home/omar/.config/gateway/trash/1612345678901-a.txt")
+        );
+        assert_eq!(
+            backup_path(Path::new("/var/www/index.html")),
+            PathBuf::from("/var/www/index.html.gateway-backup")
+        );
+        // A backup replaces the previous one of the same file
+        let backup: PathBuf = backup_path(Path::new("/var/www/index.html"));
+        activity.undo_stack.clear();
+        activity.push_undo(UndoAction::RemoteBackup(
+            PathBuf::from("/var/www/index.html"),
+            backup.clone(),
+        ));
+        activity.push_undo(UndoAction::RemoteBackup(
+            PathBuf::from("/var/www/about.html"),
+            backup_path(Path::new("/var/www/about.html")),
+        ));
+        activity.push_undo(UndoAction::RemoteBackup(
+            PathBuf::from("/var/www/index.html"),
+            backup.clone(),
+        ));
+        assert_eq!(activity.undo_stack.len(), 2);
+        assert_eq!(
+            activity.undo_stack[1],
+            UndoAction::RemoteBackup(PathBuf::from("/var/www/index.html"), backup)
+        );
+    }
+}
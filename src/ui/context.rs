@@ -30,11 +30,12 @@ extern crate tui;
 // Locals
 use super::input::InputHandler;
 use crate::host::Localhost;
+use crate::system::config_client::ConfigClient;
 
 // Includes
 use crossterm::event::DisableMouseCapture;
 use crossterm::execute;
-use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
 use std::io::{stdout, Stdout, Write};
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
@@ -44,22 +45,26 @@ use tui::Terminal;
 /// Context holds data structures used by the ui
 pub struct Context {
     pub local: Localhost,
+    pub(crate) config_client: Option<ConfigClient>,
     pub(crate) input_hnd: InputHandler,
     pub(crate) terminal: Terminal<CrosstermBackend<Stdout>>,
+    title_set: bool, // Has terminal title been changed?
 }
 
 impl Context {
     /// ### new
     ///
     /// Instantiates a new Context
-    pub fn new(local: Localhost) -> Context {
+    pub fn new(local: Localhost, config_client: Option<ConfigClient>) -> Context {
         // Create terminal
         let mut stdout = stdout();
         assert!(execute!(stdout, EnterAlternateScreen).is_ok());
         Context {
             local,
+            config_client,
             input_hnd: InputHandler::new(),
             terminal: Terminal::new(CrosstermBackend::new(stdout)).unwrap(),
+            title_set: false,
         }
     }
 
@@ -82,10 +87,30 @@ impl Context {
     pub fn clear_screen(&mut self) {
         let _ = self.terminal.clear();
     }
+
+    /// ### set_title
+    ///
+    /// Set terminal title
+    pub fn set_title(&mut self, title: &str) {
+        if execute!(self.terminal.backend_mut(), SetTitle(title)).is_ok() {
+            self.title_set = true;
+        }
+    }
+
+    /// ### reset_title
+    ///
+    /// Clear terminal title, if it has been changed
+    pub fn reset_title(&mut self) {
+        if self.title_set {
+            let _ = execute!(self.terminal.backend_mut(), SetTitle(""));
+            self.title_set = false;
+        }
+    }
 }
 
 impl Drop for Context {
     fn drop(&mut self) {
+        self.reset_title();
         // Re-enable terminal stuff
         let _ = execute!(
             self.terminal.backend_mut(),
@@ -27,3 +27,4 @@
 pub mod activities;
 pub mod context;
 pub(crate) mod input;
+pub mod prompt;
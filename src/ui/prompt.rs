@@ -0,0 +1,164 @@
+//! ## Prompt
+//!
+//! `prompt` is the module which reads input from the terminal before the UI is started
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+extern crate crossterm;
+
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use std::io::Write;
+
+/// ## PasswordInput
+///
+/// PasswordInput describes how the password prompt has been answered
+#[derive(std::fmt::Debug, PartialEq)]
+pub enum PasswordInput {
+    Password(String), // Password typed
+    Empty,            // Enter pressed without typing: connect without password
+    Interrupted,      // <CTRL+C> pressed
+    Eof,              // <CTRL+D> pressed without typing
+}
+
+/// ## PasswordReader
+///
+/// PasswordReader collects the keys typed at the password prompt
+#[derive(Default)]
+pub struct PasswordReader {
+    buffer: String,
+}
+
+impl PasswordReader {
+    /// ### handle_key
+    ///
+    /// Handle key typed at the prompt; returns the answer once the input is complete
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<PasswordInput> {
+        let ctrl: bool = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => Some(PasswordInput::Interrupted),
+            // As terminals do, <CTRL+D> is EOF only on an empty line
+            KeyCode::Char('d') if ctrl => match self.buffer.is_empty() {
+                true => Some(PasswordInput::Eof),
+                false => None,
+            },
+            KeyCode::Enter => match self.buffer.is_empty() {
+                true => Some(PasswordInput::Empty),
+                false => Some(PasswordInput::Password(std::mem::take(&mut self.buffer))),
+            },
+            KeyCode::Backspace => {
+                self.buffer.pop();
+                None
+            }
+            KeyCode::Char(ch) if !ctrl => {
+                self.buffer.push(ch);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+/// ### tty_print
+///
+/// Print `text` on the terminal (`/dev/tty`), so it's displayed even if stderr is redirected
+/// and tools such as `sshpass` can detect the prompt; stderr is used if the terminal can't be opened
+fn tty_print(text: &str) {
+    #[cfg(unix)]
+    if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        let _ = tty.write_all(text.as_bytes());
+        let _ = tty.flush();
+        return;
+    }
+    eprint!("{}", text);
+    let _ = std::io::stderr().flush();
+}
+
+/// ### read_password
+///
+/// Print `prompt` on the terminal and read a password from the terminal, without echoing it.
+/// The terminal is in raw mode while reading, so <CTRL+C> and <CTRL+D> are returned as answers
+/// instead of killing the process; the terminal is always restored before returning
+pub fn read_password(prompt: &str) -> Result<PasswordInput, String> {
+    tty_print(prompt);
+    enable_raw_mode().map_err(|err| format!("Could not set terminal in raw mode: {}", err))?;
+    let mut reader: PasswordReader = PasswordReader::default();
+    let result: Result<PasswordInput, String> = loop {
+        match read() {
+            Ok(Event::Key(key)) => {
+                if let Some(input) = reader.handle_key(key) {
+                    break Ok(input);
+                }
+            }
+            Ok(_) => { /* Nothing to do */ }
+            Err(err) => break Err(format!("Could not read password from prompt: {}", err)),
+        }
+    };
+    let _ = disable_raw_mode();
+    // Enter is not echoed
+    tty_print("\n");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(ch: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(ch), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn test_ui_prompt_password_reader() {
+        let mut reader: PasswordReader = PasswordReader::default();
+        // Type password, with a typo
+        assert!(reader.handle_key(key(KeyCode::Char('p'))).is_none());
+        assert!(reader.handle_key(key(KeyCode::Char('w'))).is_none());
+        assert!(reader.handle_key(key(KeyCode::Char('x'))).is_none());
+        assert!(reader.handle_key(key(KeyCode::Backspace)).is_none());
+        assert!(reader.handle_key(key(KeyCode::Char('d'))).is_none());
+        // <CTRL+D> is ignored on a non empty line
+        assert!(reader.handle_key(ctrl('d')).is_none());
+        assert_eq!(
+            reader.handle_key(key(KeyCode::Enter)).unwrap(),
+            PasswordInput::Password(String::from("pwd"))
+        );
+        // Empty password is explicit
+        assert_eq!(
+            reader.handle_key(key(KeyCode::Enter)).unwrap(),
+            PasswordInput::Empty
+        );
+        // EOF
+        assert_eq!(reader.handle_key(ctrl('d')).unwrap(), PasswordInput::Eof);
+        // Interrupt, even after typing
+        assert!(reader.handle_key(key(KeyCode::Char('a'))).is_none());
+        assert_eq!(
+            reader.handle_key(ctrl('c')).unwrap(),
+            PasswordInput::Interrupted
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! ## Eol
+//!
+//! `eol` is the module which converts the line endings of text files while they're transferred
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+extern crate content_inspector;
+
+use crate::config::LineEnding;
+
+use content_inspector::ContentType;
+
+/// ### is_convertible
+///
+/// Returns whether the first bytes of a file (`head`) are ASCII or UTF-8 text, whose line endings can be converted.
+/// Binary files and other encodings (e.g. UTF-16) are never converted
+pub fn is_convertible(head: &[u8]) -> bool {
+    matches!(
+        content_inspector::inspect(head),
+        ContentType::UTF_8 | ContentType::UTF_8_BOM
+    )
+}
+
+/// ## EolConverter
+///
+/// EolConverter converts the line endings of a stream, chunk by chunk, to the `target` convention.
+/// Lone `\r` are never changed
+pub struct EolConverter {
+    target: LineEnding,
+    prev_cr: bool,        // Last byte of the previous chunk was `\r`
+    lines_changed: usize, // Line endings converted so far
+}
+
+impl EolConverter {
+    /// ### new
+    ///
+    /// Instantiates a new EolConverter to the `target` line ending
+    pub fn new(target: LineEnding) -> EolConverter {
+        EolConverter {
+            target,
+            prev_cr: false,
+            lines_changed: 0,
+        }
+    }
+
+    /// ### convert
+    ///
+    /// Convert the next chunk of the stream.
+    /// Converting to LF, a `\r` ending the chunk is held back until the next chunk (or `finish`) tells whether it's a CRLF
+    pub fn convert(&mut self, chunk: &[u8]) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::with_capacity(chunk.len() + chunk.len() / 32);
+        for byte in chunk.iter() {
+            match self.target {
+                LineEnding::Lf => {
+                    if self.prev_cr {
+                        if *byte == b'\n' {
+                            self.lines_changed += 1;
+                        } else {
+                            out.push(b'\r');
+                        }
+                    }
+                    if *byte != b'\r' {
+                        out.push(*byte);
+                    }
+                }
+                LineEnding::Crlf => {
+                    if *byte == b'\n' && !self.prev_cr {
+                        out.push(b'\r');
+                        self.lines_changed += 1;
+                    }
+                    out.push(*byte);
+                }
+            }
+            self.prev_cr = *byte == b'\r';
+        }
+        out
+    }
+
+    /// ### finish
+    ///
+    /// Returns the bytes held back at the end of the stream
+    pub fn finish(&mut self) -> Vec<u8> {
+        let prev_cr: bool = std::mem::replace(&mut self.prev_cr, false);
+        match (self.target, prev_cr) {
+            (LineEnding::Lf, true) => vec![b'\r'],
+            _ => Vec::new(),
+        }
+    }
+
+    /// ### target
+    ///
+    /// Returns the line ending the stream is converted to
+    pub fn target(&self) -> LineEnding {
+        self.target
+    }
+
+    /// ### lines_changed
+    ///
+    /// Returns the amount of line endings converted
+    pub fn lines_changed(&self) -> usize {
+        self.lines_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_utils_eol_is_convertible() {
+        assert!(is_convertible(b"hello\r\nworld\n"));
+        assert!(is_convertible("\u{feff}caff\u{e8}\n".as_bytes()));
+        assert!(!is_convertible(b"\x00\x01\x02PNG"));
+        // UTF-16
+        assert!(!is_convertible(b"\xff\xfeh\x00i\x00\n\x00"));
+    }
+
+    #[test]
+    fn test_utils_eol_converter_lf() {
+        let mut conv: EolConverter = EolConverter::new(LineEnding::Lf);
+        // CRLF split across chunks; lone CR are kept
+        let mut out: Vec<u8> = conv.convert(b"a\r\nb\r");
+        assert_eq!(out.as_slice(), b"a\nb");
+        out.extend(conv.convert(b"\nc\rd\n\r"));
+        out.extend(conv.finish());
+        assert_eq!(out.as_slice(), b"a\nb\nc\rd\n\r");
+        assert_eq!(conv.lines_changed(), 2);
+    }
+
+    #[test]
+    fn test_utils_eol_converter_crlf() {
+        let mut conv: EolConverter = EolConverter::new(LineEnding::Crlf);
+        // Existing CRLF, also split across chunks, are kept
+        let mut out: Vec<u8> = conv.convert(b"a\nb\r");
+        out.extend(conv.convert(b"\nc\n"));
+        out.extend(conv.finish());
+        assert_eq!(out.as_slice(), b"a\r\nb\r\nc\r\n");
+        assert_eq!(conv.lines_changed(), 2);
+    }
+}
@@ -28,6 +28,7 @@ extern crate textwrap;
 
 use chrono::prelude::*;
 use std::time::{Duration, SystemTime};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// ### fmt_pex
 ///
@@ -90,6 +91,30 @@ pub fn fmt_time(time: SystemTime, fmt: &str) -> String {
     format!("{}", datetime.format(fmt))
 }
 
+/// ### fmt_relative_time
+///
+/// Format time as the elapsed time from now, with the largest unit (e.g. `2 hours ago`).
+/// Times in the future are formatted as `just now`
+pub fn fmt_relative_time(time: SystemTime) -> String {
+    let elapsed: u64 = match SystemTime::now().duration_since(time) {
+        Ok(elapsed) => elapsed.as_secs(),
+        Err(_) => 0,
+    };
+    let (amount, unit): (u64, &str) = match elapsed {
+        0 => return String::from("just now"),
+        1..=59 => (elapsed, "second"),
+        60..=3599 => (elapsed / 60, "minute"),
+        3600..=86399 => (elapsed / 3600, "hour"),
+        86400..=2591999 => (elapsed / 86400, "day"),
+        2592000..=31535999 => (elapsed / 2592000, "month"),
+        _ => (elapsed / 31536000, "year"),
+    };
+    match amount {
+        1 => format!("1 {} ago", unit),
+        _ => format!("{} {}s ago", amount, unit),
+    }
+}
+
 /// ### fmt_millis
 ///
 /// Format duration as {secs}.{millis}
@@ -99,6 +124,20 @@ pub fn fmt_millis(duration: Duration) -> String {
     format!("{}.{:0width$}", seconds, millis, width = 3)
 }
 
+/// ### fmt_xattr_value
+///
+/// Format the value of an extended attribute: as text if printable, as hex bytes otherwise
+pub fn fmt_xattr_value(value: &[u8]) -> String {
+    match std::str::from_utf8(value) {
+        Ok(text) if !text.chars().any(|c| c.is_control()) => String::from(text),
+        _ => value
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<String>>()
+            .join(" "),
+    }
+}
+
 /// align_text_center
 ///
 /// Align text to center for a given width
@@ -116,6 +155,29 @@ pub fn align_text_center(text: &str, width: u16) -> String {
     .to_string()
 }
 
+/// ### elide_text_left
+///
+/// Fit text in `width` columns, replacing its beginning with `...` if it's too long,
+/// so that its end (e.g. the file name of a path) stays visible
+pub fn elide_text_left(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width < 3 {
+        return String::new();
+    }
+    let mut columns: usize = 3; // `...`
+    let mut tail: Vec<char> = Vec::new();
+    for ch in text.chars().rev() {
+        columns += ch.width().unwrap_or(0);
+        if columns > width {
+            break;
+        }
+        tail.push(ch);
+    }
+    format!("...{}", tail.into_iter().rev().collect::<String>())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -142,6 +204,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_utils_fmt_relative_time() {
+        let ago = |secs: u64| fmt_relative_time(SystemTime::now() - Duration::from_secs(secs));
+        assert_eq!(ago(0).as_str(), "just now");
+        assert_eq!(ago(1).as_str(), "1 second ago");
+        assert_eq!(ago(42).as_str(), "42 seconds ago");
+        assert_eq!(ago(60).as_str(), "1 minute ago");
+        assert_eq!(ago(3599).as_str(), "59 minutes ago");
+        assert_eq!(ago(7200).as_str(), "2 hours ago");
+        assert_eq!(ago(86400).as_str(), "1 day ago");
+        assert_eq!(ago(86400 * 6).as_str(), "6 days ago");
+        assert_eq!(ago(86400 * 45).as_str(), "1 month ago");
+        assert_eq!(ago(86400 * 365).as_str(), "1 year ago");
+        assert_eq!(ago(86400 * 365 * 3).as_str(), "3 years ago");
+        // Future
+        assert_eq!(
+            fmt_relative_time(SystemTime::now() + Duration::from_secs(3600)).as_str(),
+            "just now"
+        );
+    }
+
     #[test]
     fn test_utils_align_text_center() {
         assert_eq!(
@@ -154,6 +237,38 @@ mod tests {
             String::from("hello world!")
         );
     }
+
+    #[test]
+    fn test_utils_fmt_elide_text_left() {
+        assert_eq!(
+            elide_text_left("/home/omar/readme.md", 32).as_str(),
+            "/home/omar/readme.md"
+        );
+        assert_eq!(
+            elide_text_left("/home/omar/readme.md", 20).as_str(),
+            "/home/omar/readme.md"
+        );
+        assert_eq!(
+            elide_text_left("/home/omar/readme.md", 14).as_str(),
+            "...r/readme.md"
+        );
+        // Wide characters
+        assert_eq!(
+            elide_text_left("/tmp/日本語.txt", 12).as_str(),
+            "...本語.txt"
+        );
+        assert_eq!(elide_text_left("/tmp/日本語.txt", 10).as_str(), "...語.txt");
+        assert_eq!(elide_text_left("/home/omar", 2).as_str(), "");
+    }
+
+    #[test]
+    fn test_utils_fmt_xattr_value() {
+        assert_eq!(fmt_xattr_value(b"omar@pc").as_str(), "omar@pc");
+        assert_eq!(fmt_xattr_value(b"").as_str(), "");
+        assert_eq!(fmt_xattr_value(&[0x01, 0xff, 0x1a]).as_str(), "01 ff 1a");
+        assert_eq!(fmt_xattr_value(b"line\n").as_str(), "6c 69 6e 65 0a");
+    }
+
     #[test]
     fn test_utils_fmt_millis() {
         assert_eq!(
@@ -169,4 +284,4 @@ mod tests {
             String::from("18.192")
         );
     }
-}
\ No newline at end of file
+}
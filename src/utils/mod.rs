@@ -24,6 +24,8 @@
 */
 
 // modules
+pub mod eol;
 pub mod fmt;
 pub mod hash;
-pub mod parser;
\ No newline at end of file
+pub mod parser;
+pub mod select;
\ No newline at end of file
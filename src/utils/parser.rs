@@ -27,10 +27,14 @@
 extern crate chrono;
 extern crate whoami;
 
+use crate::filetransfer::proxy_jump::JumpHost;
 use crate::filetransfer::FileTransferProtocol;
 
 use chrono::format::ParseError;
 use chrono::prelude::*;
+use serde::Deserialize;
+use std::net::Ipv6Addr;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
 /// ### parse_remote_opt
@@ -43,23 +47,40 @@ use std::time::{Duration, SystemTime};
 ///     FTP => 21
 /// The option string has the following syntax
 /// [protocol]://[username]@{address}:[port]
-/// The only argument which is mandatory is address
+/// The only argument which is mandatory is address; IPv6 addresses must be enclosed in brackets
 /// NOTE: possible strings
 /// - 172.26.104.1
 /// - root@172.26.104.1
 /// - sftp://root@172.26.104.1
 /// - sftp://172.26.104.1:4022
 /// - sftp://172.26.104.1
+/// - sftp://[fe80::1]:4022
 /// - ...
 ///
 pub fn parse_remote_opt(
     remote: &str,
 ) -> Result<(String, u16, FileTransferProtocol, Option<String>), String> {
+    parse_remote_opt_ex(remote, |_| None)
+}
+
+/// ### parse_remote_opt_ex
+///
+/// Parse remote option string as `parse_remote_opt`, but if username is not provided,
+/// `default_username` is called with the protocol to get the default one (e.g. from configuration).
+/// If it returns `None`, the current user is still used for ssh
+pub fn parse_remote_opt_ex<F>(
+    remote: &str,
+    default_username: F,
+) -> Result<(String, u16, FileTransferProtocol, Option<String>), String>
+where
+    F: Fn(FileTransferProtocol) -> Option<String>,
+{
     let mut wrkstr: String = remote.to_string();
-    let address: String;
-    let mut port: u16 = 22;
-    let mut protocol: FileTransferProtocol = FileTransferProtocol::Sftp;
-    let mut username: Option<String> = None;
+    let mut protocol: FileTransferProtocol = FileTransferProtocol::default();
+    let mut port: u16 = match protocol {
+        FileTransferProtocol::Ftp(_) => 21,
+        _ => 22,
+    };
     // Split string by '://'
     let tokens: Vec<&str> = wrkstr.split("://").collect();
     // If length is > 1, then token[0] is protocol
@@ -94,15 +115,25 @@ pub fn parse_remote_opt(
                 }
                 _ => return Err(format!("Unknown protocol '{}'", tokens[0])),
             }
+            // Check if protocol is available
+            if !protocol.is_enabled() {
+                return Err(format!(
+                    "Protocol '{}' is not supported by this build of Gateway",
+                    tokens[0]
+                ));
+            }
             wrkstr = String::from(tokens[1]); // Wrkstr becomes tokens[1]
         }
         _ => return Err(String::from("Bad syntax")), // Too many tokens...
     }
-    // Set username to default if sftp or scp
-    if matches!(
-        protocol,
-        FileTransferProtocol::Sftp | FileTransferProtocol::Scp
-    ) {
+    // Set username to default for protocol; use current username if sftp or scp
+    let mut username: Option<String> = default_username(protocol);
+    if username.is_none()
+        && matches!(
+            protocol,
+            FileTransferProtocol::Sftp | FileTransferProtocol::Scp
+        )
+    {
         // Set username to current username
         username = Some(whoami::username());
     }
@@ -119,29 +150,247 @@ pub fn parse_remote_opt(
         _ => return Err(String::from("Bad syntax")), // Too many tokens...
     }
     // Split wrkstring by ':'
-    let tokens: Vec<&str> = wrkstr.split(':').collect();
-    match tokens.len() {
-        1 => {
-            // Address is wrkstr
-            address = wrkstr.clone();
-        }
-        2 => {
-            // Address is first token
-            address = String::from(tokens[0]);
-            // Port is second str
-            port = match tokens[1].parse::<u16>() {
-                Ok(val) => val,
-                Err(_) => {
-                    return Err(format!(
-                        "Port must be a number in range [0-65535], but is '{}'",
-                        tokens[1]
-                    ))
-                }
+    let (address, address_port): (&str, Option<u16>) = parse_address_port(wrkstr.as_str())?;
+    if let Some(address_port) = address_port {
+        port = address_port;
+    }
+    Ok((String::from(address), port, protocol, username))
+}
+
+/// ### parse_address_port
+///
+/// Split `{address}:[port]` into the address and the port, if provided.
+/// IPv6 addresses are enclosed in brackets, the port follows them (e.g. `[fe80::1]:2222`)
+fn parse_address_port(wrkstr: &str) -> Result<(&str, Option<u16>), String> {
+    let (address, port_str): (&str, Option<&str>) = match wrkstr.strip_prefix('[') {
+        Some(bracketed) => {
+            let end: usize = match bracketed.find(']') {
+                Some(end) if end > 0 => end,
+                _ => return Err(String::from("Bad syntax")), // Unclosed or empty brackets
             };
+            // Address is between brackets
+            match &bracketed[end + 1..] {
+                "" => (&bracketed[..end], None),
+                rest => match rest.strip_prefix(':') {
+                    Some(port) => (&bracketed[..end], Some(port)),
+                    None => return Err(String::from("Bad syntax")),
+                },
+            }
         }
-        _ => return Err(String::from("Bad syntax")), // Too many tokens...
+        None => {
+            let tokens: Vec<&str> = wrkstr.split(':').collect();
+            match tokens.len() {
+                // Address is wrkstr
+                1 => (wrkstr, None),
+                // Address is first token, port is second str
+                2 => (tokens[0], Some(tokens[1])),
+                _ => return Err(String::from("Bad syntax")), // Too many tokens...
+            }
+        }
+    };
+    match port_str {
+        None => Ok((address, None)),
+        Some(port_str) => match port_str.parse::<u16>() {
+            Ok(port) => Ok((address, Some(port))),
+            Err(_) => Err(format!(
+                "Port must be a number in range [0-65535], but is '{}'",
+                port_str
+            )),
+        },
+    }
+}
+
+/// ### parse_remote_url
+///
+/// Parse remote URL made of the remote option (see `parse_remote_opt_ex`) followed by an absolute path.
+/// Returns in case of success a tuple made of (address, port, protocol, username, path)
+/// The URL has the following syntax
+/// [protocol]://[username]@{address}:[port]/{path}
+/// e.g. `sftp://root@172.26.104.1:4022/var/log/syslog`
+pub fn parse_remote_url<F>(
+    url: &str,
+    default_username: F,
+) -> Result<(String, u16, FileTransferProtocol, Option<String>, PathBuf), String>
+where
+    F: Fn(FileTransferProtocol) -> Option<String>,
+{
+    // Path starts at the first '/' after the protocol
+    let host_start: usize = url.find("://").map(|x| x + 3).unwrap_or(0);
+    let path_start: usize = match url[host_start..].find('/') {
+        Some(index) => host_start + index,
+        None => return Err(format!("Missing remote path in '{}'", url)),
+    };
+    let path: &str = &url[path_start..];
+    if path.trim_end_matches('/').is_empty() {
+        return Err(format!("Missing remote path in '{}'", url));
+    }
+    let (address, port, protocol, username) =
+        parse_remote_opt_ex(&url[..path_start], default_username)?;
+    Ok((address, port, protocol, username, PathBuf::from(path)))
+}
+
+/// ### parse_transfer_target
+///
+/// Get the destination path of a one-shot transfer (`put`/`get`) of the file named `name` to `dest`:
+/// - if `dest` ends with a path separator, it's a directory, and the file keeps its name in it;
+/// - otherwise, if `is_dir` reports `dest` as an existing directory, the file keeps its name in it;
+/// - otherwise `dest` is the new name of the file.
+pub fn parse_transfer_target<F>(dest: &str, name: &str, is_dir: F) -> PathBuf
+where
+    F: FnOnce(&Path) -> bool,
+{
+    let target: PathBuf = PathBuf::from(dest);
+    match dest.ends_with(std::path::is_separator) || is_dir(target.as_path()) {
+        true => target.join(name),
+        false => target,
+    }
+}
+
+/// ### parse_jump_hosts
+///
+/// Parse jump hosts option string (as OpenSSH `-J`). Returns the list of jump hosts to go through, in order.
+/// The option string has the following syntax
+/// [username]@{address}:[port][,[username]@{address}:[port]...]
+/// IPv6 addresses must be enclosed in brackets (e.g. `root@[fe80::1]:2222`).
+/// If port is missing, 22 will be used; if username is missing, the current user will be used at connection
+pub fn parse_jump_hosts(spec: &str) -> Result<Vec<JumpHost>, String> {
+    let mut jump_hosts: Vec<JumpHost> = Vec::new();
+    for host in spec.split(',') {
+        // Split by '@'
+        let (username, wrkstr): (Option<String>, &str) = match host.rfind('@') {
+            Some(index) => (Some(String::from(&host[..index])), &host[index + 1..]),
+            None => (None, host),
+        };
+        // Split by ':'
+        let (address, port): (&str, Option<u16>) = parse_address_port(wrkstr)
+            .map_err(|err| format!("Bad jump host '{}': {}", host, err))?;
+        if address.is_empty() || matches!(&username, Some(u) if u.is_empty()) {
+            return Err(format!("Bad jump host syntax '{}'", host));
+        }
+        jump_hosts.push(JumpHost {
+            address: String::from(address),
+            port: port.unwrap_or(22),
+            username,
+        });
+    }
+    Ok(jump_hosts)
+}
+
+/// ## GatewayFile
+///
+/// GatewayFile describes the connection parameters stored in a `.gateway` file
+#[derive(Deserialize, std::fmt::Debug)]
+struct GatewayFile {
+    address: String,
+    port: Option<u16>,
+    protocol: Option<String>,
+    username: Option<String>,
+}
+
+/// ### parse_gateway_file
+///
+/// Parse the content of a `.gateway` file (TOML). Returns in case of success a tuple made of (address, port, protocol, username),
+/// as `parse_remote_opt`. Only `address` is mandatory; missing values get the same defaults of the address argument.
+/// IPv6 addresses are written without brackets (e.g. `address = "fe80::1"`).
+/// The file has the following syntax
+/// ```toml
+/// address = "192.168.1.31"
+/// port = 4022
+/// protocol = "scp"
+/// username = "root"
+/// ```
+pub fn parse_gateway_file(
+    data: &str,
+) -> Result<(String, u16, FileTransferProtocol, Option<String>), String> {
+    let file: GatewayFile = toml::de::from_str(data).map_err(|err| err.to_string())?;
+    // Colons are only allowed in IPv6 addresses (which may have a zone index, e.g. `fe80::1%eth0`)
+    let is_ipv6: bool = file
+        .address
+        .split('%')
+        .next()
+        .map(|x| x.parse::<Ipv6Addr>().is_ok())
+        .unwrap_or(false);
+    if file.address.is_empty()
+        || file.address.contains(&['@', '/', '[', ']'][..])
+        || (file.address.contains(':') && !is_ipv6)
+    {
+        return Err(format!("Bad address '{}'", file.address));
+    }
+    if matches!(&file.username, Some(u) if u.is_empty() || u.contains('@')) {
+        return Err(String::from("Bad username"));
+    }
+    // Build remote option and parse it, so defaults are the same
+    let mut remote: String = String::new();
+    if let Some(protocol) = &file.protocol {
+        remote.push_str(protocol.to_lowercase().as_str());
+        remote.push_str("://");
+    }
+    if let Some(username) = &file.username {
+        remote.push_str(username.as_str());
+        remote.push('@');
+    }
+    match is_ipv6 {
+        true => remote.push_str(format!("[{}]", file.address).as_str()),
+        false => remote.push_str(file.address.as_str()),
+    }
+    if let Some(port) = file.port {
+        remote.push_str(format!(":{}", port).as_str());
+    }
+    parse_remote_opt(remote.as_str())
+}
+
+/// ### parse_bytesize
+///
+/// Parse an amount of bytes, optionally followed by a binary unit (`K`, `M` or `G`, case insensitive).
+/// e.g. `4096`, `512K`, `16M`, `1G`
+pub fn parse_bytesize(size: &str) -> Result<usize, String> {
+    let size: &str = size.trim();
+    let (digits, multiplier): (&str, usize) = match size.chars().last() {
+        Some('k') | Some('K') => (&size[..size.len() - 1], 1024),
+        Some('m') | Some('M') => (&size[..size.len() - 1], 1048576),
+        Some('g') | Some('G') => (&size[..size.len() - 1], 1073741824),
+        _ => (size, 1),
+    };
+    match digits.parse::<usize>() {
+        Ok(amount) => amount
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("Size is too big '{}'", size)),
+        Err(_) => Err(format!("Bad size '{}'", size)),
+    }
+}
+
+/// ### parse_buffer_size
+///
+/// Parse the size of the transfer buffer, in KB, which must be between 4 and 8192; returns the size in bytes
+pub fn parse_buffer_size(kb: &str) -> Result<usize, String> {
+    match kb.trim().parse::<usize>() {
+        Ok(kb) if (4..=8192).contains(&kb) => Ok(kb * 1024),
+        Ok(kb) => Err(format!("{} KB is out of range (4-8192 KB)", kb)),
+        Err(_) => Err(format!("Bad size '{}'", kb)),
+    }
+}
+
+/// ### parse_unix_pex
+///
+/// Parse UNIX permissions in octal notation (e.g. `755`, `0644`) into the user, group and others modes
+pub fn parse_unix_pex(mode: &str) -> Result<(u8, u8, u8), String> {
+    let mode: &str = mode.trim();
+    let digits: &str = match mode.len() {
+        4 if mode.starts_with('0') => &mode[1..],
+        _ => mode,
+    };
+    let modes: Vec<u8> = digits
+        .chars()
+        .filter_map(|x| x.to_digit(8))
+        .map(|x| x as u8)
+        .collect();
+    match (digits.len(), modes.len()) {
+        (3, 3) => Ok((modes[0], modes[1], modes[2])),
+        _ => Err(format!(
+            "Bad mode '{}': expected 3 octal digits (e.g. 755)",
+            mode
+        )),
     }
-    Ok((address, port, protocol, username))
 }
 
 /// ### parse_lstime
@@ -179,12 +428,54 @@ pub fn parse_lstime(tm: &str, fmt_year: &str, fmt_hours: &str) -> Result<SystemT
         .unwrap_or(SystemTime::UNIX_EPOCH))
 }
 
+/// ### parse_lstime_iso
+///
+/// Convert ls time with numeric ISO syntax to System Time.
+/// Supported syntaxes are (e.g. when `ls` is run with `--time-style=long-iso` or `full-iso`):
+/// 1. %Y-%m-%d %H:%M:%S (e.g. 2021-01-23 14:05:31; fractional seconds and time zone are ignored)
+/// 2. %Y-%m-%d %H:%M (e.g. 2021-01-23 14:05)
+/// 3. %Y-%m-%d (e.g. 2021-01-23)
+pub fn parse_lstime_iso(tm: &str) -> Result<SystemTime, ParseError> {
+    // Strip time zone and fractional seconds (full-iso)
+    let tm: String = tm
+        .split_whitespace()
+        .take(2)
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let tm: &str = tm.split('.').next().unwrap_or_default();
+    let datetime: NaiveDateTime = match NaiveDateTime::parse_from_str(tm, "%Y-%m-%d %H:%M:%S") {
+        Ok(dt) => dt,
+        Err(_) => match NaiveDateTime::parse_from_str(tm, "%Y-%m-%d %H:%M") {
+            Ok(dt) => dt,
+            Err(_) => NaiveDate::parse_from_str(tm, "%Y-%m-%d")?.and_hms(0, 0, 0),
+        },
+    };
+    // Convert datetime to system time
+    let sys_time: SystemTime = SystemTime::UNIX_EPOCH;
+    Ok(sys_time
+        .checked_add(Duration::from_secs(datetime.timestamp() as u64))
+        .unwrap_or(SystemTime::UNIX_EPOCH))
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
     #[test]
+    fn test_utils_parse_remote_opt_disabled_protocol() {
+        assert_eq!(
+            parse_remote_opt("ftp://172.26.104.1").is_ok(),
+            cfg!(feature = "ftp")
+        );
+        assert_eq!(
+            parse_remote_opt("scp://172.26.104.1").is_ok(),
+            cfg!(feature = "ssh")
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "ftp", feature = "ssh"))]
     fn test_utils_parse_remote_opt() {
         // Base case
         let result: (String, u16, FileTransferProtocol, Option<String>) =
@@ -273,6 +564,263 @@ mod tests {
         assert!(parse_remote_opt(&String::from("172.26.104.1:abc")).is_err()); // Bad port
     }
 
+    #[test]
+    fn test_utils_parse_remote_opt_ipv6() {
+        // Bracketed address
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_remote_opt(&String::from("[fe80::1]")).ok().unwrap();
+        assert_eq!(result.0, String::from("fe80::1"));
+        assert_eq!(result.1, 22); // Fallback to sftp default
+        assert_eq!(result.2, FileTransferProtocol::Sftp);
+        // Bracketed address + port
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_remote_opt(&String::from("[::1]:2222")).ok().unwrap();
+        assert_eq!(result.0, String::from("::1"));
+        assert_eq!(result.1, 2222);
+        // All together now
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_remote_opt(&String::from("ftp://anon@[2001:db8::2]:8021"))
+                .ok()
+                .unwrap();
+        assert_eq!(result.0, String::from("2001:db8::2"));
+        assert_eq!(result.1, 8021);
+        assert_eq!(result.2, FileTransferProtocol::Ftp(false));
+        assert_eq!(result.3.unwrap(), String::from("anon"));
+        // With remote path
+        let result = parse_remote_url("sftp://root@[fe80::1]:4022/tmp/a.txt", |_| None)
+            .ok()
+            .unwrap();
+        assert_eq!(result.0, String::from("fe80::1"));
+        assert_eq!(result.1, 4022);
+        assert_eq!(result.4, PathBuf::from("/tmp/a.txt"));
+        // bad syntax
+        assert!(parse_remote_opt(&String::from("fe80::1")).is_err()); // Missing brackets
+        assert!(parse_remote_opt(&String::from("[fe80::1")).is_err()); // Unclosed bracket
+        assert!(parse_remote_opt(&String::from("[]:22")).is_err()); // Empty address
+        assert!(parse_remote_opt(&String::from("[::1]22")).is_err()); // Missing ':'
+        assert!(parse_remote_opt(&String::from("[::1]:abc")).is_err()); // Bad port
+    }
+
+    #[test]
+    #[cfg(all(feature = "ftp", feature = "ssh"))]
+    fn test_utils_parse_remote_opt_default_username() {
+        let defaults = |protocol: FileTransferProtocol| match protocol {
+            FileTransferProtocol::Ftp(_) => Some(String::from("anonymous")),
+            FileTransferProtocol::Sftp => Some(String::from("deploy")),
+            FileTransferProtocol::Scp => None,
+        };
+        // Defaults apply per protocol
+        let (_, _, _, username) = parse_remote_opt_ex("ftp://172.26.104.1", defaults).unwrap();
+        assert_eq!(username.unwrap().as_str(), "anonymous");
+        let (_, _, _, username) = parse_remote_opt_ex("ftps://172.26.104.1", defaults).unwrap();
+        assert_eq!(username.unwrap().as_str(), "anonymous");
+        let (_, _, _, username) = parse_remote_opt_ex("172.26.104.1:4022", defaults).unwrap();
+        assert_eq!(username.unwrap().as_str(), "deploy");
+        // Without default, current user is used for scp
+        let (_, _, _, username) = parse_remote_opt_ex("scp://172.26.104.1", defaults).unwrap();
+        assert_eq!(username.unwrap(), whoami::username());
+        // Explicit user wins
+        let (_, _, _, username) = parse_remote_opt_ex("ftp://omar@172.26.104.1", defaults).unwrap();
+        assert_eq!(username.unwrap().as_str(), "omar");
+        let (_, _, _, username) = parse_remote_opt_ex("root@172.26.104.1", defaults).unwrap();
+        assert_eq!(username.unwrap().as_str(), "root");
+        // URLs too
+        let (_, _, _, username, _) =
+            parse_remote_url("ftp://mirror.example.com/pub/README", defaults).unwrap();
+        assert_eq!(username.unwrap().as_str(), "anonymous");
+    }
+
+    #[test]
+    #[cfg(all(feature = "ftp", feature = "ssh"))]
+    fn test_utils_parse_remote_url() {
+        let (address, port, protocol, username, path) =
+            parse_remote_url("sftp://root@172.26.104.1:4022/var/log/syslog", |_| None).unwrap();
+        assert_eq!(address.as_str(), "172.26.104.1");
+        assert_eq!(port, 4022);
+        assert_eq!(protocol, FileTransferProtocol::Sftp);
+        assert_eq!(username.unwrap().as_str(), "root");
+        assert_eq!(path, PathBuf::from("/var/log/syslog"));
+        // Defaults are the same of the remote option
+        let (address, port, protocol, username, path) =
+            parse_remote_url("ftp://mirror.example.com/pub/README", |_| None).unwrap();
+        assert_eq!(address.as_str(), "mirror.example.com");
+        assert_eq!(port, 21);
+        assert_eq!(protocol, FileTransferProtocol::Ftp(false));
+        assert!(username.is_none());
+        assert_eq!(path, PathBuf::from("/pub/README"));
+        let (address, _, protocol, _, path) =
+            parse_remote_url("172.26.104.1/tmp/a.txt", |_| None).unwrap();
+        assert_eq!(address.as_str(), "172.26.104.1");
+        assert_eq!(protocol, FileTransferProtocol::Sftp);
+        assert_eq!(path, PathBuf::from("/tmp/a.txt"));
+        // Bad syntax
+        assert!(parse_remote_url("sftp://172.26.104.1", |_| None).is_err()); // Missing path
+        assert!(parse_remote_url("sftp://172.26.104.1/", |_| None).is_err()); // Missing path
+        assert!(parse_remote_url("omar://172.26.104.1/tmp/a.txt", |_| None).is_err()); // Bad protocol
+        assert!(parse_remote_url("172.26.104.1:abc/tmp/a.txt", |_| None).is_err());
+        // Bad port
+    }
+
+    #[test]
+    fn test_utils_parse_transfer_target() {
+        // Trailing slash: directory, even if it doesn't exist (yet)
+        assert_eq!(
+            parse_transfer_target("/home/omar/", "a.txt", |_| false),
+            PathBuf::from("/home/omar/a.txt")
+        );
+        // No trailing slash: explicit name, unless it's a directory
+        assert_eq!(
+            parse_transfer_target("/home/omar/b.txt", "a.txt", |_| false),
+            PathBuf::from("/home/omar/b.txt")
+        );
+        assert_eq!(
+            parse_transfer_target("/home/omar", "a.txt", |p| p == Path::new("/home/omar")),
+            PathBuf::from("/home/omar/a.txt")
+        );
+        // Against the local file system
+        let tmp_dir: tempfile::TempDir = tempfile::TempDir::new().unwrap();
+        let dir: String = tmp_dir.path().display().to_string();
+        assert_eq!(
+            parse_transfer_target(dir.as_str(), "a.txt", |p| p.is_dir()),
+            tmp_dir.path().join("a.txt")
+        );
+        let dest: String = format!("{}/b.txt", dir);
+        assert_eq!(
+            parse_transfer_target(dest.as_str(), "a.txt", |p| p.is_dir()),
+            tmp_dir.path().join("b.txt")
+        );
+    }
+
+    #[test]
+    fn test_utils_parse_jump_hosts() {
+        // Single hop
+        let jumps: Vec<JumpHost> = parse_jump_hosts("bastion").ok().unwrap();
+        assert_eq!(jumps.len(), 1);
+        assert_eq!(jumps[0].address, String::from("bastion"));
+        assert_eq!(jumps[0].port, 22);
+        assert!(jumps[0].username.is_none());
+        // User + port
+        let jumps: Vec<JumpHost> = parse_jump_hosts("root@10.0.0.1:2222").ok().unwrap();
+        assert_eq!(jumps.len(), 1);
+        assert_eq!(jumps[0].address, String::from("10.0.0.1"));
+        assert_eq!(jumps[0].port, 2222);
+        assert_eq!(jumps[0].username.as_deref().unwrap(), "root");
+        // Chained
+        let jumps: Vec<JumpHost> = parse_jump_hosts("alice@bastion,bob@internal:8022")
+            .ok()
+            .unwrap();
+        assert_eq!(jumps.len(), 2);
+        assert_eq!(jumps[0].address, String::from("bastion"));
+        assert_eq!(jumps[0].username.as_deref().unwrap(), "alice");
+        assert_eq!(jumps[1].address, String::from("internal"));
+        assert_eq!(jumps[1].port, 8022);
+        assert_eq!(jumps[1].username.as_deref().unwrap(), "bob");
+        // IPv6
+        let jumps: Vec<JumpHost> = parse_jump_hosts("root@[fe80::1]:2222,[::1]").ok().unwrap();
+        assert_eq!(jumps.len(), 2);
+        assert_eq!(jumps[0].address, String::from("fe80::1"));
+        assert_eq!(jumps[0].port, 2222);
+        assert_eq!(jumps[0].username.as_deref().unwrap(), "root");
+        assert_eq!(jumps[1].address, String::from("::1"));
+        assert_eq!(jumps[1].port, 22);
+        // Bad syntax
+        assert!(parse_jump_hosts("").is_err());
+        assert!(parse_jump_hosts("bastion:abc").is_err()); // Bad port
+        assert!(parse_jump_hosts("bastion:22:22").is_err());
+        assert!(parse_jump_hosts("@bastion").is_err()); // Empty user
+        assert!(parse_jump_hosts("bastion,").is_err()); // Empty hop
+        assert!(parse_jump_hosts("fe80::1").is_err()); // Missing brackets
+        assert!(parse_jump_hosts("[fe80::1").is_err()); // Unclosed bracket
+    }
+
+    #[test]
+    #[cfg(all(feature = "ftp", feature = "ssh"))]
+    fn test_utils_parse_gateway_file() {
+        let result: (String, u16, FileTransferProtocol, Option<String>) = parse_gateway_file(
+            "address = \"192.168.1.31\"\nport = 4022\nprotocol = \"SCP\"\nusername = \"root\"\n",
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(result.0, String::from("192.168.1.31"));
+        assert_eq!(result.1, 4022);
+        assert_eq!(result.2, FileTransferProtocol::Scp);
+        assert_eq!(result.3.unwrap(), String::from("root"));
+        // Defaults
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_gateway_file("address = \"192.168.1.31\"\nprotocol = \"ftp\"\n")
+                .ok()
+                .unwrap();
+        assert_eq!(result.0, String::from("192.168.1.31"));
+        assert_eq!(result.1, 21);
+        assert_eq!(result.2, FileTransferProtocol::Ftp(false));
+        assert!(result.3.is_none());
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_gateway_file("address = \"192.168.1.31\"\n")
+                .ok()
+                .unwrap();
+        assert_eq!(result.1, 22);
+        assert_eq!(result.2, FileTransferProtocol::Sftp);
+        assert!(result.3.is_some());
+        // IPv6
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_gateway_file("address = \"fe80::1\"\nport = 4022\n")
+                .ok()
+                .unwrap();
+        assert_eq!(result.0, String::from("fe80::1"));
+        assert_eq!(result.1, 4022);
+        let result: (String, u16, FileTransferProtocol, Option<String>) =
+            parse_gateway_file("address = \"fe80::1%eth0\"\n")
+                .ok()
+                .unwrap();
+        assert_eq!(result.0, String::from("fe80::1%eth0"));
+        assert_eq!(result.1, 22);
+        // Malformed files
+        assert!(parse_gateway_file("").is_err());
+        assert!(parse_gateway_file("address = 192.168.1.31\n").is_err());
+        assert!(parse_gateway_file("address = \"root@192.168.1.31\"\n").is_err());
+        assert!(parse_gateway_file("address = \"192.168.1.31:22\"\n").is_err());
+        assert!(parse_gateway_file("address = \"[fe80::1]\"\n").is_err());
+        assert!(parse_gateway_file("address = \"192.168.1.31\"\nport = 70000\n").is_err());
+        assert!(parse_gateway_file("address = \"192.168.1.31\"\nprotocol = \"http\"\n").is_err());
+    }
+
+    #[test]
+    fn test_utils_parse_bytesize() {
+        assert_eq!(parse_bytesize("4096").ok().unwrap(), 4096);
+        assert_eq!(parse_bytesize("512K").ok().unwrap(), 524288);
+        assert_eq!(parse_bytesize("16m").ok().unwrap(), 16777216);
+        assert_eq!(parse_bytesize(" 1G ").ok().unwrap(), 1073741824);
+        // bad cases
+        assert!(parse_bytesize("").is_err());
+        assert!(parse_bytesize("M").is_err());
+        assert!(parse_bytesize("16MB").is_err());
+        assert!(parse_bytesize("-1K").is_err());
+    }
+
+    #[test]
+    fn test_utils_parse_buffer_size() {
+        assert_eq!(parse_buffer_size("4").ok().unwrap(), 4096);
+        assert_eq!(parse_buffer_size("256").ok().unwrap(), 262144);
+        assert_eq!(parse_buffer_size("8192").ok().unwrap(), 8388608);
+        // bad cases
+        assert!(parse_buffer_size("3").is_err());
+        assert!(parse_buffer_size("8193").is_err());
+        assert!(parse_buffer_size("64K").is_err());
+        assert!(parse_buffer_size("").is_err());
+    }
+
+    #[test]
+    fn test_utils_parse_unix_pex() {
+        assert_eq!(parse_unix_pex("755").ok().unwrap(), (7, 5, 5));
+        assert_eq!(parse_unix_pex(" 0640 ").ok().unwrap(), (6, 4, 0));
+        assert_eq!(parse_unix_pex("000").ok().unwrap(), (0, 0, 0));
+        assert!(parse_unix_pex("").is_err());
+        assert!(parse_unix_pex("75").is_err());
+        assert!(parse_unix_pex("758").is_err());
+        assert!(parse_unix_pex("1755").is_err());
+        assert!(parse_unix_pex("rwx").is_err());
+    }
+
     #[test]
     fn test_utils_parse_lstime() {
         // Good cases
@@ -312,9 +860,33 @@ mod tests {
                 .unwrap(),
             Duration::from_secs(1521331200)
         );
+        // ISO syntax is not supported
+        assert!(parse_lstime("2018-11-05 16:32", "%b %d %Y", "%b %d %H:%M").is_err());
         // bad cases
         assert!(parse_lstime("Oma 31 2018", "%b %d %Y", "%b %d %H:%M").is_err());
         assert!(parse_lstime("Feb 31 2018", "%b %d %Y", "%b %d %H:%M").is_err());
         assert!(parse_lstime("Feb 15 25:32", "%b %d %Y", "%b %d %H:%M").is_err());
     }
+
+    #[test]
+    fn test_utils_parse_lstime_iso() {
+        let secs = |tm: &str| -> u64 {
+            parse_lstime_iso(tm)
+                .ok()
+                .unwrap()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(secs("2018-11-05 16:32:10"), 1541435530);
+        assert_eq!(secs("2018-11-05 16:32"), 1541435520);
+        assert_eq!(secs("2018-11-05"), 1541376000);
+        assert_eq!(secs("2018-11-05 16:32:10.123456789 +0100"), 1541435530);
+        assert_eq!(secs("2018-11-05 16:32:10 +0100"), 1541435530);
+        // bad cases
+        assert!(parse_lstime_iso("Nov 5 2018").is_err());
+        assert!(parse_lstime_iso("2018-02-31").is_err());
+        assert!(parse_lstime_iso("2018-11-05 25:32").is_err());
+    }
 }
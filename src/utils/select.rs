@@ -0,0 +1,553 @@
+//! ## Select
+//!
+//! `select` is the module which parses and evaluates selection expressions,
+//! used to mark the files matching a predicate over their metadata (e.g. `size>1M && age>30d`)
+
+/*
+*
+*   Copyright (C) 2021 Warren Galyen
+*
+* 	This file is part of "Gateway"
+*
+*   Gateway is free software: you can redistribute it and/or modify
+*   it under the terms of the GNU General Public License as published by
+*   the Free Software Foundation, either version 3 of the License, or
+*   (at your option) any later version.
+*
+*   Gateway is distributed in the hope that it will be useful,
+*   but WITHOUT ANY WARRANTY; without even the implied warranty of
+*   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+*   GNU General Public License for more details.
+*
+*   You should have received a copy of the GNU General Public License
+*   along with Gateway.  If not, see <http://www.gnu.org/licenses/>.
+*
+*/
+
+use super::parser::parse_bytesize;
+use crate::fs::{FsEntry, FsFile};
+
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::{Duration, SystemTime};
+
+/// ## Comparison
+///
+/// Comparison operator of a predicate
+#[derive(Clone, Copy, std::fmt::Debug, PartialEq)]
+pub enum Comparison {
+    Less,         // <
+    LessEqual,    // <=
+    Greater,      // >
+    GreaterEqual, // >=
+    Equal,        // ==
+    NotEqual,     // !=
+}
+
+impl Comparison {
+    /// ### compare
+    ///
+    /// Returns whether `lhs <op> rhs` holds
+    fn compare<T: PartialOrd>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            Comparison::Less => lhs < rhs,
+            Comparison::LessEqual => lhs <= rhs,
+            Comparison::Greater => lhs > rhs,
+            Comparison::GreaterEqual => lhs >= rhs,
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+        }
+    }
+}
+
+/// ## SelectExpr
+///
+/// SelectExpr is a parsed selection expression.
+///
+/// Syntax:
+///
+/// - `size <op> <bytes>`: file size; bytes may have a `K`, `M` or `G` suffix (e.g. `size>1M`)
+/// - `age <op> <amount>`: time elapsed since the last modification; amount may have a
+///   `s`, `m`, `h`, `d` or `w` suffix, seconds otherwise (e.g. `age>30d`)
+/// - `name == <glob>` / `name != <glob>`: file name matches glob; `*` matches any sequence of characters,
+///   `?` any single character. Quote the glob (`"..."` or `'...'`) if it contains spaces or operators
+/// - `<op>` is one of `<`, `<=`, `>`, `>=`, `==`, `!=`
+/// - predicates are combined with `&&`, `||`, `!` and parentheses; `&&` binds tighter than `||`
+///
+/// Directories never match
+#[derive(std::fmt::Debug, PartialEq)]
+pub enum SelectExpr {
+    Size(Comparison, usize),
+    Age(Comparison, Duration),
+    Name(bool, String), // Whether name must match, glob
+    Not(Box<SelectExpr>),
+    And(Box<SelectExpr>, Box<SelectExpr>),
+    Or(Box<SelectExpr>, Box<SelectExpr>),
+}
+
+impl SelectExpr {
+    /// ### parse
+    ///
+    /// Parse a selection expression
+    pub fn parse(expr: &str) -> Result<SelectExpr, String> {
+        let tokens: Vec<Token> = tokenize(expr)?;
+        if tokens.is_empty() {
+            return Err(String::from("Empty expression"));
+        }
+        let mut parser: Parser = Parser { tokens, pos: 0 };
+        let expr: SelectExpr = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected '{}'", token)),
+        }
+    }
+
+    /// ### matches
+    ///
+    /// Returns whether `entry` matches the expression; ages are computed from `now`
+    pub fn matches(&self, entry: &FsEntry, now: SystemTime) -> bool {
+        match entry {
+            FsEntry::Directory(_) => false,
+            FsEntry::File(file) => self.matches_file(file, now),
+        }
+    }
+
+    /// ### matches_file
+    ///
+    /// Evaluate the expression on `file`
+    fn matches_file(&self, file: &FsFile, now: SystemTime) -> bool {
+        match self {
+            SelectExpr::Size(op, size) => op.compare(file.size, *size),
+            SelectExpr::Age(op, age) => {
+                // Files modified in the future have no age
+                let file_age: Duration = now
+                    .duration_since(file.last_change_time)
+                    .unwrap_or_else(|_| Duration::from_secs(0));
+                op.compare(file_age, *age)
+            }
+            SelectExpr::Name(equal, glob) => {
+                glob_match(glob.as_str(), file.name.as_str()) == *equal
+            }
+            SelectExpr::Not(expr) => !expr.matches_file(file, now),
+            SelectExpr::And(lhs, rhs) => lhs.matches_file(file, now) && rhs.matches_file(file, now),
+            SelectExpr::Or(lhs, rhs) => lhs.matches_file(file, now) || rhs.matches_file(file, now),
+        }
+    }
+}
+
+/// ### glob_match
+///
+/// Returns whether `name` matches `glob`; `*` matches any sequence of characters, `?` any single character
+pub fn glob_match(glob: &str, name: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut g, mut n): (usize, usize) = (0, 0);
+    // Position of the last `*` in glob and of the name char it's matching up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                backtrack = Some((g, n));
+                g += 1;
+            }
+            Some(ch) if *ch == '?' || *ch == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` match one more char
+                Some((star, matched)) => {
+                    g = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|x| *x == '*')
+}
+
+// Tokenizer
+
+/// ## Token
+///
+/// Token of a selection expression
+#[derive(std::fmt::Debug, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    OpenParen,
+    CloseParen,
+    Op(Comparison),
+    Word(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+            Token::Op(op) => write!(
+                f,
+                "{}",
+                match op {
+                    Comparison::Less => "<",
+                    Comparison::LessEqual => "<=",
+                    Comparison::Greater => ">",
+                    Comparison::GreaterEqual => ">=",
+                    Comparison::Equal => "==",
+                    Comparison::NotEqual => "!=",
+                }
+            ),
+            Token::Word(word) => write!(f, "{}", word),
+        }
+    }
+}
+
+/// ### tokenize
+///
+/// Split expression into tokens
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars: Peekable<Chars> = expr.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let token: Token = match ch {
+            ch if ch.is_whitespace() => continue,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '&' | '|' => match chars.next_if_eq(&ch) {
+                Some(_) if ch == '&' => Token::And,
+                Some(_) => Token::Or,
+                None => return Err(format!("Expected '{}{}'", ch, ch)),
+            },
+            '!' => match chars.next_if_eq(&'=') {
+                Some(_) => Token::Op(Comparison::NotEqual),
+                None => Token::Not,
+            },
+            '=' => match chars.next_if_eq(&'=') {
+                Some(_) => Token::Op(Comparison::Equal),
+                None => return Err(String::from("Expected '=='")),
+            },
+            '<' => match chars.next_if_eq(&'=') {
+                Some(_) => Token::Op(Comparison::LessEqual),
+                None => Token::Op(Comparison::Less),
+            },
+            '>' => match chars.next_if_eq(&'=') {
+                Some(_) => Token::Op(Comparison::GreaterEqual),
+                None => Token::Op(Comparison::Greater),
+            },
+            '"' | '\'' => {
+                let mut word: String = String::new();
+                loop {
+                    match chars.next() {
+                        Some(x) if x == ch => break,
+                        Some(x) => word.push(x),
+                        None => return Err(format!("Unterminated quote {}", ch)),
+                    }
+                }
+                Token::Word(word)
+            }
+            ch => {
+                let mut word: String = ch.to_string();
+                while let Some(x) = chars.next_if(|x| !is_separator(*x)) {
+                    word.push(x);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// ### is_separator
+///
+/// Returns whether `ch` ends an unquoted word
+fn is_separator(ch: char) -> bool {
+    ch.is_whitespace() || "()&|!=<>\"'".contains(ch)
+}
+
+// Parser
+
+/// ## Parser
+///
+/// Recursive descent parser for selection expressions:
+///
+/// ```txt
+/// or        := and ('||' and)*
+/// and       := unary ('&&' unary)*
+/// unary     := '!' unary | '(' or ')' | predicate
+/// predicate := field op value
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    /// ### next
+    ///
+    /// Consume next token
+    fn next(&mut self) -> Result<&Token, String> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(token)
+            }
+            None => Err(String::from("Unexpected end of expression")),
+        }
+    }
+
+    /// ### next_if
+    ///
+    /// Consume next token if it equals `token`
+    fn next_if(&mut self, token: Token) -> bool {
+        match self.tokens.get(self.pos) {
+            Some(x) if *x == token => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<SelectExpr, String> {
+        let mut expr: SelectExpr = self.parse_and()?;
+        while self.next_if(Token::Or) {
+            expr = SelectExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<SelectExpr, String> {
+        let mut expr: SelectExpr = self.parse_unary()?;
+        while self.next_if(Token::And) {
+            expr = SelectExpr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<SelectExpr, String> {
+        if self.next_if(Token::Not) {
+            return Ok(SelectExpr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.next_if(Token::OpenParen) {
+            let expr: SelectExpr = self.parse_or()?;
+            return match self.next_if(Token::CloseParen) {
+                true => Ok(expr),
+                false => Err(String::from("Missing ')'")),
+            };
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<SelectExpr, String> {
+        let field: String = match self.next()? {
+            Token::Word(word) => word.to_ascii_lowercase(),
+            token => return Err(format!("Expected field name, found '{}'", token)),
+        };
+        let op: Comparison = match self.next()? {
+            Token::Op(op) => *op,
+            token => {
+                return Err(format!(
+                    "Expected comparison after '{}', found '{}'",
+                    field, token
+                ))
+            }
+        };
+        let value: String = match self.next()? {
+            Token::Word(word) => word.clone(),
+            token => {
+                return Err(format!(
+                    "Expected value after '{}', found '{}'",
+                    field, token
+                ))
+            }
+        };
+        match field.as_str() {
+            "size" => Ok(SelectExpr::Size(op, parse_bytesize(value.as_str())?)),
+            "age" => Ok(SelectExpr::Age(op, parse_age(value.as_str())?)),
+            "name" => match op {
+                Comparison::Equal => Ok(SelectExpr::Name(true, value)),
+                Comparison::NotEqual => Ok(SelectExpr::Name(false, value)),
+                _ => Err(String::from("Names can only be compared with '==' or '!='")),
+            },
+            _ => Err(format!(
+                "Unknown field '{}' (expected 'size', 'age' or 'name')",
+                field
+            )),
+        }
+    }
+}
+
+/// ### parse_age
+///
+/// Parse an amount of time, optionally followed by a unit (`s`, `m`, `h`, `d` or `w`)
+fn parse_age(age: &str) -> Result<Duration, String> {
+    let (digits, multiplier): (&str, u64) = match age.chars().last() {
+        Some('s') => (&age[..age.len() - 1], 1),
+        Some('m') => (&age[..age.len() - 1], 60),
+        Some('h') => (&age[..age.len() - 1], 3600),
+        Some('d') => (&age[..age.len() - 1], 86400),
+        Some('w') => (&age[..age.len() - 1], 604800),
+        _ => (age, 1),
+    };
+    match digits.parse::<u64>() {
+        Ok(amount) => amount
+            .checked_mul(multiplier)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("Age is too big '{}'", age)),
+        Err(_) => Err(format!("Bad age '{}'", age)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::path::PathBuf;
+
+    const DAY: u64 = 86400;
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1000 * DAY)
+    }
+
+    fn file(name: &str, size: usize, days: u64) -> FsEntry {
+        let mtime: SystemTime = now() - Duration::from_secs(days * DAY);
+        FsEntry::File(FsFile {
+            name: String::from(name),
+            abs_path: PathBuf::from(format!("/tmp/{}", name)),
+            last_change_time: mtime,
+            last_access_time: mtime,
+            creation_time: mtime,
+            size,
+            ftype: None,
+            readonly: false,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex: None,
+            nlink: None,
+            inode: None,
+        })
+    }
+
+    fn eval(expr: &str, entry: &FsEntry) -> bool {
+        SelectExpr::parse(expr).ok().unwrap().matches(entry, now())
+    }
+
+    #[test]
+    fn test_utils_select_size_and_age() {
+        let big_old: FsEntry = file("backup.tar.gz", 4 * 1048576, 60);
+        let small_new: FsEntry = file("notes.txt", 512, 1);
+        assert!(eval("size>1M && age>30d", &big_old));
+        assert!(!eval("size>1M && age>30d", &small_new));
+        assert!(eval("size<=512", &small_new));
+        assert!(!eval("size<512", &small_new));
+        assert!(eval("size == 4M", &big_old));
+        assert!(eval("size!=4m", &small_new));
+        assert!(eval("age<2d", &small_new));
+        assert!(eval("age>=8w", &big_old));
+        assert!(eval("age>3600", &small_new));
+        assert!(eval("AGE > 23h", &small_new));
+        // Files modified in the future
+        let future: FsEntry = file("future", 1, 0);
+        let yesterday: SystemTime = now() - Duration::from_secs(DAY);
+        assert!(SelectExpr::parse("age<1s")
+            .ok()
+            .unwrap()
+            .matches(&future, yesterday));
+    }
+
+    #[test]
+    fn test_utils_select_name() {
+        let log: FsEntry = file("app.2021-03.log", 2048, 10);
+        assert!(eval("name==*.log", &log));
+        assert!(eval("name == app.????-??.log", &log));
+        assert!(eval("name!=*.txt", &log));
+        assert!(!eval("name==*.LOG", &log));
+        assert!(eval("name=='app*' && size>1K", &log));
+        let spaced: FsEntry = file("my report (final).pdf", 2048, 10);
+        assert!(eval("name == \"my report (*).pdf\"", &spaced));
+        assert!(!eval("name == my*", &file("your report", 0, 0)));
+    }
+
+    #[test]
+    fn test_utils_select_logic() {
+        let entry: FsEntry = file("data.csv", 100, 5);
+        assert!(eval("size>1K || name==*.csv", &entry));
+        assert!(eval("!(size>1K)", &entry));
+        assert!(eval("!size>1K", &entry));
+        assert!(!eval("!!size<1K", &file("data.csv", 2048, 0)));
+        // && binds tighter than ||
+        assert!(eval("name==*.csv || size>1K && age>30d", &entry));
+        assert!(!eval("(name==*.csv || size>1K) && age>30d", &entry));
+        // Directories never match
+        let dir: FsEntry = FsEntry::Directory(crate::fs::FsDirectory {
+            name: String::from("logs"),
+            abs_path: PathBuf::from("/tmp/logs"),
+            last_change_time: now(),
+            last_access_time: now(),
+            creation_time: now(),
+            readonly: false,
+            symlink: None,
+            link_target: None,
+            user: None,
+            group: None,
+            unix_pex: None,
+            nlink: None,
+            inode: None,
+        });
+        assert!(!SelectExpr::parse("size>=0")
+            .ok()
+            .unwrap()
+            .matches(&dir, now()));
+        assert!(!SelectExpr::parse("!name==x")
+            .ok()
+            .unwrap()
+            .matches(&dir, now()));
+    }
+
+    #[test]
+    fn test_utils_select_bad_expr() {
+        assert!(SelectExpr::parse("").is_err());
+        assert!(SelectExpr::parse("   ").is_err());
+        assert!(SelectExpr::parse("size").is_err());
+        assert!(SelectExpr::parse("size>").is_err());
+        assert!(SelectExpr::parse("size>1MB").is_err());
+        assert!(SelectExpr::parse("size=1M").is_err());
+        assert!(SelectExpr::parse("age>30y").is_err());
+        assert!(SelectExpr::parse("owner==root").is_err());
+        assert!(SelectExpr::parse("name>foo").is_err());
+        assert!(SelectExpr::parse("size>1M & age>1d").is_err());
+        assert!(SelectExpr::parse("size>1M age>1d").is_err());
+        assert!(SelectExpr::parse("(size>1M").is_err());
+        assert!(SelectExpr::parse("size>1M)").is_err());
+        assert!(SelectExpr::parse("name=='foo").is_err());
+        assert_eq!(
+            SelectExpr::parse("colour==red").err().unwrap(),
+            "Unknown field 'colour' (expected 'size', 'age' or 'name')"
+        );
+    }
+
+    #[test]
+    fn test_utils_select_glob_match() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(glob_match("a*b*c", "abcbc"));
+        assert!(!glob_match("a*b*c", "abcb"));
+        assert!(glob_match("?.txt", "a.txt"));
+        assert!(!glob_match("?.txt", "ab.txt"));
+        assert!(glob_match("**.rs", "main.rs"));
+        assert!(!glob_match("", "a"));
+        assert!(glob_match("résumé*", "résumé.pdf"));
+    }
+}